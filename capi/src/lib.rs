@@ -0,0 +1,3 @@
+//! The functions of `djikstra::capi`, exported from a shared and a static
+//! library named `djikstra`.
+pub use djikstra::capi::*;
@@ -0,0 +1,73 @@
+/*
+ * C interface to djikstra, built as target/release/libdjikstra.so (.dylib,
+ * .dll) and libdjikstra.a with
+ * cargo build --release -p djikstra-capi
+ *
+ * Graphs and results are owned by the caller, and freed with graph_free and
+ * result_free. Vertices and distances are size_t. No function unwinds a
+ * panic into the caller, they return NULL, 0 or false instead.
+ */
+#ifndef DJIKSTRA_H
+#define DJIKSTRA_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct DjikstraGraph DjikstraGraph;
+typedef struct DjikstraResult DjikstraResult;
+
+/* Why graph_parse returned NULL, or DJIKSTRA_OK if it did not. */
+typedef enum DjikstraStatus {
+    DJIKSTRA_OK = 0,
+    DJIKSTRA_NULL_INPUT = 1,
+    DJIKSTRA_INVALID_UTF8 = 2,
+    DJIKSTRA_PARSE_ERROR = 3,
+    DJIKSTRA_VERTEX_OUT_OF_RANGE = 4,
+    DJIKSTRA_PANIC = 5,
+} DjikstraStatus;
+
+/* Parse a graph in the input format of the command line.
+ * Returns NULL if input is NULL, is not UTF-8, cannot be parsed, or has an
+ * edge to a vertex past the number of vertices, and writes why to *status
+ * unless status is NULL. */
+DjikstraGraph *graph_parse(const char *input, DjikstraStatus *status);
+
+/* Free a graph returned by graph_parse. Does nothing if graph is NULL. */
+void graph_free(DjikstraGraph *graph);
+
+/* Number of vertices of the graph. */
+size_t graph_n_vertices(const DjikstraGraph *graph);
+
+/* Shortest paths from src to every vertex of the graph.
+ * Returns NULL if src is not a vertex of the graph, or if the distance to a
+ * reachable vertex does not fit in a size_t. */
+DjikstraResult *dijkstra_run(const DjikstraGraph *graph, size_t src);
+
+/* Free a result returned by dijkstra_run. Does nothing if result is NULL. */
+void result_free(DjikstraResult *result);
+
+/* The vertex the paths start from. */
+size_t result_source(const DjikstraResult *result);
+
+/* Write the distance from the source to v to *dist and return true, or
+ * return false without writing if v is unreachable or not in the graph. */
+bool result_dist(const DjikstraResult *result, size_t v, size_t *dist);
+
+/* Number of vertices on the shortest path from the source to v, both
+ * included, or 0 if v is unreachable or not in the graph. */
+size_t result_path_len(const DjikstraResult *result, size_t v);
+
+/* Vertices on the shortest path from the source to v, result_path_len of
+ * them, or NULL if v is unreachable or not in the graph. The vertices belong
+ * to the result and are valid until it is freed. */
+const size_t *result_path(const DjikstraResult *result, size_t v);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DJIKSTRA_H */
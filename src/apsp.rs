@@ -0,0 +1,262 @@
+//! All-pairs shortest distances, by repeated Dijkstra or Floyd-Warshall.
+//!
+#[cfg(feature = "rayon")]
+use crate::dijkstra::DijkstraWorkspace;
+use crate::dijkstra::{add_distance, dijkstra_with, DijkstraError};
+use crate::graph::Graph;
+use crate::pq::BinaryHeapQueue;
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+/// Algorithm computing the rows of the distance matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Dijkstra from every vertex, one row at a time.
+    Dijkstra,
+    /// Floyd-Warshall on the whole matrix at once.
+    FloydWarshall,
+}
+
+impl Method {
+    /// The old spelling of [`Method::Dijkstra`].
+    #[deprecated(since = "0.3.0", note = "renamed to `Method::Dijkstra`")]
+    #[allow(non_upper_case_globals)]
+    pub const Djikstra: Method = Method::Dijkstra;
+
+    /// The faster method for the density of the graph.
+    ///
+    /// Repeated Dijkstra takes about `n * m * log n` steps and Floyd-Warshall
+    /// `n^3`, so Floyd-Warshall is picked once there are more than `n^2 / log n`
+    /// edges.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::apsp::Method;
+    /// use djikstra::graph::Graph;
+    ///
+    /// let sparse = Graph::new(vec![vec![(1, 1)], vec![], vec![], vec![]]);
+    /// assert_eq!(Method::for_graph(&sparse), Method::Dijkstra);
+    /// ```
+    pub fn for_graph(graph: &Graph) -> Self {
+        let n = graph.n_vertices();
+        // the integer logarithm, as `f64::log2` needs the standard library
+        let log_n = n.max(2).ilog2() as usize;
+        if graph.n_edges().saturating_mul(log_n) > n.saturating_mul(n) {
+            Method::FloydWarshall
+        } else {
+            Method::Dijkstra
+        }
+    }
+}
+
+/// Call `on_row` with every vertex and its distances to all vertices, in
+/// order of the vertices, using `method`. Unreachable vertices are at
+/// `None`. Stops at the first error returned by `on_row`, or as soon as a
+/// distance does not fit in a `usize`.
+///
+/// With Dijkstra only one row is kept in memory at a time, so the rows can
+/// be streamed out for graphs whose matrix does not fit in memory.
+///
+/// # Example
+/// ```
+/// use djikstra::apsp::{distance_rows, Method};
+/// use djikstra::dijkstra::DijkstraError;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(0, 1)]]);
+/// let mut rows = vec![];
+/// distance_rows(&graph, Method::Dijkstra, |_vertex, row| {
+///     rows.push(row.to_vec());
+///     Ok::<(), DijkstraError>(())
+/// })
+/// .unwrap();
+/// assert_eq!(rows, vec![vec![Some(0), Some(3)], vec![Some(1), Some(0)]]);
+/// ```
+pub fn distance_rows<F, E>(graph: &Graph, method: Method, mut on_row: F) -> Result<(), E>
+where
+    F: FnMut(usize, &[Option<usize>]) -> Result<(), E>,
+    E: From<DijkstraError>,
+{
+    match method {
+        Method::Dijkstra => {
+            for src in 0..graph.n_vertices() {
+                let (_paths, dists) = dijkstra_with::<BinaryHeapQueue<usize>>(graph, src)?;
+                on_row(src, &dists)?;
+            }
+        }
+        Method::FloydWarshall => {
+            for (src, dists) in floyd_warshall(graph)?.iter().enumerate() {
+                on_row(src, dists)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Floyd-Warshall algorithm, returning the matrix of distances between
+/// all pairs of vertices, with `None` for the unreachable ones.
+///
+/// # Errors
+/// Fails if the distance between two vertices does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::apsp::floyd_warshall;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// assert_eq!(floyd_warshall(&graph).unwrap()[0], vec![Some(0), Some(3), Some(4)]);
+/// ```
+pub fn floyd_warshall(graph: &Graph) -> Result<Vec<Vec<Option<usize>>>, DijkstraError> {
+    let n_elems = graph.n_vertices();
+    let mut dists: Vec<Vec<Option<usize>>> = vec![vec![None; n_elems]; n_elems];
+    // pairs whose distance through some vertex overflowed
+    let mut overflowed = vec![];
+    for (u, neighbors) in graph.adj.iter().enumerate() {
+        dists[u][u] = Some(0);
+        for &(v, weight) in neighbors.iter() {
+            dists[u][v] = Some(dists[u][v].map_or(weight, |old| old.min(weight)));
+        }
+    }
+
+    for k in 0..n_elems {
+        let through = dists[k].clone();
+        for (u, row) in dists.iter_mut().enumerate() {
+            let Some(to_k) = row[k] else {
+                continue;
+            };
+            for (v, (dist, &from_k)) in row.iter_mut().zip(through.iter()).enumerate() {
+                let Some(from_k) = from_k else {
+                    continue;
+                };
+                let Some(new_dist) = add_distance(to_k, from_k) else {
+                    overflowed.push((u, v));
+                    continue;
+                };
+                if dist.is_none_or(|old| new_dist < old) {
+                    *dist = Some(new_dist);
+                }
+            }
+        }
+    }
+
+    // an overflowing distance is only a problem if there is no other one
+    if overflowed.iter().any(|&(u, v)| dists[u][v].is_none()) {
+        return Err(DijkstraError::Overflow);
+    }
+    Ok(dists)
+}
+
+/// Repeated Dijkstra from every vertex on the rayon thread pool, returning
+/// the matrix of distances between all pairs of vertices, with `None` for the
+/// unreachable ones.
+///
+/// Every source is a task of its own, so idle threads steal the sources left
+/// when others are slow, and every thread keeps a [`DijkstraWorkspace`] from
+/// one source to the next.
+///
+/// # Errors
+/// Fails if the distance between two vertices does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::apsp::{floyd_warshall, parallel};
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![(0, 2)]]);
+/// assert_eq!(parallel(&graph), floyd_warshall(&graph));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn parallel(graph: &Graph) -> Result<Vec<Vec<Option<usize>>>, DijkstraError> {
+    let n_elems = graph.n_vertices();
+    let mut dists: Vec<Vec<Option<usize>>> = vec![vec![None; n_elems]; n_elems];
+    dists.par_iter_mut().enumerate().try_for_each_init(
+        DijkstraWorkspace::new,
+        |workspace, (src, row)| {
+            workspace.shortest_paths(graph, src)?;
+            for (v, dist) in row.iter_mut().enumerate() {
+                *dist = workspace.dist_to(v);
+            }
+            Ok(())
+        },
+    )?;
+    Ok(dists)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::apsp::{distance_rows, floyd_warshall, Method};
+    use crate::dijkstra::DijkstraError;
+    use crate::graph::Graph;
+    use crate::test_utils::sample_graph;
+
+    fn rows(graph: &Graph, method: Method) -> Vec<Vec<Option<usize>>> {
+        let mut rows = vec![];
+        distance_rows(graph, method, |_vertex, row| {
+            rows.push(row.to_vec());
+            Ok::<(), DijkstraError>(())
+        })
+        .unwrap();
+        rows
+    }
+
+    #[test]
+    fn methods_agree() {
+        let g1 = sample_graph();
+        assert_eq!(
+            rows(&g1, Method::Dijkstra),
+            rows(&g1, Method::FloydWarshall)
+        );
+    }
+
+    #[test]
+    fn unreachable_pairs() {
+        let g1 = Graph::new(vec![vec![(1, 2), (1, 1)], vec![], vec![(0, 1)]]);
+        assert_eq!(
+            floyd_warshall(&g1).unwrap(),
+            vec![
+                vec![Some(0), Some(1), None],
+                vec![None, Some(0), None],
+                vec![Some(1), Some(2), Some(0)]
+            ]
+        );
+        assert_eq!(rows(&g1, Method::Dijkstra), floyd_warshall(&g1).unwrap());
+    }
+
+    #[test]
+    fn overflowing_distances_are_errors() {
+        let big = usize::MAX / 2;
+        let g1 = Graph::new(vec![vec![(1, big)], vec![(2, big + 2)], vec![]]);
+        assert_eq!(floyd_warshall(&g1), Err(DijkstraError::Overflow));
+        let result = distance_rows(&g1, Method::Dijkstra, |_vertex, _row| Ok(()));
+        assert_eq!(result, Err(DijkstraError::Overflow));
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn parallel_matches_floyd_warshall() {
+        use crate::apsp::parallel;
+
+        let g1 = sample_graph();
+        assert_eq!(parallel(&g1), floyd_warshall(&g1));
+        let g2 = Graph::new(vec![vec![(1, 2), (1, 1)], vec![], vec![(0, 1)]]);
+        assert_eq!(parallel(&g2), floyd_warshall(&g2));
+        assert_eq!(parallel(&Graph::new(vec![])), Ok(vec![]));
+
+        let big = usize::MAX / 2;
+        let g3 = Graph::new(vec![vec![(1, big)], vec![(2, big + 2)], vec![]]);
+        assert_eq!(parallel(&g3), Err(DijkstraError::Overflow));
+    }
+
+    #[test]
+    fn dense_graphs_use_floyd_warshall() {
+        let complete = Graph::new(
+            (0..8)
+                .map(|u| (0..8).filter(|&v| v != u).map(|v| (v, 1)).collect())
+                .collect(),
+        );
+        assert_eq!(Method::for_graph(&complete), Method::FloydWarshall);
+    }
+}
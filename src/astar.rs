@@ -0,0 +1,179 @@
+//! A* search, Dijkstra's algorithm guided by a heuristic towards a destination.
+//!
+use crate::bitset::BitSet;
+use crate::dijkstra::{
+    add_distance, build_path, check_overflows, check_source, paths_and_dists, DijkstraError,
+    PathAndDist, PathsAndDists,
+};
+use crate::graph::Graph;
+use alloc::collections::BinaryHeap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+/// A* search from `src` that settles the vertices in the order of their distance
+/// plus `heuristic`, the estimated distance left from a vertex to the destination.
+/// Returns a list of paths and the distances from the source, in the same form
+/// as `dijkstra`.
+///
+/// The distances are shortest distances as long as the heuristic is consistent,
+/// i.e. it never decreases by more than the weight of an edge along that edge.
+/// With a heuristic of 0 this settles the vertices in the same order as Dijkstra.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::astar::astar;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 9)], vec![(2, 1)], vec![]]);
+/// let (paths, dists) = astar(&graph, 0, |_vertex| 0).unwrap();
+/// assert_eq!(paths[2], Some(vec![0, 1, 2]));
+/// assert_eq!(dists[2], Some(4));
+/// ```
+pub fn astar<H>(graph: &Graph, src: usize, heuristic: H) -> Result<PathsAndDists, DijkstraError>
+where
+    H: Fn(usize) -> usize,
+{
+    check_source(graph, src)?;
+    let (parents, dists_from_src) = search(graph, src, None, heuristic)?;
+    Ok(paths_and_dists(&parents, src, &dists_from_src))
+}
+
+/// Shortest path from `src` to `dst` found by A* search with `heuristic`,
+/// stopping as soon as `dst` is settled.
+/// Returns the path and its distance, or `None` if `dst` cannot be reached.
+///
+/// # Errors
+/// Fails if `src` or `dst` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::astar::astar_path;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// assert_eq!(astar_path(&graph, 0, 2, |v| 2 - v), Ok(Some((vec![0, 1, 2], 4))));
+/// ```
+pub fn astar_path<H>(
+    graph: &Graph,
+    src: usize,
+    dst: usize,
+    heuristic: H,
+) -> Result<Option<PathAndDist>, DijkstraError>
+where
+    H: Fn(usize) -> usize,
+{
+    check_source(graph, src)?;
+    check_source(graph, dst)?;
+    let (parents, dists_from_src) = search(graph, src, Some(dst), heuristic)?;
+    Ok(build_path(&parents, src, dst).map(|path| (path, dists_from_src[dst])))
+}
+
+/// Settle vertices from `src` in the order of distance plus heuristic until
+/// `dst` (or every reachable vertex if there is none) is settled.
+/// Returns the parent of every vertex and the distances from `src`, or
+/// `Overflow` if a vertex can only be reached by overflowing distances.
+fn search<H>(
+    graph: &Graph,
+    src: usize,
+    dst: Option<usize>,
+    heuristic: H,
+) -> Result<(Vec<Option<usize>>, Vec<usize>), DijkstraError>
+where
+    H: Fn(usize) -> usize,
+{
+    let n_elems = graph.n_vertices();
+    let mut parents = vec![None; n_elems];
+    let mut dists_from_src = vec![usize::MAX; n_elems];
+    let mut settled = BitSet::new(n_elems);
+    let mut overflowed = vec![];
+    // entries are (distance + heuristic, vertex), stale ones are skipped when popped
+    let mut queue = BinaryHeap::new();
+
+    dists_from_src[src] = 0;
+    queue.push(Reverse((heuristic(src), src)));
+
+    while let Some(Reverse((_estimate, node))) = queue.pop() {
+        if !settled.insert(node) {
+            continue;
+        }
+        if Some(node) == dst {
+            return Ok((parents, dists_from_src));
+        }
+
+        for &(neighbour, dist) in graph.neighbors_of(node).iter() {
+            if settled.contains(neighbour) {
+                continue;
+            }
+            let Some(new_dist) = add_distance(dists_from_src[node], dist) else {
+                overflowed.push(neighbour);
+                continue;
+            };
+            if new_dist < dists_from_src[neighbour] {
+                dists_from_src[neighbour] = new_dist;
+                parents[neighbour] = Some(node);
+                // the estimate only orders the queue, so it can saturate
+                queue.push(Reverse((
+                    new_dist.saturating_add(heuristic(neighbour)),
+                    neighbour,
+                )));
+            }
+        }
+    }
+
+    check_overflows(&overflowed, &dists_from_src)?;
+    Ok((parents, dists_from_src))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::astar::{astar, astar_path};
+    use crate::dijkstra::{dijkstra, shortest_path, DijkstraError};
+    use crate::graph::Graph;
+    use crate::test_utils::sample_graph;
+
+    fn graph() -> Graph {
+        sample_graph()
+    }
+
+    #[test]
+    fn zero_heuristic_agrees_with_dijkstra() {
+        let g1 = graph();
+        let (_paths, dists) = astar(&g1, 6, |_| 0).unwrap();
+        assert_eq!(dists, dijkstra(&g1, 6).unwrap().1);
+    }
+
+    #[test]
+    fn consistent_heuristic_finds_shortest_paths() {
+        let g1 = graph();
+        // exact distances to 5 are a consistent heuristic
+        let to_5: Vec<usize> = (0..g1.n_vertices())
+            .map(|v| shortest_path(&g1, v, 5).unwrap().unwrap().1)
+            .collect();
+
+        for src in 0..g1.n_vertices() {
+            let (_path, dist) = astar_path(&g1, src, 5, |v| to_5[v]).unwrap().unwrap();
+            assert_eq!(dist, to_5[src]);
+        }
+    }
+
+    #[test]
+    fn unreachable_destination() {
+        let g1 = Graph::new(vec![vec![(1, 2)], vec![], vec![(0, 1)]]);
+        assert_eq!(astar_path(&g1, 0, 2, |_| 0), Ok(None));
+        assert_eq!(astar(&g1, 0, |_| 0).unwrap().1[2], None);
+    }
+
+    #[test]
+    fn overflowing_distances_are_errors() {
+        let big = usize::MAX / 2;
+        let g1 = Graph::new(vec![vec![(1, big)], vec![(2, big + 2)], vec![]]);
+        assert_eq!(astar(&g1, 0, |_| 0), Err(DijkstraError::Overflow));
+        assert_eq!(astar_path(&g1, 0, 1, |_| 0), Ok(Some((vec![0, 1], big))));
+    }
+}
@@ -0,0 +1,98 @@
+//! Bellman-Ford algorithm, mostly useful as a reference to compare Dijkstra against.
+//!
+use crate::dijkstra::{add_distance, build_path, check_source, DijkstraError, PathsAndDists};
+use crate::graph::Graph;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Bellman-Ford algorithm that takes in a graph and a source node.
+/// Returns a list of paths and the distances from the source, in the same
+/// form as `dijkstra`.
+///
+/// Relaxes every edge until no distance changes, at most `n_vertices - 1` times.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::bellman_ford::bellman_ford;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 9)], vec![(2, 1)], vec![]]);
+/// let (paths, dists) = bellman_ford(&graph, 0).unwrap();
+/// assert_eq!(paths[2], Some(vec![0, 1, 2]));
+/// assert_eq!(dists[2], Some(4));
+/// ```
+pub fn bellman_ford(graph: &Graph, src: usize) -> Result<PathsAndDists, DijkstraError> {
+    check_source(graph, src)?;
+    let n_elems = graph.n_vertices();
+    let mut parents = vec![None; n_elems];
+    let mut dists_from_src: Vec<Option<usize>> = vec![None; n_elems];
+    let mut overflowed = vec![false; n_elems];
+    dists_from_src[src] = Some(0);
+
+    for _ in 1..n_elems {
+        let mut changed = false;
+        for (node, neighbours) in graph.adj.iter().enumerate() {
+            let Some(dist_src) = dists_from_src[node] else {
+                continue;
+            };
+            for &(neighbour, dist) in neighbours.iter() {
+                let Some(new_dist) = add_distance(dist_src, dist) else {
+                    overflowed[neighbour] = true;
+                    continue;
+                };
+                if dists_from_src[neighbour].is_none_or(|old| old > new_dist) {
+                    dists_from_src[neighbour] = Some(new_dist);
+                    parents[neighbour] = Some(node);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    // an overflowing distance is only a problem if there is no other one
+    if (0..n_elems).any(|v| overflowed[v] && dists_from_src[v].is_none()) {
+        return Err(DijkstraError::Overflow);
+    }
+    let paths_from_src = (0..n_elems).map(|v| build_path(&parents, src, v)).collect();
+
+    Ok((paths_from_src, dists_from_src))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bellman_ford::bellman_ford;
+    use crate::dijkstra::{dijkstra, DijkstraError};
+    use crate::graph::Graph;
+    use crate::test_utils::sample_graph;
+
+    #[test]
+    fn agrees_with_dijkstra() {
+        let g1 = sample_graph();
+
+        assert_eq!(bellman_ford(&g1, 6), dijkstra(&g1, 6));
+    }
+
+    #[test]
+    fn unreachable_vertices() {
+        let g1 = Graph::new(vec![vec![(1, 2)], vec![], vec![(0, 1)]]);
+        let (paths, dists) = bellman_ford(&g1, 0).unwrap();
+        assert_eq!(paths, vec![Some(vec![0]), Some(vec![0, 1]), None]);
+        assert_eq!(dists, vec![Some(0), Some(2), None]);
+    }
+
+    #[test]
+    fn overflowing_distances_are_errors() {
+        let big = usize::MAX / 2;
+        let g1 = Graph::new(vec![vec![(1, big)], vec![(2, big + 2)], vec![]]);
+        assert_eq!(bellman_ford(&g1, 0), Err(DijkstraError::Overflow));
+        let g2 = Graph::new(vec![vec![(1, big), (2, 1)], vec![(2, big + 2)], vec![]]);
+        assert_eq!(bellman_ford(&g2, 0), dijkstra(&g2, 0));
+    }
+}
@@ -0,0 +1,348 @@
+//! Timing the algorithm on a graph, as done by the `benchmark` subcommand.
+//!
+//! # Example
+//! ```
+//! use djikstra::bench::Benchmark;
+//! use djikstra::graph::Graph;
+//!
+//! let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+//! let result = Benchmark::new(&graph).runs(50).warmup(5).run().unwrap();
+//! assert_eq!(result.runs_ns.len(), 50);
+//! assert!(result.stats.min <= result.stats.median);
+//! ```
+use crate::dijkstra::dijkstra;
+use crate::graph::Graph;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Number of runs made with a target confidence interval before checking it.
+const MIN_ADAPTIVE_RUNS: usize = 10;
+
+/// Algorithm timed by a [`Benchmark`], called with the graph and the source.
+type Run<'a> = Box<dyn Fn(&Graph, usize) + Sync + 'a>;
+
+/// Repeated runs of an algorithm on a graph, configured with the builder
+/// methods and timed by [`Benchmark::run`].
+pub struct Benchmark<'a> {
+    graph: &'a Graph,
+    source: usize,
+    runs: usize,
+    warmup: usize,
+    threads: usize,
+    contention: bool,
+    target_ci: Option<f64>,
+    max_time: Duration,
+    algorithm: Run<'a>,
+    on_run: Box<dyn Fn() + Sync + 'a>,
+}
+
+impl<'a> Benchmark<'a> {
+    /// 1000 runs of Dijkstra from vertex 0 on one thread, without warmup.
+    pub fn new(graph: &'a Graph) -> Self {
+        Self {
+            graph,
+            source: 0,
+            runs: 1000,
+            warmup: 0,
+            threads: 1,
+            contention: false,
+            target_ci: None,
+            max_time: Duration::from_secs(60),
+            algorithm: Box::new(|graph, source| {
+                let _paths_and_dists = dijkstra(graph, source);
+            }),
+            on_run: Box::new(|| {}),
+        }
+    }
+
+    /// Run from `source` instead of vertex 0.
+    pub fn source(mut self, source: usize) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Number of timed runs.
+    pub fn runs(mut self, runs: usize) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    /// Number of runs before the timed ones, left out of the statistics.
+    pub fn warmup(mut self, warmup: usize) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// Split the runs between `threads` threads sharing the graph, each timing its own runs.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Time all the runs on one thread while the other threads keep running
+    /// the algorithm, to measure the latency of a run under load.
+    pub fn contention(mut self, contention: bool) -> Self {
+        self.contention = contention;
+        self
+    }
+
+    /// Instead of a fixed number of runs, run until the 95% confidence
+    /// interval of the mean is within `target_pct` percent of the mean,
+    /// discarding outliers, or until `max_time` has passed.
+    pub fn target_ci(mut self, target_pct: f64, max_time: Duration) -> Self {
+        self.target_ci = Some(target_pct);
+        self.max_time = max_time;
+        self
+    }
+
+    /// Time `algorithm` instead of Dijkstra.
+    pub fn algorithm<F: Fn(&Graph, usize) + Sync + 'a>(mut self, algorithm: F) -> Self {
+        self.algorithm = Box::new(algorithm);
+        self
+    }
+
+    /// Call `on_run` after every run, warmup included, e.g. to report progress.
+    pub fn on_run<F: Fn() + Sync + 'a>(mut self, on_run: F) -> Self {
+        self.on_run = Box::new(on_run);
+        self
+    }
+
+    /// Make the runs and compute the statistics of their times.
+    /// Returns `None` if there were no runs to compute statistics from.
+    pub fn run(&self) -> Option<BenchmarkResult> {
+        let threads = self.threads;
+        let timed_run = || {
+            let start = Instant::now();
+            (self.algorithm)(self.graph, self.source);
+            let duration = start.elapsed();
+            (self.on_run)();
+            duration.as_nanos()
+        };
+        // the share of `runs` made by the thread `index`
+        let share =
+            |runs: usize, index: usize| runs / threads + usize::from(index < runs % threads);
+
+        thread::scope(|scope| {
+            for index in 0..threads {
+                scope.spawn(move || {
+                    for _ in 0..share(self.warmup, index) {
+                        timed_run();
+                    }
+                });
+            }
+        });
+
+        let batch = |runs: usize| -> Vec<u128> {
+            if self.contention {
+                let done = AtomicBool::new(false);
+                thread::scope(|scope| {
+                    for _ in 1..threads {
+                        scope.spawn(|| {
+                            while !done.load(Ordering::Relaxed) {
+                                (self.algorithm)(self.graph, self.source);
+                            }
+                        });
+                    }
+                    let results = (0..runs).map(|_| timed_run()).collect();
+                    done.store(true, Ordering::Relaxed);
+                    results
+                })
+            } else {
+                thread::scope(|scope| {
+                    let handles: Vec<_> = (0..threads)
+                        .map(|index| {
+                            scope.spawn(move || {
+                                (0..share(runs, index))
+                                    .map(|_| timed_run())
+                                    .collect::<Vec<u128>>()
+                            })
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .flat_map(|handle| handle.join().expect("benchmark thread panicked"))
+                        .collect()
+                })
+            }
+        };
+
+        let (runs_ns, outliers) = match self.target_ci {
+            Some(target_pct) => {
+                let started = Instant::now();
+                let mut results = batch(MIN_ADAPTIVE_RUNS.max(threads));
+                while !converged(&results, target_pct) && started.elapsed() < self.max_time {
+                    // grow by a tenth so the last batch does not overshoot much
+                    let more = batch((results.len() / 10).max(threads));
+                    results.extend(more);
+                }
+                discard_outliers(&results)
+            }
+            None => (batch(self.runs), 0),
+        };
+
+        let stats = TimingStats::from_samples(&runs_ns)?;
+        Some(BenchmarkResult {
+            runs_ns,
+            outliers,
+            stats,
+        })
+    }
+}
+
+/// Times of the runs of a [`Benchmark`] and their statistics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkResult {
+    /// Duration of every run kept, in nanoseconds.
+    pub runs_ns: Vec<u128>,
+    /// Number of runs discarded as outliers, only with a target confidence interval.
+    pub outliers: usize,
+    pub stats: TimingStats,
+}
+
+/// Statistics of the running times of a benchmark, in nanoseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimingStats {
+    pub mean: f64,
+    pub std_dev: f64,
+    /// Half the width of the 95% confidence interval of the mean.
+    pub ci95: f64,
+    pub min: u128,
+    pub median: u128,
+    pub p95: u128,
+    pub p99: u128,
+    pub max: u128,
+}
+
+impl TimingStats {
+    /// Compute the statistics of the samples, `None` if there are none.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::bench::TimingStats;
+    ///
+    /// let stats = TimingStats::from_samples(&[30, 10, 20]).unwrap();
+    /// assert_eq!((stats.mean, stats.median, stats.max), (20.0, 20, 30));
+    /// ```
+    pub fn from_samples(samples: &[u128]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        // nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+
+        let n = samples.len() as f64;
+        let mean = samples.iter().map(|&x| x as f64).sum::<f64>() / n;
+        let variance = samples
+            .iter()
+            .map(|&x| (x as f64 - mean).powi(2))
+            .sum::<f64>()
+            / n;
+
+        Some(Self {
+            mean,
+            std_dev: variance.sqrt(),
+            ci95: 1.96 * variance.sqrt() / n.sqrt(),
+            min: sorted[0],
+            median: percentile(50.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+impl fmt::Display for TimingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "  mean:    {0:.0}ns", self.mean)?;
+        writeln!(f, "  95% CI:  ±{0:.0}ns", self.ci95)?;
+        writeln!(f, "  std dev: {0:.0}ns", self.std_dev)?;
+        writeln!(f, "  min:     {0}ns", self.min)?;
+        writeln!(f, "  median:  {0}ns", self.median)?;
+        writeln!(f, "  p95:     {0}ns", self.p95)?;
+        writeln!(f, "  p99:     {0}ns", self.p99)?;
+        writeln!(f, "  max:     {0}ns", self.max)
+    }
+}
+
+/// Split the samples into the ones within Tukey's fences, 1.5 times the
+/// interquartile range away from the quartiles, and the number of the others.
+pub fn discard_outliers(samples: &[u128]) -> (Vec<u128>, usize) {
+    if samples.len() < 4 {
+        return (samples.to_vec(), 0);
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let q1 = sorted[sorted.len() / 4] as f64;
+    let q3 = sorted[sorted.len() * 3 / 4] as f64;
+    let fences = (q1 - 1.5 * (q3 - q1))..=(q3 + 1.5 * (q3 - q1));
+
+    let kept: Vec<u128> = samples
+        .iter()
+        .cloned()
+        .filter(|&x| fences.contains(&(x as f64)))
+        .collect();
+    let discarded = samples.len() - kept.len();
+    (kept, discarded)
+}
+
+/// Whether the confidence interval of the mean of the samples, without the
+/// outliers, is within `target_pct` percent of the mean.
+fn converged(samples: &[u128], target_pct: f64) -> bool {
+    let (kept, _outliers) = discard_outliers(samples);
+    TimingStats::from_samples(&kept)
+        .is_some_and(|stats| stats.ci95 <= stats.mean * target_pct / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bench::{discard_outliers, Benchmark, TimingStats};
+    use crate::graph::Graph;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn percentiles_are_nearest_rank() {
+        let samples: Vec<u128> = (1..=100).collect();
+        let stats = TimingStats::from_samples(&samples).unwrap();
+        assert_eq!(
+            (stats.min, stats.median, stats.p95, stats.p99),
+            (1, 50, 95, 99)
+        );
+        assert_eq!(stats.mean, 50.5);
+        assert_eq!(TimingStats::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn outliers_are_outside_the_fences() {
+        let (kept, discarded) = discard_outliers(&[10, 11, 12, 10, 11, 1000]);
+        assert_eq!((kept, discarded), (vec![10, 11, 12, 10, 11], 1));
+    }
+
+    #[test]
+    fn runs_are_shared_between_threads() {
+        let graph = Graph::new(vec![vec![(1, 3)], vec![]]);
+        let calls = AtomicUsize::new(0);
+        let result = Benchmark::new(&graph)
+            .runs(25)
+            .warmup(4)
+            .threads(3)
+            .algorithm(|graph, source| assert_eq!((graph.n_vertices(), source), (2, 1)))
+            .source(1)
+            .on_run(|| {
+                calls.fetch_add(1, Ordering::Relaxed);
+            })
+            .run()
+            .unwrap();
+        assert_eq!(result.runs_ns.len(), 25);
+        assert_eq!(calls.load(Ordering::Relaxed), 29);
+        assert_eq!(Benchmark::new(&graph).runs(0).run(), None);
+    }
+}
@@ -0,0 +1,74 @@
+//! Breadth-first search, shortest paths by number of edges.
+//!
+use crate::dijkstra::{build_path, PathsAndDists};
+use crate::graph::Graph;
+use alloc::collections::VecDeque;
+use alloc::vec;
+
+/// Breadth-first search that takes in a graph and a source node.
+/// Returns a list of paths and the distances from the source, in the same
+/// form as `dijkstra`, except that the weights are ignored: every path has
+/// the fewest edges and its distance is the number of edges.
+///
+/// # Example
+/// ```
+/// use djikstra::bfs::bfs;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 9)], vec![(2, 1)], vec![]]);
+/// let (paths, dists) = bfs(&graph, 0);
+/// assert_eq!(paths[2], Some(vec![0, 2]));
+/// assert_eq!(dists[2], Some(1));
+/// ```
+pub fn bfs(graph: &Graph, src: usize) -> PathsAndDists {
+    let n_elems = graph.n_vertices();
+    let mut parents = vec![None; n_elems];
+    let mut dists_from_src = vec![None; n_elems];
+    let mut queue = VecDeque::from([(src, 0)]);
+    dists_from_src[src] = Some(0);
+
+    while let Some((node, dist_src)) = queue.pop_front() {
+        for &(neighbour, _dist) in graph.neighbors_of(node).iter() {
+            if dists_from_src[neighbour].is_none() {
+                dists_from_src[neighbour] = Some(dist_src + 1);
+                parents[neighbour] = Some(node);
+                queue.push_back((neighbour, dist_src + 1));
+            }
+        }
+    }
+
+    let paths_from_src = (0..n_elems).map(|v| build_path(&parents, src, v)).collect();
+
+    (paths_from_src, dists_from_src)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bfs::bfs;
+    use crate::dijkstra::dijkstra;
+    use crate::graph::Graph;
+
+    #[test]
+    fn agrees_with_dijkstra_on_unit_weights() {
+        let g1 = Graph::new(vec![
+            vec![(1, 1), (6, 1)],
+            vec![(0, 1), (2, 1), (3, 1)],
+            vec![(6, 1), (1, 1), (3, 1), (4, 1)],
+            vec![(1, 1), (2, 1), (4, 1), (7, 1)],
+            vec![(2, 1), (3, 1), (7, 1), (5, 1)],
+            vec![(4, 1), (7, 1)],
+            vec![(0, 1), (1, 1), (2, 1)],
+            vec![(4, 1), (5, 1), (3, 1)],
+        ]);
+
+        assert_eq!(bfs(&g1, 0).1, dijkstra(&g1, 0).unwrap().1);
+    }
+
+    #[test]
+    fn unreachable_vertices() {
+        let g1 = Graph::new(vec![vec![(1, 2)], vec![], vec![(0, 1)]]);
+        let (paths, dists) = bfs(&g1, 0);
+        assert_eq!(paths, vec![Some(vec![0]), Some(vec![0, 1]), None]);
+        assert_eq!(dists, vec![Some(0), Some(1), None]);
+    }
+}
@@ -0,0 +1,134 @@
+//! Fixed-size set of small integers packed in bits, for the visited vertices
+//! of the searches.
+//!
+use alloc::vec;
+use alloc::vec::Vec;
+
+const BITS: usize = u64::BITS as usize;
+
+/// Set of the integers below `len`, one bit each, so marking every vertex of
+/// a graph takes 64 times less memory than a `Vec<bool>`.
+///
+/// # Example
+/// ```
+/// use djikstra::bitset::BitSet;
+///
+/// let mut visited = BitSet::new(100);
+/// assert!(visited.insert(42));
+/// assert!(!visited.insert(42));
+/// assert!(visited.contains(42) && !visited.contains(43));
+/// assert_eq!(visited.count(), 1);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    /// Empty set of the integers below `len`.
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(BITS)],
+            len,
+        }
+    }
+
+    /// Number of integers the set can hold, not the number it holds.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the set can hold no integer.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether `i` is in the set.
+    ///
+    /// # Panics
+    /// If `i` is not below [`BitSet::len`].
+    pub fn contains(&self, i: usize) -> bool {
+        assert!(i < self.len, "{} is out of a set of {}", i, self.len);
+        self.words[i / BITS] & (1 << (i % BITS)) != 0
+    }
+
+    /// Add `i` to the set, returning whether it was not in it.
+    ///
+    /// # Panics
+    /// If `i` is not below [`BitSet::len`].
+    pub fn insert(&mut self, i: usize) -> bool {
+        let was_in = self.contains(i);
+        self.words[i / BITS] |= 1 << (i % BITS);
+        !was_in
+    }
+
+    /// Remove `i` from the set, returning whether it was in it.
+    ///
+    /// # Panics
+    /// If `i` is not below [`BitSet::len`].
+    pub fn remove(&mut self, i: usize) -> bool {
+        let was_in = self.contains(i);
+        self.words[i / BITS] &= !(1 << (i % BITS));
+        was_in
+    }
+
+    /// Number of integers in the set.
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Remove every integer and make room for the ones below `len`, keeping
+    /// the memory already allocated.
+    pub fn reset(&mut self, len: usize) {
+        self.words.clear();
+        self.words.resize(len.div_ceil(BITS), 0);
+        self.len = len;
+    }
+
+    /// The integers in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len).filter(|&i| self.contains(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bitset::BitSet;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn behaves_like_a_vec_of_bools() {
+        let len = 200;
+        let mut set = BitSet::new(len);
+        let mut bools = vec![false; len];
+        for i in (0..len).filter(|i| i % 3 == 0 || i % 64 == 63) {
+            assert_eq!(set.insert(i), !bools[i]);
+            bools[i] = true;
+        }
+        for i in (0..len).filter(|i| i % 5 == 0) {
+            assert_eq!(set.remove(i), bools[i]);
+            bools[i] = false;
+        }
+        for (i, &b) in bools.iter().enumerate() {
+            assert_eq!(set.contains(i), b);
+        }
+        assert_eq!(set.count(), bools.iter().filter(|&&b| b).count());
+        let expected: Vec<usize> = (0..len).filter(|&i| bools[i]).collect();
+        assert_eq!(set.iter().collect::<Vec<_>>(), expected);
+
+        set.reset(10);
+        assert_eq!((set.len(), set.count()), (10, 0));
+        assert!(BitSet::new(0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_the_set() {
+        BitSet::new(64).insert(64);
+    }
+}
@@ -0,0 +1,296 @@
+//! Cache of shortest path trees by source, for services answering many
+//! queries from the same few sources.
+//!
+use crate::dijkstra::{shortest_path_tree, DijkstraError, PathAndDist, ShortestPathTree};
+use crate::graph::Adjacency;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+
+/// Shortest path trees of the last `capacity` sources queried, evicting the
+/// least recently used one when a new source comes in.
+///
+/// The trees are kept behind an [`Arc`], so a hit hands out the kept tree
+/// without copying it, and the tree stays usable after the cache is unlocked
+/// or the tree is evicted.
+///
+/// The trees are only valid for the graph they were computed on. The cache
+/// forgets them by itself when the [`Adjacency::generation`] of the graph
+/// changes, e.g. after an edit through a [`GraphEditor`]. Call
+/// [`RouteCache::invalidate`] when a graph that does not count its changes,
+/// such as a plain [`Graph`], changes.
+///
+/// [`GraphEditor`]: crate::editor::GraphEditor
+/// [`Graph`]: crate::graph::Graph
+///
+/// # Example
+/// ```
+/// use djikstra::cache::RouteCache;
+/// use djikstra::editor::GraphEditor;
+/// use djikstra::graph::Graph;
+///
+/// let mut graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// let mut cache = RouteCache::new(8);
+/// assert_eq!(cache.shortest_path(&graph, 0, 2), Ok(Some((vec![0, 1, 2], 4))));
+/// // from the tree of 0 computed by the first query
+/// assert_eq!(cache.shortest_path(&graph, 0, 1), Ok(Some((vec![0, 1], 3))));
+/// assert_eq!((cache.hits(), cache.misses()), (1, 1));
+///
+/// graph.add_edge(0, 2, 1);
+/// cache.invalidate();
+/// assert_eq!(cache.shortest_path(&graph, 0, 2), Ok(Some((vec![0, 2], 1))));
+///
+/// // an editor counts its edits, so the cache notices them
+/// let mut editor = GraphEditor::new(graph);
+/// assert_eq!(cache.shortest_path(&editor, 0, 2), Ok(Some((vec![0, 2], 1))));
+/// editor.remove_edge(0, 2).unwrap();
+/// assert_eq!(cache.shortest_path(&editor, 0, 2), Ok(Some((vec![0, 1, 2], 4))));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RouteCache {
+    capacity: usize,
+    // the tree of every source with the tick it was last used at
+    trees: BTreeMap<usize, (u64, Arc<ShortestPathTree>)>,
+    // the generation of the graph the trees were computed on
+    generation: Option<u64>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl RouteCache {
+    /// Cache keeping the trees of up to `capacity` sources, none for 0.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            trees: BTreeMap::new(),
+            generation: None,
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Maximum number of trees kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of trees kept.
+    pub fn len(&self) -> usize {
+        self.trees.len()
+    }
+
+    /// Whether no tree is kept.
+    pub fn is_empty(&self) -> bool {
+        self.trees.is_empty()
+    }
+
+    /// Number of lookups answered from a kept tree.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of lookups of a source whose tree was not kept.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// The kept tree of `src` in `graph`, marking it as the most recently
+    /// used. Every tree is forgotten first if `graph` changed since they were
+    /// computed.
+    pub fn get(&mut self, graph: &impl Adjacency, src: usize) -> Option<Arc<ShortestPathTree>> {
+        self.sync(graph);
+        self.tick += 1;
+        match self.trees.get_mut(&src) {
+            Some((last_used, tree)) => {
+                self.hits += 1;
+                *last_used = self.tick;
+                Some(Arc::clone(tree))
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Keep `tree`, computed on `graph`, in place of the tree of the same
+    /// source or of the least recently used one if the cache is full, and
+    /// hand it back shared with the cache.
+    pub fn insert(
+        &mut self,
+        graph: &impl Adjacency,
+        tree: ShortestPathTree,
+    ) -> Arc<ShortestPathTree> {
+        self.sync(graph);
+        let tree = Arc::new(tree);
+        if self.capacity == 0 {
+            return tree;
+        }
+        let src = tree.source();
+        if !self.trees.contains_key(&src) && self.trees.len() == self.capacity {
+            let oldest = self
+                .trees
+                .iter()
+                .min_by_key(|(_src, (last_used, _tree))| *last_used)
+                .map(|(&src, _entry)| src);
+            if let Some(oldest) = oldest {
+                self.trees.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.trees.insert(src, (self.tick, Arc::clone(&tree)));
+        tree
+    }
+
+    /// The tree of `src`, computed on `graph` and kept unless it already was.
+    ///
+    /// # Errors
+    /// Fails if `src` is not a vertex of the graph, or if the distance to a
+    /// reachable vertex does not fit in a `usize`.
+    pub fn tree(
+        &mut self,
+        graph: &impl Adjacency,
+        src: usize,
+    ) -> Result<Arc<ShortestPathTree>, DijkstraError> {
+        if let Some(tree) = self.get(graph, src) {
+            return Ok(tree);
+        }
+        let tree = shortest_path_tree(graph, src)?;
+        Ok(self.insert(graph, tree))
+    }
+
+    /// Shortest path from `src` to `dst` and its distance, from the kept tree
+    /// of `src` or from a new one computed on `graph` and kept.
+    ///
+    /// # Errors
+    /// Fails if `src` is not a vertex of the graph, or if the distance to a
+    /// reachable vertex does not fit in a `usize`.
+    pub fn shortest_path(
+        &mut self,
+        graph: &impl Adjacency,
+        src: usize,
+        dst: usize,
+    ) -> Result<Option<PathAndDist>, DijkstraError> {
+        if let Some(tree) = self.get(graph, src) {
+            return Ok(path_in(&tree, dst));
+        }
+        let tree = shortest_path_tree(graph, src)?;
+        let path = path_in(&tree, dst);
+        self.insert(graph, tree);
+        Ok(path)
+    }
+
+    /// Forget the tree of `src`.
+    pub fn remove(&mut self, src: usize) {
+        self.trees.remove(&src);
+    }
+
+    /// Forget every tree, e.g. after the graph changed.
+    pub fn invalidate(&mut self) {
+        self.trees.clear();
+    }
+
+    /// Forget every tree if the generation of `graph` is not the one they
+    /// were computed on.
+    fn sync(&mut self, graph: &impl Adjacency) {
+        let generation = graph.generation();
+        if generation != self.generation {
+            self.trees.clear();
+            self.generation = generation;
+        }
+    }
+}
+
+/// Shortest path from the source of `tree` to `dst` and its distance.
+pub(crate) fn path_in(tree: &ShortestPathTree, dst: usize) -> Option<PathAndDist> {
+    let dist = tree.dist_to(dst)?;
+    Some((tree.path_to(dst)?.into_vertices(), dist))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cache::RouteCache;
+    use crate::dijkstra::{shortest_path, shortest_path_tree};
+    use crate::editor::GraphEditor;
+    use crate::graph::Graph;
+    use crate::test_utils::sample_graph;
+    use alloc::sync::Arc;
+    use alloc::vec;
+
+    #[test]
+    fn answers_like_the_algorithm() {
+        let g1 = sample_graph();
+        let mut cache = RouteCache::new(3);
+        for _ in 0..2 {
+            for src in 0..g1.n_vertices() {
+                for dst in 0..g1.n_vertices() {
+                    let expected = shortest_path(&g1, src, dst).unwrap();
+                    let cached = cache.shortest_path(&g1, src, dst).unwrap();
+                    assert_eq!(cached.map(|p| p.1), expected.map(|p| p.1));
+                }
+            }
+        }
+        // one miss every time a source comes back after being evicted
+        assert_eq!(cache.misses(), 2 * g1.n_vertices() as u64);
+        assert_eq!(cache.len(), 3);
+        assert_eq!(
+            cache.tree(&g1, 7).as_deref(),
+            shortest_path_tree(&g1, 7).as_ref()
+        );
+        assert!(cache.shortest_path(&g1, 8, 0).is_err());
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used() {
+        let graph = Graph::new(vec![vec![(1, 1)], vec![(2, 1)], vec![(0, 1)]]);
+        let mut cache = RouteCache::new(2);
+        let first = cache.tree(&graph, 0).unwrap();
+        cache.tree(&graph, 1).unwrap();
+        // a hit shares the kept tree instead of copying it
+        assert!(Arc::ptr_eq(&cache.tree(&graph, 0).unwrap(), &first));
+        // 1 is the least recently used
+        cache.tree(&graph, 2).unwrap();
+        assert!(cache.get(&graph, 1).is_none());
+        assert!(cache.get(&graph, 0).is_some());
+        assert!(cache.get(&graph, 2).is_some());
+
+        cache.remove(0);
+        assert!(cache.get(&graph, 0).is_none());
+        cache.invalidate();
+        assert!(cache.is_empty());
+
+        let mut disabled = RouteCache::new(0);
+        assert_eq!(
+            disabled.shortest_path(&graph, 0, 2),
+            Ok(Some((vec![0, 1, 2], 2)))
+        );
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn edits_invalidate_the_trees() {
+        let mut editor = GraphEditor::new(sample_graph());
+        let mut cache = RouteCache::new(8);
+        for src in 0..editor.graph().n_vertices() {
+            cache.tree(&editor, src).unwrap();
+        }
+        assert!(cache.get(&editor, 0).is_some());
+
+        editor.set_weight(0, 6, 9).unwrap();
+        assert!(cache.get(&editor, 0).is_none());
+        assert!(cache.is_empty());
+        assert_eq!(
+            cache.shortest_path(&editor, 0, 6),
+            shortest_path(editor.graph(), 0, 6)
+        );
+
+        // undoing is a change as well, even back to a graph seen before
+        editor.undo();
+        assert_eq!(
+            cache.shortest_path(&editor, 0, 6),
+            Ok(Some((vec![0, 6], 2)))
+        );
+        assert_eq!(cache.len(), 1);
+    }
+}
@@ -0,0 +1,226 @@
+//! C interface to the graph and the algorithm, declared in `include/djikstra.h`.
+//!
+//! Graphs and results are opaque pointers owned by the caller, who frees them
+//! with [`graph_free`] and [`result_free`]. The `djikstra-capi` crate of the
+//! workspace exports them from a shared and a static library, built with
+//! `cargo build --release -p djikstra-capi`.
+//!
+//! No panic unwinds into the caller: the functions return `NULL`, 0 or
+//! `false` instead.
+use crate::dijkstra::{shortest_paths, DijkstraResult};
+use crate::graph::Graph;
+use alloc::boxed::Box;
+use core::ffi::{c_char, CStr};
+use core::ptr;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Why [`graph_parse`] returned `NULL`, or [`Status::Ok`] if it did not.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// The graph was parsed.
+    Ok = 0,
+    /// The input is `NULL`.
+    NullInput = 1,
+    /// The input is not UTF-8.
+    InvalidUtf8 = 2,
+    /// The input is not in the input format.
+    ParseError = 3,
+    /// An edge leads to a vertex past the number of vertices.
+    VertexOutOfRange = 4,
+    /// The library panicked, which is a bug.
+    Panic = 5,
+}
+
+/// Run `body`, or return `default` if it panics rather than unwinding
+/// into the C caller, which is undefined behavior.
+fn guard<T>(default: T, body: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(body)).unwrap_or(default)
+}
+
+/// Parse a graph in the input format of the command line from a NUL-terminated string.
+///
+/// Returns `NULL` if `input` is `NULL`, is not UTF-8, cannot be parsed, or
+/// has an edge to a vertex past the number of vertices. Writes why to
+/// `status` unless it is `NULL`, or [`Status::Ok`] on success.
+///
+/// # Safety
+/// `input` must be `NULL` or point to a NUL-terminated string, and `status`
+/// must be `NULL` or point to a writable [`Status`].
+#[no_mangle]
+pub unsafe extern "C" fn graph_parse(input: *const c_char, status: *mut Status) -> *mut Graph {
+    let parsed = guard(Err(Status::Panic), || {
+        if input.is_null() {
+            return Err(Status::NullInput);
+        }
+        let input = CStr::from_ptr(input)
+            .to_str()
+            .map_err(|_e| Status::InvalidUtf8)?;
+        let graph: Graph = input.parse().map_err(|_e| Status::ParseError)?;
+        graph
+            .check_targets()
+            .map_err(|_e| Status::VertexOutOfRange)?;
+        Ok(graph)
+    });
+    if !status.is_null() {
+        *status = parsed.as_ref().err().copied().unwrap_or(Status::Ok);
+    }
+    parsed.map_or(ptr::null_mut(), |graph| Box::into_raw(Box::new(graph)))
+}
+
+/// Free a graph returned by [`graph_parse`]. Does nothing if `graph` is `NULL`.
+///
+/// # Safety
+/// `graph` must be `NULL` or returned by [`graph_parse`], and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn graph_free(graph: *mut Graph) {
+    guard((), || {
+        if !graph.is_null() {
+            drop(Box::from_raw(graph));
+        }
+    })
+}
+
+/// Number of vertices of the graph.
+///
+/// # Safety
+/// `graph` must be returned by [`graph_parse`], and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn graph_n_vertices(graph: *const Graph) -> usize {
+    guard(0, || (*graph).n_vertices())
+}
+
+/// Shortest paths from `src` to every vertex of the graph.
+///
+/// Returns `NULL` if `src` is not a vertex of the graph, or if the distance to
+/// a reachable vertex does not fit in a `size_t`.
+///
+/// # Safety
+/// `graph` must be returned by [`graph_parse`], and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn dijkstra_run(graph: *const Graph, src: usize) -> *mut DijkstraResult {
+    guard(ptr::null_mut(), || match shortest_paths(&*graph, src) {
+        Ok(result) => Box::into_raw(Box::new(result)),
+        Err(_) => ptr::null_mut(),
+    })
+}
+
+/// Free a result returned by [`dijkstra_run`]. Does nothing if `result` is `NULL`.
+///
+/// # Safety
+/// `result` must be `NULL` or returned by [`dijkstra_run`], and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn result_free(result: *mut DijkstraResult) {
+    guard((), || {
+        if !result.is_null() {
+            drop(Box::from_raw(result));
+        }
+    })
+}
+
+/// The vertex the paths start from.
+///
+/// # Safety
+/// `result` must be returned by [`dijkstra_run`], and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn result_source(result: *const DijkstraResult) -> usize {
+    guard(0, || (*result).source())
+}
+
+/// Write the distance from the source to `v` to `dist` and return `true`, or
+/// return `false` without writing if `v` is unreachable or not in the graph.
+///
+/// # Safety
+/// `result` must be returned by [`dijkstra_run`], and not freed yet, and
+/// `dist` must point to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn result_dist(
+    result: *const DijkstraResult,
+    v: usize,
+    dist: *mut usize,
+) -> bool {
+    guard(false, || match (*result).dist_to(v) {
+        Some(d) => {
+            *dist = d;
+            true
+        }
+        None => false,
+    })
+}
+
+/// Number of vertices on the shortest path from the source to `v`, both
+/// included, or 0 if `v` is unreachable or not in the graph.
+///
+/// # Safety
+/// `result` must be returned by [`dijkstra_run`], and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn result_path_len(result: *const DijkstraResult, v: usize) -> usize {
+    guard(0, || {
+        (*result).path_to(v).map_or(0, |path| path.vertices().len())
+    })
+}
+
+/// Vertices on the shortest path from the source to `v`, [`result_path_len`]
+/// of them, or `NULL` if `v` is unreachable or not in the graph.
+///
+/// The vertices belong to the result and are valid until it is freed.
+///
+/// # Safety
+/// `result` must be returned by [`dijkstra_run`], and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn result_path(result: *const DijkstraResult, v: usize) -> *const usize {
+    guard(ptr::null(), || {
+        (*result)
+            .path_to(v)
+            .map_or(ptr::null(), |path| path.vertices().as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::capi::*;
+    use core::slice;
+
+    #[test]
+    fn runs_through_pointers() {
+        unsafe {
+            let graph = graph_parse(c"3\n1,3\n2,1\n\n".as_ptr(), ptr::null_mut());
+            assert!(!graph.is_null());
+            assert_eq!(graph_n_vertices(graph), 3);
+            assert!(dijkstra_run(graph, 3).is_null());
+
+            let result = dijkstra_run(graph, 1);
+            assert_eq!(result_source(result), 1);
+            let mut dist = 0;
+            assert!(result_dist(result, 2, &mut dist));
+            assert_eq!(dist, 1);
+            assert!(!result_dist(result, 0, &mut dist));
+            assert_eq!(result_path_len(result, 0), 0);
+            assert!(result_path(result, 0).is_null());
+            let path = slice::from_raw_parts(result_path(result, 2), result_path_len(result, 2));
+            assert_eq!(path, [1, 2]);
+
+            result_free(result);
+            graph_free(graph);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        unsafe {
+            let mut status = Status::Ok;
+            assert!(graph_parse(ptr::null(), &mut status).is_null());
+            assert_eq!(status, Status::NullInput);
+            assert!(graph_parse(c"three\n".as_ptr(), &mut status).is_null());
+            assert_eq!(status, Status::ParseError);
+            assert!(graph_parse(c"2\n9,1\n\n".as_ptr(), &mut status).is_null());
+            assert_eq!(status, Status::VertexOutOfRange);
+            let graph = graph_parse(c"2\n1,1\n\n".as_ptr(), &mut status);
+            assert!(!graph.is_null());
+            assert_eq!(status, Status::Ok);
+            graph_free(graph);
+            graph_free(ptr::null_mut());
+            result_free(ptr::null_mut());
+        }
+    }
+}
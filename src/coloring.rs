@@ -0,0 +1,184 @@
+//! Greedy vertex coloring, giving every vertex a color different from the
+//! colors of its neighbors, e.g. to schedule conflicting tasks in different
+//! time slots or to allocate registers.
+//!
+//! Edge directions are ignored, since a conflict goes both ways, and so are
+//! self loops, which no coloring could satisfy.
+//!
+use crate::graph::Graph;
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+/// The order [`greedy`] colors the vertices in. The number of colors used
+/// depends on it, none is best on every graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// By increasing vertex.
+    Index,
+    /// By decreasing degree (Welsh-Powell), so that the vertices with the
+    /// most constraints get the first colors.
+    LargestFirst,
+    /// Every vertex before the ones left after removing it as a vertex of
+    /// smallest degree. Colors a graph in at most one more color than the
+    /// degeneracy, so trees and forests in 2 and planar graphs in 6.
+    SmallestLast,
+}
+
+/// Color the vertices of `graph` one at a time in `order`, each with the
+/// smallest color none of its neighbors already has. Returns the color
+/// classes, the vertices of every color in increasing order, with the colors
+/// numbered from 0 in the order they are first used.
+///
+/// # Example
+/// ```
+/// use djikstra::coloring::{greedy, Order};
+/// use djikstra::graph::Graph;
+///
+/// // a triangle with a pendant vertex
+/// let graph = Graph::new(vec![vec![(1, 1), (2, 1)], vec![(2, 1)], vec![(3, 1)], vec![]]);
+/// assert_eq!(greedy(&graph, Order::Index), vec![vec![0, 3], vec![1], vec![2]]);
+/// assert_eq!(greedy(&graph, Order::LargestFirst), vec![vec![2], vec![0, 3], vec![1]]);
+/// ```
+pub fn greedy(graph: &Graph, order: Order) -> Vec<Vec<usize>> {
+    let n_vertices = graph.n_vertices();
+    let undirected = graph.to_undirected();
+    let neighbors: Vec<Vec<usize>> = (0..n_vertices)
+        .map(|u| {
+            undirected
+                .neighbors_of(u)
+                .iter()
+                .map(|&(v, _weight)| v)
+                .filter(|&v| v != u)
+                .collect()
+        })
+        .collect();
+
+    let mut colors: Vec<Option<usize>> = vec![None; n_vertices];
+    let mut classes: Vec<Vec<usize>> = vec![];
+    // the colors of the neighbors of the vertex being colored
+    let mut taken = vec![false; n_vertices];
+    for u in vertex_order(&neighbors, order) {
+        for &v in neighbors[u].iter() {
+            if let Some(color) = colors[v] {
+                taken[color] = true;
+            }
+        }
+        let color = (0..).find(|&color| !taken[color]).unwrap();
+        for &v in neighbors[u].iter() {
+            if let Some(color) = colors[v] {
+                taken[color] = false;
+            }
+        }
+
+        colors[u] = Some(color);
+        if color == classes.len() {
+            classes.push(vec![]);
+        }
+        classes[color].push(u);
+    }
+    for class in classes.iter_mut() {
+        class.sort_unstable();
+    }
+    classes
+}
+
+/// The vertices in `order`, given the neighbors of every vertex.
+fn vertex_order(neighbors: &[Vec<usize>], order: Order) -> Vec<usize> {
+    let mut vertices: Vec<usize> = (0..neighbors.len()).collect();
+    match order {
+        Order::Index => {}
+        // stable, so ties stay by increasing vertex
+        Order::LargestFirst => vertices.sort_by_key(|&v| Reverse(neighbors[v].len())),
+        Order::SmallestLast => {
+            let mut degrees: Vec<usize> = neighbors.iter().map(Vec::len).collect();
+            let mut left: BTreeSet<(usize, usize)> =
+                vertices.iter().map(|&v| (degrees[v], v)).collect();
+            vertices.clear();
+            while let Some((_degree, u)) = left.pop_first() {
+                vertices.push(u);
+                for &v in neighbors[u].iter() {
+                    if left.remove(&(degrees[v], v)) {
+                        degrees[v] -= 1;
+                        left.insert((degrees[v], v));
+                    }
+                }
+            }
+            vertices.reverse();
+        }
+    }
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coloring::{greedy, Order};
+    use crate::graph::Graph;
+    use crate::test_utils::sample_graph;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    const ORDERS: [Order; 3] = [Order::Index, Order::LargestFirst, Order::SmallestLast];
+
+    /// Whether `classes` hold every vertex of `graph` once, with no edge
+    /// inside a class except self loops.
+    fn is_coloring(graph: &Graph, classes: &[Vec<usize>]) -> bool {
+        let mut colors = vec![None; graph.n_vertices()];
+        for (color, class) in classes.iter().enumerate() {
+            for &v in class.iter() {
+                if colors[v].replace(color).is_some() {
+                    return false;
+                }
+            }
+        }
+        (0..graph.n_vertices()).all(|u| {
+            colors[u].is_some()
+                && graph
+                    .neighbors_of(u)
+                    .iter()
+                    .all(|&(v, _weight)| u == v || colors[u] != colors[v])
+        })
+    }
+
+    #[test]
+    fn colorings_are_proper() {
+        let g1 = sample_graph();
+        let with_loop = Graph::new(vec![vec![(0, 1), (1, 1)], vec![]]);
+        for order in ORDERS {
+            assert!(is_coloring(&g1, &greedy(&g1, order)));
+            assert_eq!(greedy(&with_loop, order).len(), 2);
+            assert!(greedy(&Graph::new(vec![]), order).is_empty());
+        }
+    }
+
+    #[test]
+    fn orders_matter() {
+        // a crown graph: u_i = 2i is joined to v_j = 2j + 1 for all i != j,
+        // so that coloring by index needs a new color for every pair
+        let k = 5;
+        let mut crown = Graph::new(vec![vec![]; 2 * k]);
+        for i in 0..k {
+            for j in (0..k).filter(|&j| j != i) {
+                crown.add_edge(2 * i, 2 * j + 1, 1);
+            }
+        }
+        assert_eq!(greedy(&crown, Order::Index).len(), k);
+        assert!(is_coloring(&crown, &greedy(&crown, Order::SmallestLast)));
+
+        // a tree, which smallest last colors in 2
+        let tree = Graph::new(vec![
+            vec![(1, 1), (2, 1), (3, 1)],
+            vec![(4, 1)],
+            vec![],
+            vec![(5, 1), (6, 1)],
+            vec![],
+            vec![(7, 1)],
+            vec![],
+            vec![],
+        ]);
+        let classes = greedy(&tree, Order::SmallestLast);
+        assert_eq!(classes.len(), 2);
+        assert!(is_coloring(&tree, &classes));
+    }
+}
@@ -0,0 +1,144 @@
+//! Graph stored as a single flat arena of edges, in compressed sparse row form.
+//!
+use crate::graph::{
+    count_edges, parse_header, parse_neighbors, Adjacency, Graph, IndexType, ParseGraphError,
+    WeightType,
+};
+use crate::trace;
+use alloc::vec::Vec;
+use core::{mem, str::FromStr};
+
+/// Graph whose edges are stored one vertex after the other in a single
+/// vector, with the range of the edges of every vertex, so that it takes two
+/// allocations whatever its number of vertices and its edges are contiguous.
+///
+/// It cannot be edited, convert it to a [`Graph`] with [`CsrGraph::to_graph`]
+/// and back to change it. The algorithms of the `dijkstra` module run on both.
+///
+/// # Example
+/// ```
+/// use djikstra::csr::CsrGraph;
+/// use djikstra::dijkstra::dijkstra;
+/// use djikstra::graph::Graph;
+///
+/// let graph: CsrGraph = "3\n1,3\n2,1\n\n".parse().unwrap();
+/// assert_eq!(graph.neighbors_of(1), &[(2, 1)]);
+/// assert_eq!(dijkstra(&graph, 0), dijkstra(&graph.to_graph(), 0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrGraph<Ix = usize, W = usize> {
+    // the edges of vertex `v` are `edges[offsets[v]..offsets[v + 1]]`
+    offsets: Vec<usize>,
+    edges: Vec<(Ix, W)>,
+}
+
+impl<Ix: IndexType, W: WeightType> CsrGraph<Ix, W> {
+    /// Number of vertices
+    pub fn n_vertices(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    /// Number of edges
+    pub fn n_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Get neighbors of a vertex
+    pub fn neighbors_of(&self, vertex: usize) -> &[(Ix, W)] {
+        &self.edges[self.offsets[vertex]..self.offsets[vertex + 1]]
+    }
+
+    /// Number of bytes the graph holds on the heap.
+    pub fn memory_usage(&self) -> usize {
+        self.offsets.capacity() * mem::size_of::<usize>()
+            + self.edges.capacity() * mem::size_of::<(Ix, W)>()
+    }
+
+    /// The same graph as adjacency lists.
+    pub fn to_graph(&self) -> Graph<Ix, W> {
+        Graph::from_weighted_adjacency(
+            (0..self.n_vertices())
+                .map(|v| self.neighbors_of(v).to_vec())
+                .collect(),
+        )
+    }
+}
+
+impl<Ix: IndexType, W: WeightType> From<&Graph<Ix, W>> for CsrGraph<Ix, W> {
+    fn from(graph: &Graph<Ix, W>) -> Self {
+        let mut offsets = Vec::with_capacity(graph.n_vertices() + 1);
+        let mut edges = Vec::with_capacity(graph.n_edges());
+        offsets.push(0);
+        for neighbors in graph.adj.iter() {
+            edges.extend_from_slice(neighbors);
+            offsets.push(edges.len());
+        }
+        Self { offsets, edges }
+    }
+}
+
+impl<Ix: IndexType, W: WeightType> Adjacency for CsrGraph<Ix, W> {
+    type Index = Ix;
+    type Weight = W;
+
+    fn n_vertices(&self) -> usize {
+        CsrGraph::n_vertices(self)
+    }
+
+    fn neighbors_of(&self, vertex: usize) -> &[(Ix, W)] {
+        CsrGraph::neighbors_of(self, vertex)
+    }
+}
+
+impl FromStr for CsrGraph {
+    type Err = ParseGraphError;
+
+    /// Parse a string in the input format of [`Graph`] straight into the
+    /// arena, without building the adjacency lists.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let _span = trace::span!("parse");
+        let (n_vertex, lines) = parse_header(s)?;
+
+        let mut offsets = Vec::with_capacity(n_vertex + 1);
+        let mut edges = Vec::with_capacity(count_edges(lines));
+        offsets.push(0);
+        for neighbors in lines.lines().take(n_vertex) {
+            parse_neighbors(neighbors, &mut edges)?;
+            offsets.push(edges.len());
+        }
+        // the vertices without a line have no edges
+        offsets.resize(n_vertex + 1, edges.len());
+
+        Ok(Self { offsets, edges })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::csr::CsrGraph;
+    use crate::dijkstra::dijkstra;
+    use crate::graph::Graph;
+    use crate::test_utils::sample_graph;
+
+    #[test]
+    fn parses_like_the_adjacency_lists() {
+        let input = "4\n2,3 1,3\n0,3\n\n";
+        let graph: Graph = input.parse().unwrap();
+        let csr: CsrGraph = input.parse().unwrap();
+        assert_eq!(csr, CsrGraph::from(&graph));
+        assert_eq!(csr.to_graph(), graph);
+        assert_eq!((csr.n_vertices(), csr.n_edges()), (4, 3));
+        assert_eq!(csr.neighbors_of(3), &[]);
+        assert!("2\n1,x\n".parse::<CsrGraph>().is_err());
+    }
+
+    #[test]
+    fn runs_like_the_adjacency_lists() {
+        let graph = sample_graph();
+        let csr = CsrGraph::from(&graph.to_index_type::<u32>().unwrap());
+        for src in 0..graph.n_vertices() {
+            assert_eq!(dijkstra(&csr, src), dijkstra(&graph, src));
+        }
+        assert!(csr.memory_usage() < graph.memory_usage());
+    }
+}
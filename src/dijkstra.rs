@@ -0,0 +1,2555 @@
+// Dijkstra algorithm !!!
+// uses Graph and PriorityQueue
+
+use crate::bitset::BitSet;
+use crate::graph::{Adjacency, IndexType, WeightType};
+use crate::path::Path;
+use crate::pq::{MinPriorityQueue, PriorityQueue};
+use crate::trace;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::{FusedIterator, Rev};
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::{error, fmt, mem};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+/// The error type returned when the algorithm cannot run on a graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DijkstraError {
+    /// The source is not a vertex of the graph.
+    InvalidSource { source: usize, n_vertices: usize },
+    /// The graph has no vertices, so there is no source to start from.
+    EmptyGraph,
+    /// A distance is too large to fit in a `usize`.
+    Overflow,
+}
+
+impl fmt::Display for DijkstraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DijkstraError::InvalidSource { source, n_vertices } => write!(
+                f,
+                "source {} must be below {}, the number of vertices",
+                source, n_vertices
+            ),
+            DijkstraError::EmptyGraph => write!(f, "the graph has no vertices"),
+            DijkstraError::Overflow => write!(f, "a distance is too large to fit in a usize"),
+        }
+    }
+}
+
+impl error::Error for DijkstraError {}
+
+/// Check that `src` is a vertex of the graph.
+pub(crate) fn check_source(graph: &impl Adjacency, src: usize) -> Result<(), DijkstraError> {
+    match graph.n_vertices() {
+        0 => Err(DijkstraError::EmptyGraph),
+        n_vertices if src >= n_vertices => Err(DijkstraError::InvalidSource {
+            source: src,
+            n_vertices,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Distance `dist_src + weight` of a vertex through an edge of `weight`, or
+/// `None` if it does not fit in a `usize`. `usize::MAX` itself is left for
+/// the vertices that are not reached yet.
+///
+/// An overflowing distance is longer than any other, so it never is the
+/// shortest one unless the vertex cannot be reached any other way.
+pub(crate) fn add_distance(dist_src: usize, weight: usize) -> Option<usize> {
+    dist_src
+        .checked_add(weight)
+        .filter(|&dist| dist != usize::MAX)
+}
+
+/// Dijkstra algorithm that takes in a graph and a source node!
+/// Returns a list of paths
+///
+/// A thin wrapper around [`shortest_paths`] for callers that want the paths and
+/// distances as they are rather than a [`DijkstraResult`].
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::{dijkstra, DijkstraError};
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![]]);
+/// let (paths, dists) = dijkstra(&graph, 0).unwrap();
+/// assert_eq!(dists, vec![Some(0), Some(3)]);
+/// assert_eq!(
+///     dijkstra(&graph, 2),
+///     Err(DijkstraError::InvalidSource { source: 2, n_vertices: 2 })
+/// );
+/// ```
+pub fn dijkstra(graph: &impl Adjacency, src: usize) -> Result<PathsAndDists, DijkstraError> {
+    shortest_paths(graph, src).map(DijkstraResult::into_parts)
+}
+
+/// Path to every vertex from the source and the distance to every vertex,
+/// both `None` if the vertex is unreachable.
+pub type PathsAndDists = (Vec<Option<Vec<usize>>>, Vec<Option<usize>>);
+
+/// Path from a source to a destination and its distance.
+pub type PathAndDist = (Vec<usize>, usize);
+
+/// Shortest paths from `src` to every vertex of the graph.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::shortest_paths;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![], vec![]]);
+/// let result = shortest_paths(&graph, 0).unwrap();
+/// assert_eq!(result.dist_to(2), Some(4));
+/// let path = result.path_to(2).unwrap();
+/// assert_eq!(path.vertices(), &[0, 1, 2]);
+/// assert_eq!(path.edges().collect::<Vec<_>>(), vec![(0, 1, 3), (1, 2, 1)]);
+/// assert_eq!(result.dist_to(3), None);
+/// ```
+pub fn shortest_paths(graph: &impl Adjacency, src: usize) -> Result<DijkstraResult, DijkstraError> {
+    let (paths, dists) = dijkstra_with::<PriorityQueue<usize>>(graph, src)?;
+    Ok(DijkstraResult::new(src, paths, dists))
+}
+
+/// Shortest paths from `src` to every vertex of the graph using the priority
+/// queue backend `Q`, with the counts of what the search did.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::shortest_paths_with_stats;
+/// use djikstra::graph::Graph;
+/// use djikstra::pq::BinaryHeapQueue;
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 5)], vec![(2, 1)], vec![]]);
+/// let result = shortest_paths_with_stats::<BinaryHeapQueue<usize>>(&graph, 0).unwrap();
+/// let stats = result.stats().unwrap();
+/// assert_eq!((stats.settled, stats.relaxations, stats.decrease_keys), (3, 3, 3));
+/// assert_eq!(stats.max_frontier, 2);
+/// ```
+pub fn shortest_paths_with_stats<Q>(
+    graph: &impl Adjacency,
+    src: usize,
+) -> Result<DijkstraResult, DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+{
+    check_source(graph, src)?;
+    let mut workspace = DijkstraWorkspace::default();
+    search_in::<Q, _>(graph, src, &mut workspace, |_v, _dist, _parent| true)?;
+    let (paths, dists) = paths_and_dists(&workspace.parents, src, &workspace.dists_from_src);
+    Ok(DijkstraResult {
+        stats: Some(workspace.stats),
+        ..DijkstraResult::new(src, paths, dists)
+    })
+}
+
+/// Counts of what a search did, to compare the work of queue backends or of
+/// searches on different graphs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchStats {
+    /// Vertices whose distance became final.
+    pub settled: usize,
+    /// Edges followed from a settled vertex to a vertex that was not.
+    pub relaxations: usize,
+    /// Times a relaxation lowered the distance of a vertex, and its key in the queue.
+    pub decrease_keys: usize,
+    /// Largest number of vertices reached but not settled at the same time.
+    pub max_frontier: usize,
+}
+
+/// Shortest paths from a source to every vertex of a graph, as found by
+/// [`shortest_paths`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DijkstraResult {
+    source: usize,
+    parents: Vec<Option<usize>>,
+    paths: Vec<Option<Path>>,
+    dists: Vec<Option<usize>>,
+    stats: Option<SearchStats>,
+}
+
+impl DijkstraResult {
+    /// Results from the paths and distances returned by the `dijkstra_*` functions
+    /// for the source `source`.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::dijkstra::{dijkstra_with, DijkstraResult};
+    /// use djikstra::graph::Graph;
+    /// use djikstra::pq::BinaryHeapQueue;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3)], vec![]]);
+    /// let (paths, dists) = dijkstra_with::<BinaryHeapQueue<usize>>(&graph, 0).unwrap();
+    /// let result = DijkstraResult::new(0, paths, dists);
+    /// assert_eq!(result.dist_to(1), Some(3));
+    /// ```
+    pub fn new(source: usize, paths: Vec<Option<Vec<usize>>>, dists: Vec<Option<usize>>) -> Self {
+        let parents = paths
+            .iter()
+            .map(|path| {
+                let path = path.as_ref()?;
+                path.len().checked_sub(2).map(|i| path[i])
+            })
+            .collect();
+        let paths = paths
+            .into_iter()
+            .map(|path| path.map(|vertices| Path::from_distances(vertices, &dists)))
+            .collect();
+        Self {
+            source,
+            parents,
+            paths,
+            dists,
+            stats: None,
+        }
+    }
+
+    /// The vertex the paths start from.
+    pub fn source(&self) -> usize {
+        self.source
+    }
+
+    /// Distance from the source to `v`, `None` if `v` is unreachable or not in the graph.
+    pub fn dist_to(&self, v: usize) -> Option<usize> {
+        self.dists.get(v).copied().flatten()
+    }
+
+    /// What the search did to find the paths, only recorded by
+    /// [`shortest_paths_with_stats`].
+    pub fn stats(&self) -> Option<SearchStats> {
+        self.stats
+    }
+
+    /// Shortest path from the source to `v`, `None` if `v` is unreachable or
+    /// not in the graph.
+    pub fn path_to(&self, v: usize) -> Option<&Path> {
+        self.paths.get(v)?.as_ref()
+    }
+
+    /// Vertices on the shortest path from `v` back to the source, following the
+    /// parents without building the path. `None` if `v` is unreachable or not
+    /// in the graph.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::dijkstra::shortest_paths;
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+    /// let result = shortest_paths(&graph, 0).unwrap();
+    /// assert_eq!(result.iter_path(2).unwrap().collect::<Vec<_>>(), vec![2, 1, 0]);
+    /// assert_eq!(result.iter_path(2).unwrap().from_source().collect::<Vec<_>>(), vec![0, 1, 2]);
+    /// ```
+    pub fn iter_path(&self, v: usize) -> Option<PathIter<'_>> {
+        self.dist_to(v)?;
+        Some(PathIter {
+            parents: &self.parents,
+            next: Some(v),
+        })
+    }
+
+    /// Every reachable vertex with its distance and path, in order of the vertices.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::dijkstra::shortest_paths;
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph = Graph::new(vec![vec![(2, 5)], vec![], vec![]]);
+    /// let result = shortest_paths(&graph, 0).unwrap();
+    /// let reachable: Vec<_> = result.reachable().map(|(v, dist, _path)| (v, dist)).collect();
+    /// assert_eq!(reachable, vec![(0, 0), (2, 5)]);
+    /// ```
+    pub fn reachable(&self) -> impl Iterator<Item = (usize, usize, &Path)> + '_ {
+        self.paths
+            .iter()
+            .enumerate()
+            .filter_map(|(v, path)| Some((v, self.dists[v]?, path.as_ref()?)))
+    }
+
+    /// Number of bytes the result holds on the heap, the paths included.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::dijkstra::{shortest_path_tree, shortest_paths};
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+    /// let result = shortest_paths(&graph, 0).unwrap();
+    /// let tree = shortest_path_tree(&graph, 0).unwrap();
+    /// assert!(tree.memory_usage() < result.memory_usage());
+    /// ```
+    pub fn memory_usage(&self) -> usize {
+        let paths: usize = self.paths.iter().flatten().map(Path::memory_usage).sum();
+        self.parents.capacity() * mem::size_of::<Option<usize>>()
+            + self.paths.capacity() * mem::size_of::<Option<Path>>()
+            + paths
+            + self.dists.capacity() * mem::size_of::<Option<usize>>()
+    }
+
+    /// The paths and distances, as returned by [`dijkstra`].
+    pub fn into_parts(self) -> PathsAndDists {
+        let paths = self
+            .paths
+            .into_iter()
+            .map(|path| path.map(Path::into_vertices))
+            .collect();
+        (paths, self.dists)
+    }
+}
+
+/// Shortest path tree from a source, as found by [`shortest_path_tree`]: the
+/// parent and the distance of every vertex, without the paths.
+///
+/// Takes memory proportional to the number of vertices, where the paths of
+/// [`DijkstraResult`] can take up to its square. Paths are built on demand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShortestPathTree {
+    source: usize,
+    parents: Vec<Option<usize>>,
+    dists: Vec<Option<usize>>,
+}
+
+impl ShortestPathTree {
+    /// The vertex the paths start from.
+    pub fn source(&self) -> usize {
+        self.source
+    }
+
+    /// The vertex before `v` on the shortest path from the source, `None`
+    /// for the source, and for vertices that are unreachable or not in the graph.
+    pub fn parent(&self, v: usize) -> Option<usize> {
+        self.parents.get(v).copied().flatten()
+    }
+
+    /// Distance from the source to `v`, `None` if `v` is unreachable or not in the graph.
+    pub fn dist_to(&self, v: usize) -> Option<usize> {
+        self.dists.get(v).copied().flatten()
+    }
+
+    /// Vertices on the shortest path from `v` back to the source, following the
+    /// parents. `None` if `v` is unreachable or not in the graph.
+    pub fn iter_path(&self, v: usize) -> Option<PathIter<'_>> {
+        self.dist_to(v)?;
+        Some(PathIter {
+            parents: &self.parents,
+            next: Some(v),
+        })
+    }
+
+    /// Shortest path from the source to `v`, following the parents back from
+    /// `v`. `None` if `v` is unreachable or not in the graph.
+    pub fn path_to(&self, v: usize) -> Option<Path> {
+        self.dist_to(v)?;
+        let vertices = build_path(&self.parents, self.source, v)?;
+        Some(Path::from_distances(vertices, &self.dists))
+    }
+
+    /// Number of bytes the tree holds on the heap.
+    pub fn memory_usage(&self) -> usize {
+        (self.parents.capacity() + self.dists.capacity()) * mem::size_of::<Option<usize>>()
+    }
+
+    /// The parent and the distance of every vertex.
+    pub fn into_parts(self) -> (Vec<Option<usize>>, Vec<Option<usize>>) {
+        (self.parents, self.dists)
+    }
+}
+
+/// Vertices of a shortest path from its last vertex back to the source, as
+/// returned by [`DijkstraResult::iter_path`] and [`ShortestPathTree::iter_path`].
+#[derive(Debug, Clone)]
+pub struct PathIter<'a> {
+    parents: &'a [Option<usize>],
+    next: Option<usize>,
+}
+
+impl PathIter<'_> {
+    /// The same vertices from the source to the last vertex, collected first
+    /// as the parents only lead back to the source.
+    pub fn from_source(self) -> Rev<vec::IntoIter<usize>> {
+        self.collect::<Vec<_>>().into_iter().rev()
+    }
+}
+
+impl Iterator for PathIter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let v = self.next?;
+        self.next = self.parents[v];
+        Some(v)
+    }
+}
+
+impl FusedIterator for PathIter<'_> {}
+
+/// Shortest path tree from a source to every vertex of a graph, keeping only
+/// the parent and the distance of every vertex, for graphs too large to
+/// store every path.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::shortest_path_tree;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![], vec![]]);
+/// let tree = shortest_path_tree(&graph, 0).unwrap();
+/// assert_eq!(tree.parent(2), Some(1));
+/// assert_eq!(tree.dist_to(2), Some(4));
+/// assert_eq!(tree.path_to(2).unwrap().vertices(), &[0, 1, 2]);
+/// assert_eq!(tree.path_to(3), None);
+/// ```
+pub fn shortest_path_tree(
+    graph: &impl Adjacency,
+    src: usize,
+) -> Result<ShortestPathTree, DijkstraError> {
+    check_source(graph, src)?;
+    let (parents, dists_from_src) =
+        search::<PriorityQueue<usize>, _>(graph, src, |_v, _dist, _parent| true)?;
+    let dists = dists_from_src
+        .into_iter()
+        .map(|dist| (dist != usize::MAX).then_some(dist))
+        .collect();
+    Ok(ShortestPathTree {
+        source: src,
+        parents,
+        dists,
+    })
+}
+
+/// Dijkstra algorithm using the priority queue backend `Q`.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra_with;
+/// use djikstra::graph::Graph;
+/// use djikstra::pq::BinaryHeapQueue;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(0, 3)]]);
+/// let (paths, dists) = dijkstra_with::<BinaryHeapQueue<usize>>(&graph, 0).unwrap();
+/// ```
+pub fn dijkstra_with<Q>(graph: &impl Adjacency, src: usize) -> Result<PathsAndDists, DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+{
+    dijkstra_with_progress::<Q, _>(graph, src, |_| {})
+}
+
+/// Dijkstra algorithm using the priority queue backend `Q` that calls
+/// `on_settle` with every vertex as soon as its distance is final,
+/// e.g. to report progress on large graphs.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra_with_progress;
+/// use djikstra::graph::Graph;
+/// use djikstra::pq::PriorityQueue;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(0, 3)]]);
+/// let mut settled = 0;
+/// dijkstra_with_progress::<PriorityQueue<usize>, _>(&graph, 0, |_vertex| settled += 1).unwrap();
+/// ```
+pub fn dijkstra_with_progress<Q, F>(
+    graph: &impl Adjacency,
+    src: usize,
+    on_settle: F,
+) -> Result<PathsAndDists, DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+    F: FnMut(usize),
+{
+    check_source(graph, src)?;
+    let mut on_settle = on_settle;
+    let (parents, dists_from_src) = search::<Q, _>(graph, src, |v, _dist, _parent| {
+        on_settle(v);
+        true
+    })?;
+
+    Ok(paths_and_dists(&parents, src, &dists_from_src))
+}
+
+/// Dijkstra algorithm using the priority queue backend `Q` that calls
+/// `on_settle` with every vertex as soon as its distance is final, and stops
+/// as soon as it returns `false`, without exploring the rest of the graph.
+///
+/// Only the vertices settled until then, including the one `on_settle` returned
+/// `false` for, have a path and a distance. The others have neither.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra_while;
+/// use djikstra::graph::Graph;
+/// use djikstra::pq::PriorityQueue;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// // stop after the two vertices closest to 0
+/// let mut n_settled = 0;
+/// let (paths, dists) = dijkstra_while::<PriorityQueue<usize>, _>(&graph, 0, |_vertex| {
+///     n_settled += 1;
+///     n_settled < 2
+/// })
+/// .unwrap();
+/// assert_eq!(dists, vec![Some(0), Some(3), None]);
+/// assert_eq!(paths[2], None);
+/// ```
+pub fn dijkstra_while<Q, F>(
+    graph: &impl Adjacency,
+    src: usize,
+    mut on_settle: F,
+) -> Result<PathsAndDists, DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+    F: FnMut(usize) -> bool,
+{
+    check_source(graph, src)?;
+    let mut settled = BitSet::new(graph.n_vertices());
+    let (parents, dists_from_src) = search::<Q, _>(graph, src, |v, _dist, _parent| {
+        settled.insert(v);
+        on_settle(v)
+    })?;
+
+    // the paths and distances of the vertices left in the queue are not final
+    Ok((0..graph.n_vertices())
+        .map(|v| match settled.contains(v) {
+            true => (build_path(&parents, src, v), Some(dists_from_src[v])),
+            false => (None, None),
+        })
+        .unzip())
+}
+
+/// Dijkstra algorithm using the priority queue backend `Q` that calls
+/// `on_settle` with every reachable vertex, its distance and its parent on the
+/// shortest path (`None` for `src`) as soon as they are final, closest first.
+/// Stops as soon as `on_settle` returns `false`.
+///
+/// Nothing is kept besides what the search needs, so the results can be
+/// written out while the search goes on, e.g. on graphs too large to wait for.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra_streaming;
+/// use djikstra::graph::Graph;
+/// use djikstra::pq::PriorityQueue;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// let mut settled = vec![];
+/// dijkstra_streaming::<PriorityQueue<usize>, _>(&graph, 0, |vertex, dist, parent| {
+///     settled.push((vertex, dist, parent));
+///     true
+/// })
+/// .unwrap();
+/// assert_eq!(settled, vec![(0, 0, None), (1, 3, Some(0)), (2, 4, Some(1))]);
+/// ```
+pub fn dijkstra_streaming<Q, F>(
+    graph: &impl Adjacency,
+    src: usize,
+    on_settle: F,
+) -> Result<(), DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+    F: FnMut(usize, usize, Option<usize>) -> bool,
+{
+    check_source(graph, src)?;
+    search::<Q, _>(graph, src, on_settle)?;
+    Ok(())
+}
+
+/// The `k` vertices closest to `src` as `(vertex, distance)`, closest first and
+/// `src` itself at distance 0 the first of them, e.g. the nearest facilities.
+/// Fewer if fewer than `k` vertices can be reached.
+///
+/// The search stops as soon as `k` vertices are settled, so it only explores
+/// around `src` for small `k`.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::k_nearest;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 1)], vec![], vec![(3, 1)], vec![]]);
+/// assert_eq!(k_nearest(&graph, 0, 3), Ok(vec![(0, 0), (2, 1), (3, 2)]));
+/// ```
+pub fn k_nearest(
+    graph: &impl Adjacency,
+    src: usize,
+    k: usize,
+) -> Result<Vec<(usize, usize)>, DijkstraError> {
+    check_source(graph, src)?;
+    let mut nearest = Vec::with_capacity(k.min(graph.n_vertices()));
+    if k == 0 {
+        return Ok(nearest);
+    }
+    search::<PriorityQueue<usize>, _>(graph, src, |v, dist, _parent| {
+        nearest.push((v, dist));
+        nearest.len() < k
+    })?;
+    Ok(nearest)
+}
+
+/// The vertices at a distance from `src` between `min` and `max`, both included,
+/// as `(vertex, distance)` closest first, e.g. the ring of vertices 10 to 20 km away.
+///
+/// The search stops at the first vertex farther than `max`, so only the
+/// vertices up to `max` are explored.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::vertices_in_range;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 1)], vec![], vec![(3, 1)], vec![]]);
+/// assert_eq!(vertices_in_range(&graph, 0, 1, 2), Ok(vec![(2, 1), (3, 2)]));
+/// ```
+pub fn vertices_in_range(
+    graph: &impl Adjacency,
+    src: usize,
+    min: usize,
+    max: usize,
+) -> Result<Vec<(usize, usize)>, DijkstraError> {
+    check_source(graph, src)?;
+    let mut in_range = vec![];
+    search::<PriorityQueue<usize>, _>(graph, src, |v, dist, _parent| {
+        if dist > max {
+            return false;
+        }
+        if dist >= min {
+            in_range.push((v, dist));
+        }
+        true
+    })?;
+    Ok(in_range)
+}
+
+/// Order in which vertices at the same distance from the source are settled,
+/// which decides between shortest paths of the same length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TieBreak {
+    /// Settle the vertex with the lowest id first.
+    LowestId,
+    /// Prefer the shortest path with the fewest edges, then the lowest id.
+    FewestHops,
+    /// Settle them in whatever order the queue backend does, the fastest.
+    Unspecified,
+}
+
+/// Dijkstra algorithm breaking ties between vertices at the same distance with
+/// `ties`, so the paths are the same on every run whatever the queue backend.
+///
+/// The tie-break is part of the keys of the queue, which every backend
+/// extracts by increasing key and then by increasing vertex: the distance for
+/// `LowestId`, and the distance times the number of vertices plus the number
+/// of edges of the path for `FewestHops`.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`, which with `FewestHops` is the
+/// distance times the number of vertices.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::{dijkstra_with_ties, TieBreak};
+/// use djikstra::graph::Graph;
+///
+/// // 2 and 3 are as far from 0, so is 4 through either of them
+/// let graph = Graph::new(vec![vec![(1, 1), (3, 2)], vec![(2, 1)], vec![(4, 1)], vec![(4, 1)], vec![]]);
+/// let (paths, _dists) = dijkstra_with_ties(&graph, 0, TieBreak::LowestId).unwrap();
+/// assert_eq!(paths[4], Some(vec![0, 1, 2, 4]));
+/// let (paths, _dists) = dijkstra_with_ties(&graph, 0, TieBreak::FewestHops).unwrap();
+/// assert_eq!(paths[4], Some(vec![0, 3, 4]));
+/// ```
+pub fn dijkstra_with_ties(
+    graph: &impl Adjacency,
+    src: usize,
+    ties: TieBreak,
+) -> Result<PathsAndDists, DijkstraError> {
+    dijkstra_with_ties_in::<PriorityQueue<usize>>(graph, src, ties)
+}
+
+/// [`dijkstra_with_ties`] using the priority queue backend `Q`.
+fn dijkstra_with_ties_in<Q>(
+    graph: &impl Adjacency,
+    src: usize,
+    ties: TieBreak,
+) -> Result<PathsAndDists, DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+{
+    if ties != TieBreak::FewestHops {
+        return dijkstra_with::<Q>(graph, src);
+    }
+    check_source(graph, src)?;
+
+    // a shortest path has fewer edges than vertices, so the keys order the
+    // vertices by distance, then by number of edges
+    let n_elems = graph.n_vertices();
+    let mut too_far = false;
+    let (parents, keys) = search_weighted::<Q, _, _>(
+        graph,
+        src,
+        |_u, _v, weight| {
+            let key = weight
+                .checked_mul(n_elems)
+                .and_then(|key| key.checked_add(1));
+            too_far |= key.is_none();
+            key
+        },
+        |_v, _dist, _parent| true,
+    )?;
+    if too_far {
+        return Err(DijkstraError::Overflow);
+    }
+    // the vertices that were not reached have no path, so no distance either
+    let dists_from_src: Vec<usize> = keys.iter().map(|key| key / n_elems).collect();
+    Ok(paths_and_dists(&parents, src, &dists_from_src))
+}
+
+/// Number of settled vertices between two checks of whether to stop a search.
+const CANCEL_CHECK_EVERY: usize = 1024;
+
+/// Paths and distances found by a search that may have been stopped early.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialPaths {
+    /// Path from the source to every vertex settled before the search stopped.
+    pub paths: Vec<Option<Vec<usize>>>,
+    /// Distance from the source to every vertex settled before the search stopped.
+    pub dists: Vec<Option<usize>>,
+    /// Whether the search stopped before settling every reachable vertex.
+    pub cancelled: bool,
+}
+
+/// Dijkstra algorithm that stops as soon as `cancel` is set, e.g. by another
+/// thread, checking it every few thousand settled vertices.
+///
+/// The vertices settled before the search stopped have their final path and
+/// distance, the others have neither, as with [`dijkstra_while`].
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra_with_cancel;
+/// use djikstra::graph::Graph;
+/// use std::sync::atomic::AtomicBool;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// let result = dijkstra_with_cancel(&graph, 0, &AtomicBool::new(false)).unwrap();
+/// assert!(!result.cancelled);
+/// assert_eq!(result.dists, vec![Some(0), Some(3), Some(4)]);
+///
+/// // only the source is settled before the first check
+/// let result = dijkstra_with_cancel(&graph, 0, &AtomicBool::new(true)).unwrap();
+/// assert!(result.cancelled);
+/// assert_eq!(result.dists, vec![Some(0), None, None]);
+/// ```
+pub fn dijkstra_with_cancel(
+    graph: &impl Adjacency,
+    src: usize,
+    cancel: &AtomicBool,
+) -> Result<PartialPaths, DijkstraError> {
+    dijkstra_until(graph, src, || cancel.load(Ordering::Relaxed))
+}
+
+/// Dijkstra algorithm that stops once `deadline` has passed, checking it every
+/// few thousand settled vertices.
+///
+/// The vertices settled before the search stopped have their final path and
+/// distance, the others have neither, as with [`dijkstra_while`].
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra_with_deadline;
+/// use djikstra::graph::Graph;
+/// use std::time::{Duration, Instant};
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// let deadline = Instant::now() + Duration::from_secs(1);
+/// let result = dijkstra_with_deadline(&graph, 0, deadline).unwrap();
+/// assert!(!result.cancelled);
+/// ```
+#[cfg(feature = "std")]
+pub fn dijkstra_with_deadline(
+    graph: &impl Adjacency,
+    src: usize,
+    deadline: Instant,
+) -> Result<PartialPaths, DijkstraError> {
+    dijkstra_until(graph, src, || Instant::now() >= deadline)
+}
+
+/// [`dijkstra_while`] calling `stop` on the first settled vertex and then
+/// every [`CANCEL_CHECK_EVERY`] vertices, until it returns `true`.
+fn dijkstra_until<F>(
+    graph: &impl Adjacency,
+    src: usize,
+    mut stop: F,
+) -> Result<PartialPaths, DijkstraError>
+where
+    F: FnMut() -> bool,
+{
+    let mut n_settled = 0;
+    let mut cancelled = false;
+    let (paths, dists) = dijkstra_while::<PriorityQueue<usize>, _>(graph, src, |_vertex| {
+        cancelled = n_settled % CANCEL_CHECK_EVERY == 0 && stop();
+        n_settled += 1;
+        !cancelled
+    })?;
+
+    Ok(PartialPaths {
+        paths,
+        dists,
+        cancelled,
+    })
+}
+
+/// Time spent in every phase of a run of the algorithm, as measured by `dijkstra_profiled`.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Phases {
+    /// Allocating the distances and parents and filling the queue with every vertex.
+    pub init: Duration,
+    /// Extracting the closest vertex from the queue and decreasing keys.
+    pub queue: Duration,
+    /// Scanning the edges of the settled vertices and updating the distances,
+    /// without the time spent decreasing keys.
+    pub relaxation: Duration,
+    /// Following the parents back to `src` to build the path to every vertex.
+    pub paths: Duration,
+}
+
+#[cfg(feature = "std")]
+impl Phases {
+    /// Time spent in all the phases.
+    pub fn total(&self) -> Duration {
+        self.init + self.queue + self.relaxation + self.paths
+    }
+}
+
+/// Dijkstra algorithm using the priority queue backend `Q` that also measures
+/// the time spent in every phase of the run.
+///
+/// Every queue operation is timed on its own, which slows the run down, so the
+/// phases are meant to be compared with each other rather than with other runs.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::{dijkstra, dijkstra_profiled};
+/// use djikstra::graph::Graph;
+/// use djikstra::pq::PriorityQueue;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(0, 3)]]);
+/// let (paths_and_dists, phases) = dijkstra_profiled::<PriorityQueue<usize>>(&graph, 0).unwrap();
+/// assert_eq!(Ok(paths_and_dists), dijkstra(&graph, 0));
+/// assert!(phases.total() >= phases.queue);
+/// ```
+#[cfg(feature = "std")]
+pub fn dijkstra_profiled<Q>(
+    graph: &impl Adjacency,
+    src: usize,
+) -> Result<(PathsAndDists, Phases), DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+{
+    check_source(graph, src)?;
+    let mut phases = Phases::default();
+    let mut workspace = DijkstraWorkspace::default();
+    search_weighted_in::<Q, _, _, _>(
+        graph,
+        src,
+        &mut workspace,
+        |_u, _v, weight| Some(weight),
+        |_v, _dist, _parent| true,
+        &mut phases,
+    )?;
+
+    let mark = phases.mark();
+    let paths_and_dists = paths_and_dists(&workspace.parents, src, &workspace.dists_from_src);
+    phases.add(Phase::Paths, mark);
+
+    Ok((paths_and_dists, phases))
+}
+
+/// A phase of a run of the algorithm, see [`Phases`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Init,
+    Queue,
+    Relaxation,
+    #[cfg(feature = "std")]
+    Paths,
+}
+
+/// Clock of the phases of [`search_weighted_in`]. The searches pass `()`,
+/// which does nothing and compiles away, and `dijkstra_profiled` passes its
+/// [`Phases`].
+trait PhaseTimer {
+    /// When a phase started.
+    type Mark: Copy;
+
+    /// Start of a phase, now.
+    fn mark(&self) -> Self::Mark;
+
+    /// Add the time since `mark` to `phase`.
+    fn add(&mut self, phase: Phase, mark: Self::Mark);
+}
+
+impl PhaseTimer for () {
+    type Mark = ();
+
+    #[inline(always)]
+    fn mark(&self) {}
+
+    #[inline(always)]
+    fn add(&mut self, _phase: Phase, _mark: ()) {}
+}
+
+#[cfg(feature = "std")]
+impl PhaseTimer for Phases {
+    /// The time and the queue time so far, as the queue operations made while
+    /// relaxing are not part of the relaxation.
+    type Mark = (Instant, Duration);
+
+    fn mark(&self) -> Self::Mark {
+        (Instant::now(), self.queue)
+    }
+
+    fn add(&mut self, phase: Phase, (start, queue): Self::Mark) {
+        let elapsed = start.elapsed();
+        match phase {
+            Phase::Init => self.init += elapsed,
+            Phase::Queue => self.queue += elapsed,
+            Phase::Relaxation => self.relaxation += elapsed.saturating_sub(self.queue - queue),
+            Phase::Paths => self.paths += elapsed,
+        }
+    }
+}
+
+/// Shortest path from `src` to `dst`.
+/// The search stops as soon as `dst` is settled instead of exploring the whole graph.
+/// Returns the path and its distance, or `None` if `dst` cannot be reached.
+///
+/// # Errors
+/// Fails if `src` or `dst` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::shortest_path;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// assert_eq!(shortest_path(&graph, 0, 2), Ok(Some((vec![0, 1, 2], 4))));
+/// ```
+pub fn shortest_path(
+    graph: &impl Adjacency,
+    src: usize,
+    dst: usize,
+) -> Result<Option<PathAndDist>, DijkstraError> {
+    check_source(graph, src)?;
+    check_source(graph, dst)?;
+    let (parents, dists_from_src) =
+        search::<PriorityQueue<usize>, _>(graph, src, |v, _dist, _parent| v != dst)?;
+    Ok(build_path(&parents, src, dst).map(|path| (path, dists_from_src[dst])))
+}
+
+/// Shortest route from `src` to `dst` stopping at every vertex of `waypoints`
+/// in order, made of the shortest path from every stop to the next one.
+/// Returns the whole route, whose [`Path::cost`] is its total distance, or
+/// `None` if a stop cannot be reached from the previous one.
+///
+/// The segments are independent, so the route may go through a vertex more than once.
+///
+/// # Errors
+/// Fails if `src`, a waypoint or `dst` is not a vertex of the graph, or if the
+/// distance to a reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::route_via;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 1), (2, 5)], vec![(2, 1)], vec![(1, 2)]]);
+/// let route = route_via(&graph, 0, &[2, 1], 2).unwrap().unwrap();
+/// assert_eq!(route.vertices(), &[0, 1, 2, 1, 2]);
+/// assert_eq!(route.cost(), 5);
+/// ```
+pub fn route_via(
+    graph: &impl Adjacency,
+    src: usize,
+    waypoints: &[usize],
+    dst: usize,
+) -> Result<Option<Path>, DijkstraError> {
+    for &stop in waypoints.iter().chain([src, dst].iter()) {
+        check_source(graph, stop)?;
+    }
+
+    let mut route = Path::new(vec![src], vec![]);
+    let mut from = src;
+    for &to in waypoints.iter().chain(core::iter::once(&dst)) {
+        let (parents, dists_from_src) =
+            search::<PriorityQueue<usize>, _>(graph, from, |v, _dist, _parent| v != to)?;
+        let Some(segment) = build_path(&parents, from, to) else {
+            return Ok(None);
+        };
+        let weights = segment
+            .windows(2)
+            .map(|edge| dists_from_src[edge[1]] - dists_from_src[edge[0]])
+            .collect();
+        route.append(Path::new(segment, weights));
+        from = to;
+    }
+    Ok(Some(route))
+}
+
+/// Vertices and edges a search must not go through, e.g. a failed node or a
+/// closed road, without removing them from the graph.
+///
+/// Edges are directed: avoid both `(u, v)` and `(v, u)` to close an undirected edge.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::Avoid;
+///
+/// let avoid = Avoid::new().vertex(3).edge(0, 1);
+/// assert!(!avoid.allows(2, 3));
+/// assert!(!avoid.allows(0, 1));
+/// assert!(avoid.allows(1, 0));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Avoid {
+    /// Vertices that cannot be entered.
+    pub vertices: BTreeSet<usize>,
+    /// Edges `(from, to)` that cannot be followed.
+    pub edges: BTreeSet<(usize, usize)>,
+}
+
+impl Avoid {
+    /// Nothing to avoid.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Avoid the vertex `v` as well.
+    pub fn vertex(mut self, v: usize) -> Self {
+        self.vertices.insert(v);
+        self
+    }
+
+    /// Avoid the edge from `u` to `v` as well.
+    pub fn edge(mut self, u: usize, v: usize) -> Self {
+        self.edges.insert((u, v));
+        self
+    }
+
+    /// Whether the edge from `u` to `v` can be followed.
+    pub fn allows(&self, u: usize, v: usize) -> bool {
+        !self.vertices.contains(&v) && !self.edges.contains(&(u, v))
+    }
+}
+
+/// Shortest paths from `src` to every vertex of the graph that do not go
+/// through the vertices and edges of `avoid`. The vertices only reachable
+/// through them have no path.
+///
+/// `src` itself is not avoided, the paths start there even if it is in `avoid`.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::{dijkstra_avoiding, Avoid};
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 1), (2, 5)], vec![(2, 1)], vec![]]);
+/// let (paths, dists) = dijkstra_avoiding(&graph, 0, &Avoid::new().vertex(1)).unwrap();
+/// assert_eq!((paths[2].clone(), dists[2]), (Some(vec![0, 2]), Some(5)));
+/// assert_eq!(dists[1], None);
+/// ```
+pub fn dijkstra_avoiding(
+    graph: &impl Adjacency,
+    src: usize,
+    avoid: &Avoid,
+) -> Result<PathsAndDists, DijkstraError> {
+    dijkstra_filtered(graph, src, |u, v, _weight| avoid.allows(u, v))
+}
+
+/// Shortest path from `src` to `dst` that does not go through the vertices
+/// and edges of `avoid`, as [`shortest_path`] does on the graph without them.
+///
+/// # Errors
+/// Fails if `src` or `dst` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::{shortest_path_avoiding, Avoid};
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 1), (2, 5)], vec![(2, 1)], vec![]]);
+/// let closed = Avoid::new().edge(1, 2);
+/// assert_eq!(shortest_path_avoiding(&graph, 0, 2, &closed), Ok(Some((vec![0, 2], 5))));
+/// ```
+pub fn shortest_path_avoiding(
+    graph: &impl Adjacency,
+    src: usize,
+    dst: usize,
+    avoid: &Avoid,
+) -> Result<Option<PathAndDist>, DijkstraError> {
+    shortest_path_filtered(graph, src, dst, |u, v, _weight| avoid.allows(u, v))
+}
+
+/// Shortest paths from `src` to every vertex of the graph following only the
+/// edges `(from, to, weight)` for which `keep` returns `true`, e.g. the roads
+/// high enough for a vehicle, without building the graph of those edges.
+///
+/// `keep` is called every time an edge is relaxed, so it may be called more
+/// than once for an edge and should be cheap.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra_filtered;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 1), (2, 5)], vec![(2, 1)], vec![]]);
+/// // only the edges lighter than 3
+/// let (paths, dists) = dijkstra_filtered(&graph, 0, |_u, _v, weight| weight < 3).unwrap();
+/// assert_eq!((paths[2].clone(), dists[2]), (Some(vec![0, 1, 2]), Some(2)));
+/// ```
+pub fn dijkstra_filtered<F>(
+    graph: &impl Adjacency,
+    src: usize,
+    keep: F,
+) -> Result<PathsAndDists, DijkstraError>
+where
+    F: Fn(usize, usize, usize) -> bool,
+{
+    check_source(graph, src)?;
+    let (parents, dists_from_src) = search_weighted::<PriorityQueue<usize>, _, _>(
+        graph,
+        src,
+        |u, v, weight| keep(u, v, weight).then_some(weight),
+        |_v, _dist, _parent| true,
+    )?;
+    Ok(paths_and_dists(&parents, src, &dists_from_src))
+}
+
+/// Shortest path from `src` to `dst` following only the edges `(from, to, weight)`
+/// for which `keep` returns `true`, as [`shortest_path`] does on the graph of those edges.
+///
+/// # Errors
+/// Fails if `src` or `dst` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::shortest_path_filtered;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 1), (2, 5)], vec![(2, 1)], vec![]]);
+/// let no_shortcut = |u: usize, v: usize, _weight: usize| (u, v) != (1, 2);
+/// assert_eq!(shortest_path_filtered(&graph, 0, 2, no_shortcut), Ok(Some((vec![0, 2], 5))));
+/// ```
+pub fn shortest_path_filtered<F>(
+    graph: &impl Adjacency,
+    src: usize,
+    dst: usize,
+    keep: F,
+) -> Result<Option<PathAndDist>, DijkstraError>
+where
+    F: Fn(usize, usize, usize) -> bool,
+{
+    check_source(graph, src)?;
+    check_source(graph, dst)?;
+    let (parents, dists_from_src) = search_weighted::<PriorityQueue<usize>, _, _>(
+        graph,
+        src,
+        |u, v, weight| keep(u, v, weight).then_some(weight),
+        |v, _dist, _parent| v != dst,
+    )?;
+    Ok(build_path(&parents, src, dst).map(|path| (path, dists_from_src[dst])))
+}
+
+/// Shortest paths from `src` to every vertex of the graph where the cost of an
+/// edge `(from, to, weight)` is `cost(from, to, weight)` instead of its weight,
+/// so one graph serves several cost models, e.g. the weights as lengths for
+/// the shortest paths and `length / speed` for the fastest ones.
+/// The distances are sums of costs.
+///
+/// `cost` is called every time an edge is relaxed, so it may be called more
+/// than once for an edge and should be cheap.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the cost of a path to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra_with_cost;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 1), (2, 5)], vec![(2, 1)], vec![]]);
+/// // vertex 1 is a toll gate, ten times as expensive to enter
+/// let toll = |_u: usize, v: usize, weight: usize| if v == 1 { 10 * weight } else { weight };
+/// let (paths, dists) = dijkstra_with_cost(&graph, 0, toll).unwrap();
+/// assert_eq!((paths[2].clone(), dists[2]), (Some(vec![0, 2]), Some(5)));
+/// assert_eq!(dists[1], Some(10));
+/// ```
+pub fn dijkstra_with_cost<C>(
+    graph: &impl Adjacency,
+    src: usize,
+    cost: C,
+) -> Result<PathsAndDists, DijkstraError>
+where
+    C: Fn(usize, usize, usize) -> usize,
+{
+    check_source(graph, src)?;
+    let (parents, dists_from_src) = search_weighted::<PriorityQueue<usize>, _, _>(
+        graph,
+        src,
+        |u, v, weight| Some(cost(u, v, weight)),
+        |_v, _dist, _parent| true,
+    )?;
+    Ok(paths_and_dists(&parents, src, &dists_from_src))
+}
+
+/// Shortest path from `src` to `dst` where the cost of an edge `(from, to, weight)`
+/// is `cost(from, to, weight)`, as in [`dijkstra_with_cost`]. Returns the path
+/// and its cost, or `None` if `dst` cannot be reached.
+///
+/// # Errors
+/// Fails if `src` or `dst` is not a vertex of the graph, or if the cost of a path to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::shortest_path_with_cost;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 1), (2, 5)], vec![(2, 1)], vec![]]);
+/// // every edge takes 4 more to cross, favouring paths with fewer edges
+/// let crossing = |_u: usize, _v: usize, weight: usize| weight + 4;
+/// assert_eq!(shortest_path_with_cost(&graph, 0, 2, crossing), Ok(Some((vec![0, 2], 9))));
+/// ```
+pub fn shortest_path_with_cost<C>(
+    graph: &impl Adjacency,
+    src: usize,
+    dst: usize,
+    cost: C,
+) -> Result<Option<PathAndDist>, DijkstraError>
+where
+    C: Fn(usize, usize, usize) -> usize,
+{
+    check_source(graph, src)?;
+    check_source(graph, dst)?;
+    let (parents, dists_from_src) = search_weighted::<PriorityQueue<usize>, _, _>(
+        graph,
+        src,
+        |u, v, weight| Some(cost(u, v, weight)),
+        |v, _dist, _parent| v != dst,
+    )?;
+    Ok(build_path(&parents, src, dst).map(|path| (path, dists_from_src[dst])))
+}
+
+/// Shortest paths for many `(src, dst)` queries at once.
+///
+/// Queries are grouped by source so every source is searched only once, and the
+/// search from a source stops as soon as all of its destinations are settled.
+/// Returns the path and distance of every query in the order of the queries,
+/// or `None` for the queries whose destination cannot be reached.
+///
+/// # Errors
+/// Fails if a source or a destination is not a vertex of the graph, before
+/// any query is answered, or if the distance to a reachable vertex does not
+/// fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::batch_shortest_paths;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// let answers = batch_shortest_paths(&graph, &[(0, 2), (2, 0), (0, 1)]);
+/// assert_eq!(answers, Ok(vec![Some((vec![0, 1, 2], 4)), None, Some((vec![0, 1], 3))]));
+/// ```
+pub fn batch_shortest_paths(
+    graph: &impl Adjacency,
+    queries: &[(usize, usize)],
+) -> Result<Vec<Option<PathAndDist>>, DijkstraError> {
+    let by_source = queries_by_source(graph, queries)?;
+    let answered = answer_sources(graph, queries, &by_source, 0, 1)?;
+    Ok(in_query_order(queries.len(), answered))
+}
+
+/// Shortest paths for many `(src, dst)` queries at once, like
+/// `batch_shortest_paths`, with the sources shared between `threads` threads.
+/// The answers are still in the order of the queries.
+///
+/// # Errors
+/// Fails if a source or a destination is not a vertex of the graph, before
+/// any query is answered, or if the distance to a reachable vertex does not
+/// fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::{batch_shortest_paths, batch_shortest_paths_parallel};
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![(0, 1)]]);
+/// let queries = [(0, 2), (2, 1), (1, 0), (0, 1)];
+/// assert_eq!(
+///     batch_shortest_paths_parallel(&graph, &queries, 4),
+///     batch_shortest_paths(&graph, &queries)
+/// );
+/// ```
+#[cfg(feature = "std")]
+pub fn batch_shortest_paths_parallel(
+    graph: &(impl Adjacency + Sync),
+    queries: &[(usize, usize)],
+    threads: usize,
+) -> Result<Vec<Option<PathAndDist>>, DijkstraError> {
+    let by_source = queries_by_source(graph, queries)?;
+    let answered = if threads <= 1 {
+        answer_sources(graph, queries, &by_source, 0, 1)?
+    } else {
+        // every thread takes every `threads`-th source and answers its queries
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|first| {
+                    let by_source = &by_source;
+                    scope.spawn(move || answer_sources(graph, queries, by_source, first, threads))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Result<Vec<_>, _>>()
+        })?
+        .into_iter()
+        .flatten()
+        .collect()
+    };
+    Ok(in_query_order(queries.len(), answered))
+}
+
+/// Shortest paths from every vertex of `srcs` to every vertex of the graph,
+/// keyed by source.
+///
+/// The sources are shared between the available cores, and every thread keeps
+/// its buffers from one source to the next. A source listed more than once is
+/// only searched from once.
+///
+/// # Errors
+/// Fails if a source is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::{dijkstra_many, shortest_paths};
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![(0, 2)]]);
+/// let trees = dijkstra_many(&graph, &[2, 0]).unwrap();
+/// assert_eq!(trees.keys().copied().collect::<Vec<_>>(), vec![0, 2]);
+/// assert_eq!(trees[&2], shortest_paths(&graph, 2).unwrap());
+/// ```
+#[cfg(feature = "std")]
+pub fn dijkstra_many(
+    graph: &(impl Adjacency + Sync),
+    srcs: &[usize],
+) -> Result<BTreeMap<usize, DijkstraResult>, DijkstraError> {
+    let sources: BTreeSet<usize> = srcs.iter().copied().collect();
+    for &src in sources.iter() {
+        check_source(graph, src)?;
+    }
+    let sources: Vec<usize> = sources.into_iter().collect();
+    let threads = std::thread::available_parallelism()
+        .map_or(1, usize::from)
+        .min(sources.len())
+        .max(1);
+
+    // every thread takes every `threads`-th source
+    let trees = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|first| {
+                let sources = &sources;
+                scope.spawn(move || trees_from(graph, sources, first, threads))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    Ok(trees.into_iter().flatten().collect())
+}
+
+/// Shortest paths from every `step`-th source of `sources`, starting at
+/// `first`, searching in the same workspace every time.
+#[cfg(feature = "std")]
+fn trees_from(
+    graph: &impl Adjacency,
+    sources: &[usize],
+    first: usize,
+    step: usize,
+) -> Result<Vec<(usize, DijkstraResult)>, DijkstraError> {
+    let mut workspace = DijkstraWorkspace::default();
+    sources
+        .iter()
+        .skip(first)
+        .step_by(step.max(1))
+        .map(|&src| {
+            search_in::<PriorityQueue<usize>, _>(
+                graph,
+                src,
+                &mut workspace,
+                |_v, _dist, _parent| true,
+            )?;
+            let (paths, dists) =
+                paths_and_dists(&workspace.parents, src, &workspace.dists_from_src);
+            Ok((src, DijkstraResult::new(src, paths, dists)))
+        })
+        .collect()
+}
+
+/// Every source of the queries with the indices of its queries, checking
+/// that the sources and the destinations are vertices of the graph.
+fn queries_by_source(
+    graph: &impl Adjacency,
+    queries: &[(usize, usize)],
+) -> Result<Vec<(usize, Vec<usize>)>, DijkstraError> {
+    let mut by_source: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (idx, &(src, dst)) in queries.iter().enumerate() {
+        check_source(graph, src)?;
+        check_source(graph, dst)?;
+        by_source.entry(src).or_default().push(idx);
+    }
+    Ok(by_source.into_iter().collect())
+}
+
+/// Answer the queries of every `step`-th source of `by_source`, starting at
+/// `first`, searching from every source until all of its destinations are
+/// settled. Returns the index of every query answered with its answer.
+fn answer_sources(
+    graph: &impl Adjacency,
+    queries: &[(usize, usize)],
+    by_source: &[(usize, Vec<usize>)],
+    first: usize,
+    step: usize,
+) -> Result<Vec<(usize, Option<PathAndDist>)>, DijkstraError> {
+    let mut answered = vec![];
+    for (src, indices) in by_source.iter().skip(first).step_by(step.max(1)) {
+        let mut remaining: BTreeSet<usize> = indices.iter().map(|&idx| queries[idx].1).collect();
+        let (parents, dists_from_src) =
+            search::<PriorityQueue<usize>, _>(graph, *src, |v, _dist, _parent| {
+                remaining.remove(&v);
+                !remaining.is_empty()
+            })?;
+
+        for &idx in indices {
+            let dst = queries[idx].1;
+            let answer = build_path(&parents, *src, dst).map(|path| (path, dists_from_src[dst]));
+            answered.push((idx, answer));
+        }
+    }
+    Ok(answered)
+}
+
+/// The answers of `answer_sources` in the order of the `n_queries` queries.
+fn in_query_order(
+    n_queries: usize,
+    answered: Vec<(usize, Option<PathAndDist>)>,
+) -> Vec<Option<PathAndDist>> {
+    let mut answers = vec![None; n_queries];
+    for (idx, answer) in answered {
+        answers[idx] = answer;
+    }
+    answers
+}
+
+/// Runs the main loop of the algorithm.
+/// Calls `on_settle` with every vertex whose distance becomes final, its
+/// distance and its parent, and stops early as soon as it returns `false`.
+/// Returns the parent of every vertex and the distances from `src`, or
+/// `Overflow` if a vertex can only be reached by overflowing distances.
+fn search<Q, F>(
+    graph: &impl Adjacency,
+    src: usize,
+    on_settle: F,
+) -> Result<SearchTree, DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+    F: FnMut(usize, usize, Option<usize>) -> bool,
+{
+    search_weighted::<Q, _, F>(graph, src, |_u, _v, weight| Some(weight), on_settle)
+}
+
+/// Buffers of a search, the distances, the parents and the settled vertices,
+/// kept from one search to the next so they are only allocated once, e.g. by
+/// a thread answering many queries in a loop.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::DijkstraWorkspace;
+/// use djikstra::graph::Graph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 5)], vec![(2, 1)], vec![]]);
+/// let mut workspace = DijkstraWorkspace::new();
+/// for dst in 0..3 {
+///     let (_path, dist) = workspace.shortest_path(&graph, 0, dst).unwrap().unwrap();
+///     assert_eq!(workspace.dist_to(dst), Some(dist));
+/// }
+/// workspace.shortest_paths(&graph, 1).unwrap();
+/// assert_eq!(workspace.path_to(2), Some(vec![1, 2]));
+/// assert_eq!(workspace.dist_to(0), None);
+/// ```
+#[derive(Debug, Default)]
+pub struct DijkstraWorkspace {
+    source: Option<usize>,
+    parents: Vec<Option<usize>>,
+    dists_from_src: Vec<usize>,
+    checked: BitSet,
+    // vertices whose distance through some edge overflowed
+    overflowed: Vec<usize>,
+    stats: SearchStats,
+}
+
+impl DijkstraWorkspace {
+    /// Workspace with no buffer allocated yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Source of the last search, `None` before the first one.
+    pub fn source(&self) -> Option<usize> {
+        self.source
+    }
+
+    /// Search the shortest paths from `src` to every vertex of `graph`.
+    ///
+    /// # Errors
+    /// Fails if `src` is not a vertex of the graph, or if the distance to a
+    /// reachable vertex does not fit in a `usize`.
+    pub fn shortest_paths(
+        &mut self,
+        graph: &impl Adjacency,
+        src: usize,
+    ) -> Result<(), DijkstraError> {
+        check_source(graph, src)?;
+        search_in::<PriorityQueue<usize>, _>(graph, src, self, |_v, _dist, _parent| true)
+    }
+
+    /// Shortest path from `src` to `dst` and its distance, `None` if `dst`
+    /// cannot be reached, searching until `dst` is settled.
+    ///
+    /// # Errors
+    /// Fails if `src` or `dst` is not a vertex of the graph, or if the distance
+    /// to a reachable vertex does not fit in a `usize`.
+    pub fn shortest_path(
+        &mut self,
+        graph: &impl Adjacency,
+        src: usize,
+        dst: usize,
+    ) -> Result<Option<PathAndDist>, DijkstraError> {
+        check_source(graph, src)?;
+        check_source(graph, dst)?;
+        search_in::<PriorityQueue<usize>, _>(graph, src, self, |v, _dist, _parent| v != dst)?;
+        Ok(self
+            .path_to(dst)
+            .map(|path| (path, self.dists_from_src[dst])))
+    }
+
+    /// Distance from the source of the last search to `v`, `None` if `v` was
+    /// not settled by it.
+    ///
+    /// # Panics
+    /// If `v` is not a vertex of the graph of the last search.
+    pub fn dist_to(&self, v: usize) -> Option<usize> {
+        self.checked.contains(v).then(|| self.dists_from_src[v])
+    }
+
+    /// Shortest path from the source of the last search to `v`, `None` if `v`
+    /// was not settled by it.
+    ///
+    /// # Panics
+    /// If `v` is not a vertex of the graph of the last search.
+    pub fn path_to(&self, v: usize) -> Option<Vec<usize>> {
+        let src = self.source?;
+        match self.checked.contains(v) {
+            true => build_path(&self.parents, src, v),
+            false => None,
+        }
+    }
+
+    /// Counts of what the last search did.
+    pub fn stats(&self) -> SearchStats {
+        self.stats
+    }
+
+    /// Empty the buffers for a search from `src` on a graph of `n_elems` vertices.
+    fn reset(&mut self, src: usize, n_elems: usize) {
+        self.source = Some(src);
+        self.parents.clear();
+        self.parents.resize(n_elems, None);
+        self.dists_from_src.clear();
+        self.dists_from_src.resize(n_elems, usize::MAX);
+        self.checked.reset(n_elems);
+        self.overflowed.clear();
+        self.stats = SearchStats::default();
+    }
+}
+
+/// [`search`] in the buffers of `workspace`, which hold the parents and the
+/// distances from `src` afterwards.
+fn search_in<Q, F>(
+    graph: &impl Adjacency,
+    src: usize,
+    workspace: &mut DijkstraWorkspace,
+    on_settle: F,
+) -> Result<(), DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+    F: FnMut(usize, usize, Option<usize>) -> bool,
+{
+    search_weighted_in::<Q, _, F, _>(
+        graph,
+        src,
+        workspace,
+        |_u, _v, weight| Some(weight),
+        on_settle,
+        &mut (),
+    )
+}
+
+/// [`search`] where `weigh` is called with every edge `(u, v, weight)` to
+/// relax and returns the weight to use instead, or `None` to skip the edge.
+fn search_weighted<Q, W, F>(
+    graph: &impl Adjacency,
+    src: usize,
+    weigh: W,
+    on_settle: F,
+) -> Result<SearchTree, DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+    W: FnMut(usize, usize, usize) -> Option<usize>,
+    F: FnMut(usize, usize, Option<usize>) -> bool,
+{
+    let mut workspace = DijkstraWorkspace::default();
+    search_weighted_in::<Q, W, F, _>(graph, src, &mut workspace, weigh, on_settle, &mut ())?;
+    Ok((workspace.parents, workspace.dists_from_src))
+}
+
+/// [`search_weighted`] in the buffers of `workspace`, timing its phases with `timer`.
+fn search_weighted_in<Q, W, F, T>(
+    graph: &impl Adjacency,
+    src: usize,
+    workspace: &mut DijkstraWorkspace,
+    mut weigh: W,
+    mut on_settle: F,
+    timer: &mut T,
+) -> Result<(), DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+    W: FnMut(usize, usize, usize) -> Option<usize>,
+    F: FnMut(usize, usize, Option<usize>) -> bool,
+    T: PhaseTimer,
+{
+    let mut pq = {
+        let _span = trace::span!("init");
+        let mark = timer.mark();
+        let n_elems = graph.n_vertices();
+        workspace.reset(src, n_elems);
+        let mut pq = Q::from_keys(0..n_elems);
+        workspace.dists_from_src[src] = 0;
+        pq.change_key(&src, 0);
+        timer.add(Phase::Init, mark);
+        pq
+    };
+    let DijkstraWorkspace {
+        source: _,
+        parents,
+        dists_from_src,
+        checked,
+        overflowed,
+        stats,
+    } = workspace;
+
+    let _main_loop = trace::span!("main loop");
+    // vertices reached but not settled yet, starting with `src`
+    let mut frontier = 1;
+    stats.max_frontier = 1;
+    loop {
+        let mark = timer.mark();
+        let extracted = pq.extract_min();
+        timer.add(Phase::Queue, mark);
+        let Some((node, dist_src)) = extracted else {
+            break;
+        };
+        // only unreachable vertices are left
+        if dist_src == usize::MAX {
+            break;
+        }
+        stats.settled += 1;
+        frontier -= 1;
+        trace::progress(stats.settled, dist_src);
+        if !on_settle(node, dist_src, parents[node]) {
+            // the vertices left were not needed
+            checked.insert(node);
+            return Ok(());
+        }
+
+        let mark = timer.mark();
+        let neighbours = graph.neighbors_of(node);
+
+        for &(neighbour, dist) in neighbours.iter() {
+            let neighbour = neighbour.index();
+            if checked.contains(neighbour) {
+                continue;
+            }
+            let Some(dist) = weigh(node, neighbour, dist.weight()) else {
+                continue;
+            };
+            stats.relaxations += 1;
+            let Some(new_dist) = add_distance(dist_src, dist) else {
+                overflowed.push(neighbour);
+                continue;
+            };
+            if dists_from_src[neighbour] > new_dist {
+                if dists_from_src[neighbour] == usize::MAX {
+                    frontier += 1;
+                    stats.max_frontier = stats.max_frontier.max(frontier);
+                }
+                dists_from_src[neighbour] = new_dist;
+                parents[neighbour] = Some(node);
+                let mark = timer.mark();
+                pq.change_key(&neighbour, new_dist);
+                timer.add(Phase::Queue, mark);
+                stats.decrease_keys += 1;
+            }
+        }
+        checked.insert(node);
+        timer.add(Phase::Relaxation, mark);
+    }
+
+    check_overflows(overflowed, dists_from_src)
+}
+
+/// `Overflow` if one of the `overflowed` vertices, whose distance through some
+/// edge overflowed, was not reached any other way.
+pub(crate) fn check_overflows(
+    overflowed: &[usize],
+    dists_from_src: &[usize],
+) -> Result<(), DijkstraError> {
+    match overflowed.iter().any(|&v| dists_from_src[v] == usize::MAX) {
+        true => Err(DijkstraError::Overflow),
+        false => Ok(()),
+    }
+}
+
+/// Parent of every vertex and distance from the source, `usize::MAX` if it was not reached.
+type SearchTree = (Vec<Option<usize>>, Vec<usize>);
+
+/// Path from `src` to every vertex, following the parents, and its distance
+/// from `dists_from_src`, both `None` for the vertices that were not reached.
+pub(crate) fn paths_and_dists(
+    parents: &[Option<usize>],
+    src: usize,
+    dists_from_src: &[usize],
+) -> PathsAndDists {
+    let _span = trace::span!("paths");
+    (0..parents.len())
+        .map(|v| {
+            let path = build_path(parents, src, v);
+            let dist = path.as_ref().map(|_| dists_from_src[v]);
+            (path, dist)
+        })
+        .unzip()
+}
+
+/// Follows the parents of `v` back to `src`.
+/// Returns `None` if `v` was not reached from `src`.
+pub(crate) fn build_path(parents: &[Option<usize>], src: usize, v: usize) -> Option<Vec<usize>> {
+    let mut path = vec![v];
+    while let Some(node) = parents[*path.last().unwrap()] {
+        path.push(node);
+    }
+    path.reverse();
+    if path.len() > 1 || path[0] == src {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dijkstra::{
+        batch_shortest_paths, dijkstra, dijkstra_filtered, dijkstra_streaming, dijkstra_while,
+        dijkstra_with, dijkstra_with_cancel, dijkstra_with_cost, dijkstra_with_ties,
+        dijkstra_with_ties_in, k_nearest, route_via, shortest_path, shortest_path_filtered,
+        shortest_path_tree, shortest_path_with_cost, shortest_paths, shortest_paths_with_stats,
+        vertices_in_range, DijkstraError, DijkstraWorkspace, PathIter, TieBreak,
+    };
+    #[cfg(feature = "std")]
+    use crate::dijkstra::{
+        batch_shortest_paths_parallel, dijkstra_many, dijkstra_profiled, dijkstra_with_deadline,
+    };
+    use crate::dijkstra::{dijkstra_avoiding, shortest_path_avoiding, Avoid};
+    use crate::graph::Graph;
+    use crate::path::Path;
+    use crate::pq::{BinaryHeapQueue, DaryHeapQueue, PriorityQueue, RadixHeapQueue};
+    use crate::test_utils::{sample_adjacency, sample_graph};
+    use core::sync::atomic::AtomicBool;
+
+    #[test]
+    fn correct_path() {
+        let g1 = Graph::new(vec![
+            vec![(1, 4), (2, 1)],
+            vec![(0, 4), (2, 2), (3, 5)],
+            vec![(0, 1), (1, 2), (3, 5)],
+            vec![(1, 5), (2, 1)],
+        ]);
+        let (paths, _dists) = dijkstra(&g1, 2).unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                Some(vec![2, 0]),
+                Some(vec![2, 1]),
+                Some(vec![2]),
+                Some(vec![2, 3])
+            ]
+        )
+    }
+
+    #[test]
+    fn correct_path_lg() {
+        let g1 = sample_graph();
+
+        let (paths, _dists) = dijkstra(&g1, 6).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                Some(vec![6, 0]),
+                Some(vec![6, 1]),
+                Some(vec![6, 1, 3, 2]),
+                Some(vec![6, 1, 3]),
+                Some(vec![6, 1, 3, 4]),
+                Some(vec![6, 1, 3, 4, 5]),
+                Some(vec![6]),
+                Some(vec![6, 1, 3, 7])
+            ]
+        );
+    }
+
+    #[test]
+    fn backends_agree() {
+        let g1 = sample_graph();
+
+        for src in 0..g1.n_vertices() {
+            let expected = dijkstra(&g1, src);
+            assert_eq!(expected, dijkstra_with::<BinaryHeapQueue<usize>>(&g1, src));
+            assert_eq!(expected, dijkstra_with::<DaryHeapQueue<usize>>(&g1, src));
+            assert_eq!(expected, dijkstra_with::<RadixHeapQueue<usize>>(&g1, src));
+        }
+    }
+
+    #[test]
+    fn query_matches_full_run() {
+        let g1 = sample_graph();
+
+        let (paths, dists) = dijkstra(&g1, 6).unwrap();
+        for v in 0..g1.n_vertices() {
+            assert_eq!(shortest_path(&g1, 6, v), Ok(paths[v].clone().zip(dists[v])));
+        }
+    }
+
+    #[test]
+    fn queries_to_a_missing_vertex_fail() {
+        let graph = Graph::new(vec![vec![(1, 3)], vec![]]);
+        let invalid = Err(DijkstraError::InvalidSource {
+            source: 9,
+            n_vertices: 2,
+        });
+        assert_eq!(shortest_path(&graph, 0, 9), invalid);
+        assert_eq!(
+            shortest_path_filtered(&graph, 0, 9, |_u, _v, _w| true),
+            invalid
+        );
+        assert_eq!(shortest_path_avoiding(&graph, 0, 9, &Avoid::new()), invalid);
+        assert_eq!(
+            shortest_path_with_cost(&graph, 0, 9, |_u, _v, w| w),
+            invalid
+        );
+        assert_eq!(crate::astar::astar_path(&graph, 0, 9, |_v| 0), invalid);
+    }
+
+    #[test]
+    fn stopped_run_keeps_only_settled_vertices() {
+        let g1 = sample_graph();
+
+        let (all_paths, all_dists) = dijkstra(&g1, 6).unwrap();
+        let mut order = vec![];
+        let (paths, dists) = dijkstra_while::<PriorityQueue<usize>, _>(&g1, 6, |v| {
+            order.push(v);
+            order.len() < 4
+        })
+        .unwrap();
+        assert_eq!(order.len(), 4);
+        for v in 0..g1.n_vertices() {
+            if order.contains(&v) {
+                assert_eq!((&paths[v], dists[v]), (&all_paths[v], all_dists[v]));
+            } else {
+                assert_eq!((&paths[v], dists[v]), (&None, None));
+            }
+        }
+        // settled in order of distance
+        assert!(order.windows(2).all(|w| all_dists[w[0]] <= all_dists[w[1]]));
+    }
+
+    #[test]
+    fn avoided_vertices_and_edges_are_not_used() {
+        let g1 = sample_graph();
+        assert_eq!(dijkstra_avoiding(&g1, 6, &Avoid::new()), dijkstra(&g1, 6));
+
+        // the same as removing vertex 1 and the edge from 4 to 5
+        let avoid = Avoid::new().vertex(1).edge(4, 5);
+        let mut removed = Graph::new(g1.adj.clone());
+        for u in 0..g1.n_vertices() {
+            removed.remove_edge(u, 1);
+        }
+        removed.remove_edge(4, 5);
+        let (paths, dists) = dijkstra_avoiding(&g1, 6, &avoid).unwrap();
+        assert_eq!(
+            (paths.clone(), dists.clone()),
+            dijkstra(&removed, 6).unwrap()
+        );
+        assert!(paths.iter().flatten().all(|path| !path.contains(&1)));
+        for dst in 0..g1.n_vertices() {
+            let expected = paths[dst].clone().zip(dists[dst]);
+            assert_eq!(shortest_path_avoiding(&g1, 6, dst, &avoid), Ok(expected));
+        }
+        // the source is not avoided
+        let from_avoided = dijkstra_avoiding(&g1, 1, &avoid).unwrap();
+        assert_eq!(from_avoided.1[1], Some(0));
+    }
+
+    #[test]
+    fn filtered_edges_are_not_followed() {
+        let g1 = sample_graph();
+        assert_eq!(dijkstra_filtered(&g1, 0, |_, _, _| true), dijkstra(&g1, 0));
+
+        // the same as the graph without the edges heavier than 3
+        let light = Graph::new(
+            g1.adj
+                .iter()
+                .map(|edges| edges.iter().copied().filter(|&(_v, w)| w <= 3).collect())
+                .collect(),
+        );
+        let keep = |_u: usize, _v: usize, weight: usize| weight <= 3;
+        for src in 0..g1.n_vertices() {
+            let filtered = dijkstra_filtered(&g1, src, keep).unwrap();
+            assert_eq!(filtered, dijkstra(&light, src).unwrap());
+            for dst in 0..g1.n_vertices() {
+                let expected = filtered.0[dst].clone().zip(filtered.1[dst]);
+                assert_eq!(shortest_path_filtered(&g1, src, dst, keep), Ok(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn costs_replace_the_weights() {
+        let g1 = sample_graph();
+        let weight = |_u: usize, _v: usize, weight: usize| weight;
+        assert_eq!(dijkstra_with_cost(&g1, 0, weight), dijkstra(&g1, 0));
+
+        // the same as the graph with every weight replaced by its cost
+        let hops = |_u: usize, _v: usize, _weight: usize| 1;
+        let unweighted = Graph::new(
+            g1.adj
+                .iter()
+                .map(|edges| edges.iter().map(|&(v, _w)| (v, 1)).collect())
+                .collect(),
+        );
+        for src in 0..g1.n_vertices() {
+            let (_paths, dists) = dijkstra_with_cost(&g1, src, hops).unwrap();
+            assert_eq!(dists, dijkstra(&unweighted, src).unwrap().1);
+            for (dst, &dist) in dists.iter().enumerate() {
+                let (path, cost) = shortest_path_with_cost(&g1, src, dst, hops)
+                    .unwrap()
+                    .unwrap();
+                assert_eq!((Some(path.len() - 1), Some(cost)), (dist, dist));
+            }
+        }
+
+        let huge = |_u: usize, _v: usize, _weight: usize| usize::MAX / 2;
+        assert_eq!(
+            shortest_path_with_cost(&g1, 0, 1, huge),
+            Ok(Some((vec![0, 1], usize::MAX / 2)))
+        );
+        assert_eq!(
+            dijkstra_with_cost(&g1, 0, huge),
+            Err(DijkstraError::Overflow)
+        );
+    }
+
+    #[test]
+    fn nearest_vertices_come_closest_first() {
+        let g1 = sample_graph();
+        let (_paths, dists) = dijkstra(&g1, 5).unwrap();
+        let all = k_nearest(&g1, 5, g1.n_vertices()).unwrap();
+        assert_eq!(all.len(), g1.n_vertices());
+        assert_eq!(all[0], (5, 0));
+        assert!(all.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+        assert!(all.iter().all(|&(v, dist)| dists[v] == Some(dist)));
+        for k in 0..g1.n_vertices() {
+            assert_eq!(k_nearest(&g1, 5, k).unwrap(), all[..k]);
+        }
+        assert_eq!(k_nearest(&g1, 5, 100).unwrap(), all);
+
+        // only the reachable vertices
+        let g2 = Graph::new(vec![vec![(1, 1)], vec![], vec![(0, 1)]]);
+        assert_eq!(k_nearest(&g2, 0, 3), Ok(vec![(0, 0), (1, 1)]));
+        assert_eq!(k_nearest(&g2, 0, 0), Ok(vec![]));
+        assert!(k_nearest(&g2, 3, 1).is_err());
+    }
+
+    #[test]
+    fn workspaces_are_reused_across_searches() {
+        let g1 = sample_graph();
+        let mut workspace = DijkstraWorkspace::new();
+        assert_eq!(workspace.source(), None);
+        for src in 0..g1.n_vertices() {
+            workspace.shortest_paths(&g1, src).unwrap();
+            let (paths, dists) = dijkstra(&g1, src).unwrap();
+            for v in 0..g1.n_vertices() {
+                assert_eq!(workspace.dist_to(v), dists[v]);
+                assert_eq!(
+                    workspace.path_to(v).map(|p| p.len()),
+                    paths[v].as_ref().map(|p| p.len())
+                );
+            }
+            for dst in 0..g1.n_vertices() {
+                let found = workspace.shortest_path(&g1, src, dst).unwrap();
+                assert_eq!(
+                    found.map(|p| p.1),
+                    shortest_path(&g1, src, dst).unwrap().map(|p| p.1)
+                );
+            }
+        }
+        assert_eq!(workspace.source(), Some(7));
+
+        // on a smaller graph, without the results of the last search
+        let g2 = Graph::new(vec![vec![(1, 1)], vec![], vec![]]);
+        assert_eq!(
+            workspace.shortest_path(&g2, 0, 1),
+            Ok(Some((vec![0, 1], 1)))
+        );
+        assert_eq!(workspace.shortest_path(&g2, 0, 2), Ok(None));
+        assert_eq!(workspace.stats().settled, 2);
+        assert!(workspace.shortest_path(&g2, 0, 3).is_err());
+    }
+
+    #[test]
+    fn vertices_in_range_are_in_the_band() {
+        let g1 = sample_graph();
+        let all = k_nearest(&g1, 0, g1.n_vertices()).unwrap();
+        for min in 0..8 {
+            for max in 0..8 {
+                let band: Vec<_> = all
+                    .iter()
+                    .copied()
+                    .filter(|&(_v, dist)| (min..=max).contains(&dist))
+                    .collect();
+                assert_eq!(vertices_in_range(&g1, 0, min, max), Ok(band));
+            }
+        }
+        assert_eq!(vertices_in_range(&g1, 0, 0, usize::MAX), Ok(all));
+
+        let g2 = Graph::new(vec![vec![(1, 1)], vec![(2, 10)], vec![]]);
+        assert_eq!(vertices_in_range(&g2, 0, 1, 5), Ok(vec![(1, 1)]));
+        assert_eq!(vertices_in_range(&g2, 0, 5, 1), Ok(vec![]));
+    }
+
+    #[test]
+    fn route_goes_through_every_waypoint() {
+        let g1 = sample_graph();
+        let (path, dist) = shortest_path(&g1, 0, 5).unwrap().unwrap();
+        let direct = route_via(&g1, 0, &[], 5).unwrap().unwrap();
+        assert_eq!((direct.vertices(), direct.cost()), (path.as_slice(), dist));
+
+        let route = route_via(&g1, 0, &[2, 7], 5).unwrap().unwrap();
+        let legs =
+            [(0, 2), (2, 7), (7, 5)].map(|(u, v)| shortest_path(&g1, u, v).unwrap().unwrap().1);
+        assert_eq!(route.cost(), legs.iter().sum::<usize>());
+        assert_eq!(
+            route.cost(),
+            route.edges().map(|(_u, _v, weight)| weight).sum::<usize>()
+        );
+        assert_eq!(
+            (route.vertices()[0], route.vertices().last()),
+            (0, Some(&5))
+        );
+        assert!(route.contains(2) && route.contains(7));
+
+        let g2 = Graph::new(vec![vec![(1, 1)], vec![], vec![(0, 1)]]);
+        assert_eq!(route_via(&g2, 0, &[2], 1), Ok(None));
+        assert_eq!(
+            route_via(&g2, 0, &[3], 1),
+            Err(DijkstraError::InvalidSource {
+                source: 3,
+                n_vertices: 3
+            })
+        );
+    }
+
+    #[test]
+    fn stats_are_only_recorded_when_asked() {
+        let mut adjacency = sample_adjacency();
+        adjacency.push(vec![]);
+        let g1 = Graph::new(adjacency);
+        let result = shortest_paths_with_stats::<PriorityQueue<usize>>(&g1, 6).unwrap();
+        let stats = result.stats().unwrap();
+        assert_eq!(stats.settled, 8);
+        // every edge but the ones back to a settled vertex
+        assert!(stats.relaxations <= g1.n_edges());
+        assert!(stats.decrease_keys >= stats.settled - 1);
+        assert!(stats.max_frontier <= stats.settled);
+        let heap = shortest_paths_with_stats::<BinaryHeapQueue<usize>>(&g1, 6).unwrap();
+        assert_eq!(heap.stats(), Some(stats));
+
+        let plain = shortest_paths(&g1, 6).unwrap();
+        assert_eq!(plain.stats(), None);
+        assert_eq!(result.into_parts(), plain.into_parts());
+    }
+
+    #[test]
+    fn ties_are_broken_the_same_way_by_every_backend() {
+        // 4 is as far through 2 as through 3, which is fewer edges away
+        let graph = Graph::new(vec![
+            vec![(1, 1), (3, 2)],
+            vec![(2, 1)],
+            vec![(4, 1)],
+            vec![(4, 1)],
+            vec![],
+        ]);
+        let lowest_id = dijkstra_with_ties(&graph, 0, TieBreak::LowestId).unwrap();
+        assert_eq!(lowest_id.0[4], Some(vec![0, 1, 2, 4]));
+        assert_eq!(
+            dijkstra_with::<PriorityQueue<usize>>(&graph, 0),
+            Ok(lowest_id.clone())
+        );
+        assert_eq!(
+            dijkstra_with::<BinaryHeapQueue<usize>>(&graph, 0),
+            Ok(lowest_id.clone())
+        );
+        assert_eq!(
+            dijkstra_with::<DaryHeapQueue<usize>>(&graph, 0),
+            Ok(lowest_id.clone())
+        );
+        assert_eq!(
+            dijkstra_with::<RadixHeapQueue<usize>>(&graph, 0),
+            Ok(lowest_id.clone())
+        );
+
+        let fewest_hops = dijkstra_with_ties(&graph, 0, TieBreak::FewestHops).unwrap();
+        assert_eq!(fewest_hops.0[4], Some(vec![0, 3, 4]));
+        assert_eq!(fewest_hops.1, lowest_id.1);
+        for result in [
+            dijkstra_with_ties_in::<BinaryHeapQueue<usize>>(&graph, 0, TieBreak::FewestHops),
+            dijkstra_with_ties_in::<DaryHeapQueue<usize>>(&graph, 0, TieBreak::FewestHops),
+            dijkstra_with_ties_in::<RadixHeapQueue<usize>>(&graph, 0, TieBreak::FewestHops),
+        ] {
+            assert_eq!(result.as_ref(), Ok(&fewest_hops));
+        }
+
+        // through edges of weight 0, 1 is as far through 4 as through 2, which
+        // is settled after it by lowest id but has the path of fewer edges
+        let zero = Graph::new(vec![
+            vec![(2, 1), (3, 0)],
+            vec![],
+            vec![(1, 0)],
+            vec![(4, 0)],
+            vec![(1, 1)],
+        ]);
+        let (paths, dists) = dijkstra_with_ties(&zero, 0, TieBreak::LowestId).unwrap();
+        assert_eq!(paths[1], Some(vec![0, 3, 4, 1]));
+        assert_eq!(dists, vec![Some(0), Some(1), Some(1), Some(0), Some(0)]);
+        let (paths, fewest_dists) = dijkstra_with_ties(&zero, 0, TieBreak::FewestHops).unwrap();
+        assert_eq!(paths[1], Some(vec![0, 2, 1]));
+        assert_eq!(fewest_dists, dists);
+        let too_far = Graph::new(vec![vec![(1, usize::MAX / 2)], vec![]]);
+        assert_eq!(
+            dijkstra_with_ties(&too_far, 0, TieBreak::FewestHops),
+            Err(DijkstraError::Overflow)
+        );
+        assert_eq!(
+            dijkstra_with_ties(&graph, 5, TieBreak::FewestHops),
+            Err(DijkstraError::InvalidSource {
+                source: 5,
+                n_vertices: 5
+            })
+        );
+    }
+
+    #[test]
+    fn cancelled_run_keeps_only_the_source() {
+        // a chain long enough to be checked more than once
+        let n = 3000;
+        let chain = Graph::new((0..n).map(|v| vec![((v + 1) % n, 1)]).collect());
+
+        let result = dijkstra_with_cancel(&chain, 0, &AtomicBool::new(false)).unwrap();
+        assert!(!result.cancelled);
+        assert_eq!((result.paths, result.dists), dijkstra(&chain, 0).unwrap());
+
+        let result = dijkstra_with_cancel(&chain, 5, &AtomicBool::new(true)).unwrap();
+        assert!(result.cancelled);
+        assert_eq!(result.paths[5], Some(vec![5]));
+        assert_eq!(result.dists.iter().flatten().count(), 1);
+        assert!(dijkstra_with_cancel(&chain, n, &AtomicBool::new(false)).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn passed_deadline_stops_the_run() {
+        use std::time::{Duration, Instant};
+
+        let chain = Graph::new((0..3000).map(|v| vec![((v + 1) % 3000, 1)]).collect());
+        let result = dijkstra_with_deadline(&chain, 0, Instant::now()).unwrap();
+        assert!(result.cancelled);
+        assert_eq!(result.dists.iter().flatten().count(), 1);
+
+        let later = Instant::now() + Duration::from_secs(60);
+        assert!(!dijkstra_with_deadline(&chain, 0, later).unwrap().cancelled);
+    }
+
+    #[test]
+    fn streamed_records_match_full_run() {
+        let mut adjacency = sample_adjacency();
+        adjacency.push(vec![]);
+        let g1 = Graph::new(adjacency);
+        let (paths, dists) = dijkstra(&g1, 2).unwrap();
+
+        let mut records = vec![];
+        dijkstra_streaming::<PriorityQueue<usize>, _>(&g1, 2, |vertex, dist, parent| {
+            records.push((vertex, dist, parent));
+            true
+        })
+        .unwrap();
+        assert_eq!(records.len(), 8);
+        assert!(records.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+        for (vertex, dist, parent) in records {
+            let path = paths[vertex].as_ref().unwrap();
+            assert_eq!(Some(dist), dists[vertex]);
+            assert_eq!(parent, path.len().checked_sub(2).map(|idx| path[idx]));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn profiled_run_matches() {
+        let mut adjacency = sample_adjacency();
+        adjacency.push(vec![(0, 1)]);
+        let g1 = Graph::new(adjacency);
+
+        let (paths_and_dists, _phases) =
+            dijkstra_profiled::<BinaryHeapQueue<usize>>(&g1, 6).unwrap();
+        assert_eq!(Ok(paths_and_dists), dijkstra(&g1, 6));
+    }
+
+    #[test]
+    fn result_accessors_match_tuple() {
+        let mut adjacency = sample_adjacency();
+        adjacency.push(vec![(0, 1)]);
+        let g1 = Graph::new(adjacency);
+        let (paths, dists) = dijkstra(&g1, 5).unwrap();
+        let result = shortest_paths(&g1, 5).unwrap();
+
+        assert_eq!(result.source(), 5);
+        for v in 0..8 {
+            assert_eq!(result.dist_to(v), dists[v]);
+            assert_eq!(result.path_to(v).map(Path::vertices), paths[v].as_deref());
+            let walked: Option<Vec<usize>> =
+                result.iter_path(v).map(|it| it.from_source().collect());
+            assert_eq!(walked, paths[v]);
+            if let Some(path) = result.path_to(v) {
+                assert_eq!(Some(path.cost()), g1.path_cost(path.vertices()));
+                assert_eq!(Some(path.cost()), dists[v]);
+            }
+        }
+        // 8 only has an edge out of it
+        assert_eq!((result.dist_to(8), result.path_to(8)), (None, None));
+        assert_eq!(result.dist_to(9), None);
+        assert_eq!(result.reachable().count(), 8);
+        assert!(result.iter_path(8).is_none());
+        assert_eq!(result.into_parts(), (paths, dists));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn old_names_still_work() {
+        let g1 = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+        assert_eq!(crate::djikstra::djikstra(&g1, 0), dijkstra(&g1, 0));
+        assert_eq!(
+            crate::djikstra::djikstra_with::<BinaryHeapQueue<usize>>(&g1, 1),
+            dijkstra_with::<BinaryHeapQueue<usize>>(&g1, 1)
+        );
+    }
+
+    #[test]
+    fn invalid_sources_are_errors() {
+        let g1 = Graph::new(vec![vec![(1, 3)], vec![]]);
+        assert_eq!(
+            dijkstra(&g1, 2),
+            Err(DijkstraError::InvalidSource {
+                source: 2,
+                n_vertices: 2
+            })
+        );
+        assert_eq!(
+            dijkstra(&Graph::new(vec![]), 0),
+            Err(DijkstraError::EmptyGraph)
+        );
+        let streamed = dijkstra_streaming::<PriorityQueue<usize>, _>(&g1, 5, |_, _, _| true);
+        assert!(streamed.is_err());
+        assert!(batch_shortest_paths(&g1, &[(0, 1), (2, 0)]).is_err());
+    }
+
+    #[test]
+    fn overflowing_distances_are_errors() {
+        let big = usize::MAX / 2;
+        let g1 = Graph::new(vec![vec![(1, big)], vec![(2, big + 2)], vec![]]);
+        assert_eq!(dijkstra(&g1, 0), Err(DijkstraError::Overflow));
+        #[cfg(feature = "std")]
+        assert_eq!(
+            dijkstra_profiled::<BinaryHeapQueue<usize>>(&g1, 0)
+                .map(|(paths_and_dists, _)| paths_and_dists),
+            Err(DijkstraError::Overflow)
+        );
+        // the search stops before reaching 2
+        assert_eq!(shortest_path(&g1, 0, 1), Ok(Some((vec![0, 1], big))));
+
+        // a distance of `usize::MAX` would look unreachable
+        let g2 = Graph::new(vec![vec![(1, usize::MAX)], vec![]]);
+        assert_eq!(dijkstra(&g2, 0), Err(DijkstraError::Overflow));
+    }
+
+    #[test]
+    fn overflowing_longer_paths_are_ignored() {
+        let big = usize::MAX / 2;
+        // 0 -> 1 -> 2 overflows, but 0 -> 2 is a shorter path that fits
+        let g1 = Graph::new(vec![
+            vec![(1, big), (2, big + 1)],
+            vec![(2, big + 2)],
+            vec![],
+        ]);
+        let (paths, dists) = dijkstra(&g1, 0).unwrap();
+        assert_eq!(paths[2], Some(vec![0, 2]));
+        assert_eq!(dists[2], Some(big + 1));
+    }
+
+    #[test]
+    fn query_unreachable() {
+        let g1 = Graph::new(vec![vec![(1, 1)], vec![], vec![(0, 1)]]);
+        assert_eq!(shortest_path(&g1, 0, 2), Ok(None));
+    }
+
+    #[test]
+    fn batch_matches_single_queries() {
+        let g1 = sample_graph();
+        let queries = vec![(6, 5), (0, 2), (6, 0), (6, 5), (3, 3)];
+
+        let answers = batch_shortest_paths(&g1, &queries).unwrap();
+        for (&(src, dst), answer) in queries.iter().zip(answers) {
+            assert_eq!(Ok(answer), shortest_path(&g1, src, dst));
+        }
+    }
+    #[test]
+    #[cfg(feature = "std")]
+    fn parallel_batch_keeps_query_order() {
+        let g1 = sample_graph();
+        let queries: Vec<(usize, usize)> = (0..8)
+            .flat_map(|src| (0..8).rev().map(move |dst| (src, dst)))
+            .collect();
+
+        let answers = batch_shortest_paths(&g1, &queries);
+        assert!(answers.is_ok());
+        for threads in [2, 3, 16] {
+            assert_eq!(
+                batch_shortest_paths_parallel(&g1, &queries, threads),
+                answers
+            );
+        }
+    }
+
+    #[test]
+    fn batches_with_a_missing_destination_fail() {
+        let g = Graph::new(vec![vec![(1, 3)], vec![]]);
+        let queries = [(0, 1), (1, 0), (0, 9)];
+        let missing = Err(DijkstraError::InvalidSource {
+            source: 9,
+            n_vertices: 2,
+        });
+        assert_eq!(batch_shortest_paths(&g, &queries), missing);
+        #[cfg(feature = "std")]
+        for threads in [1, 2] {
+            assert_eq!(
+                batch_shortest_paths_parallel(&g, &queries, threads),
+                missing
+            );
+        }
+    }
+
+    #[test]
+    fn tree_builds_the_same_paths() {
+        let mut adjacency = sample_adjacency();
+        adjacency.push(vec![(0, 1)]);
+        let g1 = Graph::new(adjacency);
+        for src in 0..9 {
+            let tree = shortest_path_tree(&g1, src).unwrap();
+            let result = shortest_paths(&g1, src).unwrap();
+            assert_eq!(tree.source(), src);
+            assert_eq!(tree.parent(src), None);
+            for v in 0..10 {
+                assert_eq!(tree.dist_to(v), result.dist_to(v));
+                assert_eq!(tree.path_to(v).as_ref(), result.path_to(v));
+                let walked = |it: PathIter<'_>| it.collect::<Vec<_>>();
+                assert_eq!(
+                    tree.iter_path(v).map(walked),
+                    result.iter_path(v).map(walked)
+                );
+            }
+        }
+        assert_eq!(
+            shortest_path_tree(&g1, 9),
+            Err(DijkstraError::InvalidSource {
+                source: 9,
+                n_vertices: 9
+            })
+        );
+    }
+
+    #[test]
+    fn index_types_give_the_same_paths() {
+        let g1 = sample_graph();
+        let small = g1.to_index_type::<u16>().unwrap();
+        let narrow = g1.to_index_type::<u32>().unwrap();
+        for src in 0..8 {
+            assert_eq!(dijkstra(&small, src), dijkstra(&g1, src));
+            assert_eq!(
+                dijkstra_with::<BinaryHeapQueue<usize>>(&narrow, src),
+                dijkstra(&g1, src)
+            );
+            assert_eq!(shortest_path(&small, src, 5), shortest_path(&g1, src, 5));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn many_sources_match_single_runs() {
+        let mut adjacency = sample_adjacency();
+        adjacency.push(vec![]);
+        let g1 = Graph::new(adjacency);
+        let srcs = [8, 3, 0, 5, 3, 1, 7, 2, 6, 4];
+        let trees = dijkstra_many(&g1, &srcs).unwrap();
+        assert_eq!(trees.len(), 9);
+        for (&src, tree) in trees.iter() {
+            assert_eq!(*tree, shortest_paths(&g1, src).unwrap());
+        }
+
+        assert_eq!(dijkstra_many(&g1, &[]), Ok(Default::default()));
+        assert_eq!(
+            dijkstra_many(&g1, &[0, 9]),
+            Err(DijkstraError::InvalidSource {
+                source: 9,
+                n_vertices: 9
+            })
+        );
+    }
+
+    #[test]
+    fn agrees_with_bellman_ford_on_random_graphs() {
+        use crate::bellman_ford::bellman_ford;
+        use crate::test_utils::{check, GraphStrategy};
+
+        for directed in [true, false] {
+            let strategy = GraphStrategy {
+                directed,
+                ..GraphStrategy::default()
+            };
+            let result = check(&strategy, 200, 7, |graph| {
+                let src = graph.n_vertices() - 1;
+                let (_paths, dists) = dijkstra_with::<BinaryHeapQueue<usize>>(graph, src).unwrap();
+                let (_paths, expected) = bellman_ford(graph, src).unwrap();
+                if dists == expected {
+                    Ok(())
+                } else {
+                    Err(format!("expected {:?}, got {:?}", expected, dists))
+                }
+            });
+            if let Err(failure) = result {
+                panic!("{}", failure);
+            }
+        }
+    }
+}
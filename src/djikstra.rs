@@ -1,104 +1,99 @@
-// Djikstra algorithm !!!
-// uses Graph and PriorityQueue
+//! The `dijkstra` module under its old, misspelled name, kept until the next
+//! release so that code written against it still builds.
+//!
+#![allow(deprecated)]
+pub use crate::dijkstra::*;
 
 use crate::graph::Graph;
-use crate::pq::PriorityQueue;
+use crate::pq::MinPriorityQueue;
 
-/// Djikstra algorithm that takes in a graph and a source node!
-/// Returns a list of paths
-pub fn djikstra(graph: &Graph, src: usize) -> (Vec<Option<Vec<usize>>>, Vec<usize>) {
-    let n_elems = graph.n_vertices();
-    let mut parents = vec![None; n_elems];
-    let mut dists_from_src = vec![usize::MAX; n_elems];
-    let mut checked = vec![false; n_elems];
-    let mut pq: PriorityQueue<usize> = PriorityQueue::from_keys(0..n_elems - 1);
-
-    dists_from_src[src] = 0;
-    pq.change_key(&src, 0);
-
-    while let Some((node, dist_src)) = pq.extract_min() {
-        let neighbours = graph.neighbors_of(node);
-
-        for &(neighbour, dist) in neighbours.iter() {
-            if !checked[neighbour] && dists_from_src[neighbour] > dist + dist_src {
-                dists_from_src[neighbour] = dist + dist_src;
-                parents[neighbour] = Some(node);
-                pq.change_key(&neighbour, dists_from_src[neighbour]);
-            }
-        }
-        checked[node] = true;
-    }
-
-    let paths_from_src = (0..n_elems)
-        .map(|v| {
-            let mut path = vec![v];
-            while let Some(node) = parents[*path.last().unwrap()] {
-                path.push(node);
-            }
-            path.reverse();
-            if path.len() > 1 || path[0] == src {
-                Some(path)
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    (paths_from_src, dists_from_src)
+/// Renamed to [`dijkstra`](crate::dijkstra::dijkstra).
+///
+/// # Errors
+/// See [`dijkstra`](crate::dijkstra::dijkstra).
+#[deprecated(since = "0.3.0", note = "renamed to `dijkstra::dijkstra`")]
+pub fn djikstra(graph: &Graph, src: usize) -> Result<PathsAndDists, DijkstraError> {
+    dijkstra(graph, src)
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::djikstra::djikstra;
-    use crate::graph::Graph;
+/// Renamed to [`dijkstra_with`](crate::dijkstra::dijkstra_with).
+///
+/// # Errors
+/// See [`dijkstra_with`](crate::dijkstra::dijkstra_with).
+#[deprecated(since = "0.3.0", note = "renamed to `dijkstra::dijkstra_with`")]
+pub fn djikstra_with<Q>(graph: &Graph, src: usize) -> Result<PathsAndDists, DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+{
+    dijkstra_with::<Q>(graph, src)
+}
 
-    #[test]
-    fn correct_path() {
-        let g1 = Graph::new(vec![
-            vec![(1, 4), (2, 1)],
-            vec![(0, 4), (2, 2), (3, 5)],
-            vec![(0, 1), (1, 2), (3, 5)],
-            vec![(1, 5), (2, 1)],
-        ]);
-        let (paths, _dists) = djikstra(&g1, 2);
-        assert_eq!(
-            paths,
-            vec![
-                Some(vec![2, 0]),
-                Some(vec![2, 1]),
-                Some(vec![2]),
-                Some(vec![2, 3])
-            ]
-        )
-    }
+/// Renamed to [`dijkstra_with_progress`](crate::dijkstra::dijkstra_with_progress).
+///
+/// # Errors
+/// See [`dijkstra_with_progress`](crate::dijkstra::dijkstra_with_progress).
+#[deprecated(
+    since = "0.3.0",
+    note = "renamed to `dijkstra::dijkstra_with_progress`"
+)]
+pub fn djikstra_with_progress<Q, F>(
+    graph: &Graph,
+    src: usize,
+    on_settle: F,
+) -> Result<PathsAndDists, DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+    F: FnMut(usize),
+{
+    dijkstra_with_progress::<Q, F>(graph, src, on_settle)
+}
 
-    #[test]
-    fn correct_path_lg() {
-        let g1 = Graph::new(vec![
-            vec![(1, 3), (6, 2)],
-            vec![(0, 3), (2, 4), (3, 1), (6, 1), (4, 4), (7, 6)],
-            vec![(6, 6), (1, 4), (3, 2), (4, 2)],
-            vec![(1, 1), (2, 2), (4, 1), (7, 2)],
-            vec![(2, 2), (3, 1), (1, 4), (7, 1), (5, 3)],
-            vec![(4, 3), (7, 4)],
-            vec![(0, 2), (1, 1), (2, 6), (4, 5)],
-            vec![(4, 1), (5, 4), (3, 2), (1, 6)],
-        ]);
+/// Renamed to [`dijkstra_while`](crate::dijkstra::dijkstra_while).
+///
+/// # Errors
+/// See [`dijkstra_while`](crate::dijkstra::dijkstra_while).
+#[deprecated(since = "0.3.0", note = "renamed to `dijkstra::dijkstra_while`")]
+pub fn djikstra_while<Q, F>(
+    graph: &Graph,
+    src: usize,
+    on_settle: F,
+) -> Result<PathsAndDists, DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+    F: FnMut(usize) -> bool,
+{
+    dijkstra_while::<Q, F>(graph, src, on_settle)
+}
 
-        let (paths, _dists) = djikstra(&g1, 6);
+/// Renamed to [`dijkstra_streaming`](crate::dijkstra::dijkstra_streaming).
+///
+/// # Errors
+/// See [`dijkstra_streaming`](crate::dijkstra::dijkstra_streaming).
+#[deprecated(since = "0.3.0", note = "renamed to `dijkstra::dijkstra_streaming`")]
+pub fn djikstra_streaming<Q, F>(
+    graph: &Graph,
+    src: usize,
+    on_settle: F,
+) -> Result<(), DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+    F: FnMut(usize, usize, Option<usize>) -> bool,
+{
+    dijkstra_streaming::<Q, F>(graph, src, on_settle)
+}
 
-        assert_eq!(
-            paths,
-            vec![
-                Some(vec![6, 0]),
-                Some(vec![6, 1]),
-                Some(vec![6, 1, 3, 2]),
-                Some(vec![6, 1, 3]),
-                Some(vec![6, 1, 3, 4]),
-                Some(vec![6, 1, 3, 4, 5]),
-                Some(vec![6]),
-                Some(vec![6, 1, 3, 7])
-            ]
-        );
-    }
+/// Renamed to [`dijkstra_profiled`](crate::dijkstra::dijkstra_profiled).
+///
+/// # Errors
+/// See [`dijkstra_profiled`](crate::dijkstra::dijkstra_profiled).
+#[cfg(feature = "std")]
+#[deprecated(since = "0.3.0", note = "renamed to `dijkstra::dijkstra_profiled`")]
+pub fn djikstra_profiled<Q>(
+    graph: &Graph,
+    src: usize,
+) -> Result<(PathsAndDists, Phases), DijkstraError>
+where
+    Q: MinPriorityQueue<usize>,
+{
+    dijkstra_profiled::<Q>(graph, src)
 }
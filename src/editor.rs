@@ -0,0 +1,464 @@
+//! Edits of a graph recorded in a journal, to undo, redo and replay them.
+//!
+use crate::graph::{Adjacency, Graph};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::{error, fmt, str::FromStr};
+use serde::{Deserialize, Serialize};
+
+/// One change of the edges of a graph.
+///
+/// It is written and parsed as the REPL command making it, e.g.
+/// `add-edge 3 7 5`, so a journal of edits is a script of commands.
+///
+/// # Example
+/// ```
+/// use djikstra::editor::Edit;
+///
+/// let edit: Edit = "set-weight 1 2 9".parse().unwrap();
+/// assert_eq!(edit, Edit::SetWeight { from: 1, to: 2, weight: 9 });
+/// assert_eq!(edit.to_string(), "set-weight 1 2 9");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Edit {
+    /// Add an edge from `from` to `to`, after the other neighbors of `from`.
+    AddEdge {
+        from: usize,
+        to: usize,
+        weight: usize,
+    },
+    /// Remove every edge from `from` to `to`.
+    RemoveEdge { from: usize, to: usize },
+    /// Set the weight of every edge from `from` to `to`.
+    SetWeight {
+        from: usize,
+        to: usize,
+        weight: usize,
+    },
+}
+
+impl Edit {
+    /// The vertex whose edges the edit changes.
+    pub fn from(&self) -> usize {
+        match *self {
+            Edit::AddEdge { from, .. }
+            | Edit::RemoveEdge { from, .. }
+            | Edit::SetWeight { from, .. } => from,
+        }
+    }
+
+    /// The vertex the changed edges go to.
+    pub fn to(&self) -> usize {
+        match *self {
+            Edit::AddEdge { to, .. } | Edit::RemoveEdge { to, .. } | Edit::SetWeight { to, .. } => {
+                to
+            }
+        }
+    }
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::AddEdge { from, to, weight } => write!(f, "add-edge {} {} {}", from, to, weight),
+            Edit::RemoveEdge { from, to } => write!(f, "remove-edge {} {}", from, to),
+            Edit::SetWeight { from, to, weight } => {
+                write!(f, "set-weight {} {} {}", from, to, weight)
+            }
+        }
+    }
+}
+
+impl FromStr for Edit {
+    type Err = EditError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let numbers: Option<Vec<usize>> =
+            words.iter().skip(1).map(|word| word.parse().ok()).collect();
+        match (words.first().copied(), numbers.as_deref()) {
+            (Some("add-edge"), Some(&[from, to, weight])) => Ok(Edit::AddEdge { from, to, weight }),
+            (Some("remove-edge"), Some(&[from, to])) => Ok(Edit::RemoveEdge { from, to }),
+            (Some("set-weight"), Some(&[from, to, weight])) => {
+                Ok(Edit::SetWeight { from, to, weight })
+            }
+            (Some("add-edge" | "set-weight"), _) => Err(EditError::Parse(format!(
+                "expected {} <from> <to> <weight>",
+                words[0]
+            ))),
+            (Some("remove-edge"), _) => Err(EditError::Parse(
+                "expected remove-edge <from> <to>".to_string(),
+            )),
+            _ => Err(EditError::Parse(format!("unknown edit {:?}", s.trim()))),
+        }
+    }
+}
+
+/// The error type of the edits of a [`GraphEditor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EditError {
+    /// A vertex of the edit is not in the graph.
+    InvalidVertex { vertex: usize, n_vertices: usize },
+    /// There is no edge to remove or reweight.
+    NoEdge { from: usize, to: usize },
+    /// The text is not an edit.
+    Parse(String),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::InvalidVertex { vertex, n_vertices } => write!(
+                f,
+                "vertex {} is not in the graph of {} vertices",
+                vertex, n_vertices
+            ),
+            EditError::NoEdge { from, to } => write!(f, "there is no edge from {} to {}", from, to),
+            EditError::Parse(message) => write!(f, "cannot parse edit: {}", message),
+        }
+    }
+}
+
+impl error::Error for EditError {}
+
+/// What an edit changed in the neighbors of its `from` vertex, to undo it
+/// without a copy of all of them.
+#[derive(Debug)]
+enum Before {
+    /// The edge was pushed after the other neighbors.
+    Added,
+    /// The edges removed, with their positions among the neighbors.
+    Removed(Vec<(usize, (usize, usize))>),
+    /// The weights of the edges reweighted, with their positions.
+    Reweighted(Vec<(usize, usize)>),
+}
+
+/// Graph whose edits are recorded, so they can be undone, redone, and
+/// replayed on another copy of the graph for reproducible what-if routing.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::shortest_path;
+/// use djikstra::editor::{Edit, GraphEditor};
+/// use djikstra::graph::Graph;
+///
+/// let mut editor = GraphEditor::new(Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]));
+/// editor.add_edge(0, 2, 1).unwrap();
+/// assert_eq!(shortest_path(editor.graph(), 0, 2), Ok(Some((vec![0, 2], 1))));
+///
+/// // what if the shortcut did not exist
+/// editor.undo();
+/// assert_eq!(shortest_path(editor.graph(), 0, 2), Ok(Some((vec![0, 1, 2], 4))));
+/// editor.redo();
+/// assert_eq!(editor.journal(), &[Edit::AddEdge { from: 0, to: 2, weight: 1 }]);
+/// ```
+#[derive(Debug)]
+pub struct GraphEditor {
+    graph: Graph,
+    // the edits applied with what they changed in the neighbors of their
+    // `from` vertex
+    done: Vec<Edit>,
+    before: Vec<Before>,
+    // the edits undone, the last one undone first
+    undone: Vec<Edit>,
+    // the number of edits applied, undone and redone
+    generation: u64,
+}
+
+impl GraphEditor {
+    /// Editor of `graph`, with an empty journal.
+    pub fn new(graph: Graph) -> Self {
+        Self {
+            graph,
+            done: vec![],
+            before: vec![],
+            undone: vec![],
+            generation: 0,
+        }
+    }
+
+    /// The graph with every edit of the journal applied.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    /// The edited graph, without the journal.
+    pub fn into_graph(self) -> Graph {
+        self.graph
+    }
+
+    /// The edits applied, oldest first, without the ones undone.
+    pub fn journal(&self) -> &[Edit] {
+        &self.done
+    }
+
+    /// Number of edits applied, undone and redone so far, so that a
+    /// [`RouteCache`] of the editor forgets its trees when the graph changes.
+    ///
+    /// [`RouteCache`]: crate::cache::RouteCache
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Apply `edit` and record it in the journal, forgetting the edits undone.
+    ///
+    /// # Errors
+    /// Fails, leaving the graph unchanged, if a vertex of the edit is not in
+    /// the graph, or if there is no edge to remove or reweight.
+    pub fn apply(&mut self, edit: Edit) -> Result<(), EditError> {
+        self.redo_edit(edit)?;
+        self.undone.clear();
+        Ok(())
+    }
+
+    /// Apply [`Edit::AddEdge`].
+    ///
+    /// # Errors
+    /// Fails if `from` or `to` is not a vertex of the graph.
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: usize) -> Result<(), EditError> {
+        self.apply(Edit::AddEdge { from, to, weight })
+    }
+
+    /// Apply [`Edit::RemoveEdge`].
+    ///
+    /// # Errors
+    /// Fails if `from` or `to` is not a vertex of the graph, or if there is
+    /// no edge from `from` to `to`.
+    pub fn remove_edge(&mut self, from: usize, to: usize) -> Result<(), EditError> {
+        self.apply(Edit::RemoveEdge { from, to })
+    }
+
+    /// Apply [`Edit::SetWeight`].
+    ///
+    /// # Errors
+    /// Fails if `from` or `to` is not a vertex of the graph, or if there is
+    /// no edge from `from` to `to`.
+    pub fn set_weight(&mut self, from: usize, to: usize, weight: usize) -> Result<(), EditError> {
+        self.apply(Edit::SetWeight { from, to, weight })
+    }
+
+    /// Undo the last edit applied, returning it, or `None` if there is none.
+    pub fn undo(&mut self) -> Option<Edit> {
+        let edit = self.done.pop()?;
+        let before = self.before.pop().expect("every edit has what it changed");
+        let neighbors = &mut self.graph.adj[edit.from()];
+        match before {
+            Before::Added => {
+                neighbors.pop();
+            }
+            // by increasing position, so every edge goes back to its place
+            Before::Removed(edges) => {
+                for (position, edge) in edges {
+                    neighbors.insert(position, edge);
+                }
+            }
+            Before::Reweighted(weights) => {
+                for (position, weight) in weights {
+                    neighbors[position].1 = weight;
+                }
+            }
+        }
+        self.undone.push(edit);
+        self.generation += 1;
+        Some(edit)
+    }
+
+    /// Apply again the last edit undone, returning it, or `None` if there is none.
+    pub fn redo(&mut self) -> Option<Edit> {
+        let edit = self.undone.pop()?;
+        self.redo_edit(edit)
+            .expect("an edit undone applies to the graph it was undone on");
+        Some(edit)
+    }
+
+    /// Apply every edit of `edits` in order, e.g. the journal of another editor.
+    ///
+    /// # Errors
+    /// Fails at the first edit that cannot be applied, keeping the edits
+    /// before it.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::editor::GraphEditor;
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph = || Graph::new(vec![vec![(1, 3)], vec![]]);
+    /// let mut first = GraphEditor::new(graph());
+    /// first.set_weight(0, 1, 9).unwrap();
+    /// first.add_edge(1, 0, 2).unwrap();
+    ///
+    /// let mut second = GraphEditor::new(graph());
+    /// second.replay(first.journal().iter().copied()).unwrap();
+    /// assert_eq!(second.graph(), first.graph());
+    /// ```
+    pub fn replay(&mut self, edits: impl IntoIterator<Item = Edit>) -> Result<(), EditError> {
+        for edit in edits {
+            self.apply(edit)?;
+        }
+        Ok(())
+    }
+
+    /// Apply `edit` and record it, keeping the edits undone.
+    fn redo_edit(&mut self, edit: Edit) -> Result<(), EditError> {
+        let n_vertices = self.graph.n_vertices();
+        if let Some(vertex) = [edit.from(), edit.to()]
+            .into_iter()
+            .find(|&v| v >= n_vertices)
+        {
+            return Err(EditError::InvalidVertex { vertex, n_vertices });
+        }
+        // the edges from `from` to `to`, with their positions
+        let edges: Vec<(usize, (usize, usize))> = self.graph.adj[edit.from()]
+            .iter()
+            .enumerate()
+            .filter(|&(_position, &(v, _weight))| v == edit.to())
+            .map(|(position, &edge)| (position, edge))
+            .collect();
+        if edges.is_empty() && !matches!(edit, Edit::AddEdge { .. }) {
+            return Err(EditError::NoEdge {
+                from: edit.from(),
+                to: edit.to(),
+            });
+        }
+        let before = match edit {
+            Edit::AddEdge { from, to, weight } => {
+                self.graph.add_edge(from, to, weight);
+                Before::Added
+            }
+            Edit::RemoveEdge { from, to } => {
+                self.graph.remove_edge(from, to);
+                Before::Removed(edges)
+            }
+            Edit::SetWeight { from, to, weight } => {
+                self.graph.set_weight(from, to, weight);
+                Before::Reweighted(
+                    edges
+                        .into_iter()
+                        .map(|(position, (_v, weight))| (position, weight))
+                        .collect(),
+                )
+            }
+        };
+        self.done.push(edit);
+        self.before.push(before);
+        self.generation += 1;
+        Ok(())
+    }
+}
+
+impl Adjacency for GraphEditor {
+    type Index = usize;
+    type Weight = usize;
+
+    fn n_vertices(&self) -> usize {
+        self.graph.n_vertices()
+    }
+
+    fn neighbors_of(&self, vertex: usize) -> &[(usize, usize)] {
+        self.graph.neighbors_of(vertex)
+    }
+
+    fn generation(&self) -> Option<u64> {
+        Some(self.generation)
+    }
+}
+
+impl From<Graph> for GraphEditor {
+    fn from(graph: Graph) -> Self {
+        Self::new(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::editor::{Edit, EditError, GraphEditor};
+    use crate::graph::Graph;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn sample() -> Graph {
+        Graph::new(vec![vec![(1, 3), (2, 5), (1, 4)], vec![(2, 1)], vec![]])
+    }
+
+    #[test]
+    fn undo_restores_every_edit() {
+        let mut editor = GraphEditor::new(sample());
+        editor.remove_edge(0, 1).unwrap();
+        editor.set_weight(0, 2, 1).unwrap();
+        editor.add_edge(2, 0, 7).unwrap();
+        assert_eq!(
+            editor.graph(),
+            &Graph::new(vec![vec![(2, 1)], vec![(2, 1)], vec![(0, 7)]])
+        );
+        while editor.undo().is_some() {}
+        // the removed edges come back in their places
+        assert_eq!(editor.graph().adj, sample().adj);
+
+        assert_eq!(editor.redo(), Some(Edit::RemoveEdge { from: 0, to: 1 }));
+        assert_eq!(editor.journal().len(), 1);
+        // a new edit forgets the ones undone
+        editor.add_edge(1, 0, 1).unwrap();
+        assert_eq!(editor.redo(), None);
+        assert_eq!(editor.journal().len(), 2);
+    }
+
+    #[test]
+    fn undo_restores_parallel_edges() {
+        let mut editor = GraphEditor::new(sample());
+        editor.set_weight(0, 1, 9).unwrap();
+        editor.add_edge(0, 1, 2).unwrap();
+        editor.remove_edge(0, 1).unwrap();
+        assert_eq!(editor.graph().neighbors_of(0), &[(2, 5)]);
+        editor.undo();
+        assert_eq!(
+            editor.graph().neighbors_of(0),
+            &[(1, 9), (2, 5), (1, 9), (1, 2)]
+        );
+        editor.undo();
+        editor.undo();
+        assert_eq!(editor.graph().adj, sample().adj);
+    }
+
+    #[test]
+    fn failed_edits_change_nothing() {
+        let mut editor = GraphEditor::new(sample());
+        assert_eq!(
+            editor.add_edge(0, 3, 1),
+            Err(EditError::InvalidVertex {
+                vertex: 3,
+                n_vertices: 3
+            })
+        );
+        assert_eq!(
+            editor.set_weight(2, 0, 1),
+            Err(EditError::NoEdge { from: 2, to: 0 })
+        );
+        assert!(editor.journal().is_empty());
+        assert_eq!(editor.undo(), None);
+        assert_eq!(editor.into_graph().adj, sample().adj);
+    }
+
+    #[test]
+    fn journals_replay_as_text() {
+        let mut editor = GraphEditor::new(sample());
+        editor.set_weight(1, 2, 8).unwrap();
+        editor.remove_edge(0, 2).unwrap();
+        let script: Vec<_> = editor
+            .journal()
+            .iter()
+            .map(|edit| edit.to_string())
+            .collect();
+        assert_eq!(script, vec!["set-weight 1 2 8", "remove-edge 0 2"]);
+
+        let mut other = GraphEditor::new(sample());
+        other
+            .replay(script.iter().map(|line| line.parse().unwrap()))
+            .unwrap();
+        assert_eq!(other.graph(), editor.graph());
+        assert!("add-edge 1 2".parse::<Edit>().is_err());
+        assert!("walk 1 2".parse::<Edit>().is_err());
+    }
+}
@@ -0,0 +1,135 @@
+//! The error type covering everything that can go wrong in the crate, so that
+//! callers mixing parsing and the algorithms can use `?` on all of them.
+//!
+use crate::dijkstra::DijkstraError;
+use crate::editor::EditError;
+use crate::graph::{OddCycle, ParseGraphError};
+use alloc::string::String;
+use core::{error, fmt};
+
+/// Any error returned by the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A graph cannot be parsed.
+    Graph(ParseGraphError),
+    /// An input other than a graph, e.g. a start vertex or a list of queries,
+    /// cannot be parsed. The cause of the error is within the variant.
+    Input(String),
+    /// The algorithm cannot run on the graph.
+    Dijkstra(DijkstraError),
+    /// An edit cannot be applied to the graph.
+    Edit(EditError),
+    /// The graph is not bipartite, with a cycle of odd length in it.
+    NotBipartite(OddCycle),
+    /// A file cannot be read or written, e.g. by the external-memory search.
+    /// The message of the I/O error is within the variant.
+    Io(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Graph(e) => write!(f, "cannot parse graph: {}", e),
+            Error::Input(message) => write!(f, "{}", message),
+            Error::Dijkstra(e) => write!(f, "{}", e),
+            Error::Edit(e) => write!(f, "{}", e),
+            Error::NotBipartite(e) => write!(f, "the graph is not bipartite: {}", e),
+            Error::Io(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Graph(e) => Some(e),
+            Error::Input(_) | Error::Io(_) => None,
+            Error::Dijkstra(e) => Some(e),
+            Error::Edit(e) => Some(e),
+            Error::NotBipartite(e) => Some(e),
+        }
+    }
+}
+
+impl From<ParseGraphError> for Error {
+    fn from(e: ParseGraphError) -> Self {
+        Error::Graph(e)
+    }
+}
+
+impl From<DijkstraError> for Error {
+    fn from(e: DijkstraError) -> Self {
+        Error::Dijkstra(e)
+    }
+}
+
+impl From<EditError> for Error {
+    fn from(e: EditError) -> Self {
+        Error::Edit(e)
+    }
+}
+
+impl From<OddCycle> for Error {
+    fn from(e: OddCycle) -> Self {
+        Error::NotBipartite(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dijkstra::{dijkstra, DijkstraError};
+    use crate::editor::{EditError, GraphEditor};
+    use crate::error::Error;
+    use crate::graph::{Graph, OddCycle};
+    use crate::matching::hopcroft_karp;
+    use std::error::Error as _;
+    use std::str::FromStr;
+
+    fn distance(graph: &str, src: usize, dst: usize) -> Result<Option<usize>, Error> {
+        let graph = Graph::from_str(graph)?;
+        let (_paths, dists) = dijkstra(&graph, src)?;
+        Ok(dists[dst])
+    }
+
+    #[test]
+    fn errors_convert_with_question_mark() {
+        assert_eq!(distance("2\n1,3\n0,3", 0, 1), Ok(Some(3)));
+        let parse_error = distance("2\n1;3\n0,3", 0, 1).unwrap_err();
+        assert!(matches!(parse_error, Error::Graph(_)));
+        assert!(parse_error.source().is_some());
+        assert_eq!(
+            distance("2\n1,3\n0,3", 5, 1),
+            Err(Error::Dijkstra(DijkstraError::InvalidSource {
+                source: 5,
+                n_vertices: 2
+            }))
+        );
+    }
+
+    #[test]
+    fn edit_errors_convert() {
+        let remove = |from, to| -> Result<(), Error> {
+            let mut editor = GraphEditor::new(Graph::from_str("2\n1,3\n\n")?);
+            editor.remove_edge(from, to)?;
+            Ok(())
+        };
+        assert_eq!(remove(0, 1), Ok(()));
+        assert_eq!(
+            remove(1, 0),
+            Err(Error::Edit(EditError::NoEdge { from: 1, to: 0 }))
+        );
+        assert!(remove(1, 0).unwrap_err().source().is_some());
+    }
+
+    #[test]
+    fn odd_cycles_convert() {
+        let matching = |graph: &str| -> Result<usize, Error> {
+            Ok(hopcroft_karp(&Graph::from_str(graph)?)?.len())
+        };
+        assert_eq!(matching("2\n1,1\n\n"), Ok(1));
+        assert_eq!(
+            matching("3\n1,1\n2,1\n0,1"),
+            Err(Error::NotBipartite(OddCycle(vec![0, 1, 2])))
+        );
+    }
+}
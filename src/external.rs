@@ -0,0 +1,672 @@
+//! Dijkstra on graphs larger than memory, with the graph and the results on disk.
+//!
+//! The graph is converted once to a binary [`DiskGraph`], the edges of every
+//! vertex one after the other as in [`crate::csr::CsrGraph`], and read back
+//! with positional reads so only the edges of the settled vertices are
+//! loaded, the page cache of the operating system keeping the hot ones.
+//! [`dijkstra_external`] keeps a bit per vertex and the frontier in memory,
+//! and the distances and parents in files of which at most a few chunks
+//! are in memory at a time.
+//!
+//! The files are read with positional reads rather than mapped in memory.
+//! A mapping needs `unsafe` and is undefined behavior if another process
+//! truncates or rewrites the file while it is mapped, where a read only
+//! fails with an [`io::Error`]. A mapping of a graph larger than memory
+//! also does not fit in the address space of 32-bit targets, and each read
+//! here is a whole adjacency list or chunk, so the cost of the system call
+//! is small next to copying its bytes out of the page cache.
+//!
+use crate::bitset::BitSet;
+use crate::dijkstra::{add_distance, DijkstraError};
+use crate::error::Error;
+use crate::graph::{parse_neighbors, ParseGraphError};
+use alloc::collections::{BTreeMap, BTreeSet};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::{error, fmt};
+
+/// First bytes of a [`DiskGraph`] file.
+const MAGIC: &[u8; 8] = b"DJKCSR\x00\x01";
+/// Bytes before the offsets: the magic, the number of vertices and of edges.
+const HEADER_LEN: u64 = 24;
+
+/// The error type of the external-memory search.
+#[derive(Debug)]
+pub enum ExternalError {
+    /// A file cannot be read or written.
+    Io(io::Error),
+    /// The input graph cannot be parsed.
+    Graph(ParseGraphError),
+    /// The algorithm cannot run on the graph.
+    Dijkstra(DijkstraError),
+}
+
+impl fmt::Display for ExternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExternalError::Io(e) => write!(f, "{}", e),
+            ExternalError::Graph(e) => write!(f, "cannot parse graph: {}", e),
+            ExternalError::Dijkstra(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for ExternalError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ExternalError::Io(e) => Some(e),
+            ExternalError::Graph(e) => Some(e),
+            ExternalError::Dijkstra(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for ExternalError {
+    fn from(e: io::Error) -> Self {
+        ExternalError::Io(e)
+    }
+}
+
+impl From<ParseGraphError> for ExternalError {
+    fn from(e: ParseGraphError) -> Self {
+        ExternalError::Graph(e)
+    }
+}
+
+impl From<DijkstraError> for ExternalError {
+    fn from(e: DijkstraError) -> Self {
+        ExternalError::Dijkstra(e)
+    }
+}
+
+impl From<ExternalError> for Error {
+    fn from(e: ExternalError) -> Self {
+        match e {
+            ExternalError::Io(e) => Error::Io(e.to_string()),
+            ExternalError::Graph(e) => Error::Graph(e),
+            ExternalError::Dijkstra(e) => Error::Dijkstra(e),
+        }
+    }
+}
+
+/// Read exactly `buf.len()` bytes at `offset` of `file`, without moving its cursor.
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+    }
+    #[cfg(not(unix))]
+    {
+        let mut file = file;
+        file.seek(SeekFrom::Start(offset))?;
+        io::Read::read_exact(&mut file, buf)
+    }
+}
+
+fn read_u64_at(file: &File, offset: u64) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    read_exact_at(file, &mut buf, offset)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Graph stored in a file in compressed sparse row form, read on demand:
+/// the number of vertices and of edges, the offset of the edges of every
+/// vertex, then every edge as a `(neighbor, weight)` pair, all as
+/// little-endian `u64`.
+///
+/// # Example
+/// ```
+/// use djikstra::external::DiskGraph;
+///
+/// let path = std::env::temp_dir().join("djikstra-disk-graph-example.bin");
+/// let graph = DiskGraph::create(&mut "3\n1,3\n2,1\n\n".as_bytes(), &path).unwrap();
+/// assert_eq!((graph.n_vertices(), graph.n_edges()), (3, 2));
+/// let mut edges = vec![];
+/// graph.neighbors_of(1, &mut edges).unwrap();
+/// assert_eq!(edges, vec![(2, 1)]);
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct DiskGraph {
+    file: File,
+    n_vertices: usize,
+    n_edges: usize,
+}
+
+impl DiskGraph {
+    /// Convert a graph in the input format of [`crate::graph::Graph`] read from
+    /// `input` to the file at `path`, one line at a time, so the graph never
+    /// needs to fit in memory.
+    ///
+    /// # Errors
+    /// Fails if the input cannot be read or parsed, e.g. if an edge goes to a
+    /// vertex past the number of vertices, or if the file cannot be written.
+    pub fn create(input: &mut impl BufRead, path: &Path) -> Result<Self, ExternalError> {
+        let mut line = String::new();
+        input.read_line(&mut line)?;
+        let n_vertices: usize = line
+            .trim_end()
+            .parse()
+            .map_err(|e| ParseGraphError(format!("cannot parse n_vertices: {}", e)))?;
+
+        // the offsets and the edges are written at the same time, through
+        // two handles with cursors of their own
+        let edges_start = HEADER_LEN + (n_vertices as u64 + 1) * 8;
+        let mut offsets = BufWriter::new(File::create(path)?);
+        let mut edges = BufWriter::new(OpenOptions::new().write(true).open(path)?);
+        offsets.seek(SeekFrom::Start(HEADER_LEN))?;
+        edges.seek(SeekFrom::Start(edges_start))?;
+
+        let mut n_edges = 0_u64;
+        let mut parsed = vec![];
+        for v in 0..n_vertices {
+            offsets.write_all(&n_edges.to_le_bytes())?;
+            line.clear();
+            if input.read_line(&mut line)? == 0 {
+                // the vertices without a line have no edges
+                continue;
+            }
+            parsed.clear();
+            parse_neighbors(&line, &mut parsed)?;
+            for &(neighbor, weight) in parsed.iter() {
+                if neighbor >= n_vertices {
+                    return Err(ParseGraphError(format!(
+                        "edge from {} to {}, past the {} vertices",
+                        v, neighbor, n_vertices
+                    ))
+                    .into());
+                }
+                edges.write_all(&(neighbor as u64).to_le_bytes())?;
+                edges.write_all(&(weight as u64).to_le_bytes())?;
+            }
+            n_edges += parsed.len() as u64;
+        }
+        offsets.write_all(&n_edges.to_le_bytes())?;
+        edges.flush()?;
+
+        offsets.seek(SeekFrom::Start(0))?;
+        offsets.write_all(MAGIC)?;
+        offsets.write_all(&(n_vertices as u64).to_le_bytes())?;
+        offsets.write_all(&n_edges.to_le_bytes())?;
+        offsets.flush()?;
+        drop((offsets, edges));
+        Ok(Self::open(path)?)
+    }
+
+    /// Open a graph written by [`DiskGraph::create`].
+    ///
+    /// # Errors
+    /// Fails if the file cannot be read or is not a graph.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let mut magic = [0; 8];
+        read_exact_at(&file, &mut magic, 0)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not a graph written by DiskGraph::create",
+            ));
+        }
+        let to_usize = |n: u64| {
+            usize::try_from(n)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "the graph is too large"))
+        };
+        let n_vertices = to_usize(read_u64_at(&file, 8)?)?;
+        let n_edges = to_usize(read_u64_at(&file, 16)?)?;
+        Ok(Self {
+            file,
+            n_vertices,
+            n_edges,
+        })
+    }
+
+    /// Number of vertices
+    pub fn n_vertices(&self) -> usize {
+        self.n_vertices
+    }
+
+    /// Number of edges
+    pub fn n_edges(&self) -> usize {
+        self.n_edges
+    }
+
+    /// Read the `(neighbor, weight)` edges of `vertex` into `edges`, replacing
+    /// what it held.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be read, or if its offsets of the edges of
+    /// `vertex` are not a range of the edges.
+    ///
+    /// # Panics
+    /// If `vertex` is not a vertex of the graph.
+    pub fn neighbors_of(&self, vertex: usize, edges: &mut Vec<(usize, usize)>) -> io::Result<()> {
+        assert!(
+            vertex < self.n_vertices,
+            "vertex {} is not in the graph",
+            vertex
+        );
+        let mut bounds = [0; 16];
+        read_exact_at(&self.file, &mut bounds, HEADER_LEN + vertex as u64 * 8)?;
+        let (start, end) = bounds.split_at(8);
+        let start = u64::from_le_bytes(start.try_into().unwrap());
+        let end = u64::from_le_bytes(end.try_into().unwrap());
+
+        let len = end
+            .checked_sub(start)
+            .filter(|_len| end <= self.n_edges as u64)
+            .and_then(|len| usize::try_from(len).ok()?.checked_mul(16))
+            .ok_or(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "edges {}..{} of vertex {} are not in the {} edges of the graph",
+                    start, end, vertex, self.n_edges
+                ),
+            ))?;
+        let mut bytes = vec![0; len];
+        let edges_start = HEADER_LEN + (self.n_vertices as u64 + 1) * 8;
+        read_exact_at(&self.file, &mut bytes, edges_start + start * 16)?;
+        edges.clear();
+        edges.extend(bytes.chunks_exact(16).map(|edge| {
+            let (v, weight) = edge.split_at(8);
+            (
+                u64::from_le_bytes(v.try_into().unwrap()) as usize,
+                u64::from_le_bytes(weight.try_into().unwrap()) as usize,
+            )
+        }));
+        Ok(())
+    }
+}
+
+/// How much of the results [`dijkstra_external`] keeps in memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryLimits {
+    /// Number of vertices per chunk of the distances and of the parents.
+    pub chunk_len: usize,
+    /// Number of chunks of the distances, and as many of the parents, in memory.
+    pub max_chunks: usize,
+}
+
+impl Default for MemoryLimits {
+    /// 64 chunks of 65 536 vertices, 64 MiB for the distances and the parents.
+    fn default() -> Self {
+        Self {
+            chunk_len: 1 << 16,
+            max_chunks: 64,
+        }
+    }
+}
+
+/// Array of `u64` in a file, of which at most `max_chunks` chunks are in
+/// memory, the least recently used written back first.
+#[derive(Debug)]
+struct ChunkedArray {
+    file: File,
+    limits: MemoryLimits,
+    // chunks in memory by index, with the tick they were last used at and
+    // whether they changed since they were read
+    chunks: BTreeMap<usize, (u64, bool, Vec<u64>)>,
+    tick: u64,
+}
+
+impl ChunkedArray {
+    /// Array of `len` times `fill` in a new file at `path`.
+    fn create(path: &Path, len: usize, fill: u64, limits: MemoryLimits) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        let mut w = BufWriter::new(&file);
+        for _ in 0..len {
+            w.write_all(&fill.to_le_bytes())?;
+        }
+        w.flush()?;
+        drop(w);
+        Ok(Self {
+            file,
+            limits: MemoryLimits {
+                chunk_len: limits.chunk_len.max(1),
+                max_chunks: limits.max_chunks.max(1),
+            },
+            chunks: BTreeMap::new(),
+            tick: 0,
+        })
+    }
+
+    fn get(&mut self, i: usize) -> io::Result<u64> {
+        let chunk_len = self.limits.chunk_len;
+        Ok(self.chunk(i / chunk_len)?[i % chunk_len])
+    }
+
+    fn set(&mut self, i: usize, value: u64) -> io::Result<()> {
+        let chunk_len = self.limits.chunk_len;
+        self.chunk(i / chunk_len)?[i % chunk_len] = value;
+        self.chunks.get_mut(&(i / chunk_len)).unwrap().1 = true;
+        Ok(())
+    }
+
+    /// The chunk at `index`, read from the file if it is not in memory.
+    fn chunk(&mut self, index: usize) -> io::Result<&mut Vec<u64>> {
+        self.tick += 1;
+        if !self.chunks.contains_key(&index) {
+            if self.chunks.len() == self.limits.max_chunks {
+                let oldest = *self
+                    .chunks
+                    .iter()
+                    .min_by_key(|(_index, (last_used, _dirty, _values))| *last_used)
+                    .unwrap()
+                    .0;
+                let (_last_used, dirty, values) = self.chunks.remove(&oldest).unwrap();
+                if dirty {
+                    self.write_chunk(oldest, &values)?;
+                }
+            }
+            let start = (index * self.limits.chunk_len) as u64 * 8;
+            let len = self
+                .file
+                .metadata()?
+                .len()
+                .saturating_sub(start)
+                .min(self.limits.chunk_len as u64 * 8);
+            let mut bytes = vec![0; len as usize];
+            read_exact_at(&self.file, &mut bytes, start)?;
+            let values = bytes
+                .chunks_exact(8)
+                .map(|value| u64::from_le_bytes(value.try_into().unwrap()))
+                .collect();
+            self.chunks.insert(index, (0, false, values));
+        }
+        let (last_used, _dirty, values) = self.chunks.get_mut(&index).unwrap();
+        *last_used = self.tick;
+        Ok(values)
+    }
+
+    fn write_chunk(&self, index: usize, values: &[u64]) -> io::Result<()> {
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start((index * self.limits.chunk_len) as u64 * 8))?;
+        let bytes: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        file.write_all(&bytes)
+    }
+
+    /// Write every changed chunk back to the file.
+    fn flush(&mut self) -> io::Result<()> {
+        for (&index, (_last_used, dirty, values)) in self.chunks.iter() {
+            if *dirty {
+                self.write_chunk(index, values)?;
+            }
+        }
+        for (_last_used, dirty, _values) in self.chunks.values_mut() {
+            *dirty = false;
+        }
+        Ok(())
+    }
+}
+
+/// Distances and parents found by [`dijkstra_external`], in files, read a
+/// few chunks at a time.
+#[derive(Debug)]
+pub struct DiskResults {
+    source: usize,
+    dists: ChunkedArray,
+    parents: ChunkedArray,
+}
+
+impl DiskResults {
+    /// The vertex the paths start from.
+    pub fn source(&self) -> usize {
+        self.source
+    }
+
+    /// Distance from the source to `v`, `None` if `v` is unreachable.
+    ///
+    /// # Errors
+    /// Fails if the file of the distances cannot be read.
+    pub fn dist_to(&mut self, v: usize) -> io::Result<Option<usize>> {
+        let dist = self.dists.get(v)?;
+        Ok((dist != u64::MAX).then_some(dist as usize))
+    }
+
+    /// Shortest path from the source to `v`, `None` if `v` is unreachable.
+    ///
+    /// # Errors
+    /// Fails if the files of the results cannot be read.
+    pub fn path_to(&mut self, v: usize) -> io::Result<Option<Vec<usize>>> {
+        if self.dist_to(v)?.is_none() {
+            return Ok(None);
+        }
+        let mut path = vec![v];
+        while let Some(parent) =
+            Some(self.parents.get(*path.last().unwrap())?).filter(|&parent| parent != u64::MAX)
+        {
+            path.push(parent as usize);
+        }
+        path.reverse();
+        Ok(Some(path))
+    }
+}
+
+/// Dijkstra from `src` on a graph on disk, writing the distances and parents
+/// to the files `<results>.dists` and `<results>.parents`, with at most
+/// `limits` of them in memory.
+///
+/// Besides the chunks of the results, the search keeps a bit per vertex to
+/// mark the settled ones, the edges of one vertex, and the frontier, which
+/// holds an entry per vertex reached and not settled yet: the entry of a
+/// vertex is replaced when a shorter path to it is found.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, if the distance to a reachable
+/// vertex does not fit in a `usize`, or if a file cannot be read or written.
+///
+/// # Example
+/// ```
+/// use djikstra::external::{dijkstra_external, DiskGraph, MemoryLimits};
+///
+/// let dir = std::env::temp_dir();
+/// let graph_path = dir.join("djikstra-external-example.bin");
+/// let graph = DiskGraph::create(&mut "3\n1,3\n2,1\n\n".as_bytes(), &graph_path).unwrap();
+/// let results_path = dir.join("djikstra-external-example");
+/// let mut results = dijkstra_external(&graph, 0, &results_path, MemoryLimits::default()).unwrap();
+/// assert_eq!(results.dist_to(2).unwrap(), Some(4));
+/// assert_eq!(results.path_to(2).unwrap(), Some(vec![0, 1, 2]));
+/// # for ext in ["bin", "dists", "parents"] {
+/// #     std::fs::remove_file(dir.join("djikstra-external-example").with_extension(ext)).unwrap();
+/// # }
+/// ```
+pub fn dijkstra_external(
+    graph: &DiskGraph,
+    src: usize,
+    results: &Path,
+    limits: MemoryLimits,
+) -> Result<DiskResults, ExternalError> {
+    let n_elems = graph.n_vertices();
+    if n_elems == 0 {
+        return Err(DijkstraError::EmptyGraph.into());
+    }
+    if src >= n_elems {
+        return Err(DijkstraError::InvalidSource {
+            source: src,
+            n_vertices: n_elems,
+        }
+        .into());
+    }
+
+    let mut dists =
+        ChunkedArray::create(&results.with_extension("dists"), n_elems, u64::MAX, limits)?;
+    let mut parents = ChunkedArray::create(
+        &results.with_extension("parents"),
+        n_elems,
+        u64::MAX,
+        limits,
+    )?;
+    let mut settled = BitSet::new(n_elems);
+    // vertices some distance overflowed for, an error if they stay unreached
+    let mut overflowed = vec![];
+
+    // ordered by distance, the old distance of a vertex being in `dists`
+    let mut queue = BTreeSet::from([(0_usize, src)]);
+    dists.set(src, 0)?;
+    let mut edges = vec![];
+    while let Some((dist, u)) = queue.pop_first() {
+        settled.insert(u);
+        graph.neighbors_of(u, &mut edges)?;
+        for &(v, weight) in edges.iter() {
+            if settled.contains(v) {
+                continue;
+            }
+            let Some(new_dist) = add_distance(dist, weight) else {
+                overflowed.push(v);
+                continue;
+            };
+            let old_dist = dists.get(v)?;
+            if (new_dist as u64) < old_dist {
+                if old_dist != u64::MAX {
+                    queue.remove(&(old_dist as usize, v));
+                }
+                dists.set(v, new_dist as u64)?;
+                parents.set(v, u as u64)?;
+                queue.insert((new_dist, v));
+            }
+        }
+    }
+    for v in overflowed {
+        if !settled.contains(v) {
+            return Err(DijkstraError::Overflow.into());
+        }
+    }
+    dists.flush()?;
+    parents.flush()?;
+
+    Ok(DiskResults {
+        source: src,
+        dists,
+        parents,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dijkstra::{dijkstra, DijkstraError};
+    use crate::error::Error;
+    use crate::external::{dijkstra_external, DiskGraph, ExternalError, MemoryLimits, HEADER_LEN};
+    use crate::graph::Graph;
+    use std::io::{self, Seek, SeekFrom, Write};
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("djikstra-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn runs_like_dijkstra_in_tiny_chunks() {
+        let input = "8
+1,3 6,2
+0,3 2,4 3,1 6,1 4,4 7,6
+6,6 1,4 3,2 4,2
+1,1 2,2 4,1 7,2
+2,2 3,1 1,4 7,1 5,3
+4,3 7,4
+0,2 1,1 2,6 4,5
+4,1 5,4 3,2 1,6
+";
+        let graph: Graph = input.parse().unwrap();
+        let graph_path = temp_path("tiny-chunks-graph");
+        let disk = DiskGraph::create(&mut input.as_bytes(), &graph_path).unwrap();
+        assert_eq!((disk.n_vertices(), disk.n_edges()), (8, 31));
+        let mut edges = vec![];
+        for v in 0..graph.n_vertices() {
+            disk.neighbors_of(v, &mut edges).unwrap();
+            assert_eq!(edges, graph.neighbors_of(v));
+        }
+
+        let results_path = temp_path("tiny-chunks");
+        let limits = MemoryLimits {
+            chunk_len: 3,
+            max_chunks: 1,
+        };
+        for src in 0..graph.n_vertices() {
+            let (paths, dists) = dijkstra(&graph, src).unwrap();
+            let mut results = dijkstra_external(&disk, src, &results_path, limits).unwrap();
+            for v in 0..graph.n_vertices() {
+                assert_eq!(results.dist_to(v).unwrap(), dists[v]);
+                let path = results.path_to(v).unwrap().unwrap();
+                assert_eq!(graph.path_cost(&path), dists[v]);
+                assert_eq!(path.len(), paths[v].as_ref().unwrap().len());
+            }
+        }
+        assert!(matches!(
+            dijkstra_external(&disk, 8, &results_path, limits),
+            Err(ExternalError::Dijkstra(DijkstraError::InvalidSource { .. }))
+        ));
+        for path in [
+            graph_path,
+            results_path.with_extension("dists"),
+            results_path.with_extension("parents"),
+        ] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn unreachable_vertices_and_bad_inputs() {
+        let graph_path = temp_path("unreachable-graph");
+        let disk = DiskGraph::create(&mut "4\n1,2\n\n0,1\n".as_bytes(), &graph_path).unwrap();
+        let results_path = temp_path("unreachable");
+        let mut results =
+            dijkstra_external(&disk, 0, &results_path, MemoryLimits::default()).unwrap();
+        assert_eq!(results.dist_to(2).unwrap(), None);
+        assert_eq!(results.path_to(3).unwrap(), None);
+        assert_eq!(results.path_to(1).unwrap(), Some(vec![0, 1]));
+
+        let out_of_range = DiskGraph::create(&mut "2\n5,1\n".as_bytes(), &graph_path);
+        assert!(matches!(
+            out_of_range.map_err(Error::from),
+            Err(Error::Graph(_))
+        ));
+        assert!(DiskGraph::create(&mut "2\n1;1\n".as_bytes(), &graph_path).is_err());
+        std::fs::write(&graph_path, "2\n1,1\n").unwrap();
+        assert!(DiskGraph::open(&graph_path).is_err());
+        for path in [
+            graph_path,
+            results_path.with_extension("dists"),
+            results_path.with_extension("parents"),
+        ] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn corrupt_offsets_are_errors() {
+        let graph_path = temp_path("corrupt-offsets-graph");
+        DiskGraph::create(&mut "2\n1,1\n0,1\n".as_bytes(), &graph_path).unwrap();
+        // the edges of vertex 1 end before they start
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&graph_path)
+            .unwrap();
+        file.seek(SeekFrom::Start(HEADER_LEN + 16)).unwrap();
+        file.write_all(&0_u64.to_le_bytes()).unwrap();
+        drop(file);
+
+        let disk = DiskGraph::open(&graph_path).unwrap();
+        let mut edges = vec![];
+        let error = disk.neighbors_of(1, &mut edges).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let error = dijkstra_external(&disk, 1, &temp_path("corrupt"), MemoryLimits::default())
+            .map_err(Error::from)
+            .unwrap_err();
+        assert!(matches!(error, Error::Io(_)));
+        for path in [
+            graph_path,
+            temp_path("corrupt").with_extension("dists"),
+            temp_path("corrupt").with_extension("parents"),
+        ] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Reading and writing graphs in file formats other than the crate's own.
+//!
+//! The crate's own format is handled by `Graph::from_str` and `Graph`'s `Display`.
+use crate::graph::{Graph, ParseGraphError};
+use std::io::{self, Write};
+
+/// Parse a graph in the DIMACS shortest path challenge format.
+///
+/// Lines starting with `c` are comments, the `p sp N M` line declares the number
+/// of vertices and arcs, and every `a U V W` line is an arc from `U` to `V` with weight `W`.
+/// Vertices are numbered from 1 in the file and from 0 in the returned graph.
+///
+/// # Example
+/// ```
+/// use djikstra::formats::parse_dimacs;
+///
+/// let graph = parse_dimacs("c example\np sp 2 1\na 1 2 7\n").unwrap();
+/// assert_eq!(graph.neighbors_of(0), &[(1, 7)]);
+/// ```
+pub fn parse_dimacs(s: &str) -> Result<Graph, ParseGraphError> {
+    let mut adj: Option<Vec<Vec<(usize, usize)>>> = None;
+
+    for (line_no, line) in s.lines().enumerate() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let parse = |field: &str| {
+            field.parse::<usize>().map_err(|e| {
+                ParseGraphError(format!(
+                    "line {}: cannot parse {:?}: {}",
+                    line_no + 1,
+                    field,
+                    e
+                ))
+            })
+        };
+
+        match fields.as_slice() {
+            [] | ["c", ..] => {}
+            ["p", "sp", n_vertex, _n_arcs] => adj = Some(vec![vec![]; parse(n_vertex)?]),
+            ["a", u, v, weight] => {
+                let adj = adj.as_mut().ok_or(ParseGraphError(format!(
+                    "line {}: arc before the problem line",
+                    line_no + 1
+                )))?;
+                let (u, v, weight) = (parse(u)?, parse(v)?, parse(weight)?);
+                if u == 0 || v == 0 || u > adj.len() || v > adj.len() {
+                    return Err(ParseGraphError(format!(
+                        "line {}: vertex out of range 1..={}",
+                        line_no + 1,
+                        adj.len()
+                    )));
+                }
+                adj[u - 1].push((v - 1, weight));
+            }
+            _ => {
+                return Err(ParseGraphError(format!(
+                    "line {}: unexpected line {:?}",
+                    line_no + 1,
+                    line
+                )))
+            }
+        }
+    }
+
+    adj.map(Graph::new)
+        .ok_or(ParseGraphError("missing problem line".to_string()))
+}
+
+/// Write a graph in the DIMACS shortest path challenge format, see [`parse_dimacs`].
+pub fn write_dimacs<W: Write + ?Sized>(graph: &Graph, w: &mut W) -> io::Result<()> {
+    writeln!(w, "p sp {} {}", graph.n_vertices(), graph.n_edges())?;
+    for (u, neighbors) in graph.adj.iter().enumerate() {
+        for &(v, weight) in neighbors.iter() {
+            writeln!(w, "a {} {} {}", u + 1, v + 1, weight)?;
+        }
+    }
+    Ok(())
+}
+
+/// Parse a graph from JSON of the form `{"adj": [[[1, 3], [2, 3]], [[0, 3]], [[0, 3]]]}`,
+/// where every vertex has a list of `[vertex, weight]` pairs.
+pub fn parse_json(s: &str) -> Result<Graph, ParseGraphError> {
+    serde_json::from_str(s).map_err(|e| ParseGraphError(format!("cannot parse json: {}", e)))
+}
+
+/// Write a graph as JSON, see [`parse_json`].
+pub fn write_json<W: Write + ?Sized>(graph: &Graph, w: &mut W) -> io::Result<()> {
+    serde_json::to_writer(&mut *w, graph)?;
+    writeln!(w)
+}
+
+/// Parse the coordinates of the vertices from `vertex,longitude,latitude` CSV
+/// rows, with an optional header line. Vertices without a row have no coordinates.
+///
+/// # Example
+/// ```
+/// use djikstra::formats::parse_coordinates;
+///
+/// let coords = parse_coordinates("vertex,longitude,latitude\n1,13.4,52.5\n", 3).unwrap();
+/// assert_eq!(coords, vec![None, Some((13.4, 52.5)), None]);
+/// ```
+pub fn parse_coordinates(
+    s: &str,
+    n_vertices: usize,
+) -> Result<Vec<Option<(f64, f64)>>, ParseGraphError> {
+    let mut coords = vec![None; n_vertices];
+    for (line_no, line) in s.lines().enumerate() {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let error = |message: &str| {
+            ParseGraphError(format!("line {}: {}: {:?}", line_no + 1, message, line))
+        };
+        match fields.as_slice() {
+            [""] => {}
+            [vertex, _, _] if line_no == 0 && vertex.parse::<usize>().is_err() => {}
+            [vertex, longitude, latitude] => {
+                let vertex: usize = vertex.parse().map_err(|_| error("cannot parse vertex"))?;
+                if vertex >= n_vertices {
+                    return Err(error(&format!("vertex out of range 0..{}", n_vertices)));
+                }
+                let longitude = longitude
+                    .parse()
+                    .map_err(|_| error("cannot parse longitude"))?;
+                let latitude = latitude
+                    .parse()
+                    .map_err(|_| error("cannot parse latitude"))?;
+                coords[vertex] = Some((longitude, latitude));
+            }
+            _ => return Err(error("expected vertex,longitude,latitude")),
+        }
+    }
+    Ok(coords)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Graph {
+        Graph::new(vec![vec![(1, 3), (2, 3)], vec![], vec![(0, 1)]])
+    }
+
+    #[test]
+    fn dimacs_round_trips() {
+        let mut out = vec![];
+        write_dimacs(&sample(), &mut out).unwrap();
+        let written = String::from_utf8(out).unwrap();
+        assert_eq!(written, "p sp 3 3\na 1 2 3\na 1 3 3\na 3 1 1\n");
+        assert_eq!(parse_dimacs(&written).unwrap(), sample());
+    }
+
+    #[test]
+    fn coordinates_skip_header_and_blank_lines() {
+        let coords = parse_coordinates("vertex,x,y\n\n2, -0.5, 51.5\n0,1,2\n", 3).unwrap();
+        assert_eq!(coords, vec![Some((1.0, 2.0)), None, Some((-0.5, 51.5))]);
+        assert!(parse_coordinates("3,1,2\n", 3).is_err());
+        assert!(parse_coordinates("0,1\n", 3).is_err());
+        assert!(parse_coordinates("0,1,2\nvertex,x,y\n", 3).is_err());
+    }
+
+    #[test]
+    fn dimacs_rejects_out_of_range() {
+        assert!(parse_dimacs("p sp 2 1\na 1 3 7\n").is_err());
+        assert!(parse_dimacs("a 1 2 7\n").is_err());
+        assert!(parse_dimacs("c no problem line\n").is_err());
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let mut out = vec![];
+        write_json(&sample(), &mut out).unwrap();
+        let written = String::from_utf8(out).unwrap();
+        assert_eq!(written, "{\"adj\":[[[1,3],[2,3]],[],[[0,1]]]}\n");
+        assert_eq!(parse_json(&written).unwrap(), sample());
+    }
+}
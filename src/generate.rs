@@ -0,0 +1,173 @@
+//! Random graph generators for creating benchmark inputs.
+//!
+//! All generators create undirected graphs, i.e. every edge is stored in the
+//! neighbor lists of both of its vertices with the same weight.
+//! Weights are drawn uniformly from `1..=max_weight`.
+use crate::graph::Graph;
+use rand::{Rng, RngExt};
+
+/// Erdős–Rényi G(n, p) graph where every pair of vertices is connected with probability `p`.
+///
+/// Skips over non-edges geometrically (Batagelj & Brandes) so sparse graphs
+/// take time proportional to the number of edges rather than `n²`.
+///
+/// # Example
+/// ```
+/// use djikstra::generate::gnp;
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let mut rng = StdRng::seed_from_u64(42);
+/// let graph = gnp(100, 0.05, 10, &mut rng);
+/// assert_eq!(graph.n_vertices(), 100);
+/// ```
+pub fn gnp<R: Rng + ?Sized>(n: usize, p: f64, max_weight: usize, rng: &mut R) -> Graph {
+    let mut adj = vec![vec![]; n];
+
+    if p >= 1.0 {
+        for u in 0..n {
+            for v in 0..u {
+                add_edge(&mut adj, u, v, max_weight, rng);
+            }
+        }
+    } else if p > 0.0 {
+        let log_q = (1.0 - p).ln();
+        let (mut u, mut v) = (1, -1_i64);
+        while u < n {
+            let r: f64 = rng.random();
+            v += 1 + ((1.0 - r).ln() / log_q).floor() as i64;
+            while v >= u as i64 && u < n {
+                v -= u as i64;
+                u += 1;
+            }
+            if u < n {
+                add_edge(&mut adj, u, v as usize, max_weight, rng);
+            }
+        }
+    }
+
+    Graph::new(adj)
+}
+
+/// Grid graph with `n` vertices laid out row by row in `ceil(sqrt(n))` columns,
+/// each connected to its right and lower neighbor.
+pub fn grid<R: Rng + ?Sized>(n: usize, max_weight: usize, rng: &mut R) -> Graph {
+    let mut adj = vec![vec![]; n];
+    let cols = (n as f64).sqrt().ceil() as usize;
+
+    for u in 0..n {
+        if (u + 1) % cols != 0 && u + 1 < n {
+            add_edge(&mut adj, u, u + 1, max_weight, rng);
+        }
+        if u + cols < n {
+            add_edge(&mut adj, u, u + cols, max_weight, rng);
+        }
+    }
+
+    Graph::new(adj)
+}
+
+/// Barabási–Albert preferential attachment graph.
+///
+/// Starts from a complete graph on `m + 1` vertices and connects every further
+/// vertex to `m` distinct existing vertices chosen proportionally to their degree.
+pub fn barabasi_albert<R: Rng + ?Sized>(
+    n: usize,
+    m: usize,
+    max_weight: usize,
+    rng: &mut R,
+) -> Graph {
+    let mut adj = vec![vec![]; n];
+    let initial = (m + 1).min(n);
+    // every vertex appears once per incident edge, so sampling
+    // uniformly from this list is sampling proportionally to degree
+    let mut endpoints = vec![];
+
+    for u in 0..initial {
+        for v in 0..u {
+            add_edge(&mut adj, u, v, max_weight, rng);
+            endpoints.push(u);
+            endpoints.push(v);
+        }
+    }
+
+    for u in initial..n {
+        let mut targets: Vec<usize> = Vec::with_capacity(m);
+        while targets.len() < m.min(u) {
+            let v = if endpoints.is_empty() {
+                rng.random_range(0..u)
+            } else {
+                endpoints[rng.random_range(0..endpoints.len())]
+            };
+            if !targets.contains(&v) {
+                targets.push(v);
+            }
+        }
+        for v in targets {
+            add_edge(&mut adj, u, v, max_weight, rng);
+            endpoints.push(u);
+            endpoints.push(v);
+        }
+    }
+
+    Graph::new(adj)
+}
+
+/// Add an undirected edge between `u` and `v` with a random weight.
+fn add_edge<R: Rng + ?Sized>(
+    adj: &mut [Vec<(usize, usize)>],
+    u: usize,
+    v: usize,
+    max_weight: usize,
+    rng: &mut R,
+) {
+    let weight = rng.random_range(1..=max_weight.max(1));
+    adj[u].push((v, weight));
+    adj[v].push((u, weight));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn is_undirected(graph: &Graph) -> bool {
+        graph.adj.iter().enumerate().all(|(u, neighbors)| {
+            neighbors
+                .iter()
+                .all(|&(v, weight)| graph.neighbors_of(v).contains(&(u, weight)))
+        })
+    }
+
+    #[test]
+    fn gnp_extremes() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(gnp(10, 0.0, 5, &mut rng).n_edges(), 0);
+        assert_eq!(gnp(10, 1.0, 5, &mut rng).n_edges(), 90);
+    }
+
+    #[test]
+    fn gnp_is_undirected_and_seeded() {
+        let g1 = gnp(200, 0.05, 5, &mut StdRng::seed_from_u64(7));
+        let g2 = gnp(200, 0.05, 5, &mut StdRng::seed_from_u64(7));
+        assert!(is_undirected(&g1));
+        assert!(g1.adj.iter().flatten().all(|&(_, w)| (1..=5).contains(&w)));
+        assert_eq!(g1, g2);
+    }
+
+    #[test]
+    fn grid_has_correct_edges() {
+        let graph = grid(9, 1, &mut StdRng::seed_from_u64(0));
+        // 3x3 grid has 12 undirected edges
+        assert_eq!(graph.n_edges(), 24);
+        assert_eq!(graph.neighbors_of(4).len(), 4);
+        assert!(is_undirected(&graph));
+    }
+
+    #[test]
+    fn barabasi_albert_has_correct_edges() {
+        let graph = barabasi_albert(50, 2, 10, &mut StdRng::seed_from_u64(1));
+        // 3 initial edges plus 2 for each of the 47 other vertices
+        assert_eq!(graph.n_edges(), 2 * (3 + 2 * 47));
+        assert!(is_undirected(&graph));
+    }
+}
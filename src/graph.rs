@@ -1,17 +1,283 @@
-use std::{fmt, str::FromStr};
+use crate::trace;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::{error, fmt, mem, str::FromStr};
+use serde::{Deserialize, Serialize};
+
+/// Integer type of the vertices stored in the adjacency lists of a [`Graph`].
+///
+/// Smaller types take less memory per edge but limit the number of vertices,
+/// and let the graph share the vertex ids of other code, e.g. through FFI.
+pub trait IndexType: Copy + Ord + fmt::Debug + fmt::Display + Send + Sync + 'static {
+    /// The vertex `index`, or `None` if it does not fit in the type.
+    fn new(index: usize) -> Option<Self>;
+
+    /// The vertex as a `usize`.
+    fn index(self) -> usize;
+}
+
+macro_rules! impl_index_type {
+    ($($ty:ty),*) => {
+        $(
+            impl IndexType for $ty {
+                fn new(index: usize) -> Option<Self> {
+                    Self::try_from(index).ok()
+                }
+
+                fn index(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_index_type!(u16, u32, u64, usize);
+
+/// Integer type of the weights stored in the adjacency lists of a [`Graph`].
+///
+/// The algorithms add the weights as `usize` and check for overflows, so a
+/// smaller type only limits the weights of the edges, e.g. `u32` for road
+/// graphs in meters or seconds, and halves the memory of their edges with
+/// `u32` vertices.
+pub trait WeightType: Copy + Ord + fmt::Debug + fmt::Display + Send + Sync + 'static {
+    /// The weight `weight`, or `None` if it does not fit in the type.
+    fn new(weight: usize) -> Option<Self>;
+
+    /// The weight as a `usize`.
+    fn weight(self) -> usize;
+}
+
+macro_rules! impl_weight_type {
+    ($($ty:ty),*) => {
+        $(
+            impl WeightType for $ty {
+                fn new(weight: usize) -> Option<Self> {
+                    Self::try_from(weight).ok()
+                }
+
+                fn weight(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_weight_type!(u16, u32, u64, usize);
+
+/// Storage of the edges of a graph the algorithms can run on, either the
+/// adjacency lists of a [`Graph`] or the flat arena of a [`CsrGraph`].
+///
+/// [`CsrGraph`]: crate::csr::CsrGraph
+pub trait Adjacency {
+    /// Integer type of the vertices stored with the edges.
+    type Index: IndexType;
+
+    /// Integer type of the weights stored with the edges.
+    type Weight: WeightType;
+
+    /// Number of vertices.
+    fn n_vertices(&self) -> usize;
+
+    /// Every edge out of `vertex` as `(neighbor, weight)`.
+    fn neighbors_of(&self, vertex: usize) -> &[(Self::Index, Self::Weight)];
+
+    /// Number of times the edges changed, for what is computed from them to
+    /// notice it, or `None` if the storage does not count its changes.
+    fn generation(&self) -> Option<u64> {
+        None
+    }
+}
+
+impl<Ix: IndexType, W: WeightType> Adjacency for Graph<Ix, W> {
+    type Index = Ix;
+    type Weight = W;
+
+    fn n_vertices(&self) -> usize {
+        Graph::n_vertices(self)
+    }
+
+    fn neighbors_of(&self, vertex: usize) -> &[(Ix, W)] {
+        Graph::neighbors_of(self, vertex)
+    }
+}
 
 /// Graph data structure based on adjacency lists
 ///
+/// The vertices in the adjacency lists are stored as `Ix` and the weights as
+/// `W`, `usize` unless another [`IndexType`] or [`WeightType`] is picked to
+/// save memory.
+///
 /// NOTE: no guarantees about the graph being in a valid state are made
 /// and the user must therefore make sure that the string they are parsing
 /// or they vector they are making a graph out of is a valid graph
 ///
-#[derive(Debug)]
-pub struct Graph {
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Graph<Ix = usize, W = usize> {
     // `adj` is the adjacency list
     // the index corresponds to a vertex and the value at that index
     // is the list of neighbors with associated weights
-    pub adj: Vec<Vec<(usize, usize)>>,
+    pub adj: Vec<Vec<(Ix, W)>>,
+}
+
+impl<Ix: IndexType> Graph<Ix> {
+    /// create a graph from a given adjacency list with vertices of any [`IndexType`]
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph = Graph::from_adjacency(vec![vec![(1_u32, 3)], vec![]]);
+    /// assert_eq!(graph.neighbors_of(0), &[(1, 3)]);
+    /// ```
+    pub fn from_adjacency(adj: Vec<Vec<(Ix, usize)>>) -> Self {
+        Self { adj }
+    }
+}
+
+impl<Ix: IndexType, W: WeightType> Graph<Ix, W> {
+    /// create a graph from a given adjacency list with vertices of any
+    /// [`IndexType`] and weights of any [`WeightType`]
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph = Graph::from_weighted_adjacency(vec![vec![(1_u32, 3_u16)], vec![]]);
+    /// assert_eq!(graph.neighbors_of(0), &[(1, 3)]);
+    /// ```
+    pub fn from_weighted_adjacency(adj: Vec<Vec<(Ix, W)>>) -> Self {
+        Self { adj }
+    }
+
+    /// Number of vertices
+    pub fn n_vertices(&self) -> usize {
+        self.adj.len()
+    }
+
+    /// Number of edges
+    pub fn n_edges(&self) -> usize {
+        self.adj.iter().fold(0, |acc, x| acc + x.len())
+    }
+
+    /// Get neighbors of a vertex
+    pub fn neighbors_of(&self, vertex: usize) -> &[(Ix, W)] {
+        &self.adj[vertex]
+    }
+
+    /// Number of bytes the graph holds on the heap, the spare capacity of the
+    /// adjacency lists included.
+    pub fn memory_usage(&self) -> usize {
+        let lists = self.adj.capacity() * mem::size_of::<Vec<(Ix, W)>>();
+        let edges: usize = self
+            .adj
+            .iter()
+            .map(|neighbors| neighbors.capacity() * mem::size_of::<(Ix, W)>())
+            .sum();
+        lists + edges
+    }
+
+    /// Number of bytes a graph of `n_vertices` and `n_edges` holds on the heap
+    /// without spare capacity, e.g. to check that it fits in memory before loading it.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3)], vec![(0, 3)]]);
+    /// assert_eq!(Graph::<usize>::estimate_memory(2, 2), graph.memory_usage());
+    /// // vertex ids of 4 bytes instead of 8 save 8 bytes per edge, with the padding
+    /// assert!(Graph::<u32>::estimate_memory(2, 2) <= graph.memory_usage());
+    /// // and weights of 4 bytes as well halve the edges
+    /// assert_eq!(Graph::<u32, u32>::estimate_memory(0, 2) * 2, Graph::<usize>::estimate_memory(0, 2));
+    /// ```
+    pub fn estimate_memory(n_vertices: usize, n_edges: usize) -> usize {
+        n_vertices * mem::size_of::<Vec<(Ix, W)>>() + n_edges * mem::size_of::<(Ix, W)>()
+    }
+
+    /// Check that every edge leads to a vertex of the graph, which parsing
+    /// does not, before running anything that indexes by the targets.
+    ///
+    /// # Errors
+    /// Fails on the first edge whose target is not below [`Self::n_vertices`].
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph: Graph = "2\n1,3\n9,1\n".parse().unwrap();
+    /// assert_eq!(graph.check_targets().unwrap_err().to_string(), "vertex 9 out of range 0..2");
+    /// ```
+    pub fn check_targets(&self) -> Result<(), ParseGraphError> {
+        let n_vertices = self.n_vertices();
+        match self
+            .adj
+            .iter()
+            .flatten()
+            .find(|&&(v, _weight)| v.index() >= n_vertices)
+        {
+            Some(&(v, _weight)) => Err(ParseGraphError(format!(
+                "vertex {} out of range 0..{}",
+                v, n_vertices
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// The same graph with its vertices stored as `Jx`, or `None` if a vertex
+    /// does not fit in `Jx`.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph: Graph = "2\n1,3\n\n".parse().unwrap();
+    /// let small = graph.to_index_type::<u16>().unwrap();
+    /// assert_eq!(small.neighbors_of(0), &[(1_u16, 3)]);
+    /// ```
+    pub fn to_index_type<Jx: IndexType>(&self) -> Option<Graph<Jx, W>> {
+        let adj = self
+            .adj
+            .iter()
+            .map(|neighbors| {
+                neighbors
+                    .iter()
+                    .map(|&(v, weight)| Some((Jx::new(v.index())?, weight)))
+                    .collect()
+            })
+            .collect::<Option<_>>()?;
+        Some(Graph { adj })
+    }
+
+    /// The same graph with its weights stored as `V`, or `None` if a weight
+    /// does not fit in `V`.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph: Graph = "2\n1,3\n\n".parse().unwrap();
+    /// let narrow = graph.to_index_type::<u32>().unwrap().to_weight_type::<u32>().unwrap();
+    /// assert_eq!(narrow.neighbors_of(0), &[(1_u32, 3_u32)]);
+    /// assert!(Graph::new(vec![vec![(0, 1 << 40)]]).to_weight_type::<u32>().is_none());
+    /// ```
+    pub fn to_weight_type<V: WeightType>(&self) -> Option<Graph<Ix, V>> {
+        let adj = self
+            .adj
+            .iter()
+            .map(|neighbors| {
+                neighbors
+                    .iter()
+                    .map(|&(v, weight)| Some((v, V::new(weight.weight())?)))
+                    .collect()
+            })
+            .collect::<Option<_>>()?;
+        Some(Graph { adj })
+    }
 }
 
 impl Graph {
@@ -32,27 +298,346 @@ impl Graph {
         Self { adj }
     }
 
-    /// Number of vertices
-    pub fn n_vertices(&self) -> usize {
-        self.adj.len()
+    /// Add an edge from `from` to `to`, after the other neighbors of `from`.
+    ///
+    /// # Panics
+    /// If `from` or `to` is not a vertex of the graph.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let mut graph = Graph::new(vec![vec![(1, 3)], vec![]]);
+    /// graph.add_edge(1, 0, 2);
+    /// assert_eq!(graph, Graph::new(vec![vec![(1, 3)], vec![(0, 2)]]));
+    /// ```
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: usize) {
+        assert!(to < self.n_vertices(), "vertex {} is not in the graph", to);
+        self.adj[from].push((to, weight));
     }
 
-    /// Number of edges
-    pub fn n_edges(&self) -> usize {
-        self.adj.iter().fold(0, |acc, x| acc + x.len())
+    /// Remove every edge from `from` to `to`.
+    /// Returns whether there was any.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let mut graph = Graph::new(vec![vec![(1, 3), (1, 5)], vec![(0, 2)]]);
+    /// assert!(graph.remove_edge(0, 1));
+    /// assert!(!graph.remove_edge(0, 1));
+    /// assert_eq!(graph, Graph::new(vec![vec![], vec![(0, 2)]]));
+    /// ```
+    pub fn remove_edge(&mut self, from: usize, to: usize) -> bool {
+        let n_neighbors = self.adj[from].len();
+        self.adj[from].retain(|&(v, _)| v != to);
+        self.adj[from].len() != n_neighbors
     }
 
-    /// Get neighbors of a vertex
-    pub fn neighbors_of(&self, vertex: usize) -> &[(usize, usize)] {
-        &self.adj[vertex]
+    /// Remove every edge into and out of `vertex`, leaving it isolated so the
+    /// other vertices keep their indices. [`Graph::compact`] drops it later
+    /// when given its index.
+    ///
+    /// # Panics
+    /// If `vertex` is not a vertex of the graph.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let mut graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![(0, 2)]]);
+    /// graph.remove_vertex(1);
+    /// assert_eq!(graph, Graph::new(vec![vec![], vec![], vec![(0, 2)]]));
+    /// ```
+    pub fn remove_vertex(&mut self, vertex: usize) {
+        self.adj[vertex].clear();
+        for neighbors in self.adj.iter_mut() {
+            neighbors.retain(|&(v, _)| v != vertex);
+        }
+    }
+
+    /// Drop the vertices of `removed`, e.g. after [`Graph::remove_vertex`],
+    /// with the edges into and out of them, and renumber the others densely
+    /// in the same order. Vertices without edges that are not in `removed`
+    /// are kept. Returns the new index of every old vertex, `None` for the
+    /// dropped ones, to translate the indices kept elsewhere.
+    ///
+    /// # Panics
+    /// If a vertex of `removed` is not a vertex of the graph.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let mut graph = Graph::new(vec![vec![(1, 3), (2, 3)], vec![], vec![(3, 1)], vec![]]);
+    /// graph.remove_vertex(1);
+    /// let mapping = graph.compact(&[1]);
+    /// assert_eq!(mapping, vec![Some(0), None, Some(1), Some(2)]);
+    /// assert_eq!(graph, Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]));
+    /// ```
+    pub fn compact(&mut self, removed: &[usize]) -> Vec<Option<usize>> {
+        let mut kept = vec![true; self.n_vertices()];
+        for &v in removed.iter() {
+            assert!(v < self.n_vertices(), "vertex {} is not in the graph", v);
+            kept[v] = false;
+        }
+
+        let mut n_kept = 0;
+        let mapping: Vec<Option<usize>> = kept
+            .iter()
+            .map(|&keep| {
+                keep.then(|| {
+                    n_kept += 1;
+                    n_kept - 1
+                })
+            })
+            .collect();
+
+        let adj = mem::take(&mut self.adj);
+        self.adj = adj
+            .into_iter()
+            .zip(kept)
+            .filter(|&(_, keep)| keep)
+            .map(|(neighbors, _)| {
+                neighbors
+                    .into_iter()
+                    .filter_map(|(v, weight)| Some((mapping[v]?, weight)))
+                    .collect()
+            })
+            .collect();
+        mapping
+    }
+
+    /// Set the weight of every edge from `from` to `to`.
+    /// Returns whether there was any.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let mut graph = Graph::new(vec![vec![(1, 3)], vec![]]);
+    /// assert!(graph.set_weight(0, 1, 9));
+    /// assert!(!graph.set_weight(1, 0, 9));
+    /// assert_eq!(graph, Graph::new(vec![vec![(1, 9)], vec![]]));
+    /// ```
+    pub fn set_weight(&mut self, from: usize, to: usize, weight: usize) -> bool {
+        let mut found = false;
+        for edge in self.adj[from].iter_mut().filter(|(v, _)| *v == to) {
+            edge.1 = weight;
+            found = true;
+        }
+        found
+    }
+
+    /// Total weight of the edges along `path`, taking the lightest of parallel
+    /// edges, or `None` if two consecutive vertices are not joined by an edge.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+    /// assert_eq!(graph.path_cost(&[0, 1, 2]), Some(4));
+    /// assert_eq!(graph.path_cost(&[0, 2]), None);
+    /// ```
+    pub fn path_cost(&self, path: &[usize]) -> Option<usize> {
+        path.windows(2)
+            .map(|edge| {
+                self.adj[edge[0]]
+                    .iter()
+                    .filter(|&&(v, _)| v == edge[1])
+                    .map(|&(_, weight)| weight)
+                    .min()
+            })
+            .sum()
+    }
+
+    /// Undirected version of the graph, with the reverse of every edge added.
+    /// Parallel edges are merged into one edge with the minimum weight, and
+    /// the neighbors keep the order in which they first appear.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3)], vec![(0, 2)], vec![(0, 1)]]);
+    /// let undirected = Graph::new(vec![vec![(1, 2), (2, 1)], vec![(0, 2)], vec![(0, 1)]]);
+    /// assert_eq!(graph.to_undirected(), undirected);
+    /// ```
+    pub fn to_undirected(&self) -> Self {
+        let mut adj: Vec<Vec<(usize, usize)>> = self.adj.clone();
+        for (u, neighbors) in self.adj.iter().enumerate() {
+            for &(v, weight) in neighbors.iter() {
+                adj[v].push((u, weight));
+            }
+        }
+
+        for neighbors in adj.iter_mut() {
+            // position of every neighbor in the merged list
+            let mut positions: BTreeMap<usize, usize> = BTreeMap::new();
+            let mut merged: Vec<(usize, usize)> = Vec::with_capacity(neighbors.len());
+            for &(v, weight) in neighbors.iter() {
+                match positions.get(&v) {
+                    Some(&i) => merged[i].1 = merged[i].1.min(weight),
+                    None => {
+                        positions.insert(v, merged.len());
+                        merged.push((v, weight));
+                    }
+                }
+            }
+            *neighbors = merged;
+        }
+
+        Self { adj }
+    }
+
+    /// Label every vertex with its weakly connected component, ignoring edge directions.
+    /// Components are numbered from 0 in the order of their smallest vertex.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    ///
+    /// let graph = Graph::new(vec![vec![(2, 1)], vec![], vec![]]);
+    /// assert_eq!(graph.components(), vec![0, 1, 0]);
+    /// ```
+    pub fn components(&self) -> Vec<usize> {
+        // union-find with path halving
+        let mut parent: Vec<usize> = (0..self.n_vertices()).collect();
+        fn find(parent: &mut [usize], mut v: usize) -> usize {
+            while parent[v] != v {
+                parent[v] = parent[parent[v]];
+                v = parent[v];
+            }
+            v
+        }
+
+        for (u, neighbors) in self.adj.iter().enumerate() {
+            for &(v, _) in neighbors.iter() {
+                let (root_u, root_v) = (find(&mut parent, u), find(&mut parent, v));
+                parent[root_u.max(root_v)] = root_u.min(root_v);
+            }
+        }
+
+        let mut labels = vec![usize::MAX; self.n_vertices()];
+        let mut n_components = 0;
+        for v in 0..self.n_vertices() {
+            let root = find(&mut parent, v);
+            if labels[root] == usize::MAX {
+                labels[root] = n_components;
+                n_components += 1;
+            }
+            labels[v] = labels[root];
+        }
+        labels
+    }
+
+    /// Split the vertices in two sides with every edge going between them,
+    /// ignoring edge directions. The smallest vertex of every weakly connected
+    /// component is on the first side.
+    ///
+    /// # Errors
+    /// Fails with a cycle of odd length if there is one, since its vertices
+    /// cannot alternate between two sides.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::{Graph, OddCycle};
+    ///
+    /// let square = Graph::new(vec![vec![(1, 1)], vec![(2, 1)], vec![(3, 1)], vec![(0, 1)]]);
+    /// assert_eq!(square.bipartition(), Ok((vec![0, 2], vec![1, 3])));
+    ///
+    /// let triangle = Graph::new(vec![vec![(1, 1)], vec![(2, 1)], vec![(0, 1)]]);
+    /// assert_eq!(triangle.bipartition(), Err(OddCycle(vec![0, 1, 2])));
+    /// ```
+    pub fn bipartition(&self) -> Result<(Vec<usize>, Vec<usize>), OddCycle> {
+        let n_vertices = self.n_vertices();
+        let mut undirected: Vec<Vec<usize>> = vec![vec![]; n_vertices];
+        for (u, neighbors) in self.adj.iter().enumerate() {
+            for &(v, _weight) in neighbors.iter() {
+                undirected[u].push(v);
+                undirected[v].push(u);
+            }
+        }
+
+        // breadth-first trees, so that two vertices of the same depth parity
+        // joined by an edge close a cycle of odd length through their parents
+        let mut parent: Vec<usize> = (0..n_vertices).collect();
+        let mut depth: Vec<Option<usize>> = vec![None; n_vertices];
+        for root in 0..n_vertices {
+            if depth[root].is_some() {
+                continue;
+            }
+            depth[root] = Some(0);
+            let mut queue = VecDeque::from([root]);
+            while let Some(u) = queue.pop_front() {
+                let depth_u = depth[u].unwrap();
+                for &v in undirected[u].iter() {
+                    match depth[v] {
+                        None => {
+                            depth[v] = Some(depth_u + 1);
+                            parent[v] = u;
+                            queue.push_back(v);
+                        }
+                        Some(depth_v) if depth_v.is_multiple_of(2) == depth_u.is_multiple_of(2) => {
+                            return Err(OddCycle::closed_by(u, v, &parent, &depth));
+                        }
+                        Some(_depth_v) => {}
+                    }
+                }
+            }
+        }
+
+        let (even, odd): (Vec<usize>, Vec<usize>) =
+            (0..n_vertices).partition(|&v| depth[v].unwrap().is_multiple_of(2));
+        Ok((even, odd))
     }
 }
 
+/// A cycle of odd length found by [`Graph::bipartition`], ignoring edge
+/// directions: every vertex is joined to the next one and the last one to
+/// the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OddCycle(pub Vec<usize>);
+
+impl OddCycle {
+    /// The cycle made of the edge between `u` and `v`, of the same depth
+    /// parity, and their paths to their closest common ancestor.
+    fn closed_by(u: usize, v: usize, parent: &[usize], depth: &[Option<usize>]) -> Self {
+        let (mut from_u, mut from_v) = (vec![u], vec![v]);
+        let (mut a, mut b) = (u, v);
+        while a != b {
+            if depth[a] >= depth[b] {
+                a = parent[a];
+                from_u.push(a);
+            } else {
+                b = parent[b];
+                from_v.push(b);
+            }
+        }
+        // down from the common ancestor to u, then up from v, leaving the
+        // ancestor, which ends both paths, to close the cycle
+        from_v.pop();
+        from_u.reverse();
+        from_u.append(&mut from_v);
+        OddCycle(from_u)
+    }
+}
+
+impl fmt::Display for OddCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let vertices: Vec<String> = self.0.iter().map(|v| v.to_string()).collect();
+        write!(f, "odd cycle {}", vertices.join(" - "))
+    }
+}
+
+impl error::Error for OddCycle {}
+
 /// The error type returned when we run into any error when parsing
 /// a graph.
 /// The cause of the error is within the struct and can be accessed easily
-#[derive(Debug, PartialEq, Eq)]
-pub struct ParseGraphError(String);
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseGraphError(pub(crate) String);
 
 impl fmt::Display for ParseGraphError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -60,6 +645,8 @@ impl fmt::Display for ParseGraphError {
     }
 }
 
+impl error::Error for ParseGraphError {}
+
 impl FromStr for Graph {
     type Err = ParseGraphError;
 
@@ -80,40 +667,135 @@ impl FromStr for Graph {
     /// let graph2 = graph_str.parse::<Graph>();
     /// ```
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (n_vertex_str, edges) = s
-            .split_once('\n')
-            .ok_or(ParseGraphError("cannot split on newline".to_string()))?;
-
-        let n_vertex = n_vertex_str
-            .parse()
-            .map_err(|e| ParseGraphError(format!("cannot parse n_vertices: {}", e)))?;
+        let _span = trace::span!("parse");
+        let (n_vertex, edges) = parse_header(s)?;
 
         let mut adj = vec![vec![]; n_vertex];
 
         for (vertex, neighbors) in edges.lines().take(n_vertex).enumerate() {
-            let neighbors_parsed = neighbors.split_whitespace().map(|edge_str| {
-                edge_str.split_once(',').ok_or(ParseGraphError(
-                    "vertex doesnt have weight with it".to_string(),
-                ))
-            });
-
-            for res in neighbors_parsed {
-                let (v, weight) = res?;
-                adj[vertex].push((
-                    v.parse()
-                        .map_err(|e| ParseGraphError(format!("cannot parse vertex: {}", e)))?,
-                    weight
-                        .parse()
-                        .map_err(|e| ParseGraphError(format!("cannot parse weight: {}", e)))?,
-                ))
-            }
+            // one comma per edge, so the list is allocated once
+            adj[vertex].reserve_exact(count_edges(neighbors));
+            parse_neighbors(neighbors, &mut adj[vertex])?;
         }
 
         Ok(Self { adj })
     }
 }
 
-impl PartialEq for Graph {
+/// Split a graph in the input format into its number of vertices and the
+/// lines of edges that follow.
+pub(crate) fn parse_header(s: &str) -> Result<(usize, &str), ParseGraphError> {
+    let (n_vertex_str, edges) = s
+        .split_once('\n')
+        .ok_or(ParseGraphError("cannot split on newline".to_string()))?;
+
+    let n_vertex = n_vertex_str
+        .parse()
+        .map_err(|e| ParseGraphError(format!("cannot parse n_vertices: {}", e)))?;
+    Ok((n_vertex, edges))
+}
+
+/// Upper bound on the number of edges in `s`, one per comma, to size the
+/// vectors before parsing them.
+pub(crate) fn count_edges(s: &str) -> usize {
+    s.bytes().filter(|&b| b == b',').count()
+}
+
+/// Parse the `vertex,weight` pairs of a line of edges of the input format
+/// and push them to `edges`.
+///
+/// Lines of plain digits, commas and spaces are parsed byte by byte. Others,
+/// e.g. with a `+` sign or an error, are parsed again token by token so they
+/// are read and reported the same way.
+pub(crate) fn parse_neighbors(
+    line: &str,
+    edges: &mut Vec<(usize, usize)>,
+) -> Result<(), ParseGraphError> {
+    let n_parsed = edges.len();
+    if parse_neighbors_bytes(line.as_bytes(), edges).is_some() {
+        return Ok(());
+    }
+
+    edges.truncate(n_parsed);
+    for edge_str in line.split_whitespace() {
+        let (v, weight) = edge_str.split_once(',').ok_or(ParseGraphError(
+            "vertex doesnt have weight with it".to_string(),
+        ))?;
+        edges.push((
+            v.parse()
+                .map_err(|e| ParseGraphError(format!("cannot parse vertex: {}", e)))?,
+            weight
+                .parse()
+                .map_err(|e| ParseGraphError(format!("cannot parse weight: {}", e)))?,
+        ));
+    }
+    Ok(())
+}
+
+/// [`parse_neighbors`] on a line of ASCII digits, commas and whitespace,
+/// `None` on anything else or if a number overflows.
+fn parse_neighbors_bytes(line: &[u8], edges: &mut Vec<(usize, usize)>) -> Option<()> {
+    let is_space = |b: u8| matches!(b, b' ' | b'\t' | b'\r' | b'\x0b' | b'\x0c');
+    let mut i = 0;
+    loop {
+        while i < line.len() && is_space(line[i]) {
+            i += 1;
+        }
+        if i == line.len() {
+            return Some(());
+        }
+        let (v, comma) = parse_digits(line, i)?;
+        if line.get(comma) != Some(&b',') {
+            return None;
+        }
+        let (weight, end) = parse_digits(line, comma + 1)?;
+        if end < line.len() && !is_space(line[end]) {
+            return None;
+        }
+        edges.push((v, weight));
+        i = end;
+    }
+}
+
+/// The number written in decimal from `line[start]` and the index after its
+/// last digit, `None` if there are no digits or too many to be sure the
+/// number did not overflow.
+fn parse_digits(line: &[u8], start: usize) -> Option<(usize, usize)> {
+    // any number of this many digits fits in a `usize`
+    const MAX_DIGITS: usize = usize::MAX.ilog10() as usize;
+
+    let mut value: usize = 0;
+    let mut end = start;
+    while let Some(&byte) = line.get(end) {
+        let digit = byte.wrapping_sub(b'0');
+        if digit >= 10 {
+            break;
+        }
+        value = value.wrapping_mul(10).wrapping_add(usize::from(digit));
+        end += 1;
+    }
+    (1..=MAX_DIGITS)
+        .contains(&(end - start))
+        .then_some((value, end))
+}
+
+impl<Ix: IndexType, W: WeightType> fmt::Display for Graph<Ix, W> {
+    /// Write the graph in the same format `from_str` parses:
+    /// the number of vertices followed by one line of `vertex,weight` pairs per vertex.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.n_vertices())?;
+        for neighbors in self.adj.iter() {
+            let edges: Vec<String> = neighbors
+                .iter()
+                .map(|(v, weight)| format!("{},{}", v, weight))
+                .collect();
+            writeln!(f, "{}", edges.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+impl<Ix: IndexType, W: WeightType> PartialEq for Graph<Ix, W> {
     /// This method tests for self and other values to be equal, and is used by `==`.
     ///
     /// NOTE: we consider two graphs equal if each of their
@@ -148,6 +830,8 @@ impl PartialEq for Graph {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dijkstra::dijkstra;
+    use crate::test_utils::sample_graph;
 
     #[test]
     fn correctly_equal() {
@@ -164,6 +848,27 @@ mod tests {
         assert_eq!(g1, g2);
     }
 
+    #[test]
+    fn path_cost_takes_lightest_parallel_edge() {
+        let g1 = Graph::new(vec![vec![(1, 5), (1, 2)], vec![(0, 1)]]);
+        assert_eq!(g1.path_cost(&[0, 1, 0]), Some(3));
+        assert_eq!(g1.path_cost(&[1]), Some(0));
+        assert_eq!(g1.path_cost(&[1, 1]), None);
+    }
+
+    #[test]
+    fn edits_only_touch_the_given_edges() {
+        let mut g1 = Graph::new(vec![vec![(1, 3), (2, 4), (1, 5)], vec![(0, 1)], vec![]]);
+        assert!(g1.set_weight(0, 1, 7));
+        assert!(g1.remove_edge(1, 0));
+        g1.add_edge(2, 2, 1);
+        assert_eq!(
+            g1,
+            Graph::new(vec![vec![(1, 7), (2, 4), (1, 7)], vec![], vec![(2, 1)]])
+        );
+        assert!(!g1.remove_edge(1, 0));
+    }
+
     #[test]
     fn correctly_unequal() {
         let g1 = Graph::new(vec![
@@ -243,6 +948,20 @@ mod tests {
         assert_eq!(parsed.unwrap(), should_be);
     }
 
+    #[test]
+    fn checks_edge_targets() {
+        let parsed = Graph::from_str("3\n1,3\n3,1\n").unwrap();
+        assert_eq!(
+            parsed.check_targets(),
+            Err(ParseGraphError("vertex 3 out of range 0..3".to_string()))
+        );
+        assert_eq!(
+            Graph::new(vec![vec![(1, 3)], vec![(0, 3)]]).check_targets(),
+            Ok(())
+        );
+        assert_eq!(Graph::new(vec![]).check_targets(), Ok(()));
+    }
+
     #[test]
     fn fails_when_no_n_vertices() {
         let graph_str = r#"1,3 2,3
@@ -292,4 +1011,162 @@ mod tests {
         assert!(parsed.is_ok());
         assert_eq!(parsed.unwrap(), should_be);
     }
+
+    #[test]
+    fn byte_parser_agrees_with_tokens() {
+        let parse = |line: &str| {
+            let mut edges = vec![];
+            parse_neighbors(line, &mut edges).map(|()| edges)
+        };
+        assert_eq!(parse(" 1,3\t2,40 \r"), Ok(vec![(1, 3), (2, 40)]));
+        // left to the token parser
+        assert_eq!(parse("+1,3 2,4"), Ok(vec![(1, 3), (2, 4)]));
+        assert_eq!(parse("1,3\u{a0}2,4"), Ok(vec![(1, 3), (2, 4)]));
+        let error = |line: &str| parse(line).unwrap_err().to_string();
+        assert_eq!(error("1,3 2"), "vertex doesnt have weight with it");
+        assert_eq!(
+            error("1,3,4"),
+            "cannot parse weight: invalid digit found in string"
+        );
+        assert_eq!(
+            error(",4"),
+            "cannot parse vertex: cannot parse integer from empty string"
+        );
+        assert_eq!(
+            error("99999999999999999999,1"),
+            "cannot parse vertex: number too large to fit in target type"
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let g1 = Graph::new(vec![vec![(1, 3), (2, 3)], vec![], vec![(0, 3)]]);
+        assert_eq!(g1.to_string(), "3\n1,3 2,3\n\n0,3\n");
+        assert_eq!(Graph::from_str(&g1.to_string()).unwrap(), g1);
+    }
+
+    #[test]
+    fn index_types_keep_the_graph() {
+        let g1 = Graph::new(vec![vec![(1, 3), (2, 3)], vec![], vec![(0, 3)]]);
+        let small = g1.to_index_type::<u16>().unwrap();
+        assert_eq!(small.n_edges(), 3);
+        assert_eq!(small.to_string(), g1.to_string());
+        assert_eq!(small.to_index_type::<usize>().unwrap(), g1);
+
+        let g2 = Graph::new(vec![vec![(70_000, 1)]]);
+        assert_eq!(g2.to_index_type::<u16>(), None);
+        assert!(g2.to_index_type::<u32>().is_some());
+    }
+
+    #[test]
+    fn undirected_keeps_minimum_weight() {
+        let g1 = Graph::new(vec![vec![(1, 5), (1, 4)], vec![(0, 3), (2, 1)], vec![]]);
+        let should_be = Graph::new(vec![vec![(1, 3)], vec![(0, 3), (2, 1)], vec![(1, 1)]]);
+        assert_eq!(g1.to_undirected(), should_be);
+        assert_eq!(should_be.to_undirected(), should_be);
+    }
+
+    #[test]
+    fn components_are_weak() {
+        let g1 = Graph::new(vec![
+            vec![],
+            vec![(0, 1)],
+            vec![(4, 1)],
+            vec![],
+            vec![(3, 1)],
+            vec![],
+        ]);
+        assert_eq!(g1.components(), vec![0, 0, 1, 1, 1, 2]);
+    }
+
+    #[test]
+    fn compact_keeps_the_distances() {
+        let mut g1 = sample_graph();
+        g1.remove_vertex(3);
+        g1.remove_vertex(6);
+        let (_paths, before) = dijkstra(&g1, 0).unwrap();
+
+        let mapping = g1.compact(&[3, 6]);
+        assert_eq!(
+            mapping,
+            vec![
+                Some(0),
+                Some(1),
+                Some(2),
+                None,
+                Some(3),
+                Some(4),
+                None,
+                Some(5)
+            ]
+        );
+        assert_eq!(g1.n_vertices(), 6);
+        let (_paths, after) = dijkstra(&g1, 0).unwrap();
+        for (old, new) in mapping.iter().enumerate() {
+            match new {
+                Some(new) => assert_eq!(after[*new], before[old]),
+                None => assert_eq!(before[old], None),
+            }
+        }
+        // nothing left to drop
+        let again = g1.compact(&[]);
+        assert!(again.iter().enumerate().all(|(v, &new)| new == Some(v)));
+    }
+
+    #[test]
+    fn compact_keeps_isolated_vertices_not_removed() {
+        let mut g1 = Graph::new(vec![vec![(1, 3)], vec![(0, 3)], vec![], vec![(0, 1)]]);
+        g1.remove_vertex(1);
+        let mapping = g1.compact(&[1]);
+        assert_eq!(mapping, vec![Some(0), None, Some(1), Some(2)]);
+        assert_eq!(g1, Graph::new(vec![vec![], vec![], vec![(0, 1)]]));
+
+        // a vertex still connected loses its edges as well
+        let mut g2 = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+        assert_eq!(g2.compact(&[1]), vec![Some(0), None, Some(1)]);
+        assert_eq!(g2, Graph::new(vec![vec![], vec![]]));
+    }
+
+    #[test]
+    fn bipartition_sides_or_odd_cycle() {
+        // two squares and an isolated vertex, with edges in both directions
+        let g1 = Graph::new(vec![
+            vec![(1, 1)],
+            vec![(2, 1), (0, 1)],
+            vec![(3, 1)],
+            vec![(0, 1)],
+            vec![],
+            vec![(7, 1), (6, 1)],
+            vec![(8, 1)],
+            vec![(8, 1)],
+            vec![],
+        ]);
+        let (left, right) = g1.bipartition().unwrap();
+        assert_eq!(left, vec![0, 2, 4, 5, 8]);
+        assert_eq!(right, vec![1, 3, 6, 7]);
+
+        // a cycle of length 5 far from the root, through a long tail
+        let mut g2 = Graph::new(vec![vec![]; 9]);
+        for (u, v) in [
+            (0, 1),
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+            (7, 3),
+            (8, 0),
+        ] {
+            g2.add_edge(u, v, 1);
+        }
+        let OddCycle(cycle) = g2.bipartition().unwrap_err();
+        assert_eq!(cycle.len(), 5);
+        for (i, &u) in cycle.iter().enumerate() {
+            let v = cycle[(i + 1) % cycle.len()];
+            assert!(g2.path_cost(&[u, v]).is_some() || g2.path_cost(&[v, u]).is_some());
+        }
+        let self_loop = Graph::new(vec![vec![], vec![(1, 1)]]);
+        assert_eq!(self_loop.bipartition(), Err(OddCycle(vec![1])));
+    }
 }
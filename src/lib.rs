@@ -1,3 +1,73 @@
+//! Dijkstra's algorithm and the tools around it to run it on real graphs.
+//!
+//! Without the default `std` feature, only the graph, the queues and the
+//! algorithms are built, on `core` and `alloc`, so that they can run where
+//! there is no standard library.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod apsp;
+pub mod astar;
+pub mod bellman_ford;
+#[cfg(feature = "std")]
+pub mod bench;
+pub mod bfs;
+pub mod bitset;
+pub mod cache;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod coloring;
+pub mod csr;
+pub mod dijkstra;
+#[deprecated(since = "0.3.0", note = "renamed to `dijkstra`")]
 pub mod djikstra;
+pub mod editor;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod external;
+#[cfg(feature = "std")]
+pub mod formats;
+#[cfg(feature = "std")]
+pub mod generate;
 pub mod graph;
+pub mod matching;
+#[cfg(feature = "std")]
+pub mod mst;
+pub mod multi;
+pub mod narrow;
+#[cfg(feature = "std")]
+pub mod oracle;
+#[cfg(feature = "std")]
+pub mod output;
+pub mod path;
 pub mod pq;
+pub mod prelude;
+#[cfg(feature = "std")]
+pub mod repl;
+#[cfg(feature = "std")]
+pub mod sample;
+#[cfg(feature = "std")]
+pub mod server;
+#[cfg(feature = "std")]
+pub mod simplify;
+#[cfg(feature = "inline-neighbors")]
+pub mod small;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod stochastic;
+#[cfg(any(feature = "test-utils", test))]
+pub mod test_utils;
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod validate;
+pub mod verify;
+#[cfg(feature = "std")]
+pub mod visualize;
+pub mod walk;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use error::Error;
+pub use graph::Graph;
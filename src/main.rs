@@ -1,12 +1,137 @@
-//! CLI interface for running and benchmarking the Djikstra algorithm.
-use clap::{Args, Parser, Subcommand};
-use djikstra::djikstra::djikstra;
+//! CLI interface for running and benchmarking the Dijkstra algorithm.
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use djikstra::apsp::{distance_rows, Method};
+use djikstra::astar::astar;
+use djikstra::bellman_ford::bellman_ford;
+use djikstra::bench::{Benchmark, BenchmarkResult, TimingStats};
+use djikstra::bfs::bfs;
+use djikstra::cache::RouteCache;
+use djikstra::dijkstra::{
+    batch_shortest_paths_parallel, dijkstra_profiled, dijkstra_streaming, dijkstra_while,
+    dijkstra_with, dijkstra_with_progress, route_via, shortest_path, shortest_path_avoiding,
+    shortest_paths_with_stats, Avoid, DijkstraError, PathsAndDists, SearchStats,
+};
+use djikstra::editor::{Edit, EditError, GraphEditor};
 use djikstra::graph::Graph;
+use djikstra::mst::{kruskal, prim, total_weight};
+use djikstra::output::{Results, TreeResults, VertexResult, CSV_PATH_SEPARATOR};
+use djikstra::pq::{BinaryHeapQueue, DaryHeapQueue, PriorityQueue, RadixHeapQueue};
+use djikstra::repl::{Repl, HELP};
+use djikstra::server;
+use djikstra::simplify::contract_chains;
+use djikstra::stats::{degree_histograms, weight_histogram, GraphStats};
+use djikstra::validate::{validate_input, Severity};
+use djikstra::verify::check_sssp;
+use djikstra::visualize::{force_layout, write_svg};
+use djikstra::{formats, generate, sample, Error};
+use indicatif::{ProgressBar, ProgressStyle};
+use log::{debug, error, info, warn, LevelFilter};
+use rand::{rngs::StdRng, RngExt, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::net::TcpListener;
 use std::str::FromStr;
-use std::time::Instant;
-use std::{fs, path::PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    process, thread,
+};
 
-/// CLI interface for running and benchmarking the Djikstra algorithm.
+/// Global allocator that keeps track of the currently allocated and the peak
+/// number of heap bytes, so the benchmark can report peak memory.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+impl CountingAllocator {
+    /// Record `size` more allocated bytes.
+    fn grow(size: usize) {
+        let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+        PEAK_ALLOCATED.fetch_max(allocated, Ordering::Relaxed);
+    }
+
+    /// Record `size` less allocated bytes.
+    fn shrink(size: usize) {
+        ALLOCATED.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    /// Start tracking a new peak from the current allocation.
+    /// Returns the current number of allocated bytes.
+    fn reset_peak() -> usize {
+        let allocated = ALLOCATED.load(Ordering::Relaxed);
+        PEAK_ALLOCATED.store(allocated, Ordering::Relaxed);
+        allocated
+    }
+
+    /// Peak number of allocated bytes since the last `reset_peak`.
+    fn peak() -> usize {
+        PEAK_ALLOCATED.load(Ordering::Relaxed)
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            Self::grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        Self::shrink(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            Self::shrink(layout.size());
+            Self::grow(new_size);
+        }
+        new_ptr
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Logger writing the diagnostics to stderr as `LEVEL message` lines,
+/// so stdout is left for the results.
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{0:<5} {1}", record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// CLI interface for running and benchmarking the Dijkstra algorithm.
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
@@ -14,6 +139,29 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Print only the result records, without the running time or other prose.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Level of the diagnostics written to stderr.
+    /// Defaults to debug in verbose mode, error in quiet mode and info otherwise.
+    #[arg(long, value_name = "LEVEL", global = true)]
+    #[arg(value_enum)]
+    log_level: Option<LogLevel>,
+
+    /// Seed for every random choice made by the commands, random if omitted.
+    /// The seed is printed in verbose mode so a run can be repeated.
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+
+    /// Output file the results are written to, `-` or omitted for stdout.
+    #[arg(short, long, value_name = "FILE", global = true)]
+    output: Option<PathBuf>,
+
+    /// TOML file with defaults for the flags, instead of `~/.config/djikstra.toml`.
+    #[arg(long, value_name = "FILE", global = true)]
+    config: Option<PathBuf>,
+
     /// Subcommands.
     #[command(subcommand)]
     command: Commands,
@@ -26,142 +174,3459 @@ enum Commands {
 
     /// Benchmarks the algorithm on the input graph.
     Benchmark(BenchmarkArgs),
+
+    /// Finds the shortest path between two vertices of the input graph.
+    Query(QueryArgs),
+
+    /// Generates a random graph in the input format.
+    Generate(GenerateArgs),
+
+    /// Converts a graph between file formats.
+    Convert(ConvertArgs),
+
+    /// Checks the input file for structural problems.
+    Validate(ValidateArgs),
+
+    /// Prints summary statistics of the input graph.
+    Stats(StatsArgs),
+
+    /// Serves shortest path queries on the input graph over HTTP.
+    Serve(ServeArgs),
+
+    /// Answers queries typed interactively on the input graph.
+    Repl(ReplArgs),
+
+    /// Lists the sizes of the weakly connected components of the input graph.
+    Components(ComponentsArgs),
+
+    /// Computes the distances between all pairs of vertices.
+    Matrix(MatrixArgs),
+
+    /// Finds a minimum spanning tree of the input graph.
+    Mst(MstArgs),
+
+    /// Contracts chains of degree-2 vertices into single edges.
+    Simplify(SimplifyArgs),
+
+    /// Extracts a smaller random sample of the input graph.
+    Sample(SampleArgs),
+
+    /// Draws the input graph as an SVG image, highlighting a shortest path.
+    Visualize(VisualizeArgs),
+
+    /// Prints the header and the first vertices of a graph file without reading all of it.
+    Head(HeadArgs),
+
+    /// Adds, removes and reweights edges of the input graph.
+    Edit(EditArgs),
+
+    /// Runs the algorithm and compares the results with the expected ones.
+    Verify(VerifyArgs),
+
+    /// Runs several algorithms and reports the vertices they disagree on.
+    Diff(DiffArgs),
+
+    /// Checks the algorithms against Bellman-Ford on random graphs.
+    Selftest(SelftestArgs),
+
+    /// Breaks down the time of a run into its phases, from reading the input to building the paths.
+    Profile(ProfileArgs),
+
+    /// Prints a shell completion script for the subcommands and flags.
+    Completions(CompletionsArgs),
 }
 
 /// Arguments for the run subcommand.
 #[derive(Args)]
 struct RunArgs {
-    /// Input file that contains the graph.
-    #[arg(short = 'i', long = "input", value_name = "FILE")]
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
     input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Start vertex to use instead of the one in the input file.
+    #[arg(long, value_name = "N")]
+    start: Option<usize>,
+    /// Algorithm to find the shortest paths with.
+    #[arg(long, value_enum, default_value_t = Algorithm::Dijkstra)]
+    algorithm: Algorithm,
+    /// Format the results are printed in, `table` if omitted and printing to a terminal.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    /// CSV file with the `vertex,longitude,latitude` of the vertices, for `--format geojson`.
+    #[arg(long, value_name = "FILE")]
+    coords: Option<PathBuf>,
+    /// Leave the unreachable vertices out of the results.
+    #[arg(long)]
+    reachable_only: bool,
+    /// Print only the K vertices closest to the start vertex, closest first,
+    /// stopping the search as soon as they are found.
+    #[arg(long, value_name = "K")]
+    nearest: Option<usize>,
+    /// Write a `vertex distance parent` record as soon as every vertex is settled,
+    /// closest first, instead of the results after the search.
+    #[arg(long)]
+    stream: bool,
+    /// Run again whenever the input file changes, until interrupted.
+    #[arg(long)]
+    watch: bool,
+    /// Whether the table is written to a terminal that accepts colors.
+    #[arg(skip)]
+    color: bool,
 }
 
 /// Arguments for the benchmark subcommand.
 #[derive(Args)]
 struct BenchmarkArgs {
-    /// Input file that contains the graph.
-    #[arg(short = 'i', long = "input", value_name = "FILE")]
-    input_path: PathBuf,
+    /// Input files that contain the graphs, or glob patterns matching them.
+    /// `-` or omitted to read a single graph from stdin.
+    #[arg(
+        short = 'i',
+        long = "input",
+        value_name = "FILE",
+        num_args = 1..,
+        default_value = "-"
+    )]
+    input_paths: Vec<String>,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Start vertex to use instead of the one in every input file.
+    #[arg(long, value_name = "N")]
+    start: Option<usize>,
     /// Number of times to run the algorithm for benchmarking.
     #[arg(short, default_value_t = 1000)]
     n: usize,
+    /// Number of runs before the measured ones that are left out of the statistics.
+    #[arg(long, value_name = "K", default_value_t = 0)]
+    warmup: usize,
+    /// Priority queue backend to benchmark, or `all` to compare every backend.
+    #[arg(long, value_enum, default_value_t = PqBackend::Btree)]
+    pq: PqBackend,
+    /// Comma separated algorithms to compare on the same input.
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "dijkstra")]
+    algorithm: Vec<Algorithm>,
+    /// Write every run's time and the statistics to FILE as `csv` or `json`.
+    #[arg(long, num_args = 2, value_names = ["FORMAT", "FILE"])]
+    export: Option<Vec<String>>,
+    /// Number of threads sharing the runs, each timing its own runs.
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    threads: usize,
+    /// Time the runs on a single thread while the other threads keep running
+    /// the algorithm, to measure the latency of a run under contention.
+    #[arg(long, requires = "threads")]
+    contention: bool,
+    /// Instead of `-n` runs, run until the 95% confidence interval of the mean
+    /// is within PCT percent of the mean, discarding outliers.
+    #[arg(long, value_name = "PCT")]
+    target_ci: Option<f64>,
+    /// Stop the runs of `--target-ci` after SECS seconds even if the
+    /// confidence interval is still wider.
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 60.0,
+        requires = "target_ci"
+    )]
+    max_time: f64,
+    /// Save the statistics as the baseline NAME, to compare later runs with.
+    #[arg(long, value_name = "NAME")]
+    save_baseline: Option<String>,
+    /// Report the change of the mean time from the baseline NAME saved before.
+    #[arg(long, value_name = "NAME")]
+    compare_baseline: Option<String>,
+    /// Directory the baselines are saved in, one JSON file per baseline.
+    #[arg(long, value_name = "DIR", default_value = ".djikstra-baselines")]
+    baseline_dir: PathBuf,
+    /// Fail if the mean time of any benchmark grew by more than PCT percent
+    /// from the baseline.
+    #[arg(long, value_name = "PCT", requires = "compare_baseline")]
+    fail_threshold: Option<f64>,
+    /// Also time the Dijkstra of the petgraph and pathfinding crates on the
+    /// same graph, and compare them with the algorithms of this crate.
+    /// Needs the `compare` feature.
+    #[arg(long, hide = !cfg!(feature = "compare"))]
+    compare_crates: bool,
 }
 
-fn main() {
-    let args = Cli::parse();
+/// Arguments for the query subcommand.
+#[derive(Args)]
+struct QueryArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Vertex the path starts from.
+    #[arg(long, required_unless_present = "batch")]
+    from: Option<usize>,
+    /// Vertex the path ends at.
+    #[arg(long, required_unless_present = "batch")]
+    to: Option<usize>,
+    /// File with one `from to` query per line to answer instead of a single query.
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["from", "to"])]
+    batch: Option<PathBuf>,
+    /// Number of threads sharing the sources of the batch queries.
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "batch")]
+    threads: usize,
+    /// Vertices the path must not go through, e.g. `--avoid 3,5`.
+    #[arg(
+        long,
+        value_name = "V",
+        value_delimiter = ',',
+        conflicts_with = "batch"
+    )]
+    avoid: Vec<usize>,
+    /// Edges the path must not follow, e.g. `--avoid-edge 2:3,3:2` to close
+    /// both directions of an undirected edge.
+    #[arg(
+        long,
+        value_name = "FROM:TO",
+        value_delimiter = ',',
+        conflicts_with = "batch"
+    )]
+    avoid_edge: Vec<String>,
+    /// Vertices the path must stop at in order between `--from` and `--to`,
+    /// e.g. `--via 8,4`.
+    #[arg(
+        long,
+        value_name = "V",
+        value_delimiter = ',',
+        conflicts_with_all = ["batch", "avoid", "avoid_edge"]
+    )]
+    via: Vec<usize>,
+    /// Format the result is printed in, `table` if omitted and printing a single
+    /// path to a terminal.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    /// CSV file with the `vertex,longitude,latitude` of the vertices, for `--format geojson`.
+    #[arg(long, value_name = "FILE")]
+    coords: Option<PathBuf>,
+    /// Whether the table is written to a terminal that accepts colors.
+    #[arg(skip)]
+    color: bool,
+}
 
-    let verbosity = args.verbose;
+/// Arguments for the generate subcommand.
+#[derive(Args)]
+struct GenerateArgs {
+    /// Random graph model to generate from.
+    #[arg(long, value_enum)]
+    model: GraphModel,
+    /// Number of vertices.
+    #[arg(long)]
+    n: usize,
+    /// Probability of an edge between two vertices for the `gnp` model.
+    #[arg(long, default_value_t = 0.01)]
+    p: f64,
+    /// Number of edges added with every vertex for the `ba` model.
+    #[arg(long, default_value_t = 2)]
+    m: usize,
+    /// Largest edge weight, at least 1, weights are drawn uniformly from 1 to this.
+    #[arg(long, default_value_t = 100)]
+    max_weight: usize,
+    /// Start vertex written into the generated input, below `--n`.
+    #[arg(long, default_value_t = 0)]
+    start: usize,
+}
 
-    match &args.command {
-        Commands::Run(cmd_args) => {
-            run_command(cmd_args, verbosity);
+/// Random graph models the generate subcommand supports.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GraphModel {
+    /// Erdős–Rényi graph with every edge present with probability `p`.
+    Gnp,
+    /// Square-ish grid graph.
+    Grid,
+    /// Barabási–Albert preferential attachment graph.
+    Ba,
+}
+
+/// Arguments for the convert subcommand.
+#[derive(Args)]
+struct ConvertArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Format of the input file.
+    #[arg(long, value_enum, default_value_t = GraphFormat::Text)]
+    from: GraphFormat,
+    /// Format to convert the graph to.
+    #[arg(long, value_enum, default_value_t = GraphFormat::Text)]
+    to: GraphFormat,
+}
+
+/// Arguments for the validate subcommand.
+#[derive(Args)]
+struct ValidateArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Also check that every edge has a reverse edge with the same weight.
+    #[arg(long)]
+    undirected: bool,
+}
+
+/// Arguments for the stats subcommand.
+#[derive(Args)]
+struct StatsArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Write the distribution of the degrees or the weights as CSV instead of the summary.
+    #[arg(long, value_enum)]
+    histogram: Option<HistogramKind>,
+    /// Largest number of bins of the histogram.
+    #[arg(long, value_name = "N", default_value_t = 20, requires = "histogram")]
+    bins: usize,
+}
+
+/// Distributions `stats --histogram` can write.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum HistogramKind {
+    /// Out-degrees and in-degrees of the vertices.
+    Degrees,
+    /// Weights of the edges.
+    Weights,
+}
+
+/// Arguments for the serve subcommand.
+#[derive(Args)]
+struct ServeArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Port to listen on.
+    #[arg(long, default_value_t = 8080)]
+    port: u16,
+    /// Address to listen on.
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+    /// Number of sources whose shortest path tree is kept to answer later
+    /// requests from them, the least recently used dropped first. 0 keeps none.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    cache: usize,
+}
+
+/// Arguments for the repl subcommand.
+#[derive(Args)]
+struct ReplArgs {
+    /// Input file that contains the graph.
+    #[arg(short = 'i', long = "input", value_name = "FILE")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+}
+
+/// Arguments for the components subcommand.
+#[derive(Args)]
+struct ComponentsArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Also write the component of every vertex to FILE as `vertex,component` rows.
+    #[arg(long, value_name = "FILE")]
+    labels: Option<PathBuf>,
+}
+
+/// Arguments for the matrix subcommand.
+#[derive(Args)]
+struct MatrixArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Algorithm to compute the matrix with, `auto` picks one by the density of the graph.
+    #[arg(long, value_enum, default_value_t = MatrixMethod::Auto)]
+    method: MatrixMethod,
+    /// Format the matrix is written in.
+    #[arg(long, value_enum, default_value_t = MatrixFormat::Csv)]
+    format: MatrixFormat,
+}
+
+/// All-pairs algorithms the matrix subcommand supports.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MatrixMethod {
+    /// Floyd-Warshall for dense graphs and repeated Dijkstra otherwise.
+    Auto,
+    /// Dijkstra from every vertex, streaming the rows as they are found.
+    #[value(alias = "djikstra")]
+    Dijkstra,
+    /// Floyd-Warshall, holding the whole matrix in memory.
+    FloydWarshall,
+}
+
+/// Formats the distance matrix can be written in.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MatrixFormat {
+    /// A `vertex,0,1,..` header and one row per vertex, empty if unreachable.
+    Csv,
+    /// The number of vertices followed by the rows, all as little-endian `u64`,
+    /// with `u64::MAX` if unreachable.
+    Binary,
+}
+
+/// Arguments for the mst subcommand.
+#[derive(Args)]
+struct MstArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Algorithm to find the tree with.
+    #[arg(long, value_enum, default_value_t = MstAlgorithm::Kruskal)]
+    algorithm: MstAlgorithm,
+    /// Format the tree is printed in.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+}
+
+/// Minimum spanning tree algorithms the mst subcommand supports.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MstAlgorithm {
+    /// Prim's algorithm, growing the tree from a vertex.
+    Prim,
+    /// Kruskal's algorithm, adding the lightest edges that close no cycle.
+    Kruskal,
+}
+
+/// Arguments for the simplify subcommand.
+#[derive(Args)]
+struct SimplifyArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Comma separated vertices to keep besides the start vertex.
+    #[arg(long, value_delimiter = ',')]
+    keep: Vec<usize>,
+    /// Also write the original index of every vertex to FILE as `vertex,original` rows.
+    #[arg(long, value_name = "FILE")]
+    mapping: Option<PathBuf>,
+}
+
+/// Arguments for the sample subcommand.
+#[derive(Args)]
+struct SampleArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Number of vertices in the sample, including the start vertex.
+    #[arg(long, value_name = "N")]
+    size: usize,
+    /// How the vertices of the sample are chosen.
+    #[arg(long, value_enum, default_value_t = SampleMethod::ForestFire)]
+    method: SampleMethod,
+    /// Probability of a forest fire spreading to one more neighbor of a burning vertex.
+    #[arg(long, value_name = "P", default_value_t = 0.7)]
+    burn: f64,
+    /// Also write the original index of every vertex to FILE as `vertex,original` rows.
+    #[arg(long, value_name = "FILE")]
+    mapping: Option<PathBuf>,
+}
+
+/// Ways of choosing the vertices of a sample.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SampleMethod {
+    /// A forest fire lit at the start vertex, giving a connected sample.
+    ForestFire,
+    /// Vertices chosen uniformly at random, with the edges between them.
+    Random,
+}
+
+/// Arguments for the head subcommand.
+#[derive(Args)]
+struct HeadArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Format of the input file, `text` or `dimacs`.
+    #[arg(long, value_enum, default_value_t = GraphFormat::Text)]
+    from: GraphFormat,
+    /// Number of vertices to print the edges of.
+    #[arg(long, value_name = "N", default_value_t = 10)]
+    vertices: usize,
+}
+
+/// Arguments for the edit subcommand.
+#[derive(Args)]
+struct EditArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Remove every edge from FROM to TO.
+    #[arg(long, value_name = "FROM,TO")]
+    remove_edge: Vec<String>,
+    /// Add an edge from FROM to TO.
+    #[arg(long, value_name = "FROM,TO,WEIGHT")]
+    add_edge: Vec<String>,
+    /// Set the weight of every edge from FROM to TO.
+    #[arg(long, value_name = "FROM,TO,WEIGHT")]
+    set_weight: Vec<String>,
+    /// Apply the edits of FILE first, one per line as the `journal` command
+    /// of the REPL prints them, e.g. `add-edge 3 7 5`.
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+}
+
+/// Arguments for the verify subcommand.
+#[derive(Args)]
+struct VerifyArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Results the algorithm should find, as printed by `run`. The algorithm
+    /// starts from their source.
+    #[arg(long, value_name = "FILE")]
+    expected: PathBuf,
+    /// Format of the expected results.
+    #[arg(long, value_enum, default_value_t = ExpectedFormat::Json)]
+    format: ExpectedFormat,
+    /// Algorithm to check.
+    #[arg(long, value_enum, default_value_t = Algorithm::Dijkstra)]
+    algorithm: Algorithm,
+    /// Also require the same paths, rather than any path as short as the expected one.
+    #[arg(long)]
+    exact_paths: bool,
+}
+
+/// Formats of the expected results read by `verify`.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ExpectedFormat {
+    /// The output of `run --format json`.
+    Json,
+    /// The output of `run --format text`, with or without the running time.
+    Text,
+}
+
+/// Arguments for the diff subcommand.
+#[derive(Args)]
+struct DiffArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Start vertex to use instead of the one in the input file.
+    #[arg(long, value_name = "N")]
+    start: Option<usize>,
+    /// Comma separated algorithms to compare, each against the first one.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "dijkstra,bellman-ford"
+    )]
+    algorithms: Vec<Algorithm>,
+}
+
+/// Arguments for the selftest subcommand.
+#[derive(Args)]
+struct SelftestArgs {
+    /// Number of random graphs to check.
+    #[arg(long, value_name = "N", default_value_t = 200)]
+    graphs: usize,
+    /// Maximum number of vertices of a graph.
+    #[arg(long, value_name = "N", default_value_t = 40)]
+    max_vertices: usize,
+    /// Maximum weight of an edge, small weights make ties between paths more likely.
+    #[arg(long, value_name = "W", default_value_t = 20)]
+    max_weight: usize,
+}
+
+/// Arguments for the profile subcommand.
+#[derive(Args)]
+struct ProfileArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Start vertex to use instead of the one in the input file.
+    #[arg(long, value_name = "N")]
+    start: Option<usize>,
+    /// Priority queue backend to profile, or `all` to profile every backend.
+    #[arg(long, value_enum, default_value_t = PqBackend::Btree)]
+    pq: PqBackend,
+}
+
+/// Arguments for the visualize subcommand.
+#[derive(Args)]
+struct VisualizeArgs {
+    /// Input file that contains the graph, `-` or omitted to read from stdin.
+    #[arg(short = 'i', long = "input", value_name = "FILE", default_value = "-")]
+    input_path: PathBuf,
+    /// Add the reverse of every edge, keeping the minimum weight on duplicate edges.
+    #[arg(long)]
+    undirected: bool,
+    /// Shortest path to highlight, given as `FROM:TO`.
+    #[arg(long, value_name = "FROM:TO")]
+    path: Option<String>,
+    /// Number of rounds of the force-directed layout.
+    #[arg(long, default_value_t = 300)]
+    iterations: usize,
+}
+
+/// Arguments for the completions subcommand.
+#[derive(Args)]
+struct CompletionsArgs {
+    /// Shell to print the completion script for.
+    #[arg(value_enum)]
+    shell: Shell,
+}
+
+/// How much the commands print besides their results.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Verbosity {
+    /// Only the result records.
+    Quiet,
+    /// The results with the running time and other short notes.
+    Normal,
+    /// Also every summary, with the diagnostics logged down to the debug level.
+    Verbose,
+}
+
+/// File formats graphs can be read from and written to.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum GraphFormat {
+    /// The crate's input format: start vertex, number of vertices, and adjacency lines.
+    Text,
+    /// DIMACS shortest path challenge format (`p sp` and `a` lines, 1-indexed).
+    Dimacs,
+    /// JSON object with an `adj` list of `[vertex, weight]` pairs per vertex.
+    Json,
+}
+
+/// Formats the results of `run` and `query` can be printed in.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// `vertex distance (path)` lines followed by the running time.
+    Text,
+    /// Aligned columns with a header, colored on a terminal unless `NO_COLOR` is set.
+    Table,
+    /// A single JSON document with distances, paths and the running time.
+    Json,
+    /// `vertex,distance,path` rows with the vertices of a path separated by spaces.
+    Csv,
+    /// Graphviz DOT graph with the shortest path edges highlighted.
+    Dot,
+    /// GeoJSON features with the paths as lines between the coordinates given by `--coords`.
+    Geojson,
+}
+
+/// Shortest path algorithms that can be run and benchmarked.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Algorithm {
+    /// Dijkstra's algorithm, with the queue backends selected by `--pq`.
+    #[value(alias = "djikstra")]
+    Dijkstra,
+    /// A* search. The input has no coordinates to estimate distances from,
+    /// so the heuristic is 0 and the vertices are settled as by Dijkstra.
+    Astar,
+    /// Bellman-Ford algorithm.
+    BellmanFord,
+    /// Breadth-first search, ignoring the weights: distances are numbers of edges.
+    Bfs,
+}
+
+impl Algorithm {
+    /// Name of the algorithm as accepted on the command line.
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::Dijkstra => "dijkstra",
+            Algorithm::Astar => "astar",
+            Algorithm::BellmanFord => "bellman-ford",
+            Algorithm::Bfs => "bfs",
         }
-        Commands::Benchmark(cmd_args) => {
-            benchmark_command(cmd_args, verbosity);
+    }
+}
+
+/// Levels of the diagnostics written to stderr, from none to all of them.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// The filter letting through the records at this level or above.
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Off => LevelFilter::Off,
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
         }
     }
 }
 
-/// Run the Djikstra algorithm on the input graph.
-fn run_command(args: &RunArgs, verbose: bool) {
-    // djikstra run --input graph.txt --verbose
+/// Priority queue backends available to the algorithm.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum PqBackend {
+    /// BTreeMap based queue with linear extract-min. `hashmap`, its former
+    /// name, is still accepted but deprecated.
+    #[value(alias = "hashmap")]
+    Btree,
+    /// Binary heap based queue with lazy deletion.
+    Binary,
+    /// 4-ary heap based queue with decrease-key.
+    Dary,
+    /// Radix heap based monotone queue with lazy deletion.
+    Radix,
+    /// Every backend above, one after the other.
+    All,
+}
+
+impl PqBackend {
+    /// The concrete backends this selection stands for.
+    fn backends(self) -> Vec<PqBackend> {
+        match self {
+            PqBackend::All => vec![
+                PqBackend::Btree,
+                PqBackend::Binary,
+                PqBackend::Dary,
+                PqBackend::Radix,
+            ],
+            backend => vec![backend],
+        }
+    }
 
-    let (start_vertex, graph) = match parse_input(&args.input_path) {
-        Ok((start_vertex, graph)) => (start_vertex, graph),
+    /// Name of the backend as accepted on the command line.
+    fn name(self) -> &'static str {
+        match self {
+            PqBackend::Btree => "btree",
+            PqBackend::Binary => "binary",
+            PqBackend::Dary => "dary",
+            PqBackend::Radix => "radix",
+            PqBackend::All => "all",
+        }
+    }
+}
+
+fn main() {
+    let matches = Cli::command().get_matches();
+    let args = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    let exit_code = match run_cli(args, &matches) {
+        Ok(true) => 0,
+        // validate found errors in the input, diff or selftest found disagreements,
+        // verify found mismatches, or benchmark found regressions
+        Ok(false) => 1,
         Err(e) => {
-            eprintln!("Error parsing input: {0}", e.0);
-            return;
+            eprintln!("Error: {0}", e);
+            e.exit_code()
         }
     };
+    process::exit(exit_code);
+}
 
-    if verbose {
-        println!("Read file {0:?} successfully.", &args.input_path);
-        println!(
-            "Running algorithm on graph with {0} vertices and start vertex {1}.\n",
-            graph.n_vertices(),
-            start_vertex
-        );
+/// Run the subcommand, writing its results to the output.
+/// Returns whether the command succeeded, which is false only for `validate`
+/// on an input with errors, `diff` and `selftest` on algorithms that disagree,
+/// `verify` on results that do not match, and `benchmark` on regressions from
+/// the baseline.
+fn run_cli(mut args: Cli, matches: &ArgMatches) -> Result<bool, CliError> {
+    let verbosity = match (args.quiet, args.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (false, true) => Verbosity::Verbose,
+        (false, false) => Verbosity::Normal,
+    };
+    let log_level = args.log_level.map_or_else(
+        || match verbosity {
+            Verbosity::Quiet => LevelFilter::Error,
+            Verbosity::Normal => LevelFilter::Info,
+            Verbosity::Verbose => LevelFilter::Debug,
+        },
+        LogLevel::filter,
+    );
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(log_level);
+    }
+    use_terminal_defaults(&mut args, matches);
+    apply_config(&mut args, matches)?;
+    let args = &args;
+    // the commands that run until interrupted show their results as they go
+    let unstaged_input = match &args.command {
+        Commands::Run(RunArgs {
+            watch: true,
+            input_path,
+            ..
+        })
+        | Commands::Repl(ReplArgs { input_path, .. })
+        | Commands::Serve(ServeArgs { input_path, .. }) => Some(input_path),
+        _ => None,
+    };
+    // their output is written as it goes, which would truncate the input, or
+    // replaced on every run of the watch, which would make it run again
+    if let (Some(input_path), Some(output_path)) = (unstaged_input, &args.output) {
+        if is_same_file(input_path, output_path) {
+            return Err(CliError::Usage(format!(
+                "-o {0} is the input file, which would be overwritten while it is read",
+                output_path.display()
+            )));
+        }
     }
+    // each run of the watch replaces the output file, which it opens itself
+    if let Commands::Run(cmd_args @ RunArgs { watch: true, .. }) = &args.command {
+        return watch_command(cmd_args, verbosity, &args.output).map(|_| true);
+    }
+    let staged = unstaged_input.is_none();
+    let mut out = Output::open(&args.output, staged)
+        .map_err(|e| CliError::Io(format!("cannot open output: {0}", e)))?;
 
-    // run the algorithm
-    let start = Instant::now();
-    let (paths_from_src, dists_from_src) = djikstra(&graph, start_vertex);
-    let duration = start.elapsed();
+    let succeeded = match &args.command {
+        Commands::Run(cmd_args) => run_command(cmd_args, verbosity, &mut out).map(|_| true),
+        Commands::Benchmark(cmd_args) => benchmark_command(cmd_args, &mut out),
+        Commands::Query(cmd_args) => query_command(cmd_args, verbosity, &mut out).map(|_| true),
+        Commands::Generate(cmd_args) => {
+            generate_command(cmd_args, args.seed, &mut out).map(|_| true)
+        }
+        Commands::Convert(cmd_args) => convert_command(cmd_args, &mut out).map(|_| true),
+        Commands::Repl(cmd_args) => repl_command(cmd_args, verbosity, &mut out).map(|_| true),
+        Commands::Serve(cmd_args) => serve_command(cmd_args).map(|_| true),
+        Commands::Stats(cmd_args) => stats_command(cmd_args, &mut out).map(|_| true),
+        Commands::Validate(cmd_args) => validate_command(cmd_args, verbosity, &mut out),
+        Commands::Components(cmd_args) => {
+            components_command(cmd_args, verbosity, &mut out).map(|_| true)
+        }
+        Commands::Matrix(cmd_args) => matrix_command(cmd_args, &mut out).map(|_| true),
+        Commands::Mst(cmd_args) => mst_command(cmd_args, verbosity, &mut out).map(|_| true),
+        Commands::Simplify(cmd_args) => simplify_command(cmd_args, &mut out).map(|_| true),
+        Commands::Sample(cmd_args) => sample_command(cmd_args, args.seed, &mut out).map(|_| true),
+        Commands::Visualize(cmd_args) => visualize_command(cmd_args, &mut out).map(|_| true),
+        Commands::Head(cmd_args) => head_command(cmd_args, &mut out).map(|_| true),
+        Commands::Edit(cmd_args) => edit_command(cmd_args, &mut out).map(|_| true),
+        Commands::Verify(cmd_args) => verify_command(cmd_args, verbosity, &mut out),
+        Commands::Diff(cmd_args) => diff_command(cmd_args, verbosity, &mut out),
+        Commands::Selftest(cmd_args) => selftest_command(cmd_args, verbosity, args.seed, &mut out),
+        Commands::Profile(cmd_args) => profile_command(cmd_args, &mut out).map(|_| true),
+        Commands::Completions(cmd_args) => completions_command(cmd_args, &mut out).map(|_| true),
+    };
+
+    // flush what was written to stdout even if the command failed halfway,
+    // but leave an output file as it was
+    let finished = out.finish(succeeded.is_ok());
+    let succeeded = succeeded?;
+    finished.map_err(|e| CliError::Io(format!("cannot write output: {0}", e)))?;
+    Ok(succeeded)
+}
+
+/// Defaults for the flags of the commands, read from the config file.
+/// The values are the ones accepted on the command line, e.g.
+///
+/// ```toml
+/// algorithm = "bellman-ford"
+/// pq = "binary"
+/// format = "json"
+/// threads = 8
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    /// Shortest path algorithm of `run` and `benchmark`.
+    algorithm: Option<String>,
+    /// Priority queue backend of `benchmark`.
+    pq: Option<String>,
+    /// Output format of `run`, `query` and `mst`.
+    format: Option<String>,
+    /// Number of threads of `benchmark`.
+    threads: Option<usize>,
+}
+
+/// Print the results of `run` and `query` as a table when they go to a terminal
+/// and no format was given, colored unless the `NO_COLOR` variable is set.
+fn use_terminal_defaults(args: &mut Cli, matches: &ArgMatches) {
+    let to_stdout = args
+        .output
+        .as_ref()
+        .is_none_or(|path| path.as_os_str() == "-");
+    if !to_stdout || !io::stdout().is_terminal() {
+        return;
+    }
+    let color = std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty());
+    let Some((_name, sub_matches)) = matches.subcommand() else {
+        return;
+    };
+    let is_default = sub_matches.value_source("format") == Some(ValueSource::DefaultValue);
 
-    for idx in 0..graph.n_vertices() {
-        if let Some(path) = &paths_from_src[idx] {
-            print!("{idx} {} ", dists_from_src[idx]);
-            print!("({}", path[0]);
-            for vertex in path.iter().skip(1) {
-                print!(" -> {}", vertex);
+    match &mut args.command {
+        Commands::Run(cmd_args) => {
+            if is_default {
+                cmd_args.format = OutputFormat::Table;
+            }
+            cmd_args.color = color;
+        }
+        Commands::Query(cmd_args) if cmd_args.batch.is_none() => {
+            if is_default {
+                cmd_args.format = OutputFormat::Table;
             }
-            println!(")");
-        } else {
-            println!("{idx} inf");
+            cmd_args.color = color;
         }
+        _ => {}
     }
+}
 
-    println!("Algorithm ran in {0}ns.", duration.as_nanos());
+/// Path of the config file read when `--config` is not given:
+/// `djikstra.toml` in `$XDG_CONFIG_HOME`, or else in `~/.config`.
+fn default_config_path() -> Option<PathBuf> {
+    match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("djikstra.toml")),
+        _ => std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(".config").join("djikstra.toml")),
+    }
 }
 
-/// Benchmark the Djikstra algorithm on the input graph.
-fn benchmark_command(args: &BenchmarkArgs, verbose: bool) {
-    // djikstra benchmark --input graph.txt -n 1000
+/// Read the config file given by `--config`, or the default one if it exists,
+/// and use its values for the flags of the subcommand that were not given.
+fn apply_config(args: &mut Cli, matches: &ArgMatches) -> Result<(), CliError> {
+    let path = match &args.config {
+        Some(path) => path.clone(),
+        None => match default_config_path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(()),
+        },
+    };
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| CliError::Io(format!("cannot read {0}: {1}", path.display(), e)))?;
+    let config: Config = toml::from_str(&contents)
+        .map_err(|e| CliError::Parse(format!("{0}: {1}", path.display(), e)))?;
+    debug!("Read defaults from config file {0:?}.", path);
 
-    let (start_vertex, graph) = match parse_input(&args.input_path) {
-        Ok((start_vertex, graph)) => (start_vertex, graph),
-        Err(e) => {
-            eprintln!("Error parsing input: {0}", e.0);
-            return;
-        }
+    // a value from the config is only wanted for the flags left at their default
+    let Some((_name, sub_matches)) = matches.subcommand() else {
+        return Ok(());
     };
+    let is_default = |id: &str| sub_matches.value_source(id) == Some(ValueSource::DefaultValue);
 
-    if verbose {
-        println!(
-            "Benchmarking {0:?} over {1:?} times.",
-            &args.input_path, args.n
-        );
-        println!(
-            "Algorithm will run on graph with {0} vertices and start vertex {1}.\n",
-            graph.n_vertices(),
-            start_vertex
-        );
+    match &mut args.command {
+        Commands::Run(cmd_args) => {
+            if let Some(algorithm) = config_value(
+                &path,
+                "algorithm",
+                &config.algorithm,
+                is_default("algorithm"),
+            )? {
+                cmd_args.algorithm = algorithm;
+            }
+            if let Some(format) =
+                config_value(&path, "format", &config.format, is_default("format"))?
+            {
+                cmd_args.format = format;
+            }
+        }
+        Commands::Benchmark(cmd_args) => {
+            if let Some(algorithm) = config_value(
+                &path,
+                "algorithm",
+                &config.algorithm,
+                is_default("algorithm"),
+            )? {
+                cmd_args.algorithm = vec![algorithm];
+            }
+            if let Some(pq) = config_value(&path, "pq", &config.pq, is_default("pq"))? {
+                cmd_args.pq = pq;
+            }
+            if let Some(threads) = config.threads.filter(|_threads| is_default("threads")) {
+                cmd_args.threads = threads;
+            }
+        }
+        Commands::Query(cmd_args) => {
+            if let Some(format) =
+                config_value(&path, "format", &config.format, is_default("format"))?
+            {
+                cmd_args.format = format;
+            }
+        }
+        Commands::Mst(cmd_args) => {
+            if let Some(format) =
+                config_value(&path, "format", &config.format, is_default("format"))?
+            {
+                cmd_args.format = format;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Parse the `key` value of the config file as a flag value,
+/// `None` if it is missing or the flag was given on the command line.
+fn config_value<T: ValueEnum>(
+    path: &Path,
+    key: &str,
+    value: &Option<String>,
+    is_default: bool,
+) -> Result<Option<T>, CliError> {
+    match value {
+        Some(value) if is_default => T::from_str(value, false)
+            .map(Some)
+            .map_err(|e| CliError::Parse(format!("{0}: invalid {1}: {2}", path.display(), key, e))),
+        _ => Ok(None),
     }
+}
 
-    // benchmark the algorithm
-    let mut results: Vec<u128> = vec![];
+/// The error type returned by the subcommands, each kind exits with its own code:
+/// 2 for invalid arguments (as for the ones clap rejects), 3 for inputs that cannot
+/// be parsed, 4 for vertices that are not in the graph, 5 for I/O errors and 6 for
+/// inputs the algorithm cannot give correct results on.
+#[derive(Debug)]
+enum CliError {
+    /// The arguments cannot be used together.
+    Usage(String),
+    /// The input (or another file read by the command) cannot be parsed.
+    Parse(String),
+    /// A vertex given on the command line is not in the graph.
+    InvalidVertex(String),
+    /// Reading or writing a file or stream failed.
+    Io(String),
+    /// The algorithm cannot give correct results on the input.
+    Algorithm(String),
+}
 
-    for _ in 0..args.n {
-        let start = Instant::now();
-        let (_paths_from_src, _dists_from_src) = djikstra(&graph, start_vertex);
-        let duration = start.elapsed();
+impl CliError {
+    /// Exit code of the process for this error.
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 2,
+            CliError::Parse(_) => 3,
+            CliError::InvalidVertex(_) => 4,
+            CliError::Io(_) => 5,
+            CliError::Algorithm(_) => 6,
+        }
+    }
+}
 
-        results.push(duration.as_nanos());
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Parse(message) => write!(f, "cannot parse input: {0}", message),
+            CliError::Usage(message)
+            | CliError::InvalidVertex(message)
+            | CliError::Io(message)
+            | CliError::Algorithm(message) => write!(f, "{0}", message),
+        }
     }
+}
 
-    let avg_time = results.iter().sum::<u128>() / args.n as u128;
-    println!("Average time: {0}ns", avg_time);
+impl From<Error> for CliError {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Graph(_) | Error::Input(_) => CliError::Parse(e.to_string()),
+            Error::Dijkstra(e) => e.into(),
+            Error::Edit(EditError::Parse(_)) => CliError::Parse(e.to_string()),
+            Error::Edit(EditError::InvalidVertex { .. }) => CliError::InvalidVertex(e.to_string()),
+            Error::Edit(EditError::NoEdge { .. }) => CliError::Usage(e.to_string()),
+            Error::NotBipartite(_) => CliError::Algorithm(e.to_string()),
+            Error::Io(message) => CliError::Io(message),
+        }
+    }
 }
 
-/// The error type returned when we run into any error when parsing
-#[derive(Debug)]
-struct InputError(String);
+impl From<DijkstraError> for CliError {
+    fn from(e: DijkstraError) -> Self {
+        match e {
+            DijkstraError::InvalidSource { source, n_vertices } => {
+                CliError::InvalidVertex(format!(
+                    "start vertex {0} must be below {1}, the number of vertices",
+                    source, n_vertices
+                ))
+            }
+            DijkstraError::EmptyGraph => {
+                CliError::InvalidVertex("the graph has no vertices to start from".to_string())
+            }
+            DijkstraError::Overflow => CliError::Algorithm(
+                "a distance is too large to fit in a usize, the weights are too large".to_string(),
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        CliError::Io(format!("cannot write output: {0}", e))
+    }
+}
+
+/// Random number generator seeded with `seed`, or with a random seed if there is none.
+/// Returns the generator and the seed it was seeded with.
+fn seeded_rng(seed: Option<u64>) -> (StdRng, u64) {
+    let seed = seed.unwrap_or_else(rand::random);
+    (StdRng::seed_from_u64(seed), seed)
+}
 
-/// Parse the input file into a start vertex and a graph.
-fn parse_input(input_path: &PathBuf) -> Result<(usize, Graph), InputError> {
-    let contents = fs::read_to_string(input_path);
-    let contents = contents.map_err(|e| InputError(format!("error reading file: {}", e)))?;
+/// Where the results of a command are written: stdout, or the output file.
+///
+/// A staged output file is written under a temporary name next to it and
+/// only renamed over it once the command succeeded, so that a command whose
+/// input is the output file reads all of it, and a command that fails leaves
+/// the file as it was.
+enum Output {
+    Stdout(io::StdoutLock<'static>),
+    File(io::BufWriter<fs::File>),
+    Staged {
+        writer: io::BufWriter<fs::File>,
+        temp_path: PathBuf,
+        path: PathBuf,
+    },
+}
+
+impl Output {
+    /// Open the output file (or stdout if there is none or the path is `-`)
+    /// for writing, staged under a temporary name if `staged` and the path is
+    /// not a special file such as `/dev/null`.
+    fn open(output_path: &Option<PathBuf>, staged: bool) -> io::Result<Self> {
+        let path = match output_path {
+            Some(path) if path.as_os_str() != "-" => path,
+            _ => return Ok(Output::Stdout(io::stdout().lock())),
+        };
+        let existing = fs::metadata(path).ok();
+        if !staged
+            || existing
+                .as_ref()
+                .is_some_and(|metadata| !metadata.is_file())
+        {
+            return Ok(Output::File(io::BufWriter::new(fs::File::create(path)?)));
+        }
+
+        let file_name = path.file_name().unwrap_or(path.as_os_str());
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(file_name);
+        temp_name.push(format!(".{0}.tmp", process::id()));
+        let temp_path = path.with_file_name(temp_name);
+        let file = fs::File::create(&temp_path)?;
+        if let Some(metadata) = existing {
+            file.set_permissions(metadata.permissions())?;
+        }
+        Ok(Output::Staged {
+            writer: io::BufWriter::new(file),
+            temp_path,
+            path: path.clone(),
+        })
+    }
+
+    /// Flush what was written, and move a staged file to its path if the
+    /// command `succeeded` or remove it if it did not.
+    fn finish(self, succeeded: bool) -> io::Result<()> {
+        match self {
+            Output::Stdout(mut out) => out.flush(),
+            Output::File(mut writer) => writer.flush(),
+            Output::Staged {
+                writer,
+                temp_path,
+                path,
+            } => {
+                let written = writer.into_inner().map_err(|e| e.into_error());
+                match written {
+                    Ok(_file) if succeeded => fs::rename(&temp_path, &path),
+                    _ => {
+                        let _ = fs::remove_file(&temp_path);
+                        written.map(|_file| ())
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Stdout(out) => out.write(buf),
+            Output::File(writer) | Output::Staged { writer, .. } => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Stdout(out) => out.flush(),
+            Output::File(writer) | Output::Staged { writer, .. } => writer.flush(),
+        }
+    }
+}
+
+/// Whether `a` and `b` are paths to the same existing file.
+fn is_same_file(a: &PathBuf, b: &PathBuf) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Run the algorithm on the input graph.
+fn run_command(args: &RunArgs, verbosity: Verbosity, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra run --input graph.txt --verbose
+    run_once(args, verbosity, out)
+}
+
+/// Run the algorithm on the input graph, again every time the file changes.
+/// The results of every run replace those of the previous one in the output
+/// file, and a run that fails leaves them as they were.
+fn watch_command(
+    args: &RunArgs,
+    verbosity: Verbosity,
+    output_path: &Option<PathBuf>,
+) -> Result<(), CliError> {
+    // djikstra run --input graph.txt --watch -o results.txt
+    if args.input_path.as_os_str() == "-" {
+        return Err(CliError::Usage(
+            "--watch needs an input file rather than stdin".to_string(),
+        ));
+    }
+
+    let modified = || {
+        fs::metadata(&args.input_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+    let mut last_modified = modified();
+    info!("Watching {0:?} for changes.", &args.input_path);
+    loop {
+        let mut out = Output::open(output_path, true)
+            .map_err(|e| CliError::Io(format!("cannot open output: {0}", e)))?;
+        // the file may be halfway through being edited, so errors are reported
+        // and the next change is waited for
+        let ran = run_once(args, verbosity, &mut out);
+        if let Err(e) = &ran {
+            error!("{0}", e);
+        }
+        out.finish(ran.is_ok())
+            .map_err(|e| CliError::Io(format!("cannot write output: {0}", e)))?;
+
+        while modified() == last_modified {
+            thread::sleep(WATCH_INTERVAL);
+        }
+        last_modified = modified();
+        info!("{0:?} changed, running again.", &args.input_path);
+    }
+}
+
+/// How often `run --watch` checks whether the input file changed.
+const WATCH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How often `run --stream` flushes the records written so far.
+const STREAM_FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Run the algorithm once on the input graph.
+fn run_once(args: &RunArgs, verbosity: Verbosity, out: &mut dyn Write) -> Result<(), CliError> {
+    match args.nearest {
+        Some(0) => {
+            return Err(CliError::Usage("--nearest must be at least 1".to_string()));
+        }
+        Some(_) if args.algorithm != Algorithm::Dijkstra => {
+            return Err(CliError::Usage(
+                "--nearest is only supported with --algorithm dijkstra".to_string(),
+            ));
+        }
+        _ => {}
+    }
+    if args.stream && args.algorithm != Algorithm::Dijkstra {
+        return Err(CliError::Usage(
+            "--stream is only supported with --algorithm dijkstra".to_string(),
+        ));
+    }
+    if args.stream && matches!(args.format, OutputFormat::Dot | OutputFormat::Geojson) {
+        return Err(CliError::Usage(
+            "the dot and geojson formats are not supported with --stream".to_string(),
+        ));
+    }
+    let (start_vertex, graph) =
+        override_start(parse_input(&args.input_path, args.undirected)?, args.start)?;
+    let coords = read_coordinates(&args.coords, args.format, &graph)?;
+    if args.stream {
+        return stream_results(&graph, start_vertex, args, verbosity, out);
+    }
+
+    debug!("Read file {0:?} successfully.", &args.input_path);
+    debug!(
+        "Running {0} on graph with {1} vertices and start vertex {2}.",
+        args.algorithm.name(),
+        graph.n_vertices(),
+        start_vertex
+    );
+
+    // run the algorithm, only a full run of Dijkstra reports its progress
+    let progress = match (args.algorithm, args.nearest) {
+        (Algorithm::Dijkstra, None) => {
+            progress_bar(Some(graph.n_vertices() as u64), "Settling vertices", false)
+        }
+        _ => ProgressBar::hidden(),
+    };
+    let start = Instant::now();
+    let (paths_from_src, dists_from_src) = if let Some(k) = args.nearest {
+        let mut n_settled = 0;
+        dijkstra_while::<PriorityQueue<usize>, _>(&graph, start_vertex, |_vertex| {
+            n_settled += 1;
+            n_settled < k
+        })
+    } else if progress.is_hidden() {
+        run_algorithm(&graph, start_vertex, args.algorithm, None)
+    } else {
+        dijkstra_with_progress::<PriorityQueue<usize>, _>(&graph, start_vertex, |_| progress.inc(1))
+    }?;
+    let duration = start.elapsed();
+    progress.finish_and_clear();
+
+    let mut results = Results::new(
+        start_vertex,
+        &paths_from_src,
+        &dists_from_src,
+        duration.as_nanos(),
+    );
+    if args.reachable_only || args.nearest.is_some() {
+        results.vertices.retain(|row| row.distance.is_some());
+    }
+    if args.nearest.is_some() {
+        results.vertices.sort_by_key(|row| row.distance);
+    }
+    write_results(
+        &results,
+        &graph,
+        coords.as_deref(),
+        args.format,
+        args.color,
+        verbosity,
+        out,
+    )
+}
+
+/// Run Dijkstra from the start vertex and write a record for every vertex as
+/// soon as it is settled: `vertex distance parent` lines (`-` for the start vertex),
+/// `vertex,distance,parent` CSV rows, or one JSON object per line.
+///
+/// The output is flushed every `STREAM_FLUSH_INTERVAL`, so consumers get the
+/// records while the search goes on and keep them if it is interrupted.
+fn stream_results(
+    graph: &Graph,
+    start_vertex: usize,
+    args: &RunArgs,
+    verbosity: Verbosity,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    #[derive(Serialize)]
+    struct Record {
+        vertex: usize,
+        distance: usize,
+        parent: Option<usize>,
+    }
+
+    if args.format == OutputFormat::Csv {
+        writeln!(out, "vertex,distance,parent")?;
+    }
+    let mut result = Ok(());
+    let mut n_settled = 0;
+    let mut last_flush = Instant::now();
+    let start = Instant::now();
+    dijkstra_streaming::<PriorityQueue<usize>, _>(graph, start_vertex, |vertex, dist, parent| {
+        let parent_field = |none: &str| parent.map_or(none.to_string(), |p| p.to_string());
+        result = match args.format {
+            OutputFormat::Json => {
+                let record = Record {
+                    vertex,
+                    distance: dist,
+                    parent,
+                };
+                serde_json::to_writer(&mut *out, &record)
+                    .map_err(io::Error::from)
+                    .and_then(|_| writeln!(out))
+            }
+            OutputFormat::Csv => writeln!(out, "{0},{1},{2}", vertex, dist, parent_field("")),
+            _ => writeln!(out, "{0} {1} {2}", vertex, dist, parent_field("-")),
+        };
+        if result.is_ok() && last_flush.elapsed() >= STREAM_FLUSH_INTERVAL {
+            result = out.flush();
+            last_flush = Instant::now();
+        }
+        n_settled += 1;
+        result.is_ok() && args.nearest.is_none_or(|k| n_settled < k)
+    })?;
+    result?;
+
+    if verbosity != Verbosity::Quiet
+        && matches!(args.format, OutputFormat::Text | OutputFormat::Table)
+    {
+        writeln!(out, "Algorithm ran in {0}ns.", start.elapsed().as_nanos())?;
+    }
+    Ok(())
+}
+
+/// Find the shortest path between two vertices of the input graph.
+fn query_command(
+    args: &QueryArgs,
+    verbosity: Verbosity,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    // djikstra query --input graph.txt --from 3 --to 17
+
+    let (_start_vertex, graph) = parse_input(&args.input_path, args.undirected)?;
+
+    if let Some(batch_path) = &args.batch {
+        return batch_query(
+            &graph,
+            batch_path,
+            args.format,
+            args.threads,
+            verbosity,
+            out,
+        );
+    }
+    let (Some(from), Some(to)) = (args.from, args.to) else {
+        return Err(CliError::Usage(
+            "--from and --to are required without --batch".to_string(),
+        ));
+    };
+
+    if [from, to]
+        .iter()
+        .chain(args.via.iter())
+        .any(|&v| v >= graph.n_vertices())
+    {
+        return Err(CliError::InvalidVertex(format!(
+            "vertices must be below {0}, the number of vertices",
+            graph.n_vertices()
+        )));
+    }
+
+    debug!("Read file {0:?} successfully.", &args.input_path);
+    debug!(
+        "Finding path from {0} to {1} in graph with {2} vertices.",
+        from,
+        to,
+        graph.n_vertices()
+    );
+    let coords = read_coordinates(&args.coords, args.format, &graph)?;
+
+    let mut avoid = Avoid::new();
+    for &v in args.avoid.iter() {
+        avoid = avoid.vertex(v);
+    }
+    for edge in args.avoid_edge.iter() {
+        let (u, v) = edge
+            .split_once(':')
+            .and_then(|(u, v)| Some((u.parse().ok()?, v.parse().ok()?)))
+            .ok_or(CliError::Usage(format!(
+                "cannot parse edge {0:?}, expected FROM:TO",
+                edge
+            )))?;
+        avoid = avoid.edge(u, v);
+    }
+
+    // run the algorithm
+    let start = Instant::now();
+    let result = if !args.via.is_empty() {
+        route_via(&graph, from, &args.via, to)?.map(|route| {
+            let cost = route.cost();
+            (route.into_vertices(), cost)
+        })
+    } else if avoid == Avoid::new() {
+        shortest_path(&graph, from, to)?
+    } else {
+        shortest_path_avoiding(&graph, from, to, &avoid)?
+    };
+    let duration = start.elapsed();
+
+    let results = Results {
+        source: from,
+        time_ns: duration.as_nanos(),
+        vertices: vec![VertexResult {
+            vertex: to,
+            distance: result.as_ref().map(|(_path, dist)| *dist),
+            path: result.as_ref().map(|(path, _dist)| path.as_slice()),
+        }],
+    };
+    write_results(
+        &results,
+        &graph,
+        coords.as_deref(),
+        args.format,
+        args.color,
+        verbosity,
+        out,
+    )
+}
+
+/// Answer every `from to` query in the batch file, writing the answers in the order
+/// of the queries as `from to distance (path)` lines, `from,vertex,distance,path`
+/// CSV rows, or a JSON document with a `queries` list.
+/// The sources of the queries are shared between `threads` threads.
+fn batch_query(
+    graph: &Graph,
+    batch_path: &PathBuf,
+    format: OutputFormat,
+    threads: usize,
+    verbosity: Verbosity,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    if matches!(format, OutputFormat::Dot | OutputFormat::Geojson) {
+        return Err(CliError::Usage(
+            "the dot and geojson formats are not supported with --batch".to_string(),
+        ));
+    }
+    let queries = parse_queries(&read_input(batch_path)?, graph.n_vertices())
+        .map_err(|e| CliError::Parse(format!("{0}: {1}", batch_path.display(), e)))?;
+
+    debug!(
+        "Answering {0} queries from {1:?} on {2} threads.",
+        queries.len(),
+        batch_path,
+        threads
+    );
+
+    let start = Instant::now();
+    let answers = batch_shortest_paths_parallel(graph, &queries, threads)?;
+    let duration = start.elapsed();
+
+    let mut rows = queries
+        .iter()
+        .zip(answers.iter())
+        .map(|(&(from, to), answer)| {
+            (
+                from,
+                VertexResult {
+                    vertex: to,
+                    distance: answer.as_ref().map(|(_path, dist)| *dist),
+                    path: answer.as_ref().map(|(path, _dist)| path.as_slice()),
+                },
+            )
+        });
+
+    match format {
+        OutputFormat::Text | OutputFormat::Table => rows
+            .try_for_each(|(from, row)| {
+                write!(out, "{0} ", from)?;
+                let results = Results {
+                    source: from,
+                    time_ns: 0,
+                    vertices: vec![row],
+                };
+                results.write_text(out)
+            })
+            .and_then(|_| match verbosity {
+                Verbosity::Quiet => Ok(()),
+                _ => writeln!(out, "Algorithm ran in {0}ns.", duration.as_nanos()),
+            }),
+        OutputFormat::Csv => writeln!(out, "source,vertex,distance,path").and_then(|_| {
+            rows.try_for_each(|(from, row)| {
+                let distance = row.distance.map(|d| d.to_string()).unwrap_or_default();
+                let path: Vec<String> = row
+                    .path
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect();
+                writeln!(
+                    out,
+                    "{0},{1},{2},{3}",
+                    from,
+                    row.vertex,
+                    distance,
+                    path.join(&CSV_PATH_SEPARATOR.to_string())
+                )
+            })
+        }),
+        OutputFormat::Json => {
+            let queries: Vec<_> = rows
+                .map(|(from, row)| serde_json::json!({ "source": from, "result": row }))
+                .collect();
+            let document = serde_json::json!({
+                "time_ns": duration.as_nanos(),
+                "queries": queries,
+            });
+            writeln!(out, "{0}", document)
+        }
+        OutputFormat::Dot | OutputFormat::Geojson => {
+            unreachable!("the dot and geojson formats are rejected above")
+        }
+    }?;
+    Ok(())
+}
+
+/// Parse one `from to` query per line, ignoring empty lines.
+fn parse_queries(s: &str, n_vertices: usize) -> Result<Vec<(usize, usize)>, Error> {
+    s.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(line_no, line)| {
+            let vertices: Vec<usize> = line
+                .split_whitespace()
+                .map(|word| word.parse().ok().filter(|&v| v < n_vertices))
+                .collect::<Option<_>>()
+                .ok_or(Error::Input(format!(
+                    "line {0}: vertices must be numbers below {1}",
+                    line_no + 1,
+                    n_vertices
+                )))?;
+            match vertices[..] {
+                [from, to] => Ok((from, to)),
+                _ => Err(Error::Input(format!(
+                    "line {0}: expected `from to`",
+                    line_no + 1
+                ))),
+            }
+        })
+        .collect()
+}
+
+/// `(longitude, latitude)` of every vertex, `None` for the vertices without coordinates.
+type Coordinates = Vec<Option<(f64, f64)>>;
+
+/// Read the coordinates of the vertices of `graph` from the file given by
+/// `--coords`, which the geojson format needs.
+fn read_coordinates(
+    path: &Option<PathBuf>,
+    format: OutputFormat,
+    graph: &Graph,
+) -> Result<Option<Coordinates>, CliError> {
+    match path {
+        Some(path) => {
+            let coords = formats::parse_coordinates(&read_input(path)?, graph.n_vertices())
+                .map_err(|e| CliError::Parse(format!("{0}: {1}", path.display(), e)))?;
+            Ok(Some(coords))
+        }
+        None if format == OutputFormat::Geojson => Err(CliError::Usage(
+            "--format geojson needs the coordinates of the vertices from --coords".to_string(),
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Write the results on `graph` to `out` in the requested format, the table
+/// colored if `color` is set, followed by the running time for text and tables
+/// unless `verbosity` is quiet. The geojson format places the vertices at `coords`.
+fn write_results(
+    results: &Results,
+    graph: &Graph,
+    coords: Option<&[Option<(f64, f64)>]>,
+    format: OutputFormat,
+    color: bool,
+    verbosity: Verbosity,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    match format {
+        OutputFormat::Text => results.write_text(out).and_then(|_| match verbosity {
+            Verbosity::Quiet => Ok(()),
+            _ => writeln!(out, "Algorithm ran in {0}ns.", results.time_ns),
+        }),
+        OutputFormat::Table => results
+            .write_table(out, color)
+            .and_then(|_| match verbosity {
+                Verbosity::Quiet => Ok(()),
+                _ => writeln!(out, "Algorithm ran in {0}ns.", results.time_ns),
+            }),
+        OutputFormat::Json => results.write_json(out),
+        OutputFormat::Csv => results.write_csv(out),
+        OutputFormat::Dot => results.write_dot(graph, out),
+        OutputFormat::Geojson => results.write_geojson(coords.unwrap_or_default(), out),
+    }?;
+    Ok(())
+}
+
+/// Generate a random graph and write it in the input format.
+fn generate_command(
+    args: &GenerateArgs,
+    seed: Option<u64>,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    // djikstra generate --model gnp --n 100000 --seed 42 -o g.txt
+
+    if args.start >= args.n {
+        return Err(CliError::Usage(format!(
+            "--start {0} must be below --n {1}, the number of vertices",
+            args.start, args.n
+        )));
+    }
+    if args.max_weight == 0 {
+        return Err(CliError::Usage(
+            "--max-weight must be at least 1".to_string(),
+        ));
+    }
+    let (mut rng, seed) = seeded_rng(seed);
+
+    let graph = match args.model {
+        GraphModel::Gnp => generate::gnp(args.n, args.p, args.max_weight, &mut rng),
+        GraphModel::Grid => generate::grid(args.n, args.max_weight, &mut rng),
+        GraphModel::Ba => generate::barabasi_albert(args.n, args.m, args.max_weight, &mut rng),
+    };
+
+    debug!(
+        "Generated graph with {0} vertices and {1} edges using seed {2}.",
+        graph.n_vertices(),
+        graph.n_edges(),
+        seed
+    );
+
+    write!(out, "{0}\n{1}", args.start, graph)?;
+    Ok(())
+}
+
+/// Convert a graph from one file format to another.
+///
+/// Only the text format stores a start vertex, it is dropped when converting
+/// from text and set to 0 when converting to text.
+fn convert_command(args: &ConvertArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra convert -i graph.gr --from dimacs --to json -o graph.json
+
+    let contents = read_input(&args.input_path)?;
+    let graph = match args.from {
+        GraphFormat::Text => parse_input_str(&contents).map(|(_start_vertex, graph)| graph),
+        GraphFormat::Dimacs => formats::parse_dimacs(&contents).map_err(Error::Graph),
+        GraphFormat::Json => formats::parse_json(&contents).map_err(Error::Graph),
+    }?;
+    graph.check_targets().map_err(Error::Graph)?;
+
+    debug!(
+        "Converting graph with {0} vertices and {1} edges.",
+        graph.n_vertices(),
+        graph.n_edges()
+    );
+
+    match args.to {
+        GraphFormat::Text => write!(out, "0\n{0}", graph),
+        GraphFormat::Dimacs => formats::write_dimacs(&graph, out),
+        GraphFormat::Json => formats::write_json(&graph, out),
+    }?;
+    Ok(())
+}
+
+/// Check the input file and print a diagnostic for every problem found.
+/// Returns whether the input is free of errors.
+fn validate_command(
+    args: &ValidateArgs,
+    verbosity: Verbosity,
+    out: &mut dyn Write,
+) -> Result<bool, CliError> {
+    // djikstra validate --input graph.txt --undirected
+
+    let contents = read_input(&args.input_path)?;
+
+    let diagnostics = validate_input(&contents, args.undirected);
+    let n_errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+
+    for diagnostic in diagnostics.iter() {
+        writeln!(out, "{0}:{1}", args.input_path.display(), diagnostic)?;
+    }
+    if verbosity == Verbosity::Verbose || (verbosity == Verbosity::Normal && diagnostics.is_empty())
+    {
+        writeln!(
+            out,
+            "{0} errors, {1} warnings.",
+            n_errors,
+            diagnostics.len() - n_errors
+        )?;
+    }
+
+    Ok(n_errors == 0)
+}
+
+/// Answer the commands read line by line from stdin until it ends or `quit` is typed.
+fn repl_command(
+    args: &ReplArgs,
+    verbosity: Verbosity,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    // djikstra repl --input graph.txt
+
+    let (_start_vertex, graph) = parse_input(&args.input_path, args.undirected)?;
+
+    let interactive = io::stdin().is_terminal();
+    if verbosity == Verbosity::Verbose || (verbosity == Verbosity::Normal && interactive) {
+        eprintln!(
+            "Loaded graph with {0} vertices from {1:?}.\n{2}",
+            graph.n_vertices(),
+            &args.input_path,
+            HELP
+        );
+    }
+
+    let mut repl = Repl::editing(GraphEditor::new(graph));
+    let mut lines = io::stdin().lines();
+    loop {
+        if interactive {
+            eprint!("> ");
+        }
+        let line = match lines.next() {
+            Some(line) => line.map_err(|e| CliError::Io(format!("cannot read command: {0}", e)))?,
+            None => return Ok(()),
+        };
+        let more = repl.execute(&line, out)?;
+        out.flush()?;
+        if !more {
+            return Ok(());
+        }
+    }
+}
+
+/// Load the input graph once and answer `GET /route?from=..&to=..` requests with JSON.
+fn serve_command(args: &ServeArgs) -> Result<(), CliError> {
+    // djikstra serve --input graph.txt --port 8080
+
+    let (_start_vertex, graph) = parse_input(&args.input_path, args.undirected)?;
+
+    let listener = TcpListener::bind((args.host.as_str(), args.port)).map_err(|e| {
+        CliError::Io(format!(
+            "cannot listen on {0}:{1}: {2}",
+            args.host, args.port, e
+        ))
+    })?;
+
+    debug!(
+        "Loaded graph with {0} vertices from {1:?}.",
+        graph.n_vertices(),
+        &args.input_path
+    );
+    info!(
+        "Listening on http://{0}:{1}/route?from=..&to=..",
+        args.host, args.port
+    );
+
+    let served = match args.cache {
+        0 => server::serve(&graph, listener),
+        capacity => server::serve_cached(&graph, &Mutex::new(RouteCache::new(capacity)), listener),
+    };
+    served.map_err(|e| CliError::Io(format!("cannot serve requests: {0}", e)))
+}
+
+/// Print summary statistics of the input graph.
+fn stats_command(args: &StatsArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra stats --input graph.txt
+
+    let (start_vertex, graph) = parse_input(&args.input_path, args.undirected)?;
+
+    debug!("Read file {0:?} successfully.", &args.input_path);
+
+    if let Some(kind) = args.histogram {
+        return write_histogram(&graph, kind, args.bins, out);
+    }
+    let stats = GraphStats::of(&graph);
+    write!(out, "Start vertex: {0}\n{1}", start_vertex, stats)?;
+    Ok(())
+}
+
+/// Write the histogram of the degrees as `low,high,out_degree,in_degree` rows,
+/// or of the weights as `low,high,count` rows, every bin from `low` to `high`
+/// inclusive. Only the header is written if there are no vertices or edges.
+fn write_histogram(
+    graph: &Graph,
+    kind: HistogramKind,
+    bins: usize,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    if bins == 0 {
+        return Err(CliError::Usage("--bins must be at least 1".to_string()));
+    }
+    match kind {
+        HistogramKind::Degrees => {
+            writeln!(out, "low,high,out_degree,in_degree")?;
+            if let Some((out_degrees, in_degrees)) = degree_histograms(graph, bins) {
+                for (bin, (out_count, in_count)) in out_degrees
+                    .counts
+                    .iter()
+                    .zip(in_degrees.counts.iter())
+                    .enumerate()
+                {
+                    let (low, high) = out_degrees.bounds(bin);
+                    writeln!(out, "{0},{1},{2},{3}", low, high, out_count, in_count)?;
+                }
+            }
+        }
+        HistogramKind::Weights => {
+            writeln!(out, "low,high,count")?;
+            if let Some(weights) = weight_histogram(graph, bins) {
+                for (bin, count) in weights.counts.iter().enumerate() {
+                    let (low, high) = weights.bounds(bin);
+                    writeln!(out, "{0},{1},{2}", low, high, count)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// List the weakly connected components of the input graph as
+/// `component size` lines, the components numbered in the order of their smallest vertex.
+fn components_command(
+    args: &ComponentsArgs,
+    verbosity: Verbosity,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    // djikstra components -i graph.txt --labels labels.csv
+
+    let (_start_vertex, graph) = parse_input(&args.input_path, false)?;
+
+    let labels = graph.components();
+    let n_components = labels.iter().max().map_or(0, |&label| label + 1);
+    let mut sizes = vec![0; n_components];
+    for &label in labels.iter() {
+        sizes[label] += 1;
+    }
+
+    if verbosity != Verbosity::Quiet {
+        writeln!(
+            out,
+            "{0} components in graph with {1} vertices:",
+            n_components,
+            graph.n_vertices()
+        )?;
+    }
+    for (component, size) in sizes.iter().enumerate() {
+        writeln!(out, "{0} {1}", component, size)?;
+    }
+
+    if let Some(path) = &args.labels {
+        let written = fs::File::create(path).and_then(|file| {
+            let mut file = io::BufWriter::new(file);
+            writeln!(file, "vertex,component")?;
+            for (vertex, label) in labels.iter().enumerate() {
+                writeln!(file, "{0},{1}", vertex, label)?;
+            }
+            file.flush()
+        });
+        written.map_err(|e| CliError::Io(format!("cannot write labels: {0}", e)))?;
+    }
+    Ok(())
+}
+
+/// Compute the distances between all pairs of vertices of the input graph,
+/// writing every row of the matrix as soon as it is known.
+fn matrix_command(args: &MatrixArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra matrix -i graph.txt -o dists.csv
+
+    let (_start_vertex, graph) = parse_input(&args.input_path, args.undirected)?;
+    let method = match args.method {
+        MatrixMethod::Auto => Method::for_graph(&graph),
+        MatrixMethod::Dijkstra => Method::Dijkstra,
+        MatrixMethod::FloydWarshall => Method::FloydWarshall,
+    };
+
+    debug!(
+        "Computing distances between all pairs of {0} vertices with {1}.",
+        graph.n_vertices(),
+        match method {
+            Method::Dijkstra => "dijkstra",
+            Method::FloydWarshall => "floyd-warshall",
+        }
+    );
+
+    let n_vertices = graph.n_vertices();
+    let progress = progress_bar(Some(n_vertices as u64), "Computing rows", false);
+    match args.format {
+        MatrixFormat::Csv => {
+            write!(out, "vertex")?;
+            for v in 0..n_vertices {
+                write!(out, ",{0}", v)?;
+            }
+            writeln!(out)?;
+            distance_rows(&graph, method, |src, dists| {
+                write!(out, "{0}", src)?;
+                for &dist in dists.iter() {
+                    match dist {
+                        None => write!(out, ",")?,
+                        Some(dist) => write!(out, ",{0}", dist)?,
+                    }
+                }
+                progress.inc(1);
+                Ok::<(), CliError>(writeln!(out)?)
+            })?;
+        }
+        MatrixFormat::Binary => {
+            out.write_all(&(n_vertices as u64).to_le_bytes())?;
+            distance_rows(&graph, method, |_src, dists| {
+                for &dist in dists.iter() {
+                    let dist = dist.map_or(u64::MAX, |dist| dist as u64);
+                    out.write_all(&dist.to_le_bytes())?;
+                }
+                progress.inc(1);
+                Ok::<(), CliError>(())
+            })?;
+        }
+    }
+    progress.finish_and_clear();
+    Ok(())
+}
+
+/// Find a minimum spanning tree of the input graph, ignoring edge directions,
+/// and write its edges in the requested format.
+fn mst_command(args: &MstArgs, verbosity: Verbosity, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra mst -i graph.txt --algorithm prim
+
+    if args.format == OutputFormat::Geojson {
+        return Err(CliError::Usage(
+            "the geojson format is not supported by mst".to_string(),
+        ));
+    }
+    let (_start_vertex, graph) = parse_input(&args.input_path, false)?;
+
+    debug!("Read file {0:?} successfully.", &args.input_path);
+    debug!(
+        "Finding spanning tree of graph with {0} vertices.",
+        graph.n_vertices()
+    );
+
+    let start = Instant::now();
+    let edges = match args.algorithm {
+        MstAlgorithm::Prim => prim(&graph),
+        MstAlgorithm::Kruskal => kruskal(&graph),
+    };
+    let duration = start.elapsed();
+
+    let results = TreeResults {
+        time_ns: duration.as_nanos(),
+        total_weight: total_weight(&edges),
+        edges: &edges,
+    };
+    match args.format {
+        OutputFormat::Text | OutputFormat::Table => {
+            results.write_text(out)?;
+            if verbosity != Verbosity::Quiet {
+                writeln!(out, "Total weight: {0}", results.total_weight)?;
+                writeln!(out, "Algorithm ran in {0}ns.", results.time_ns)?;
+            }
+        }
+        OutputFormat::Json => results.write_json(out)?,
+        OutputFormat::Csv => results.write_csv(out)?,
+        OutputFormat::Dot => results.write_dot(&graph, out)?,
+        OutputFormat::Geojson => unreachable!("the geojson format is rejected above"),
+    }
+    Ok(())
+}
+
+/// Contract the chains of degree-2 vertices of the input graph and write the
+/// smaller graph in the input format, renumbering the vertices that are left.
+fn simplify_command(args: &SimplifyArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra simplify -i roads.txt --keep 3,17 --mapping mapping.csv -o small.txt
+
+    let (start_vertex, graph) =
+        override_start(parse_input(&args.input_path, args.undirected)?, None)?;
+    if let Some(&vertex) = args.keep.iter().find(|&&v| v >= graph.n_vertices()) {
+        return Err(CliError::InvalidVertex(format!(
+            "vertex {0} to keep must be below {1}, the number of vertices",
+            vertex,
+            graph.n_vertices()
+        )));
+    }
+
+    let mut keep = args.keep.clone();
+    keep.push(start_vertex);
+    let (simplified, original) = contract_chains(&graph, &keep);
+
+    debug!(
+        "Simplified graph from {0} vertices and {1} edges to {2} vertices and {3} edges.",
+        graph.n_vertices(),
+        graph.n_edges(),
+        simplified.n_vertices(),
+        simplified.n_edges()
+    );
+
+    let new_start_vertex = original.iter().position(|&v| v == start_vertex);
+    write!(
+        out,
+        "{0}\n{1}",
+        new_start_vertex.unwrap_or_default(),
+        simplified
+    )?;
+
+    if let Some(path) = &args.mapping {
+        let written = fs::File::create(path).and_then(|file| {
+            let mut file = io::BufWriter::new(file);
+            writeln!(file, "vertex,original")?;
+            for (vertex, original) in original.iter().enumerate() {
+                writeln!(file, "{0},{1}", vertex, original)?;
+            }
+            file.flush()
+        });
+        written.map_err(|e| CliError::Io(format!("cannot write mapping: {0}", e)))?;
+    }
+    Ok(())
+}
+
+/// Write a random sample of `args.size` vertices of the input graph, including
+/// the start vertex, with the edges between them in the input format.
+fn sample_command(
+    args: &SampleArgs,
+    seed: Option<u64>,
+    out: &mut dyn Write,
+) -> Result<(), CliError> {
+    // djikstra --seed 42 sample -i huge.txt --size 1000 --mapping mapping.csv -o small.txt
+
+    if args.size == 0 {
+        return Err(CliError::Usage("--size must be at least 1".to_string()));
+    }
+    if !(0.0..1.0).contains(&args.burn) {
+        return Err(CliError::Usage(
+            "--burn must be at least 0 and below 1".to_string(),
+        ));
+    }
+    let (start_vertex, graph) =
+        override_start(parse_input(&args.input_path, args.undirected)?, None)?;
+    if args.size > graph.n_vertices() {
+        warn!(
+            "The graph has only {0} vertices, all of them are kept.",
+            graph.n_vertices()
+        );
+    }
+
+    let (mut rng, seed) = seeded_rng(seed);
+    let vertices = match args.method {
+        SampleMethod::ForestFire => {
+            sample::forest_fire(&graph, args.size, args.burn, start_vertex, &mut rng)
+        }
+        SampleMethod::Random => sample::random_vertices(&graph, args.size, start_vertex, &mut rng),
+    };
+    let sampled = sample::induced_subgraph(&graph, &vertices);
+
+    debug!(
+        "Sampled {0} vertices and {1} edges out of {2} vertices and {3} edges using seed {4}.",
+        sampled.n_vertices(),
+        sampled.n_edges(),
+        graph.n_vertices(),
+        graph.n_edges(),
+        seed
+    );
+
+    let new_start_vertex = vertices.iter().position(|&v| v == start_vertex);
+    write!(
+        out,
+        "{0}\n{1}",
+        new_start_vertex.unwrap_or_default(),
+        sampled
+    )?;
+
+    if let Some(path) = &args.mapping {
+        let written = fs::File::create(path).and_then(|file| {
+            let mut file = io::BufWriter::new(file);
+            writeln!(file, "vertex,original")?;
+            for (vertex, original) in vertices.iter().enumerate() {
+                writeln!(file, "{0},{1}", vertex, original)?;
+            }
+            file.flush()
+        });
+        written.map_err(|e| CliError::Io(format!("cannot write mapping: {0}", e)))?;
+    }
+    Ok(())
+}
+
+/// Print the header of the input file and the edges of its first vertices as
+/// `vertex: to,weight ...` lines, reading only as many lines as needed.
+///
+/// DIMACS vertices are numbered from 0 as everywhere else, and the arcs are
+/// expected to be sorted by source: reading stops at the first arc from a
+/// vertex after the first ones.
+fn head_command(args: &HeadArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra head -i huge.gr --from dimacs --vertices 20
+
+    let path = args.input_path.display();
+    let reader: Box<dyn BufRead> = if args.input_path.as_os_str() == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        let file = fs::File::open(&args.input_path)
+            .map_err(|e| CliError::Io(format!("cannot read {0}: {1}", path, e)))?;
+        Box::new(io::BufReader::new(file))
+    };
+    let mut lines = reader.lines().enumerate().map(|(line_no, line)| {
+        line.map(|line| (line_no + 1, line))
+            .map_err(|e| CliError::Io(format!("cannot read {0}: {1}", path, e)))
+    });
+    let parse_error = |line_no: usize, message: String| {
+        CliError::Parse(format!("{0}: line {1}: {2}", path, line_no, message))
+    };
+    let parse_number = |line_no: usize, field: &str| {
+        field
+            .trim()
+            .parse::<usize>()
+            .map_err(|e| parse_error(line_no, format!("cannot parse {0:?}: {1}", field, e)))
+    };
+
+    let mut rows: Vec<Vec<(usize, usize)>> = vec![];
+    let n_vertices = match args.from {
+        GraphFormat::Text => {
+            let mut header = vec![];
+            for _ in 0..2 {
+                let (line_no, line) = lines.next().transpose()?.ok_or(parse_error(
+                    header.len() + 1,
+                    "missing header line".to_string(),
+                ))?;
+                header.push(parse_number(line_no, &line)?);
+            }
+            writeln!(
+                out,
+                "Start vertex: {0}\nVertices: {1}",
+                header[0], header[1]
+            )?;
+
+            // missing lines at the end are vertices without edges
+            while rows.len() < args.vertices.min(header[1]) {
+                let Some((line_no, line)) = lines.next().transpose()? else {
+                    break;
+                };
+                let row = line
+                    .split_whitespace()
+                    .map(|edge| {
+                        let (v, weight) = edge.split_once(',').ok_or(parse_error(
+                            line_no,
+                            format!("edge {0:?} has no weight", edge),
+                        ))?;
+                        Ok((parse_number(line_no, v)?, parse_number(line_no, weight)?))
+                    })
+                    .collect::<Result<_, CliError>>()?;
+                rows.push(row);
+            }
+            header[1]
+        }
+        GraphFormat::Dimacs => {
+            let mut n_vertices = None;
+            for next in lines.by_ref() {
+                let (line_no, line) = next?;
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                match fields.as_slice() {
+                    [] => {}
+                    ["c", ..] => writeln!(out, "{0}", line)?,
+                    ["p", "sp", n, m] => {
+                        let n = parse_number(line_no, n)?;
+                        writeln!(
+                            out,
+                            "Vertices: {0}\nArcs: {1}",
+                            n,
+                            parse_number(line_no, m)?
+                        )?;
+                        n_vertices = Some(n);
+                    }
+                    ["a", u, v, weight] => {
+                        if n_vertices.is_none() {
+                            return Err(parse_error(
+                                line_no,
+                                "arc before the problem line".to_string(),
+                            ));
+                        }
+                        let u = parse_number(line_no, u)?;
+                        if u == 0 {
+                            return Err(parse_error(line_no, "vertices start at 1".to_string()));
+                        }
+                        if u > args.vertices {
+                            break;
+                        }
+                        if rows.len() < u {
+                            rows.resize(u, vec![]);
+                        }
+                        let edge = (
+                            parse_number(line_no, v)?.saturating_sub(1),
+                            parse_number(line_no, weight)?,
+                        );
+                        rows[u - 1].push(edge);
+                    }
+                    _ => {
+                        return Err(parse_error(line_no, format!("unexpected line {0:?}", line)));
+                    }
+                }
+            }
+            let n_vertices =
+                n_vertices.ok_or(CliError::Parse(format!("{0}: missing problem line", path)))?;
+            rows.resize(args.vertices.min(n_vertices), vec![]);
+            n_vertices
+        }
+        GraphFormat::Json => {
+            return Err(CliError::Usage(
+                "head cannot read json without reading the whole file, use text or dimacs"
+                    .to_string(),
+            ));
+        }
+    };
+
+    for (vertex, row) in rows.iter().enumerate() {
+        let edges: Vec<String> = row
+            .iter()
+            .map(|(v, weight)| format!("{0},{1}", v, weight))
+            .collect();
+        writeln!(out, "{0}: {1}", vertex, edges.join(" "))?;
+    }
+    if rows.len() < n_vertices {
+        writeln!(out, "... {0} more vertices", n_vertices - rows.len())?;
+    }
+    Ok(())
+}
+
+/// Edit the edges of the input graph and write it back in the input format.
+/// The edits replayed are applied first, then the edges are removed, added,
+/// and reweighted.
+fn edit_command(args: &EditArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra edit -i graph.txt --add-edge 3,7,5 --remove-edge 2,4 --set-weight 1,2,9 -o new.txt
+    // djikstra edit -i graph.txt --replay journal.txt -o new.txt
+
+    let (start_vertex, graph) = parse_input(&args.input_path, false)?;
+    let n_vertices = graph.n_vertices();
+    let mut editor = GraphEditor::new(graph);
+    if let Some(path) = &args.replay {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| CliError::Io(format!("cannot read {0}: {1}", path.display(), e)))?;
+        for (line_number, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let replayed = line.parse::<Edit>().and_then(|edit| editor.apply(edit));
+            replayed.map_err(|e| {
+                let message = format!("{0} line {1}: {2}", path.display(), line_number + 1, e);
+                match e {
+                    EditError::Parse(_) => CliError::Parse(message),
+                    EditError::InvalidVertex { .. } => CliError::InvalidVertex(message),
+                    EditError::NoEdge { .. } => CliError::Usage(message),
+                }
+            })?;
+        }
+    }
+
+    // split FROM,TO[,WEIGHT] into its numbers, checking the vertices are in the graph
+    let parse_edge = |flag: &str, value: &str, len: usize| -> Result<Vec<usize>, CliError> {
+        let numbers = value
+            .split(',')
+            .map(|number| number.trim().parse::<usize>())
+            .collect::<Result<Vec<usize>, _>>()
+            .ok()
+            .filter(|numbers| numbers.len() == len)
+            .ok_or(CliError::Usage(format!(
+                "cannot parse --{0} {1:?}, expected {2}",
+                flag,
+                value,
+                ["FROM,TO", "FROM,TO,WEIGHT"][len - 2]
+            )))?;
+        if numbers[0] >= n_vertices || numbers[1] >= n_vertices {
+            return Err(CliError::InvalidVertex(format!(
+                "--{0} {1:?}: vertices must be below {2}, the number of vertices",
+                flag, value, n_vertices
+            )));
+        }
+        Ok(numbers)
+    };
+
+    for value in args.remove_edge.iter() {
+        let edge = parse_edge("remove-edge", value, 2)?;
+        editor
+            .remove_edge(edge[0], edge[1])
+            .map_err(|e| CliError::Usage(format!("--remove-edge {0:?}: {1}", value, e)))?;
+    }
+    for value in args.add_edge.iter() {
+        let edge = parse_edge("add-edge", value, 3)?;
+        editor
+            .add_edge(edge[0], edge[1], edge[2])
+            .map_err(|e| CliError::Usage(format!("--add-edge {0:?}: {1}", value, e)))?;
+    }
+    for value in args.set_weight.iter() {
+        let edge = parse_edge("set-weight", value, 3)?;
+        editor
+            .set_weight(edge[0], edge[1], edge[2])
+            .map_err(|e| CliError::Usage(format!("--set-weight {0:?}: {1}", value, e)))?;
+    }
+    let graph = editor.into_graph();
+
+    debug!(
+        "Edited graph has {0} vertices and {1} edges.",
+        graph.n_vertices(),
+        graph.n_edges()
+    );
+    write!(out, "{0}\n{1}", start_vertex, graph)?;
+    Ok(())
+}
+
+/// Results of `run` read back by `verify`.
+#[derive(Deserialize)]
+struct ExpectedResults {
+    source: usize,
+    vertices: Vec<ExpectedVertex>,
+}
+
+/// Distance and path to a vertex in the results read back by `verify`.
+#[derive(Deserialize)]
+struct ExpectedVertex {
+    vertex: usize,
+    distance: Option<usize>,
+    path: Option<Vec<usize>>,
+}
+
+impl ExpectedResults {
+    /// Parse the `vertex distance (path)` and `vertex inf` lines of the text
+    /// format, ignoring the running time. The source is the vertex whose path
+    /// is only itself.
+    fn from_text(contents: &str) -> Result<Self, Error> {
+        let mut vertices = vec![];
+        for (line_no, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() || line.starts_with("Algorithm ran in") {
+                continue;
+            }
+            let error = || Error::Input(format!("line {0}: cannot parse {1:?}", line_no + 1, line));
+            let (vertex, rest) = line.split_once(' ').ok_or_else(error)?;
+            let vertex = vertex.parse().map_err(|_| error())?;
+            let row = match rest.trim() {
+                "inf" => ExpectedVertex {
+                    vertex,
+                    distance: None,
+                    path: None,
+                },
+                rest => {
+                    let (distance, path) = rest
+                        .split_once(" (")
+                        .and_then(|(distance, path)| Some((distance, path.strip_suffix(')')?)))
+                        .ok_or_else(error)?;
+                    let path = path
+                        .split(" -> ")
+                        .map(|v| v.parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| error())?;
+                    ExpectedVertex {
+                        vertex,
+                        distance: Some(distance.parse().map_err(|_| error())?),
+                        path: Some(path),
+                    }
+                }
+            };
+            vertices.push(row);
+        }
+
+        let source = vertices
+            .iter()
+            .find_map(|row| match row.path.as_deref() {
+                Some(&[v]) => Some(v),
+                _ => None,
+            })
+            .ok_or(Error::Input(
+                "no vertex is the source of the paths".to_string(),
+            ))?;
+        Ok(Self { source, vertices })
+    }
+}
+
+/// Run the algorithm from the source of the expected results and write a line
+/// for every vertex where the distance differs or the path is not a shortest
+/// path (or not the same path with `--exact-paths`).
+/// Returns whether the results match.
+fn verify_command(
+    args: &VerifyArgs,
+    verbosity: Verbosity,
+    out: &mut dyn Write,
+) -> Result<bool, CliError> {
+    // djikstra verify -i graph.txt --expected results.json
+
+    let (_start_vertex, graph) = parse_input(&args.input_path, args.undirected)?;
+    let contents = read_input(&args.expected)?;
+    let expected = match args.format {
+        ExpectedFormat::Json => {
+            serde_json::from_str(&contents).map_err(|e| Error::Input(e.to_string()))
+        }
+        ExpectedFormat::Text => ExpectedResults::from_text(&contents),
+    }
+    .map_err(|e| CliError::Parse(format!("{0}: {1}", args.expected.display(), e)))?;
+    let (start_vertex, graph) = override_start((expected.source, graph), None)?;
+
+    let (paths, dists) = run_algorithm(&graph, start_vertex, args.algorithm, None)?;
+    let show = |dist: Option<usize>| dist.map_or("unreachable".to_string(), |d| d.to_string());
+
+    let mut mismatches = vec![];
+    if expected.vertices.len() != graph.n_vertices() {
+        mismatches.push(format!(
+            "expected {0} vertices, the graph has {1}",
+            expected.vertices.len(),
+            graph.n_vertices()
+        ));
+    }
+    for row in expected.vertices.iter() {
+        let v = row.vertex;
+        if v >= graph.n_vertices() {
+            mismatches.push(format!("vertex {0}: not in the graph", v));
+            continue;
+        }
+        let dist = dists[v];
+        if dist != row.distance {
+            mismatches.push(format!(
+                "vertex {0}: expected distance {1}, got {2}",
+                v,
+                show(row.distance),
+                show(dist)
+            ));
+        } else if args.exact_paths && paths[v] != row.path {
+            mismatches.push(format!(
+                "vertex {0}: expected path {1:?}, got {2:?}",
+                v, row.path, paths[v]
+            ));
+        } else if let Some(path) = &row.path {
+            if let Some(u) = path.iter().find(|&&u| u >= graph.n_vertices()) {
+                mismatches.push(format!(
+                    "vertex {0}: expected path {1:?} goes through {2}, not in the graph",
+                    v, path, u
+                ));
+            } else if path.first() != Some(&start_vertex) || path.last() != Some(&v) {
+                mismatches.push(format!(
+                    "vertex {0}: expected path {1:?} does not go from {2} to {0}",
+                    v, path, start_vertex
+                ));
+            } else if graph.path_cost(path) != row.distance {
+                mismatches.push(format!(
+                    "vertex {0}: expected path {1:?} does not cost {2}",
+                    v,
+                    path,
+                    show(row.distance)
+                ));
+            }
+        }
+    }
+
+    for line in mismatches.iter() {
+        writeln!(out, "{0}", line)?;
+    }
+    if verbosity != Verbosity::Quiet {
+        writeln!(
+            out,
+            "{0} mismatches with {1} from source {2}.",
+            mismatches.len(),
+            args.expected.display(),
+            start_vertex
+        )?;
+    }
+    Ok(mismatches.is_empty())
+}
+
+/// Run every algorithm from the start vertex and write a line for every vertex
+/// where its distance differs from the first algorithm's, or where the cost
+/// of its path does not match its own distance.
+/// Returns whether all the algorithms agree.
+fn diff_command(
+    args: &DiffArgs,
+    verbosity: Verbosity,
+    out: &mut dyn Write,
+) -> Result<bool, CliError> {
+    // djikstra diff -i graph.txt --algorithms dijkstra,bellman-ford
+
+    if args.algorithms.len() < 2 {
+        return Err(CliError::Usage(
+            "--algorithms needs at least two algorithms to compare".to_string(),
+        ));
+    }
+    let (start_vertex, graph) =
+        override_start(parse_input(&args.input_path, args.undirected)?, args.start)?;
+
+    let names: Vec<&str> = args.algorithms.iter().map(|a| a.name()).collect();
+    let results: Vec<_> = args
+        .algorithms
+        .iter()
+        .map(|&algorithm| {
+            debug!("Running {0}.", algorithm.name());
+            run_algorithm(&graph, start_vertex, algorithm, None)
+        })
+        .collect::<Result<_, _>>()?;
+
+    let found = disagreements(&graph, start_vertex, &names, &results);
+    for line in found.iter() {
+        writeln!(out, "{0}", line)?;
+    }
+    if verbosity != Verbosity::Quiet {
+        writeln!(
+            out,
+            "{0} disagreements between {1} on {2} vertices.",
+            found.len(),
+            names.join(", "),
+            graph.n_vertices()
+        )?;
+    }
+    Ok(found.is_empty())
+}
+
+/// Describe every vertex where the distance found by an algorithm differs
+/// from the first algorithm's, then every reason [`check_sssp`] finds for an
+/// algorithm's results not to be the shortest paths from `src`. `results`
+/// holds the paths and distances found by the algorithms called `names`.
+fn disagreements(
+    graph: &Graph,
+    src: usize,
+    names: &[&str],
+    results: &[PathsAndDists],
+) -> Vec<String> {
+    let show = |dist: Option<usize>| dist.map_or("unreachable".to_string(), |d| d.to_string());
+
+    let mut found = vec![];
+    let (_paths, first_dists) = &results[0];
+    for v in 0..graph.n_vertices() {
+        for (name, (_paths, dists)) in names.iter().zip(results.iter()) {
+            if dists[v] != first_dists[v] {
+                found.push(format!(
+                    "vertex {0}: {1} distance {2}, {3} distance {4}",
+                    v,
+                    names[0],
+                    show(first_dists[v]),
+                    name,
+                    show(dists[v])
+                ));
+            }
+        }
+    }
+    for (name, result) in names.iter().zip(results.iter()) {
+        for violation in check_sssp(graph, src, result) {
+            found.push(format!("{0}: {1}", name, violation));
+        }
+    }
+    found
+}
+
+/// Check Dijkstra with every queue backend and A* against Bellman-Ford on
+/// random graphs, writing the disagreements and the input of every graph
+/// they were found on. Returns whether all the graphs passed.
+fn selftest_command(
+    args: &SelftestArgs,
+    verbosity: Verbosity,
+    seed: Option<u64>,
+    out: &mut dyn Write,
+) -> Result<bool, CliError> {
+    // djikstra selftest --graphs 1000 --seed 42
+
+    let (mut rng, seed) = seeded_rng(seed);
+    debug!(
+        "Checking {0} random graphs with seed {1}.",
+        args.graphs, seed
+    );
+    let names = [
+        "bellman-ford",
+        "dijkstra (btree)",
+        "dijkstra (binary)",
+        "dijkstra (dary)",
+        "dijkstra (radix)",
+        "astar",
+    ];
+
+    let progress = progress_bar(Some(args.graphs as u64), "Checking graphs", false);
+    let mut n_failed = 0;
+    for index in 0..args.graphs {
+        let n = rng.random_range(1..=args.max_vertices.max(1));
+        let p = rng.random_range(0.0..0.5);
+        let graph = generate::gnp(n, p, args.max_weight, &mut rng);
+        let src = rng.random_range(0..n);
+
+        let results = [
+            bellman_ford(&graph, src)?,
+            dijkstra_with::<PriorityQueue<usize>>(&graph, src)?,
+            dijkstra_with::<BinaryHeapQueue<usize>>(&graph, src)?,
+            dijkstra_with::<DaryHeapQueue<usize>>(&graph, src)?,
+            dijkstra_with::<RadixHeapQueue<usize>>(&graph, src)?,
+            astar(&graph, src, |_vertex| 0)?,
+        ];
+        let found = disagreements(&graph, src, &names, &results);
+        if !found.is_empty() {
+            n_failed += 1;
+            writeln!(out, "graph {0} failed:", index)?;
+            for line in found.iter() {
+                writeln!(out, "  {0}", line)?;
+            }
+            write!(out, "input:\n{0}\n{1}", src, graph)?;
+        }
+        progress.inc(1);
+    }
+    progress.finish_and_clear();
+
+    if verbosity != Verbosity::Quiet {
+        writeln!(
+            out,
+            "{0} of {1} random graphs passed with seed {2}.",
+            args.graphs - n_failed,
+            args.graphs,
+            seed
+        )?;
+    }
+    Ok(n_failed == 0)
+}
+
+/// Time the phases of reading the input and running Dijkstra on it with
+/// every requested queue backend, printing a table of the phases and their share.
+fn profile_command(args: &ProfileArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra profile -i graph.txt --pq all
+
+    let start = Instant::now();
+    let contents = read_input(&args.input_path)?;
+    let reading = start.elapsed();
+
+    // the adjacency lists are built while parsing, so the two are timed together
+    let start = Instant::now();
+    let (start_vertex, graph) = parse_input_str(&contents)
+        .map_err(|e| CliError::Parse(format!("{0}: {1}", args.input_path.display(), e)))?;
+    let parsing = start.elapsed();
+
+    let mut phases = vec![("reading input", reading), ("parsing graph", parsing)];
+    let (start_vertex, graph) = if args.undirected {
+        let start = Instant::now();
+        let graph = graph.to_undirected();
+        phases.push(("building undirected graph", start.elapsed()));
+        (start_vertex, graph)
+    } else {
+        (start_vertex, graph)
+    };
+    let (start_vertex, graph) = override_start((start_vertex, graph), args.start)?;
+
+    for backend in args.pq.backends() {
+        let (_paths_and_dists, run) = match backend {
+            PqBackend::Binary => dijkstra_profiled::<BinaryHeapQueue<usize>>(&graph, start_vertex),
+            PqBackend::Dary => dijkstra_profiled::<DaryHeapQueue<usize>>(&graph, start_vertex),
+            PqBackend::Radix => dijkstra_profiled::<RadixHeapQueue<usize>>(&graph, start_vertex),
+            _ => dijkstra_profiled::<PriorityQueue<usize>>(&graph, start_vertex),
+        }?;
+        let mut phases = phases.clone();
+        phases.extend([
+            ("initialization", run.init),
+            ("queue operations", run.queue),
+            ("relaxation", run.relaxation),
+            ("path reconstruction", run.paths),
+        ]);
+        let total: Duration = phases.iter().map(|&(_phase, time)| time).sum();
+
+        writeln!(
+            out,
+            "Profile of dijkstra ({0}) on graph with {1} vertices and {2} edges:",
+            backend.name(),
+            graph.n_vertices(),
+            graph.n_edges()
+        )?;
+        for (phase, time) in phases.iter().chain([&("total", total)]) {
+            writeln!(
+                out,
+                "  {0:<25}  {1:>12}ns  {2:>5.1}%",
+                phase,
+                time.as_nanos(),
+                100.0 * time.as_secs_f64() / total.as_secs_f64().max(f64::MIN_POSITIVE)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Lay out the input graph and draw it as SVG, with the shortest path
+/// given by `--path` highlighted.
+fn visualize_command(args: &VisualizeArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra visualize -i graph.txt --path 2:7 -o out.svg
+
+    let (_start_vertex, graph) = parse_input(&args.input_path, args.undirected)?;
+
+    let endpoints = match &args.path {
+        Some(path) => {
+            let endpoints = path
+                .split_once(':')
+                .and_then(|(from, to)| Some((from.parse().ok()?, to.parse().ok()?)))
+                .ok_or(CliError::Usage(format!(
+                    "cannot parse path {0:?}, expected FROM:TO",
+                    path
+                )))?;
+            Some(endpoints)
+        }
+        None => None,
+    };
+
+    let path = match endpoints {
+        Some((from, to)) if from >= graph.n_vertices() || to >= graph.n_vertices() => {
+            return Err(CliError::InvalidVertex(format!(
+                "vertices must be below {0}, the number of vertices",
+                graph.n_vertices()
+            )));
+        }
+        Some((from, to)) => match shortest_path(&graph, from, to)? {
+            Some((path, _dist)) => path,
+            None => {
+                warn!("There is no path from {0} to {1} to highlight.", from, to);
+                vec![]
+            }
+        },
+        None => vec![],
+    };
+
+    debug!(
+        "Laying out graph with {0} vertices in {1} rounds.",
+        graph.n_vertices(),
+        args.iterations
+    );
+
+    let positions = force_layout(&graph, args.iterations);
+    write_svg(&graph, &positions, &path, out)?;
+    Ok(())
+}
+
+/// Print the completion script for the shell.
+fn completions_command(args: &CompletionsArgs, out: &mut dyn Write) -> Result<(), CliError> {
+    // djikstra completions bash > /etc/bash_completion.d/djikstra
+
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(args.shell, &mut command, name, out);
+    Ok(())
+}
+
+/// Benchmark the Dijkstra algorithm on the input graph.
+/// Returns whether no benchmark regressed beyond `--fail-threshold` from the baseline.
+fn benchmark_command(args: &BenchmarkArgs, out: &mut dyn Write) -> Result<bool, CliError> {
+    // djikstra benchmark --input graph.txt -n 1000
+    // djikstra benchmark --input 'graphs/*.txt' -n 1000
+    // djikstra benchmark --input graph.txt --compare-baseline main --fail-threshold 5
+
+    let export = match args.export.as_deref() {
+        Some([format, path]) if format == "csv" || format == "json" => {
+            Some((format.as_str(), path))
+        }
+        Some([format, _path]) => {
+            return Err(CliError::Usage(format!(
+                "unknown export format {0:?}, expected csv or json",
+                format
+            )));
+        }
+        _ => None,
+    };
+    let baseline_path = |name: &str| -> Result<PathBuf, CliError> {
+        if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+            return Err(CliError::Usage(format!(
+                "invalid baseline name {0:?}",
+                name
+            )));
+        }
+        Ok(args.baseline_dir.join(format!("{0}.json", name)))
+    };
+    // read the baseline first so a missing one fails before the runs
+    let baseline = match &args.compare_baseline {
+        Some(name) => Some((name, read_baseline(&baseline_path(name)?)?)),
+        None => None,
+    };
+    let save_path = args
+        .save_baseline
+        .as_deref()
+        .map(baseline_path)
+        .transpose()?;
+
+    if args.compare_crates && !cfg!(feature = "compare") {
+        return Err(CliError::Usage(
+            "--compare-crates needs djikstra built with the compare feature".to_string(),
+        ));
+    }
+
+    let input_paths = expand_inputs(&args.input_paths)?;
+    // with several inputs only the summary table is printed
+    let single_input = input_paths.len() == 1;
+    let mut exported = vec![];
+
+    for input_path in input_paths.iter() {
+        let (start_vertex, graph) =
+            override_start(parse_input(input_path, args.undirected)?, args.start)?;
+
+        match args.target_ci {
+            Some(target_pct) => debug!(
+                "Benchmarking {0:?} until the mean is within {1}% after {2:?} warmup runs.",
+                input_path, target_pct, args.warmup
+            ),
+            None => debug!(
+                "Benchmarking {0:?} over {1:?} times after {2:?} warmup runs.",
+                input_path, args.n, args.warmup
+            ),
+        }
+        debug!(
+            "Algorithm will run on graph with {0} vertices and start vertex {1}.",
+            graph.n_vertices(),
+            start_vertex
+        );
+
+        let timings = benchmark_graph(args, &graph, start_vertex, input_path).ok_or(
+            CliError::Usage("no runs to compute statistics from".to_string()),
+        )?;
+
+        if single_input {
+            for timing in timings.iter() {
+                write!(
+                    out,
+                    "Benchmark ({0}):\n{1}  peak memory: {2} bytes\n",
+                    timing.name, timing.stats, timing.peak_bytes
+                )?;
+                if let Some(search) = timing.search {
+                    writeln!(
+                        out,
+                        "  settled: {0}, relaxations: {1}, decrease-keys: {2}, max frontier: {3}",
+                        search.settled,
+                        search.relaxations,
+                        search.decrease_keys,
+                        search.max_frontier
+                    )?;
+                }
+                if args.target_ci.is_some() {
+                    writeln!(
+                        out,
+                        "  runs:    {0} ({1} outliers discarded)",
+                        timing.runs_ns.len(),
+                        timing.outliers
+                    )?;
+                }
+            }
+            if timings.len() > 1 {
+                write_comparison(&timings, out)?;
+            }
+        }
+        exported.extend(timings);
+    }
+
+    if !single_input {
+        write_summary(&exported, out)?;
+    }
+
+    if let Some((format, path)) = export {
+        let written = fs::File::create(path).and_then(|file| {
+            let mut file = io::BufWriter::new(file);
+            match format {
+                "csv" => write_timings_csv(&exported, &mut file)?,
+                _ => {
+                    serde_json::to_writer_pretty(&mut file, &exported)?;
+                    writeln!(file)?;
+                }
+            }
+            file.flush()
+        });
+        written.map_err(|e| CliError::Io(format!("cannot write export: {0}", e)))?;
+    }
+
+    let mut passed = true;
+    if let Some((name, baseline)) = baseline {
+        passed = write_baseline_comparison(&exported, name, &baseline, args.fail_threshold, out)?;
+    }
+    if let Some(path) = save_path {
+        let entries: Vec<BaselineEntry> = exported
+            .iter()
+            .map(|timing| BaselineEntry {
+                input: timing.input.clone(),
+                name: timing.name.clone(),
+                stats: timing.stats.clone(),
+            })
+            .collect();
+        let written = fs::create_dir_all(&args.baseline_dir)
+            .and_then(|_| fs::File::create(&path))
+            .and_then(|file| {
+                let mut file = io::BufWriter::new(file);
+                serde_json::to_writer_pretty(&mut file, &entries)?;
+                writeln!(file)?;
+                file.flush()
+            });
+        written.map_err(|e| {
+            CliError::Io(format!("cannot write baseline {0}: {1}", path.display(), e))
+        })?;
+        debug!("Saved baseline to {0:?}.", path);
+    }
+    Ok(passed)
+}
+
+/// Statistics of an algorithm and backend on an input, as saved by `benchmark --save-baseline`.
+#[derive(Serialize, Deserialize)]
+struct BaselineEntry {
+    input: String,
+    name: String,
+    stats: TimingStats,
+}
+
+/// Read the entries of a baseline saved by `benchmark --save-baseline`.
+fn read_baseline(path: &PathBuf) -> Result<Vec<BaselineEntry>, CliError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| CliError::Io(format!("cannot read baseline {0}: {1}", path.display(), e)))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| CliError::Parse(format!("{0}: {1}", path.display(), e)))
+}
+
+/// Write a table with the change of the mean time of every benchmark from the
+/// entry for the same input and name in the baseline, marking the ones that
+/// grew by more than `fail_threshold` percent as regressions.
+/// Returns whether there were no regressions.
+fn write_baseline_comparison(
+    timings: &[Timings],
+    name: &str,
+    baseline: &[BaselineEntry],
+    fail_threshold: Option<f64>,
+    w: &mut dyn Write,
+) -> io::Result<bool> {
+    let input_width = timings.iter().map(|t| t.input.len()).max().unwrap_or(0);
+    let name_width = timings.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    let mut n_regressions = 0;
+
+    writeln!(w, "Compared with baseline {0}:", name)?;
+    writeln!(
+        w,
+        "  {0:<input_width$}  {1:<name_width$}  {2:>14}  {3:>14}  {4:>8}",
+        "input", "name", "baseline", "mean", "change"
+    )?;
+    for timing in timings.iter() {
+        let entry = baseline
+            .iter()
+            .find(|entry| entry.input == timing.input && entry.name == timing.name);
+        let Some(entry) = entry else {
+            writeln!(
+                w,
+                "  {0:<input_width$}  {1:<name_width$}  {2:>14}  {3:>12.0}ns  {4:>8}",
+                timing.input, timing.name, "-", timing.stats.mean, "new"
+            )?;
+            continue;
+        };
+        let change_pct = (timing.stats.mean / entry.stats.mean - 1.0) * 100.0;
+        let regressed = fail_threshold.is_some_and(|threshold| change_pct > threshold);
+        n_regressions += usize::from(regressed);
+        writeln!(
+            w,
+            "  {0:<input_width$}  {1:<name_width$}  {2:>12.0}ns  {3:>12.0}ns  {4:>+7.1}%{5}",
+            timing.input,
+            timing.name,
+            entry.stats.mean,
+            timing.stats.mean,
+            change_pct,
+            if regressed { "  regression" } else { "" }
+        )?;
+    }
+    if let Some(threshold) = fail_threshold {
+        writeln!(
+            w,
+            "{0} regressions above {1}% from baseline {2}.",
+            n_regressions, threshold, name
+        )?;
+    }
+    Ok(n_regressions == 0)
+}
+
+/// Time every requested algorithm, with every requested backend if it uses a queue, on a graph.
+/// Returns `None` if there were no runs to compute statistics from.
+fn benchmark_graph(
+    args: &BenchmarkArgs,
+    graph: &Graph,
+    start_vertex: usize,
+    input_path: &Path,
+) -> Option<Vec<Timings>> {
+    let candidates = args
+        .algorithm
+        .iter()
+        .flat_map(|&algorithm| match algorithm {
+            Algorithm::Dijkstra => args
+                .pq
+                .backends()
+                .into_iter()
+                .map(|backend| (algorithm, Some(backend)))
+                .collect(),
+            _ => vec![(algorithm, None)],
+        });
+
+    let timings: Vec<Timings> = candidates
+        .map(|(algorithm, backend)| {
+            let name = match backend {
+                Some(backend) => format!("{0} ({1})", algorithm.name(), backend.name()),
+                None => algorithm.name().to_string(),
+            };
+            let mut timing = time_named(args, graph, start_vertex, input_path, name, {
+                move |graph, start_vertex| {
+                    let _paths_and_dists = run_algorithm(graph, start_vertex, algorithm, backend);
+                }
+            })?;
+            timing.search = search_stats(graph, start_vertex, algorithm, backend);
+            Some(timing)
+        })
+        .collect::<Option<_>>()?;
+    #[cfg(feature = "compare")]
+    if args.compare_crates {
+        let compared = compare_crates(args, graph, start_vertex, input_path)?;
+        return Some(timings.into_iter().chain(compared).collect());
+    }
+    Some(timings)
+}
+
+/// Time `run` on a graph as the benchmark `name`, with a progress bar.
+/// Returns `None` if there were no runs to compute statistics from.
+fn time_named(
+    args: &BenchmarkArgs,
+    graph: &Graph,
+    start_vertex: usize,
+    input_path: &Path,
+    name: String,
+    run: impl Fn(&Graph, usize) + Sync,
+) -> Option<Timings> {
+    let len = match args.target_ci {
+        Some(_) => None,
+        None => Some((args.warmup + args.n) as u64),
+    };
+    let progress = progress_bar(len, &name, false);
+    let (result, peak_bytes) = time_runs(graph, start_vertex, run, args, &progress);
+    progress.finish_and_clear();
+    let result = result?;
+
+    Some(Timings {
+        input: input_path.display().to_string(),
+        name,
+        peak_bytes,
+        search: None,
+        outliers: result.outliers,
+        runs_ns: result.runs_ns,
+        stats: result.stats,
+    })
+}
+
+/// Time the Dijkstra of petgraph, on the graph converted once to a
+/// `petgraph::Graph`, and of pathfinding, on the adjacency lists, from the
+/// same start vertex as the algorithms of this crate.
+/// Returns `None` if there were no runs to compute statistics from.
+#[cfg(feature = "compare")]
+fn compare_crates(
+    args: &BenchmarkArgs,
+    graph: &Graph,
+    start_vertex: usize,
+    input_path: &Path,
+) -> Option<Vec<Timings>> {
+    use petgraph::graph::NodeIndex;
+
+    let mut converted: petgraph::Graph<(), usize, petgraph::Directed, usize> =
+        petgraph::Graph::with_capacity(graph.n_vertices(), graph.n_edges());
+    for _ in 0..graph.n_vertices() {
+        converted.add_node(());
+    }
+    for u in 0..graph.n_vertices() {
+        for &(v, weight) in graph.neighbors_of(u).iter() {
+            converted.add_edge(NodeIndex::new(u), NodeIndex::new(v), weight);
+        }
+    }
+
+    let petgraph = time_named(
+        args,
+        graph,
+        start_vertex,
+        input_path,
+        "petgraph::algo::dijkstra".to_string(),
+        |_graph, start_vertex| {
+            let _dists =
+                petgraph::algo::dijkstra(&converted, NodeIndex::new(start_vertex), None, |edge| {
+                    *edge.weight()
+                });
+        },
+    )?;
+    let pathfinding = time_named(
+        args,
+        graph,
+        start_vertex,
+        input_path,
+        "pathfinding::dijkstra_all".to_string(),
+        |graph, start_vertex| {
+            let _parents = pathfinding::directed::dijkstra::dijkstra_all(&start_vertex, |&u| {
+                graph.neighbors_of(u).iter().copied()
+            });
+        },
+    )?;
+    Some(vec![petgraph, pathfinding])
+}
+
+/// Expand the glob patterns among the input paths, keeping the other paths as they are.
+fn expand_inputs(patterns: &[String]) -> Result<Vec<PathBuf>, Error> {
+    let mut input_paths = vec![];
+    for pattern in patterns.iter() {
+        if !pattern.contains(['*', '?', '[']) {
+            input_paths.push(PathBuf::from(pattern));
+            continue;
+        }
+
+        let matches = glob::glob(pattern)
+            .map_err(|e| Error::Input(format!("invalid pattern {:?}: {}", pattern, e)))?;
+        let mut matches = matches
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Error::Input(format!("error reading {:?}: {}", pattern, e)))?;
+        if matches.is_empty() {
+            return Err(Error::Input(format!("no files match {:?}", pattern)));
+        }
+        matches.sort();
+        input_paths.extend(matches);
+    }
+    Ok(input_paths)
+}
+
+/// Write one row per input and algorithm with the main statistics.
+fn write_summary(timings: &[Timings], w: &mut dyn Write) -> io::Result<()> {
+    let input_width = timings.iter().map(|t| t.input.len()).max().unwrap_or(0);
+    let name_width = timings.iter().map(|t| t.name.len()).max().unwrap_or(0);
+
+    writeln!(w, "Summary:")?;
+    writeln!(
+        w,
+        "  {0:<input_width$}  {1:<name_width$}  {2:>14}  {3:>14}  {4:>14}  {5:>14}",
+        "input", "name", "mean", "median", "p95", "peak memory"
+    )?;
+    for timing in timings.iter() {
+        writeln!(
+            w,
+            "  {0:<input_width$}  {1:<name_width$}  {2:>12.0}ns  {3:>12}ns  {4:>12}ns  {5:>8} bytes",
+            timing.input,
+            timing.name,
+            timing.stats.mean,
+            timing.stats.median,
+            timing.stats.p95,
+            timing.peak_bytes
+        )?;
+    }
+    Ok(())
+}
+
+/// Timings of every run of an algorithm and backend, as exported by `benchmark --export`.
+#[derive(Serialize)]
+struct Timings {
+    input: String,
+    name: String,
+    /// Peak heap memory used by a run on top of what was allocated before, in bytes.
+    peak_bytes: usize,
+    /// What a run of Dijkstra's algorithm did, `None` for the other algorithms.
+    search: Option<SearchStats>,
+    /// Number of runs discarded as outliers by `--target-ci`.
+    outliers: usize,
+    runs_ns: Vec<u128>,
+    stats: TimingStats,
+}
+
+/// Write a table comparing the mean and median times, with the speedup of
+/// every row relative to the first one.
+fn write_comparison(timings: &[Timings], w: &mut dyn Write) -> io::Result<()> {
+    let width = timings.iter().map(|t| t.name.len()).max().unwrap_or(0);
+    let baseline = timings[0].stats.mean;
+
+    writeln!(w, "Comparison:")?;
+    writeln!(
+        w,
+        "  {0:<width$}  {1:>14}  {2:>14}  {3:>8}  {4:>14}",
+        "name", "mean", "median", "speedup", "peak memory"
+    )?;
+    for timing in timings.iter() {
+        writeln!(
+            w,
+            "  {0:<width$}  {1:>12.0}ns  {2:>12}ns  {3:>7.2}x  {4:>8} bytes",
+            timing.name,
+            timing.stats.mean,
+            timing.stats.median,
+            baseline / timing.stats.mean,
+            timing.peak_bytes
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the timings as `input,name,measure,time_ns` rows, where `measure` is the
+/// index of the run for every run and the name of the statistic for the statistics.
+/// The peak memory is written as the `peak_bytes` measure, in bytes rather than nanoseconds.
+fn write_timings_csv(timings: &[Timings], w: &mut dyn Write) -> io::Result<()> {
+    writeln!(w, "input,name,measure,time_ns")?;
+    for timing in timings.iter() {
+        for (run, time) in timing.runs_ns.iter().enumerate() {
+            writeln!(w, "{0},{1},{2},{3}", timing.input, timing.name, run, time)?;
+        }
+        let stats = &timing.stats;
+        writeln!(
+            w,
+            "{0},{1},mean,{2:.0}",
+            timing.input, timing.name, stats.mean
+        )?;
+        writeln!(
+            w,
+            "{0},{1},std_dev,{2:.0}",
+            timing.input, timing.name, stats.std_dev
+        )?;
+        writeln!(
+            w,
+            "{0},{1},ci95,{2:.0}",
+            timing.input, timing.name, stats.ci95
+        )?;
+        writeln!(w, "{0},{1},min,{2}", timing.input, timing.name, stats.min)?;
+        writeln!(
+            w,
+            "{0},{1},median,{2}",
+            timing.input, timing.name, stats.median
+        )?;
+        writeln!(w, "{0},{1},p95,{2}", timing.input, timing.name, stats.p95)?;
+        writeln!(w, "{0},{1},p99,{2}", timing.input, timing.name, stats.p99)?;
+        writeln!(w, "{0},{1},max,{2}", timing.input, timing.name, stats.max)?;
+        writeln!(
+            w,
+            "{0},{1},peak_bytes,{2}",
+            timing.input, timing.name, timing.peak_bytes
+        )?;
+    }
+    Ok(())
+}
+
+/// Run `algorithm` on the graph, using the queue `backend` if it uses one.
+fn run_algorithm(
+    graph: &Graph,
+    start_vertex: usize,
+    algorithm: Algorithm,
+    backend: Option<PqBackend>,
+) -> Result<PathsAndDists, DijkstraError> {
+    match (algorithm, backend) {
+        (Algorithm::Dijkstra, Some(PqBackend::Binary)) => {
+            dijkstra_with::<BinaryHeapQueue<usize>>(graph, start_vertex)
+        }
+        (Algorithm::Dijkstra, Some(PqBackend::Dary)) => {
+            dijkstra_with::<DaryHeapQueue<usize>>(graph, start_vertex)
+        }
+        (Algorithm::Dijkstra, Some(PqBackend::Radix)) => {
+            dijkstra_with::<RadixHeapQueue<usize>>(graph, start_vertex)
+        }
+        (Algorithm::Dijkstra, _) => dijkstra_with::<PriorityQueue<usize>>(graph, start_vertex),
+        (Algorithm::Astar, _) => astar(graph, start_vertex, |_vertex| 0),
+        (Algorithm::BellmanFord, _) => bellman_ford(graph, start_vertex),
+        (Algorithm::Bfs, _) => Ok(bfs(graph, start_vertex)),
+    }
+}
+
+/// Counts of what a run of `algorithm` with the queue `backend` does, only for Dijkstra.
+fn search_stats(
+    graph: &Graph,
+    start_vertex: usize,
+    algorithm: Algorithm,
+    backend: Option<PqBackend>,
+) -> Option<SearchStats> {
+    let result = match (algorithm, backend) {
+        (Algorithm::Dijkstra, Some(PqBackend::Binary)) => {
+            shortest_paths_with_stats::<BinaryHeapQueue<usize>>(graph, start_vertex)
+        }
+        (Algorithm::Dijkstra, Some(PqBackend::Dary)) => {
+            shortest_paths_with_stats::<DaryHeapQueue<usize>>(graph, start_vertex)
+        }
+        (Algorithm::Dijkstra, Some(PqBackend::Radix)) => {
+            shortest_paths_with_stats::<RadixHeapQueue<usize>>(graph, start_vertex)
+        }
+        (Algorithm::Dijkstra, _) => {
+            shortest_paths_with_stats::<PriorityQueue<usize>>(graph, start_vertex)
+        }
+        _ => return None,
+    };
+    result.ok()?.stats()
+}
+
+/// Time `run` as configured by `args`, advancing `progress` after every run.
+/// Returns the times and their statistics, `None` if there were no runs,
+/// and the peak heap memory used by a run in bytes.
+///
+/// With `args.contention`, the peak memory covers all the runs made at the same time.
+fn time_runs(
+    graph: &Graph,
+    start_vertex: usize,
+    run: impl Fn(&Graph, usize) + Sync,
+    args: &BenchmarkArgs,
+    progress: &ProgressBar,
+) -> (Option<BenchmarkResult>, usize) {
+    let mut benchmark = Benchmark::new(graph)
+        .source(start_vertex)
+        .runs(args.n)
+        .warmup(args.warmup)
+        .threads(args.threads)
+        .contention(args.contention)
+        .algorithm(run)
+        .on_run(|| progress.inc(1));
+    if let Some(target_pct) = args.target_ci {
+        benchmark = benchmark.target_ci(target_pct, Duration::from_secs_f64(args.max_time));
+    }
+
+    let allocated = CountingAllocator::reset_peak();
+    let result = benchmark.run();
+    (result, CountingAllocator::peak() - allocated)
+}
+
+/// Read the input file (or stdin if the path is `-`) into a string.
+fn read_input(input_path: &PathBuf) -> Result<String, CliError> {
+    let contents = if input_path.as_os_str() == "-" {
+        let progress = progress_bar(None, "Reading stdin", true);
+        let contents = io::read_to_string(progress.wrap_read(io::stdin()));
+        progress.finish_and_clear();
+        contents
+    } else {
+        fs::File::open(input_path).and_then(|file| {
+            let size = file.metadata()?.len();
+            let progress = progress_bar(Some(size), "Reading input", true);
+            let contents = io::read_to_string(progress.wrap_read(file));
+            progress.finish_and_clear();
+            contents
+        })
+    };
+    contents.map_err(|e| CliError::Io(format!("cannot read {0}: {1}", input_path.display(), e)))
+}
+
+/// Progress bar on stderr counting up to `len` (or a spinner if the length is unknown),
+/// shown only if stderr is a terminal. `bytes` formats the counts as bytes.
+fn progress_bar(len: Option<u64>, message: &str, bytes: bool) -> ProgressBar {
+    if !io::stderr().is_terminal() {
+        return ProgressBar::hidden();
+    }
+
+    let (progress, template) = match (len, bytes) {
+        (Some(len), true) => (
+            ProgressBar::new(len),
+            "{msg} [{bar:40}] {bytes}/{total_bytes} ({eta})",
+        ),
+        (Some(len), false) => (
+            ProgressBar::new(len),
+            "{msg} [{bar:40}] {pos}/{len} ({eta})",
+        ),
+        (None, true) => (ProgressBar::new_spinner(), "{msg} {spinner} {bytes}"),
+        (None, false) => (ProgressBar::new_spinner(), "{msg} {spinner} {pos}"),
+    };
+    if let Ok(style) = ProgressStyle::with_template(template) {
+        progress.set_style(style.progress_chars("=> "));
+    }
+    progress.with_message(message.to_string())
+}
+
+/// Parse the input file (or stdin if the path is `-`) into a start vertex and a graph,
+/// adding the reverse of every edge if `undirected` is set.
+fn parse_input(input_path: &PathBuf, undirected: bool) -> Result<(usize, Graph), CliError> {
+    let contents = read_input(input_path)?;
+    let (start_vertex, graph) = parse_input_str(&contents)
+        .map_err(|e| CliError::Parse(format!("{0}: {1}", input_path.display(), e)))?;
+    if undirected {
+        Ok((start_vertex, graph.to_undirected()))
+    } else {
+        Ok((start_vertex, graph))
+    }
+}
+
+/// Replace the start vertex of a parsed input with `start`, if given,
+/// checking that it is a vertex of the graph.
+fn override_start(
+    (start_vertex, graph): (usize, Graph),
+    start: Option<usize>,
+) -> Result<(usize, Graph), CliError> {
+    let start_vertex = start.unwrap_or(start_vertex);
+    if start_vertex >= graph.n_vertices() {
+        return Err(CliError::InvalidVertex(format!(
+            "start vertex {0} must be below {1}, the number of vertices",
+            start_vertex,
+            graph.n_vertices()
+        )));
+    }
+    Ok((start_vertex, graph))
+}
 
+/// Parse the contents of an input file into a start vertex and a graph.
+fn parse_input_str(contents: &str) -> Result<(usize, Graph), Error> {
     let (start_vertex_str, graph_data) = contents
         .split_once('\n')
-        .ok_or(InputError("cannot split on newline".to_string()))?;
+        .ok_or(Error::Input("cannot split on newline".to_string()))?;
 
     let start_vertex: usize = start_vertex_str
         .parse()
-        .map_err(|e| InputError(format!("cannot parse start vertex: {}", e)))?;
+        .map_err(|e| Error::Input(format!("cannot parse start vertex: {}", e)))?;
 
-    let graph = Graph::from_str(graph_data)
-        .map_err(|e| InputError(format!("cannot parse graph: {}", e)))?;
+    let graph = Graph::from_str(graph_data)?;
+    graph.check_targets()?;
 
     Ok((start_vertex, graph))
 }
@@ -0,0 +1,188 @@
+//! Maximum matchings in bipartite graphs with the Hopcroft-Karp algorithm,
+//! e.g. to assign workers to the tasks they can do.
+//!
+//! Edge directions are ignored, and the two sides are the ones found by
+//! [`Graph::bipartition`].
+//!
+use crate::graph::{Graph, OddCycle};
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Maximum matching of a bipartite `graph` with the Hopcroft-Karp algorithm,
+/// in `O(E sqrt(V))`: as many edges as possible, no two of them sharing a
+/// vertex. Returns the matched pairs as `(left, right)`, with `left` on the
+/// first side of [`Graph::bipartition`], by increasing `left`.
+///
+/// # Errors
+/// Fails with a cycle of odd length if the graph is not bipartite.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::matching::hopcroft_karp;
+///
+/// // workers 0, 1 and 2, and the tasks 3 and 4 they can do
+/// let graph = Graph::new(vec![vec![(3, 1)], vec![(3, 1), (4, 1)], vec![(3, 1)], vec![], vec![]]);
+/// assert_eq!(hopcroft_karp(&graph).unwrap(), vec![(0, 3), (1, 4)]);
+///
+/// let triangle = Graph::new(vec![vec![(1, 1)], vec![(2, 1)], vec![(0, 1)]]);
+/// assert!(hopcroft_karp(&triangle).is_err());
+/// ```
+pub fn hopcroft_karp(graph: &Graph) -> Result<Vec<(usize, usize)>, OddCycle> {
+    let (left, _right) = graph.bipartition()?;
+    let n_vertices = graph.n_vertices();
+    let undirected = graph.to_undirected();
+    let neighbors: Vec<Vec<usize>> = (0..n_vertices)
+        .map(|u| {
+            undirected
+                .neighbors_of(u)
+                .iter()
+                .map(|&(v, _weight)| v)
+                .collect()
+        })
+        .collect();
+
+    // the vertex every vertex of either side is matched to
+    let mut mate: Vec<Option<usize>> = vec![None; n_vertices];
+    // layer of the left vertices in the alternating paths from the free ones,
+    // `None` if they are not in a layer or lead to no augmenting path
+    let mut layer: Vec<Option<usize>> = vec![None; n_vertices];
+    // the next neighbor to try from every left vertex
+    let mut next = vec![0; n_vertices];
+
+    loop {
+        // layers of the shortest alternating paths from the free left vertices
+        let mut queue = VecDeque::new();
+        for &u in left.iter() {
+            layer[u] = mate[u].is_none().then_some(0);
+            if mate[u].is_none() {
+                queue.push_back(u);
+            }
+        }
+        let mut augmentable = false;
+        while let Some(u) = queue.pop_front() {
+            for &v in neighbors[u].iter() {
+                match mate[v] {
+                    None => augmentable = true,
+                    Some(w) if layer[w].is_none() => {
+                        layer[w] = layer[u].map(|layer| layer + 1);
+                        queue.push_back(w);
+                    }
+                    Some(_w) => {}
+                }
+            }
+        }
+        if !augmentable {
+            break;
+        }
+
+        // augment along vertex-disjoint shortest paths, with an explicit
+        // stack of left vertices as the paths can be as long as the graph
+        next.fill(0);
+        for &root in left.iter() {
+            if layer[root] != Some(0) {
+                continue;
+            }
+            let mut stack = vec![root];
+            while let Some(&u) = stack.last() {
+                let Some(&v) = neighbors[u].get(next[u]) else {
+                    // no augmenting path through u in this phase
+                    layer[u] = None;
+                    stack.pop();
+                    continue;
+                };
+                next[u] += 1;
+                match mate[v] {
+                    None => {
+                        // every vertex of the stack takes the neighbor it
+                        // went through, the last one `v`
+                        for &w in stack.iter() {
+                            let through = neighbors[w][next[w] - 1];
+                            mate[w] = Some(through);
+                            mate[through] = Some(w);
+                        }
+                        break;
+                    }
+                    Some(w) if layer[w] == layer[u].map(|layer| layer + 1) => stack.push(w),
+                    Some(_w) => {}
+                }
+            }
+        }
+    }
+
+    Ok(left
+        .iter()
+        .filter_map(|&u| mate[u].map(|v| (u, v)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+    use crate::matching::hopcroft_karp;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    /// Size of a maximum matching of the left vertices `left` in the
+    /// undirected `graph`, trying every neighbor of the first one.
+    fn brute_force(graph: &Graph, left: &[usize], used: &mut Vec<bool>) -> usize {
+        let Some((&u, rest)) = left.split_first() else {
+            return 0;
+        };
+        let mut best = brute_force(graph, rest, used);
+        for &(v, _weight) in graph.neighbors_of(u).iter() {
+            if !used[v] {
+                used[v] = true;
+                best = best.max(1 + brute_force(graph, rest, used));
+                used[v] = false;
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn matchings_are_maximum() {
+        // left 0..6, right 6..12, edges from a fixed pseudo random sequence
+        let mut state: u64 = 7;
+        for _graph in 0..30 {
+            let mut graph = Graph::new(vec![vec![]; 12]);
+            for u in 0..6 {
+                for v in 6..12 {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+                    if state >> 62 == 0 {
+                        graph.add_edge(u, v, 1);
+                    }
+                }
+            }
+            let matching = hopcroft_karp(&graph).unwrap();
+            let (left, _right) = graph.bipartition().unwrap();
+            assert_eq!(
+                matching.len(),
+                brute_force(&graph.to_undirected(), &left, &mut vec![false; 12])
+            );
+
+            let mut matched = [false; 12];
+            for &(u, v) in matching.iter() {
+                assert!(graph.path_cost(&[u, v]).is_some() || graph.path_cost(&[v, u]).is_some());
+                assert!(!matched[u] && !matched[v]);
+                matched[u] = true;
+                matched[v] = true;
+            }
+        }
+    }
+
+    #[test]
+    fn long_paths_are_perfectly_matched() {
+        // a path 0 - 1 - ... - 2n - 1, listed from its end
+        let n = 1000;
+        let mut graph = Graph::new(vec![vec![]; 2 * n]);
+        for u in (1..2 * n).rev() {
+            graph.add_edge(u, u - 1, 1);
+        }
+        let matching = hopcroft_karp(&graph).unwrap();
+        assert_eq!(matching.len(), n);
+        assert_eq!(matching[0], (0, 1));
+        assert_eq!(hopcroft_karp(&Graph::new(vec![])), Ok(Vec::new()));
+    }
+}
@@ -0,0 +1,166 @@
+//! Minimum spanning trees with Prim's and Kruskal's algorithms.
+//!
+//! Both ignore the direction of the edges, so an edge listed only one way
+//! still connects its ends. On a disconnected graph they find a minimum
+//! spanning forest, with one tree per component.
+//!
+use crate::bitset::BitSet;
+use crate::graph::Graph;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Edge of a spanning tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TreeEdge {
+    /// Vertex the edge is listed from in the graph.
+    pub from: usize,
+    /// Vertex the edge is listed to in the graph.
+    pub to: usize,
+    /// Weight of the edge.
+    pub weight: usize,
+}
+
+/// Minimum spanning forest with Prim's algorithm, growing a tree from the
+/// smallest vertex of every component. The edges are returned in the order
+/// they are added to the trees.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::mst::{prim, total_weight};
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 9)], vec![(2, 1)], vec![]]);
+/// assert_eq!(total_weight(&prim(&graph)), 4);
+/// ```
+pub fn prim(graph: &Graph) -> Vec<TreeEdge> {
+    let undirected = graph.to_undirected();
+    let n_elems = undirected.n_vertices();
+    let mut in_tree = BitSet::new(n_elems);
+    let mut edges = Vec::with_capacity(n_elems.saturating_sub(1));
+    // entries are (weight, to, from), the ones to vertices already in a tree are skipped
+    let mut queue = BinaryHeap::new();
+
+    for root in 0..n_elems {
+        if !in_tree.insert(root) {
+            continue;
+        }
+        queue.extend(
+            undirected
+                .neighbors_of(root)
+                .iter()
+                .map(|&(v, weight)| Reverse((weight, v, root))),
+        );
+
+        while let Some(Reverse((weight, node, from))) = queue.pop() {
+            if !in_tree.insert(node) {
+                continue;
+            }
+            edges.push(TreeEdge {
+                from,
+                to: node,
+                weight,
+            });
+            queue.extend(
+                undirected
+                    .neighbors_of(node)
+                    .iter()
+                    .filter(|&&(v, _)| !in_tree.contains(v))
+                    .map(|&(v, weight)| Reverse((weight, v, node))),
+            );
+        }
+    }
+
+    edges
+}
+
+/// Minimum spanning forest with Kruskal's algorithm, adding the edges from
+/// the lightest one up unless they close a cycle. The edges are returned in
+/// order of weight.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::mst::{kruskal, total_weight};
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 9)], vec![(2, 1)], vec![]]);
+/// assert_eq!(total_weight(&kruskal(&graph)), 4);
+/// ```
+pub fn kruskal(graph: &Graph) -> Vec<TreeEdge> {
+    let n_elems = graph.n_vertices();
+    let mut candidates: Vec<TreeEdge> = graph
+        .adj
+        .iter()
+        .enumerate()
+        .flat_map(|(from, neighbors)| {
+            neighbors
+                .iter()
+                .map(move |&(to, weight)| TreeEdge { from, to, weight })
+        })
+        .collect();
+    // stable, so equal weights keep the order of the input
+    candidates.sort_by_key(|edge| edge.weight);
+
+    // union-find with path halving
+    let mut parent: Vec<usize> = (0..n_elems).collect();
+    fn find(parent: &mut [usize], mut v: usize) -> usize {
+        while parent[v] != v {
+            parent[v] = parent[parent[v]];
+            v = parent[v];
+        }
+        v
+    }
+
+    let mut edges = Vec::with_capacity(n_elems.saturating_sub(1));
+    for edge in candidates {
+        let (root_from, root_to) = (find(&mut parent, edge.from), find(&mut parent, edge.to));
+        if root_from != root_to {
+            parent[root_from] = root_to;
+            edges.push(edge);
+        }
+    }
+
+    edges
+}
+
+/// Sum of the weights of the edges.
+pub fn total_weight(edges: &[TreeEdge]) -> usize {
+    edges.iter().map(|edge| edge.weight).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+    use crate::mst::{kruskal, prim, total_weight};
+    use crate::test_utils::sample_graph;
+
+    fn graph() -> Graph {
+        sample_graph()
+    }
+
+    #[test]
+    fn prim_and_kruskal_agree() {
+        let g1 = graph();
+        let (tree_prim, tree_kruskal) = (prim(&g1), kruskal(&g1));
+        assert_eq!(tree_prim.len(), 7);
+        assert_eq!(tree_kruskal.len(), 7);
+        assert_eq!(total_weight(&tree_prim), 11);
+        assert_eq!(total_weight(&tree_kruskal), 11);
+    }
+
+    #[test]
+    fn directions_are_ignored() {
+        // 2 is only reachable against the direction of its edge
+        let g1 = Graph::new(vec![vec![(1, 5)], vec![], vec![(0, 1), (1, 2)]]);
+        assert_eq!(total_weight(&prim(&g1)), 3);
+        assert_eq!(total_weight(&kruskal(&g1)), 3);
+    }
+
+    #[test]
+    fn spanning_forest_on_disconnected_graph() {
+        let g1 = Graph::new(vec![vec![(1, 2)], vec![], vec![(3, 4)], vec![], vec![]]);
+        assert_eq!(prim(&g1).len(), 2);
+        assert_eq!(kruskal(&g1).len(), 2);
+        assert_eq!(total_weight(&prim(&g1)), 6);
+    }
+}
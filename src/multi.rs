@@ -0,0 +1,355 @@
+//! Graph whose edges carry several weights, e.g. a distance, a time and a toll.
+//!
+use crate::csr::CsrGraph;
+use crate::dijkstra::{dijkstra_with_cost, shortest_path_with_cost, DijkstraError};
+use crate::dijkstra::{PathAndDist, PathsAndDists};
+use crate::graph::{parse_header, Graph, ParseGraphError};
+use crate::trace;
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+/// How the cost of an edge is computed from its weights.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CostSelector {
+    /// The weight at this index, e.g. `Weight(1)` for the time.
+    Weight(usize),
+    /// The sum of the weights, each multiplied by its coefficient, e.g.
+    /// `Linear(vec![0, 1, 60])` for the time plus a minute per unit of toll.
+    Linear(Vec<usize>),
+}
+
+/// Graph whose edges all carry the same number of weights, stored once next to
+/// a single copy of the topology.
+///
+/// The input format is the one of [`Graph`], with every edge written as the
+/// neighbor followed by its weights, e.g. `1,3,7` for an edge to vertex 1 of
+/// weights 3 and 7.
+///
+/// # Example
+/// ```
+/// use djikstra::multi::{dijkstra_multi, CostSelector, MultiGraph};
+///
+/// // a short slow road and a long fast one, as distance,time
+/// let graph: MultiGraph = "3\n1,2,10 2,8,4\n2,2,10\n\n".parse().unwrap();
+/// let (paths, dists) = dijkstra_multi(&graph, 0, &CostSelector::Weight(0)).unwrap();
+/// assert_eq!((paths[2].clone(), dists[2]), (Some(vec![0, 1, 2]), Some(4)));
+/// let (paths, dists) = dijkstra_multi(&graph, 0, &CostSelector::Weight(1)).unwrap();
+/// assert_eq!((paths[2].clone(), dists[2]), (Some(vec![0, 2]), Some(4)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiGraph {
+    // the weight of every edge of the topology is its index in the edges,
+    // its weights are `weights[index * n_weights..(index + 1) * n_weights]`
+    topology: CsrGraph,
+    weights: Vec<usize>,
+    n_weights: usize,
+}
+
+impl MultiGraph {
+    /// Create a graph from adjacency lists of `(neighbor, weights)`.
+    ///
+    /// # Panics
+    /// If the edges do not all have the same number of weights.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::multi::MultiGraph;
+    ///
+    /// let graph = MultiGraph::new(vec![vec![(1, vec![3, 7])], vec![]]);
+    /// assert_eq!(graph.n_weights(), 2);
+    /// assert_eq!(graph.neighbors_of(0).collect::<Vec<_>>(), vec![(1, &[3, 7][..])]);
+    /// ```
+    pub fn new(adj: Vec<Vec<(usize, Vec<usize>)>>) -> Self {
+        let n_weights = adj
+            .iter()
+            .flatten()
+            .next()
+            .map_or(0, |(_v, weights)| weights.len());
+        let mut weights = vec![];
+        let topology = adj
+            .into_iter()
+            .map(|neighbors| {
+                neighbors
+                    .into_iter()
+                    .map(|(v, edge_weights)| {
+                        assert_eq!(
+                            edge_weights.len(),
+                            n_weights,
+                            "every edge has the same number of weights"
+                        );
+                        let index = weights.len() / n_weights.max(1);
+                        weights.extend(edge_weights);
+                        (v, index)
+                    })
+                    .collect()
+            })
+            .collect();
+        Self {
+            topology: CsrGraph::from(&Graph::new(topology)),
+            weights,
+            n_weights,
+        }
+    }
+
+    /// Number of vertices
+    pub fn n_vertices(&self) -> usize {
+        self.topology.n_vertices()
+    }
+
+    /// Number of edges
+    pub fn n_edges(&self) -> usize {
+        self.topology.n_edges()
+    }
+
+    /// Number of weights of every edge.
+    pub fn n_weights(&self) -> usize {
+        self.n_weights
+    }
+
+    /// Get neighbors of a vertex with the weights of the edges to them.
+    pub fn neighbors_of(&self, vertex: usize) -> impl Iterator<Item = (usize, &[usize])> + '_ {
+        self.topology
+            .neighbors_of(vertex)
+            .iter()
+            .map(|&(v, index)| (v, self.weights_of(index)))
+    }
+
+    /// The graph with a single weight per edge, its cost under `selector`.
+    ///
+    /// # Panics
+    /// If `selector` does not fit the number of weights, see [`dijkstra_multi`].
+    pub fn select(&self, selector: &CostSelector) -> Graph {
+        self.check_selector(selector);
+        Graph::new(
+            (0..self.n_vertices())
+                .map(|u| {
+                    self.topology
+                        .neighbors_of(u)
+                        .iter()
+                        .map(|&(v, index)| (v, self.cost(index, selector)))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    fn weights_of(&self, index: usize) -> &[usize] {
+        &self.weights[index * self.n_weights..(index + 1) * self.n_weights]
+    }
+
+    /// Cost of the edge at `index`, `usize::MAX` if it does not fit so the
+    /// search reports an overflow.
+    fn cost(&self, index: usize, selector: &CostSelector) -> usize {
+        let weights = self.weights_of(index);
+        match selector {
+            CostSelector::Weight(i) => weights[*i],
+            CostSelector::Linear(coefficients) => weights
+                .iter()
+                .zip(coefficients.iter())
+                .try_fold(0_usize, |cost, (&weight, &coefficient)| {
+                    cost.checked_add(weight.checked_mul(coefficient)?)
+                })
+                .unwrap_or(usize::MAX),
+        }
+    }
+
+    fn check_selector(&self, selector: &CostSelector) {
+        match selector {
+            CostSelector::Weight(i) => assert!(
+                *i < self.n_weights,
+                "weight {} of edges with {} weights",
+                i,
+                self.n_weights
+            ),
+            CostSelector::Linear(coefficients) => assert_eq!(
+                coefficients.len(),
+                self.n_weights,
+                "one coefficient per weight"
+            ),
+        }
+    }
+}
+
+impl FromStr for MultiGraph {
+    type Err = ParseGraphError;
+
+    /// Parse a string in the input format of [`Graph`] with several weights per edge.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let _span = trace::span!("parse");
+        let (n_vertex, lines) = parse_header(s)?;
+
+        let mut adj = Vec::with_capacity(n_vertex);
+        for line in lines.lines().take(n_vertex) {
+            let neighbors =
+                line.split_whitespace()
+                    .map(|edge_str| {
+                        let mut numbers = edge_str.split(',');
+                        let v =
+                            numbers.next().unwrap_or_default().parse().map_err(|e| {
+                                ParseGraphError(format!("cannot parse vertex: {}", e))
+                            })?;
+                        let weights = numbers
+                            .map(|weight| {
+                                weight.parse().map_err(|e| {
+                                    ParseGraphError(format!("cannot parse weight: {}", e))
+                                })
+                            })
+                            .collect::<Result<Vec<usize>, _>>()?;
+                        if weights.is_empty() {
+                            return Err(ParseGraphError(
+                                "vertex doesnt have weight with it".to_string(),
+                            ));
+                        }
+                        Ok((v, weights))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+            adj.push(neighbors);
+        }
+        // the vertices without a line have no edges
+        adj.resize_with(n_vertex, Vec::new);
+
+        let mut counts = adj.iter().flatten().map(|(_v, weights)| weights.len());
+        if let Some(n_weights) = counts.next() {
+            if let Some(other) = counts.find(|&count| count != n_weights) {
+                return Err(ParseGraphError(format!(
+                    "edges with {} and {} weights",
+                    n_weights, other
+                )));
+            }
+        }
+        Ok(Self::new(adj))
+    }
+}
+
+/// Shortest paths from `src` to every vertex of the graph, with the cost of
+/// every edge computed from its weights by `selector`.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the cost of a path to a
+/// reachable vertex does not fit in a `usize`.
+///
+/// # Panics
+/// If `selector` picks a weight past the weights of the edges, or does not
+/// have one coefficient per weight.
+///
+/// # Example
+/// ```
+/// use djikstra::multi::{dijkstra_multi, CostSelector, MultiGraph};
+///
+/// // distance,toll
+/// let graph: MultiGraph = "3\n1,2,5 2,6,0\n2,2,0\n\n".parse().unwrap();
+/// let toll_is_dear = CostSelector::Linear(vec![1, 2]);
+/// let (paths, dists) = dijkstra_multi(&graph, 0, &toll_is_dear).unwrap();
+/// assert_eq!((paths[2].clone(), dists[2]), (Some(vec![0, 2]), Some(6)));
+/// ```
+pub fn dijkstra_multi(
+    graph: &MultiGraph,
+    src: usize,
+    selector: &CostSelector,
+) -> Result<PathsAndDists, DijkstraError> {
+    graph.check_selector(selector);
+    dijkstra_with_cost(&graph.topology, src, |_u, _v, index| {
+        graph.cost(index, selector)
+    })
+}
+
+/// Shortest path from `src` to `dst`, with the cost of every edge computed
+/// from its weights by `selector`. Returns the path and its cost, or `None`
+/// if `dst` cannot be reached.
+///
+/// # Errors
+/// Fails if `src` or `dst` is not a vertex of the graph, or if the cost of a
+/// path to a reachable vertex does not fit in a `usize`.
+///
+/// # Panics
+/// If `selector` picks a weight past the weights of the edges, or does not
+/// have one coefficient per weight.
+pub fn shortest_path_multi(
+    graph: &MultiGraph,
+    src: usize,
+    dst: usize,
+    selector: &CostSelector,
+) -> Result<Option<PathAndDist>, DijkstraError> {
+    graph.check_selector(selector);
+    shortest_path_with_cost(&graph.topology, src, dst, |_u, _v, index| {
+        graph.cost(index, selector)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dijkstra::{dijkstra, shortest_path, DijkstraError};
+    use crate::multi::{dijkstra_multi, shortest_path_multi, CostSelector, MultiGraph};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn every_selector_runs_like_its_own_graph() {
+        let graph = MultiGraph::new(vec![
+            vec![(1, vec![3, 9, 0]), (6, vec![2, 1, 4])],
+            vec![(0, vec![3, 9, 0]), (2, vec![4, 2, 1]), (3, vec![1, 7, 0])],
+            vec![(6, vec![6, 3, 0]), (3, vec![2, 2, 2]), (4, vec![2, 8, 1])],
+            vec![(1, vec![1, 7, 0]), (4, vec![1, 1, 5]), (7, vec![2, 3, 0])],
+            vec![(2, vec![2, 8, 1]), (7, vec![1, 6, 3]), (5, vec![3, 2, 0])],
+            vec![(4, vec![3, 2, 0]), (7, vec![4, 4, 4])],
+            vec![(0, vec![2, 1, 4]), (1, vec![1, 5, 0]), (4, vec![5, 1, 1])],
+            vec![(4, vec![1, 6, 3]), (5, vec![4, 4, 4]), (1, vec![6, 1, 2])],
+        ]);
+        assert_eq!((graph.n_vertices(), graph.n_edges()), (8, 22));
+
+        let selectors = [
+            CostSelector::Weight(0),
+            CostSelector::Weight(1),
+            CostSelector::Weight(2),
+            CostSelector::Linear(vec![1, 2, 10]),
+        ];
+        for selector in selectors.iter() {
+            let selected = graph.select(selector);
+            for src in 0..graph.n_vertices() {
+                let (paths, dists) = dijkstra_multi(&graph, src, selector).unwrap();
+                assert_eq!(dists, dijkstra(&selected, src).unwrap().1);
+                for dst in 0..graph.n_vertices() {
+                    assert_eq!(
+                        shortest_path_multi(&graph, src, dst, selector),
+                        Ok(paths[dst].clone().zip(dists[dst]))
+                    );
+                    assert_eq!(
+                        shortest_path_multi(&graph, src, dst, selector)
+                            .unwrap()
+                            .map(|p| p.1),
+                        shortest_path(&selected, src, dst).unwrap().map(|p| p.1)
+                    );
+                }
+            }
+        }
+
+        let overflowing = CostSelector::Linear(vec![usize::MAX, 0, 0]);
+        assert_eq!(
+            dijkstra_multi(&graph, 0, &overflowing),
+            Err(DijkstraError::Overflow)
+        );
+    }
+
+    #[test]
+    fn parses_several_weights_per_edge() {
+        let graph: MultiGraph = "3\n1,2,10 2,8,4\n2,2,10\n".parse().unwrap();
+        assert_eq!(graph.n_weights(), 2);
+        let neighbors: Vec<_> = graph.neighbors_of(0).collect();
+        assert_eq!(neighbors, vec![(1, &[2, 10][..]), (2, &[8, 4][..])]);
+        assert_eq!(graph.neighbors_of(2).count(), 0);
+
+        assert!("2\n1,2,3 1,2\n\n".parse::<MultiGraph>().is_err());
+        assert!("2\n1\n\n".parse::<MultiGraph>().is_err());
+        assert!("2\n1,x\n\n".parse::<MultiGraph>().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "one coefficient per weight")]
+    fn needs_one_coefficient_per_weight() {
+        let graph = MultiGraph::new(vec![vec![(1, vec![3, 7])], vec![]]);
+        let _ = dijkstra_multi(&graph, 0, &CostSelector::Linear(vec![1]));
+    }
+}
@@ -0,0 +1,203 @@
+//! Dijkstra storing the distances and parents in narrow integer types, for
+//! graphs with many vertices and short enough paths, e.g. road graphs whose
+//! distances in meters fit in a `u32`.
+//!
+use crate::bitset::BitSet;
+use crate::dijkstra::{add_distance, check_source, DijkstraError};
+use crate::graph::{Adjacency, IndexType, WeightType};
+use alloc::collections::BinaryHeap;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::mem;
+
+/// Shortest paths from a source, with the distances stored as `D` and the
+/// parents as the index type `Ix` of the graph, found by [`dijkstra_narrow`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NarrowTree<Ix, D> {
+    source: usize,
+    // the parent of the source and of the unreached vertices is themselves
+    parents: Vec<Ix>,
+    // only meaningful for the reached vertices
+    dists: Vec<D>,
+    reached: BitSet,
+}
+
+impl<Ix: IndexType, D: WeightType> NarrowTree<Ix, D> {
+    /// The vertex the paths start from.
+    pub fn source(&self) -> usize {
+        self.source
+    }
+
+    /// Distance from the source to `v`, `None` if `v` is unreachable.
+    ///
+    /// # Panics
+    /// If `v` is not a vertex of the graph.
+    pub fn dist_to(&self, v: usize) -> Option<usize> {
+        self.reached.contains(v).then(|| self.dists[v].weight())
+    }
+
+    /// Shortest path from the source to `v`, `None` if `v` is unreachable.
+    ///
+    /// # Panics
+    /// If `v` is not a vertex of the graph.
+    pub fn path_to(&self, v: usize) -> Option<Vec<usize>> {
+        if !self.reached.contains(v) {
+            return None;
+        }
+        let mut path = vec![v];
+        while *path.last().unwrap() != self.source {
+            path.push(self.parents[*path.last().unwrap()].index());
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Number of bytes the tree holds on the heap.
+    pub fn memory_usage(&self) -> usize {
+        self.parents.capacity() * mem::size_of::<Ix>()
+            + self.dists.capacity() * mem::size_of::<D>()
+            + self.reached.len().div_ceil(8)
+    }
+}
+
+/// Dijkstra algorithm keeping the distances as `D` and the parents as the
+/// index type of the graph, so that with `u32` for both its arrays take half
+/// the memory of the ones of [`crate::dijkstra::dijkstra`].
+///
+/// The distances are added as `usize` and checked to fit in `D` before they
+/// are stored.
+///
+/// # Errors
+/// Fails if `src` is not a vertex of the graph, or if the distance to a
+/// reachable vertex does not fit in a `D`.
+///
+/// # Panics
+/// If a vertex of the graph does not fit in its index type.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::DijkstraError;
+/// use djikstra::graph::Graph;
+/// use djikstra::narrow::dijkstra_narrow;
+///
+/// let graph = Graph::from_weighted_adjacency(vec![vec![(1_u32, 3_u32)], vec![(2, 1)], vec![]]);
+/// let tree = dijkstra_narrow::<_, u32>(&graph, 0).unwrap();
+/// assert_eq!(tree.dist_to(2), Some(4));
+/// assert_eq!(tree.path_to(2), Some(vec![0, 1, 2]));
+///
+/// // 70 000 does not fit in a u16
+/// let long = Graph::from_weighted_adjacency(vec![vec![(1_u32, 40_000_u32)], vec![(2, 30_000)], vec![]]);
+/// assert_eq!(dijkstra_narrow::<_, u16>(&long, 0), Err(DijkstraError::Overflow));
+/// ```
+pub fn dijkstra_narrow<G, D>(
+    graph: &G,
+    src: usize,
+) -> Result<NarrowTree<G::Index, D>, DijkstraError>
+where
+    G: Adjacency,
+    D: WeightType,
+{
+    check_source(graph, src)?;
+    let n_elems = graph.n_vertices();
+    let to_index = |v: usize| G::Index::new(v).expect("the vertices fit in the index type");
+    let zero = D::new(0).unwrap();
+
+    let mut parents: Vec<G::Index> = (0..n_elems).map(to_index).collect();
+    let mut dists = vec![zero; n_elems];
+    let mut reached = BitSet::new(n_elems);
+    let mut settled = BitSet::new(n_elems);
+    let mut overflowed = vec![];
+    // lazy deletion: entries of vertices reached again by a better path are skipped
+    let mut queue = BinaryHeap::from([Reverse((zero, src))]);
+    reached.insert(src);
+
+    while let Some(Reverse((dist_src, node))) = queue.pop() {
+        if !settled.insert(node) {
+            continue;
+        }
+        for &(neighbour, dist) in graph.neighbors_of(node).iter() {
+            let neighbour = neighbour.index();
+            if settled.contains(neighbour) {
+                continue;
+            }
+            let Some(new_dist) = add_distance(dist_src.weight(), dist.weight()).and_then(D::new)
+            else {
+                overflowed.push(neighbour);
+                continue;
+            };
+            if !reached.contains(neighbour) || dists[neighbour] > new_dist {
+                reached.insert(neighbour);
+                dists[neighbour] = new_dist;
+                parents[neighbour] = to_index(node);
+                queue.push(Reverse((new_dist, neighbour)));
+            }
+        }
+    }
+
+    // an overflowing distance is only a problem if there is no other one
+    if overflowed.iter().any(|&v| !reached.contains(v)) {
+        return Err(DijkstraError::Overflow);
+    }
+    Ok(NarrowTree {
+        source: src,
+        parents,
+        dists,
+        reached,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dijkstra::{dijkstra, DijkstraError};
+    use crate::graph::Graph;
+    use crate::narrow::dijkstra_narrow;
+    use crate::test_utils::sample_graph;
+    use alloc::vec;
+
+    #[test]
+    fn narrow_distances_are_the_same() {
+        let g1 = sample_graph();
+        let narrow = g1
+            .to_index_type::<u32>()
+            .unwrap()
+            .to_weight_type::<u16>()
+            .unwrap();
+        assert!(narrow.memory_usage() < g1.memory_usage());
+        for src in 0..g1.n_vertices() {
+            let (paths, dists) = dijkstra(&g1, src).unwrap();
+            let tree = dijkstra_narrow::<_, u32>(&narrow, src).unwrap();
+            assert_eq!(tree.source(), src);
+            for v in 0..g1.n_vertices() {
+                assert_eq!(tree.dist_to(v), dists[v]);
+                let path = tree.path_to(v).unwrap();
+                assert_eq!(g1.path_cost(&path), dists[v]);
+                assert_eq!(path.len(), paths[v].as_ref().unwrap().len());
+            }
+            let wide = dijkstra_narrow::<_, usize>(&g1, src).unwrap();
+            assert!(tree.memory_usage() < wide.memory_usage());
+        }
+        assert!(dijkstra_narrow::<_, u32>(&narrow, 8).is_err());
+    }
+
+    #[test]
+    fn narrow_overflows() {
+        let g1 = Graph::new(vec![
+            vec![(1, 40_000), (3, 1)],
+            vec![(2, 30_000)],
+            vec![],
+            vec![(2, 1)],
+        ]);
+        let tree = dijkstra_narrow::<_, u16>(&g1, 0).unwrap();
+        // 2 is reached through 3 without overflowing
+        assert_eq!(tree.dist_to(2), Some(2));
+        assert_eq!(tree.path_to(2), Some(vec![0, 3, 2]));
+        let g2 = Graph::new(vec![vec![(1, 40_000)], vec![(2, 30_000)], vec![]]);
+        assert_eq!(
+            dijkstra_narrow::<_, u16>(&g2, 0),
+            Err(DijkstraError::Overflow)
+        );
+        let tree = dijkstra_narrow::<_, u32>(&g2, 0).unwrap();
+        assert_eq!(tree.dist_to(2), Some(70_000));
+    }
+}
@@ -0,0 +1,309 @@
+//! Distance oracle answering exact distance queries without the graph, with
+//! hub labels built once and saved to a file.
+//!
+//! Every vertex is labeled with its distances to and from a few hubs, such
+//! that the shortest path between any two vertices goes through a hub in the
+//! labels of both. The labels are computed by pruned landmark labeling: a
+//! Dijkstra search from every vertex in turn, most connected first, that stops
+//! at the vertices whose distance the labels already give.
+//!
+use crate::dijkstra::{add_distance, DijkstraError};
+use crate::graph::Graph;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Hub labels of every vertex of a graph, to answer distance queries with
+/// [`DistanceOracle::distance`] in the time of a merge of two short lists.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::oracle::DistanceOracle;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![(0, 2)]]);
+/// let oracle = DistanceOracle::build(&graph).unwrap();
+/// assert_eq!(oracle.distance(0, 2), Some(4));
+/// assert_eq!(oracle.distance(2, 1), Some(5));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DistanceOracle {
+    // `(rank, distance)` of the hubs every vertex reaches, and of the hubs
+    // reaching every vertex, sorted by the rank of the hub
+    to_hubs: Vec<Vec<(usize, usize)>>,
+    from_hubs: Vec<Vec<(usize, usize)>>,
+}
+
+impl DistanceOracle {
+    /// Compute the labels of every vertex of `graph`.
+    ///
+    /// # Errors
+    /// Fails if a distance in the graph does not fit in a `usize`.
+    pub fn build(graph: &Graph) -> Result<Self, DijkstraError> {
+        let n_elems = graph.n_vertices();
+        let mut reversed = vec![vec![]; n_elems];
+        for (u, neighbors) in graph.adj.iter().enumerate() {
+            for &(v, weight) in neighbors.iter() {
+                reversed[v].push((u, weight));
+            }
+        }
+
+        // vertices with many edges are on many shortest paths, so they
+        // make the best hubs
+        let mut order: Vec<usize> = (0..n_elems).collect();
+        order.sort_by_key(|&v| Reverse(graph.adj[v].len() + reversed[v].len()));
+
+        let mut oracle = Self {
+            to_hubs: vec![vec![]; n_elems],
+            from_hubs: vec![vec![]; n_elems],
+        };
+        let mut search = PrunedSearch::new(n_elems);
+        for (rank, &hub) in order.iter().enumerate() {
+            // the hub reaches the vertices of the forward search, and the
+            // vertices of the backward search reach the hub
+            search.run(&graph.adj, hub, |v, dist| {
+                if oracle.distance(hub, v).is_some_and(|known| known <= dist) {
+                    return false;
+                }
+                oracle.from_hubs[v].push((rank, dist));
+                true
+            })?;
+            search.run(&reversed, hub, |v, dist| {
+                if oracle.distance(v, hub).is_some_and(|known| known <= dist) {
+                    return false;
+                }
+                oracle.to_hubs[v].push((rank, dist));
+                true
+            })?;
+        }
+        Ok(oracle)
+    }
+
+    /// Number of vertices of the graph the oracle was built from.
+    pub fn n_vertices(&self) -> usize {
+        self.to_hubs.len()
+    }
+
+    /// Number of hubs in the labels of all the vertices, which the memory
+    /// taken by the oracle is proportional to.
+    pub fn n_labels(&self) -> usize {
+        self.to_hubs
+            .iter()
+            .chain(self.from_hubs.iter())
+            .map(|hubs| hubs.len())
+            .sum()
+    }
+
+    /// Shortest distance from `src` to `dst`, `None` if `dst` cannot be reached.
+    ///
+    /// # Panics
+    /// If `src` or `dst` is not a vertex of the graph, see [`DistanceOracle::try_distance`].
+    pub fn distance(&self, src: usize, dst: usize) -> Option<usize> {
+        let (mut to, mut from) = (
+            self.to_hubs[src].iter().peekable(),
+            self.from_hubs[dst].iter().peekable(),
+        );
+        let mut best: Option<usize> = None;
+        while let (Some(&&(to_rank, to_dist)), Some(&&(from_rank, from_dist))) =
+            (to.peek(), from.peek())
+        {
+            match to_rank.cmp(&from_rank) {
+                std::cmp::Ordering::Less => {
+                    to.next();
+                }
+                std::cmp::Ordering::Greater => {
+                    from.next();
+                }
+                std::cmp::Ordering::Equal => {
+                    if let Some(dist) = add_distance(to_dist, from_dist) {
+                        best = Some(best.map_or(dist, |best| best.min(dist)));
+                    }
+                    to.next();
+                    from.next();
+                }
+            }
+        }
+        best
+    }
+
+    /// Shortest distance from `src` to `dst` as [`DistanceOracle::distance`].
+    ///
+    /// # Errors
+    /// Fails if `src` or `dst` is not a vertex of the graph.
+    pub fn try_distance(&self, src: usize, dst: usize) -> Result<Option<usize>, DijkstraError> {
+        let n_vertices = self.n_vertices();
+        if let Some(&source) = [src, dst].iter().find(|&&v| v >= n_vertices) {
+            return Err(DijkstraError::InvalidSource { source, n_vertices });
+        }
+        Ok(self.distance(src, dst))
+    }
+
+    /// Save the oracle to the file at `path` as JSON, to [`DistanceOracle::load`] it later.
+    ///
+    /// # Errors
+    /// Fails if the file cannot be written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut w, self)?;
+        w.flush()
+    }
+
+    /// Load an oracle saved with [`DistanceOracle::save`].
+    ///
+    /// # Errors
+    /// Fails if the file cannot be read or is not a saved oracle.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    /// use djikstra::oracle::DistanceOracle;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+    /// let path = std::env::temp_dir().join("djikstra-oracle-example.json");
+    /// DistanceOracle::build(&graph).unwrap().save(&path).unwrap();
+    /// // later, without the graph
+    /// let oracle = DistanceOracle::load(&path).unwrap();
+    /// assert_eq!(oracle.distance(0, 2), Some(4));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let oracle: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        if oracle.to_hubs.len() != oracle.from_hubs.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the labels to and from the hubs have different numbers of vertices",
+            ));
+        }
+        Ok(oracle)
+    }
+}
+
+/// Dijkstra search reusing its arrays from one run to the next.
+struct PrunedSearch {
+    dists: Vec<Option<usize>>,
+    touched: Vec<usize>,
+}
+
+impl PrunedSearch {
+    fn new(n_elems: usize) -> Self {
+        Self {
+            dists: vec![None; n_elems],
+            touched: vec![],
+        }
+    }
+
+    /// Search from `src`, calling `visit` with every settled vertex and its
+    /// distance. The edges out of a vertex are only followed if `visit`
+    /// returns `true`.
+    fn run<F>(
+        &mut self,
+        adj: &[Vec<(usize, usize)>],
+        src: usize,
+        mut visit: F,
+    ) -> Result<(), DijkstraError>
+    where
+        F: FnMut(usize, usize) -> bool,
+    {
+        let mut overflowed = vec![];
+        let mut queue = BinaryHeap::from([Reverse((0, src))]);
+        self.dists[src] = Some(0);
+        self.touched.push(src);
+        while let Some(Reverse((dist, u))) = queue.pop() {
+            if self.dists[u] != Some(dist) || !visit(u, dist) {
+                continue;
+            }
+            for &(v, weight) in adj[u].iter() {
+                let Some(new_dist) = add_distance(dist, weight) else {
+                    overflowed.push(v);
+                    continue;
+                };
+                if self.dists[v].is_none_or(|old| old > new_dist) {
+                    if self.dists[v].is_none() {
+                        self.touched.push(v);
+                    }
+                    self.dists[v] = Some(new_dist);
+                    queue.push(Reverse((new_dist, v)));
+                }
+            }
+        }
+        // an overflowing distance is only a problem if there is no other one
+        let result = match overflowed.iter().any(|&v| self.dists[v].is_none()) {
+            true => Err(DijkstraError::Overflow),
+            false => Ok(()),
+        };
+        for v in self.touched.drain(..) {
+            self.dists[v] = None;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::apsp::{distance_rows, Method};
+    use crate::dijkstra::{dijkstra, DijkstraError};
+    use crate::graph::Graph;
+    use crate::oracle::DistanceOracle;
+    use crate::test_utils::sample_graph;
+    use crate::test_utils::{check, GraphStrategy};
+
+    #[test]
+    fn answers_every_query_exactly() {
+        let g1 = sample_graph();
+        let oracle = DistanceOracle::build(&g1).unwrap();
+        assert!(oracle.n_labels() < 2 * g1.n_vertices() * g1.n_vertices());
+        distance_rows(&g1, Method::Dijkstra, |src, row| {
+            for (dst, &dist) in row.iter().enumerate() {
+                assert_eq!(oracle.distance(src, dst), dist);
+            }
+            Ok::<(), DijkstraError>(())
+        })
+        .unwrap();
+        assert!(oracle.try_distance(0, 8).is_err());
+    }
+
+    #[test]
+    fn answers_like_dijkstra_on_random_graphs() {
+        for directed in [true, false] {
+            let strategy = GraphStrategy {
+                directed,
+                ..GraphStrategy::default()
+            };
+            let result = check(&strategy, 100, 7, |graph| {
+                let oracle = DistanceOracle::build(graph).unwrap();
+                for src in 0..graph.n_vertices() {
+                    let (_paths, dists) = dijkstra(graph, src).unwrap();
+                    for (dst, &dist) in dists.iter().enumerate() {
+                        let found = oracle.distance(src, dst);
+                        if found != dist {
+                            return Err(format!(
+                                "expected {:?} from {} to {}, got {:?}",
+                                dist, src, dst, found
+                            ));
+                        }
+                    }
+                }
+                Ok(())
+            });
+            if let Err(failure) = result {
+                panic!("{}", failure);
+            }
+        }
+    }
+
+    #[test]
+    fn saved_oracles_load_the_same() {
+        let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+        let oracle = DistanceOracle::build(&graph).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("djikstra-oracle-{}.json", std::process::id()));
+        oracle.save(&path).unwrap();
+        assert_eq!(DistanceOracle::load(&path).unwrap(), oracle);
+        std::fs::write(&path, "{\"to_hubs\": [], \"from_hubs\": [[]]}").unwrap();
+        assert!(DistanceOracle::load(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,553 @@
+//! Formatting the results of the algorithm for the command line and other tools.
+//!
+use crate::graph::Graph;
+use crate::mst::TreeEdge;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+
+/// Separator between the vertices of a path in the CSV format.
+pub const CSV_PATH_SEPARATOR: char = ' ';
+
+/// ANSI escape codes for the colors of `Results::write_table`.
+const SOURCE_COLOR: &str = "\x1b[1;32m";
+const UNREACHABLE_COLOR: &str = "\x1b[2m";
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// Distance and path to a single vertex from the source.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct VertexResult<'a> {
+    /// The vertex the row is about.
+    pub vertex: usize,
+    /// Distance from the source, `None` if the vertex is unreachable.
+    pub distance: Option<usize>,
+    /// Vertices on the shortest path from the source, `None` if the vertex is unreachable.
+    pub path: Option<&'a [usize]>,
+}
+
+/// Results of a single run of the algorithm.
+///
+/// Serializes into the stable JSON schema:
+/// ```json
+/// {
+///   "source": 0,
+///   "time_ns": 1200,
+///   "vertices": [
+///     { "vertex": 0, "distance": 0, "path": [0] },
+///     { "vertex": 1, "distance": null, "path": null }
+///   ]
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Results<'a> {
+    /// Vertex the paths start from.
+    pub source: usize,
+    /// Time the algorithm took in nanoseconds.
+    pub time_ns: u128,
+    /// One row per reported vertex.
+    pub vertices: Vec<VertexResult<'a>>,
+}
+
+impl<'a> Results<'a> {
+    /// Results for every vertex from the paths and distances returned by the algorithm.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::dijkstra::dijkstra;
+    /// use djikstra::graph::Graph;
+    /// use djikstra::output::Results;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3)], vec![(0, 3)]]);
+    /// let (paths, dists) = dijkstra(&graph, 0).unwrap();
+    /// let results = Results::new(0, &paths, &dists, 0);
+    /// assert_eq!(results.vertices[1].distance, Some(3));
+    /// ```
+    pub fn new(
+        source: usize,
+        paths: &'a [Option<Vec<usize>>],
+        dists: &[Option<usize>],
+        time_ns: u128,
+    ) -> Self {
+        let vertices = paths
+            .iter()
+            .enumerate()
+            .map(|(vertex, path)| VertexResult {
+                vertex,
+                distance: path.as_ref().and(dists[vertex]),
+                path: path.as_deref(),
+            })
+            .collect();
+
+        Self {
+            source,
+            time_ns,
+            vertices,
+        }
+    }
+
+    /// Write one `vertex distance (path)` line per vertex, or `vertex inf` if unreachable.
+    pub fn write_text<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        for row in self.vertices.iter() {
+            match (row.distance, row.path) {
+                (Some(distance), Some(path)) => {
+                    write!(w, "{} {} ({}", row.vertex, distance, path[0])?;
+                    for vertex in path.iter().skip(1) {
+                        write!(w, " -> {}", vertex)?;
+                    }
+                    writeln!(w, ")")?;
+                }
+                _ => writeln!(w, "{} inf", row.vertex)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Write a table with a `vertex distance path` header and one row per vertex,
+    /// the columns aligned so long results can be scanned on a terminal.
+    ///
+    /// With `color`, the row of the source is highlighted and the unreachable
+    /// rows are dimmed with ANSI escape codes.
+    pub fn write_table<W: Write + ?Sized>(&self, w: &mut W, color: bool) -> io::Result<()> {
+        let vertex_width = self
+            .vertices
+            .iter()
+            .map(|row| row.vertex.to_string().len())
+            .chain(["vertex".len()])
+            .max()
+            .unwrap_or(0);
+        let distance_width = self
+            .vertices
+            .iter()
+            .filter_map(|row| row.distance.map(|distance| distance.to_string().len()))
+            .chain(["distance".len()])
+            .max()
+            .unwrap_or(0);
+
+        writeln!(
+            w,
+            "{0:>vertex_width$}  {1:>distance_width$}  path",
+            "vertex", "distance"
+        )?;
+        for row in self.vertices.iter() {
+            let (row_color, distance, path) = match (row.distance, row.path) {
+                (Some(distance), Some(path)) => {
+                    let path: Vec<String> = path.iter().map(|v| v.to_string()).collect();
+                    let row_color = if row.vertex == self.source {
+                        SOURCE_COLOR
+                    } else {
+                        ""
+                    };
+                    (row_color, distance.to_string(), path.join(" -> "))
+                }
+                _ => (UNREACHABLE_COLOR, "inf".to_string(), String::new()),
+            };
+            let (start, end) = match color && !row_color.is_empty() {
+                true => (row_color, RESET_COLOR),
+                false => ("", ""),
+            };
+            let line = format!(
+                "{0:>vertex_width$}  {1:>distance_width$}  {2}",
+                row.vertex, distance, path
+            );
+            writeln!(w, "{0}{1}{2}", start, line.trim_end(), end)?;
+        }
+        Ok(())
+    }
+
+    /// Write a `vertex,distance,path` header followed by one row per vertex.
+    ///
+    /// The vertices of a path are separated by [`CSV_PATH_SEPARATOR`], so a path
+    /// from 0 through 2 to 1 is written as `0 2 1`.
+    /// Unreachable vertices have empty distance and path fields.
+    pub fn write_csv<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "vertex,distance,path")?;
+        for row in self.vertices.iter() {
+            write!(w, "{},", row.vertex)?;
+            if let Some(distance) = row.distance {
+                write!(w, "{}", distance)?;
+            }
+            write!(w, ",")?;
+            if let Some(path) = row.path {
+                let path: Vec<String> = path.iter().map(|v| v.to_string()).collect();
+                write!(w, "{}", path.join(&CSV_PATH_SEPARATOR.to_string()))?;
+            }
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+
+    /// Write `graph` in the Graphviz DOT language with the edges of the reported
+    /// paths highlighted and the distances from the source as node labels.
+    ///
+    /// Vertices without a row in the results are labelled with their index only.
+    pub fn write_dot<W: Write + ?Sized>(&self, graph: &Graph, w: &mut W) -> io::Result<()> {
+        let distances: HashMap<usize, Option<usize>> = self
+            .vertices
+            .iter()
+            .map(|row| (row.vertex, row.distance))
+            .collect();
+        let tree_edges: HashSet<(usize, usize)> = self
+            .vertices
+            .iter()
+            .filter_map(|row| row.path)
+            .flat_map(|path| path.windows(2).map(|edge| (edge[0], edge[1])))
+            .collect();
+
+        writeln!(w, "digraph {{")?;
+        for vertex in 0..graph.n_vertices() {
+            let label = match distances.get(&vertex) {
+                Some(Some(distance)) => format!("{}\\n{}", vertex, distance),
+                Some(None) => format!("{}\\ninf", vertex),
+                None => vertex.to_string(),
+            };
+            let shape = if vertex == self.source {
+                "doublecircle"
+            } else {
+                "circle"
+            };
+            writeln!(w, "    {} [label=\"{}\", shape={}];", vertex, label, shape)?;
+        }
+        for (u, neighbors) in graph.adj.iter().enumerate() {
+            for &(v, weight) in neighbors.iter() {
+                if tree_edges.contains(&(u, v)) {
+                    writeln!(
+                        w,
+                        "    {} -> {} [label=\"{}\", color=red, penwidth=2];",
+                        u, v, weight
+                    )?;
+                } else {
+                    writeln!(w, "    {} -> {} [label=\"{}\", color=gray];", u, v, weight)?;
+                }
+            }
+        }
+        writeln!(w, "}}")
+    }
+
+    /// Write the results as a single JSON document.
+    pub fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        serde_json::to_writer(&mut *w, self)?;
+        writeln!(w)
+    }
+
+    /// Write the results as a GeoJSON `FeatureCollection` with one feature per
+    /// row, placing the vertices at their `(longitude, latitude)` in `coords`.
+    ///
+    /// The geometry of a path is a `LineString`, or a `Point` for the path of the
+    /// source to itself. Unreachable vertices, and paths through a vertex without
+    /// coordinates, have a `null` geometry. The `source`, `target` and `distance`
+    /// of every path are its properties.
+    pub fn write_geojson<W: Write + ?Sized>(
+        &self,
+        coords: &[Option<(f64, f64)>],
+        w: &mut W,
+    ) -> io::Result<()> {
+        let features: Vec<serde_json::Value> = self
+            .vertices
+            .iter()
+            .map(|row| {
+                let positions: Option<Vec<[f64; 2]>> = row.path.and_then(|path| {
+                    path.iter()
+                        .map(|&v| coords.get(v).copied().flatten().map(|(x, y)| [x, y]))
+                        .collect()
+                });
+                let geometry = match positions.as_deref() {
+                    Some([position]) => {
+                        serde_json::json!({ "type": "Point", "coordinates": position })
+                    }
+                    Some(positions) => {
+                        serde_json::json!({ "type": "LineString", "coordinates": positions })
+                    }
+                    None => serde_json::Value::Null,
+                };
+                serde_json::json!({
+                    "type": "Feature",
+                    "geometry": geometry,
+                    "properties": {
+                        "source": self.source,
+                        "target": row.vertex,
+                        "distance": row.distance,
+                    },
+                })
+            })
+            .collect();
+        let collection = serde_json::json!({ "type": "FeatureCollection", "features": features });
+        serde_json::to_writer(&mut *w, &collection)?;
+        writeln!(w)
+    }
+}
+
+/// Spanning tree found by one run of a minimum spanning tree algorithm.
+///
+/// Serializes into the JSON schema:
+/// ```json
+/// {
+///   "time_ns": 1200,
+///   "total_weight": 4,
+///   "edges": [{ "from": 1, "to": 2, "weight": 1 }, { "from": 0, "to": 1, "weight": 3 }]
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct TreeResults<'a> {
+    /// Time the algorithm took in nanoseconds.
+    pub time_ns: u128,
+    /// Sum of the weights of the edges.
+    pub total_weight: usize,
+    /// Edges of the tree.
+    pub edges: &'a [TreeEdge],
+}
+
+impl<'a> TreeResults<'a> {
+    /// Write one `from to weight` line per edge.
+    pub fn write_text<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        for edge in self.edges.iter() {
+            writeln!(w, "{} {} {}", edge.from, edge.to, edge.weight)?;
+        }
+        Ok(())
+    }
+
+    /// Write a `from,to,weight` header followed by one row per edge.
+    pub fn write_csv<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "from,to,weight")?;
+        for edge in self.edges.iter() {
+            writeln!(w, "{},{},{}", edge.from, edge.to, edge.weight)?;
+        }
+        Ok(())
+    }
+
+    /// Write `graph` in the Graphviz DOT language as an undirected graph
+    /// with the edges of the tree highlighted.
+    ///
+    /// Every pair of connected vertices is drawn once, with the minimum weight
+    /// of the edges between them.
+    pub fn write_dot<W: Write + ?Sized>(&self, graph: &Graph, w: &mut W) -> io::Result<()> {
+        let tree_edges: HashSet<(usize, usize)> = self
+            .edges
+            .iter()
+            .map(|edge| (edge.from.min(edge.to), edge.from.max(edge.to)))
+            .collect();
+
+        writeln!(w, "graph {{")?;
+        for vertex in 0..graph.n_vertices() {
+            writeln!(w, "    {} [shape=circle];", vertex)?;
+        }
+        for (u, neighbors) in graph.to_undirected().adj.iter().enumerate() {
+            for &(v, weight) in neighbors.iter().filter(|&&(v, _)| u < v) {
+                if tree_edges.contains(&(u, v)) {
+                    writeln!(
+                        w,
+                        "    {} -- {} [label=\"{}\", color=red, penwidth=2];",
+                        u, v, weight
+                    )?;
+                } else {
+                    writeln!(w, "    {} -- {} [label=\"{}\", color=gray];", u, v, weight)?;
+                }
+            }
+        }
+        writeln!(w, "}}")
+    }
+
+    /// Write the tree as a single JSON document.
+    pub fn write_json<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        serde_json::to_writer(&mut *w, self)?;
+        writeln!(w)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dijkstra::PathsAndDists;
+
+    fn sample() -> PathsAndDists {
+        (
+            vec![Some(vec![0]), Some(vec![0, 2, 1]), Some(vec![0, 2]), None],
+            vec![Some(0), Some(5), Some(3), None],
+        )
+    }
+
+    #[test]
+    fn writes_text() {
+        let (paths, dists) = sample();
+        let mut out = vec![];
+        Results::new(0, &paths, &dists, 10)
+            .write_text(&mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "0 0 (0)\n1 5 (0 -> 2 -> 1)\n2 3 (0 -> 2)\n3 inf\n"
+        );
+    }
+
+    #[test]
+    fn writes_aligned_table() {
+        let (paths, dists) = sample();
+        let mut out = vec![];
+        Results::new(0, &paths, &dists, 10)
+            .write_table(&mut out, false)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "vertex  distance  path\n     0         0  0\n     1         5  0 -> 2 -> 1\n     2         3  0 -> 2\n     3       inf\n"
+        );
+
+        let mut out = vec![];
+        Results::new(0, &paths, &dists, 10)
+            .write_table(&mut out, true)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\x1b[1;32m     0         0  0\x1b[0m\n"));
+        assert!(out.contains("\x1b[2m     3       inf\x1b[0m\n"));
+    }
+
+    #[test]
+    fn writes_csv() {
+        let (paths, dists) = sample();
+        let mut out = vec![];
+        Results::new(0, &paths, &dists, 10)
+            .write_csv(&mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "vertex,distance,path\n0,0,0\n1,5,0 2 1\n2,3,0 2\n3,,\n"
+        );
+    }
+
+    #[test]
+    fn writes_dot() {
+        let graph = Graph::new(vec![vec![(2, 3)], vec![], vec![(1, 2)], vec![(0, 1)]]);
+        let (paths, dists) = sample();
+        let mut out = vec![];
+        Results::new(0, &paths, &dists, 10)
+            .write_dot(&graph, &mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "digraph {\n",
+                "    0 [label=\"0\\n0\", shape=doublecircle];\n",
+                "    1 [label=\"1\\n5\", shape=circle];\n",
+                "    2 [label=\"2\\n3\", shape=circle];\n",
+                "    3 [label=\"3\\ninf\", shape=circle];\n",
+                "    0 -> 2 [label=\"3\", color=red, penwidth=2];\n",
+                "    2 -> 1 [label=\"2\", color=red, penwidth=2];\n",
+                "    3 -> 0 [label=\"1\", color=gray];\n",
+                "}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn writes_geojson() {
+        let (paths, dists) = sample();
+        let coords = [
+            Some((13.4, 52.5)),
+            None,
+            Some((13.5, 52.4)),
+            Some((0.0, 0.0)),
+        ];
+        let mut out = vec![];
+        Results::new(0, &paths, &dists, 10)
+            .write_geojson(&coords, &mut out)
+            .unwrap();
+        let document: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        let features = document["features"].as_array().unwrap();
+
+        assert_eq!(document["type"], "FeatureCollection");
+        assert_eq!(features.len(), 4);
+        assert_eq!(
+            features[0]["geometry"],
+            serde_json::json!({ "type": "Point", "coordinates": [13.4, 52.5] })
+        );
+        // vertex 1 has no coordinates
+        assert!(features[1]["geometry"].is_null());
+        assert_eq!(
+            features[2]["geometry"]["coordinates"],
+            serde_json::json!([[13.4, 52.5], [13.5, 52.4]])
+        );
+        assert_eq!(
+            features[2]["properties"],
+            serde_json::json!({ "source": 0, "target": 2, "distance": 3 })
+        );
+        assert!(features[3]["geometry"].is_null());
+        assert!(features[3]["properties"]["distance"].is_null());
+    }
+
+    #[test]
+    fn writes_json() {
+        let (paths, dists) = sample();
+        let mut out = vec![];
+        Results::new(0, &paths, &dists, 10)
+            .write_json(&mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#"{"source":0,"time_ns":10,"vertices":["#,
+                r#"{"vertex":0,"distance":0,"path":[0]},"#,
+                r#"{"vertex":1,"distance":5,"path":[0,2,1]},"#,
+                r#"{"vertex":2,"distance":3,"path":[0,2]},"#,
+                r#"{"vertex":3,"distance":null,"path":null}]}"#,
+                "\n"
+            )
+        );
+    }
+
+    fn tree() -> Vec<TreeEdge> {
+        vec![
+            TreeEdge {
+                from: 1,
+                to: 2,
+                weight: 1,
+            },
+            TreeEdge {
+                from: 0,
+                to: 1,
+                weight: 3,
+            },
+        ]
+    }
+
+    #[test]
+    fn writes_tree_csv() {
+        let edges = tree();
+        let mut out = vec![];
+        TreeResults {
+            time_ns: 10,
+            total_weight: 4,
+            edges: &edges,
+        }
+        .write_csv(&mut out)
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "from,to,weight\n1,2,1\n0,1,3\n"
+        );
+    }
+
+    #[test]
+    fn writes_tree_dot() {
+        let graph = Graph::new(vec![vec![(1, 3), (2, 9)], vec![(2, 1)], vec![(0, 8)]]);
+        let edges = tree();
+        let mut out = vec![];
+        TreeResults {
+            time_ns: 10,
+            total_weight: 4,
+            edges: &edges,
+        }
+        .write_dot(&graph, &mut out)
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "graph {\n",
+                "    0 [shape=circle];\n",
+                "    1 [shape=circle];\n",
+                "    2 [shape=circle];\n",
+                "    0 -- 1 [label=\"3\", color=red, penwidth=2];\n",
+                "    0 -- 2 [label=\"8\", color=gray];\n",
+                "    1 -- 2 [label=\"1\", color=red, penwidth=2];\n",
+                "}\n"
+            )
+        );
+    }
+}
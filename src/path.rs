@@ -0,0 +1,166 @@
+//! A path through a graph, with the weights of its edges.
+//!
+use alloc::vec::Vec;
+use core::{fmt, mem};
+
+/// Vertices of a path together with the weight of every edge between them,
+/// so that the path can be annotated without looking the edges up again.
+///
+/// # Example
+/// ```
+/// use djikstra::path::Path;
+///
+/// let path = Path::new(vec![0, 1, 2], vec![3, 1]);
+/// assert_eq!(path.cost(), 4);
+/// assert_eq!(path.len(), 2);
+/// assert_eq!(path.edges().collect::<Vec<_>>(), vec![(0, 1, 3), (1, 2, 1)]);
+/// assert!(path.contains(1));
+/// assert_eq!(path.to_string(), "0 -> 1 -> 2");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    vertices: Vec<usize>,
+    // weight of the edge from every vertex to the next one
+    weights: Vec<usize>,
+}
+
+impl Path {
+    /// Path through `vertices`, where `weights[i]` is the weight of the edge
+    /// from `vertices[i]` to `vertices[i + 1]`.
+    ///
+    /// # Panics
+    /// If `vertices` is empty, or there is not exactly one weight less than vertices.
+    pub fn new(vertices: Vec<usize>, weights: Vec<usize>) -> Self {
+        assert!(!vertices.is_empty(), "a path has at least one vertex");
+        assert_eq!(
+            weights.len() + 1,
+            vertices.len(),
+            "a path needs one weight per edge"
+        );
+        Self { vertices, weights }
+    }
+
+    /// Path through `vertices` whose distances from the start of the path are
+    /// `dists`, taking the weight of every edge as the difference of the
+    /// distances of its vertices.
+    pub(crate) fn from_distances(vertices: Vec<usize>, dists: &[Option<usize>]) -> Self {
+        let weights = vertices
+            .windows(2)
+            .map(|edge| {
+                dists[edge[1]]
+                    .zip(dists[edge[0]])
+                    .map_or(0, |(to, from)| to.saturating_sub(from))
+            })
+            .collect();
+        Self::new(vertices, weights)
+    }
+
+    /// Total weight of the edges.
+    pub fn cost(&self) -> usize {
+        self.weights.iter().sum()
+    }
+
+    /// Number of edges.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Whether the path has no edges, i.e. stays at its first vertex.
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// The vertices in order, both ends included.
+    pub fn vertices(&self) -> &[usize] {
+        &self.vertices
+    }
+
+    /// Every edge in order as `(from, to, weight)`.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        self.vertices
+            .windows(2)
+            .zip(self.weights.iter())
+            .map(|(edge, &weight)| (edge[0], edge[1], weight))
+    }
+
+    /// Whether the path goes through `v`.
+    pub fn contains(&self, v: usize) -> bool {
+        self.vertices.contains(&v)
+    }
+
+    /// Number of bytes the path holds on the heap.
+    pub fn memory_usage(&self) -> usize {
+        (self.vertices.capacity() + self.weights.capacity()) * mem::size_of::<usize>()
+    }
+
+    /// Continue the path with `next`, which starts where the path ends.
+    ///
+    /// # Panics
+    /// If `next` does not start at the last vertex of the path.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::path::Path;
+    ///
+    /// let mut path = Path::new(vec![0, 1], vec![3]);
+    /// path.append(Path::new(vec![1, 2], vec![1]));
+    /// assert_eq!(path, Path::new(vec![0, 1, 2], vec![3, 1]));
+    /// ```
+    pub fn append(&mut self, next: Path) {
+        assert_eq!(
+            self.vertices.last(),
+            next.vertices.first(),
+            "the next path starts where the path ends"
+        );
+        self.vertices.extend_from_slice(&next.vertices[1..]);
+        self.weights.extend(next.weights);
+    }
+
+    /// The vertices, without the weights.
+    pub fn into_vertices(self) -> Vec<usize> {
+        self.vertices
+    }
+}
+
+impl fmt::Display for Path {
+    /// Write the vertices separated by `->`, as in the output of `run`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.vertices[0])?;
+        for vertex in self.vertices.iter().skip(1) {
+            write!(f, " -> {}", vertex)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::path::Path;
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn single_vertex_path() {
+        let path = Path::new(vec![4], vec![]);
+        assert_eq!(path.cost(), 0);
+        assert!(path.is_empty());
+        assert_eq!(path.edges().count(), 0);
+        assert_eq!(path.to_string(), "4");
+    }
+
+    #[test]
+    fn weights_from_distances() {
+        let dists = [Some(0), Some(7), Some(3), None];
+        let path = Path::from_distances(vec![0, 2, 1], &dists);
+        assert_eq!(path.edges().collect::<Vec<_>>(), vec![(0, 2, 3), (2, 1, 4)]);
+        assert_eq!(path.cost(), 7);
+        assert!(!path.contains(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "one weight per edge")]
+    fn needs_one_weight_per_edge() {
+        Path::new(vec![0, 1], vec![]);
+    }
+}
@@ -1,33 +1,66 @@
 //! Memory safe minimum priority queue implementations.
 //!
-use std::{collections::HashMap, hash::Hash};
+use alloc::collections::{BTreeMap, BinaryHeap};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::mem;
+
+/// Estimated number of bytes a `BTreeMap<K, V>` of `len` entries holds on the
+/// heap, with nodes of 11 entries two thirds full on average.
+pub(crate) fn btree_memory<K, V>(len: usize) -> usize {
+    const NODE_CAPACITY: usize = 11;
+    // the entries, plus the parent pointer, the index in the parent and the length
+    let node =
+        NODE_CAPACITY * (mem::size_of::<K>() + mem::size_of::<V>()) + 2 * mem::size_of::<usize>();
+    (len * 3).div_ceil(NODE_CAPACITY * 2) * node
+}
+
+/// Interface shared by all the min priority queue backends so the algorithm
+/// can be run (and benchmarked) with any of them.
+pub trait MinPriorityQueue<T> {
+    /// Create a new queue from an iterator of elements without keys.
+    /// Keys will be set to usize::MAX as default
+    fn from_keys<I>(input: I) -> Self
+    where
+        I: IntoIterator<Item = T>;
+
+    /// Change the key for an element in the priority queue.
+    /// Elements that are not in the queue are ignored.
+    fn change_key(&mut self, element: &T, key: usize);
+
+    /// Extract the element with the smallest key from the queue, the smallest
+    /// element of those with the same key.
+    /// Returns the element and its associated key as a tuple.
+    fn extract_min(&mut self) -> Option<(T, usize)>;
+}
 
 /// Non-performant and easy min priority queue implementation.
 ///
-/// Uses a HashMap under the hood with a generic key and value of type ```usize```.
+/// Uses a BTreeMap under the hood with a generic key and value of type ```usize```.
 /// Key contains the associated weight of its corresponding element in the priority queue.
 /// A key cannot have a value below 0.
 pub struct PriorityQueue<T>
 where
     T: Ord,
 {
-    pub map: HashMap<T, usize>,
+    pub map: BTreeMap<T, usize>,
 }
 
 impl<T> Default for PriorityQueue<T>
 where
-    T: Ord + Hash + Clone,
+    T: Ord + Clone,
 {
     fn default() -> Self {
         Self {
-            map: HashMap::new(),
+            map: BTreeMap::new(),
         }
     }
 }
 
 impl<T> PriorityQueue<T>
 where
-    T: Ord + Hash + Clone,
+    T: Ord + Clone,
 {
     /// Create a new PriorityQueue with no elements.
     pub fn new() -> Self {
@@ -41,7 +74,7 @@ where
         I: IntoIterator<Item = T>,
     {
         Self {
-            map: HashMap::from_iter(input.into_iter().map(|item| (item, usize::MAX))),
+            map: BTreeMap::from_iter(input.into_iter().map(|item| (item, usize::MAX))),
         }
     }
 
@@ -56,7 +89,7 @@ where
         I: IntoIterator<Item = (T, usize)>,
     {
         Self {
-            map: HashMap::from_iter(input),
+            map: BTreeMap::from_iter(input),
         }
     }
     /// Insert a new element with its key into the priority queue.
@@ -98,11 +131,383 @@ where
             None
         }
     }
+
+    /// Estimated number of bytes the queue holds on the heap.
+    pub fn memory_usage(&self) -> usize {
+        btree_memory::<T, usize>(self.map.len())
+    }
+}
+
+impl<T> MinPriorityQueue<T> for PriorityQueue<T>
+where
+    T: Ord + Clone,
+{
+    fn from_keys<I>(input: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        PriorityQueue::from_keys(input)
+    }
+
+    fn change_key(&mut self, element: &T, key: usize) {
+        PriorityQueue::change_key(self, element, key)
+    }
+
+    fn extract_min(&mut self) -> Option<(T, usize)> {
+        PriorityQueue::extract_min(self)
+    }
+}
+
+/// Binary heap based min priority queue.
+///
+/// Uses the standard library's ```BinaryHeap``` with lazy deletion: changing a key
+/// pushes a new entry and stale entries are skipped when extracting.
+/// The current key of every element still in the queue is kept in a BTreeMap.
+pub struct BinaryHeapQueue<T>
+where
+    T: Ord,
+{
+    heap: BinaryHeap<Reverse<(usize, T)>>,
+    keys: BTreeMap<T, usize>,
+}
+
+impl<T> Default for BinaryHeapQueue<T>
+where
+    T: Ord + Clone,
+{
+    fn default() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> BinaryHeapQueue<T>
+where
+    T: Ord + Clone,
+{
+    /// Create a new BinaryHeapQueue with no elements.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a new element with its key into the priority queue.
+    pub fn insert(&mut self, element: T, key: usize) {
+        self.keys.insert(element.clone(), key);
+        self.heap.push(Reverse((key, element)));
+    }
+
+    /// Estimated number of bytes the queue holds on the heap, the entries
+    /// left behind by changed keys included.
+    pub fn memory_usage(&self) -> usize {
+        self.heap.capacity() * mem::size_of::<Reverse<(usize, T)>>()
+            + btree_memory::<T, usize>(self.keys.len())
+    }
+}
+
+impl<T> MinPriorityQueue<T> for BinaryHeapQueue<T>
+where
+    T: Ord + Clone,
+{
+    fn from_keys<I>(input: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut pq = Self::new();
+        for element in input {
+            pq.insert(element, usize::MAX);
+        }
+        pq
+    }
+
+    fn change_key(&mut self, element: &T, key: usize) {
+        if let Some(obj) = self.keys.get_mut(element) {
+            *obj = key;
+            self.heap.push(Reverse((key, element.clone())));
+        }
+    }
+
+    fn extract_min(&mut self) -> Option<(T, usize)> {
+        while let Some(Reverse((key, element))) = self.heap.pop() {
+            // skip entries whose key has since been changed
+            if self.keys.get(&element) == Some(&key) {
+                self.keys.remove(&element);
+                return Some((element, key));
+            }
+        }
+        None
+    }
+}
+
+/// D-ary heap based min priority queue, 4-ary by default.
+///
+/// Keeps one entry per element and moves it when its key changes, with the
+/// position of every element in the heap kept in a BTreeMap. Entries of equal
+/// keys are extracted by increasing element, as in the other queues. A wider heap is
+/// shallower, so changing a key moves an entry fewer times, at the cost of
+/// comparing more children when extracting.
+pub struct DaryHeapQueue<T, const D: usize = 4>
+where
+    T: Ord,
+{
+    heap: Vec<(usize, T)>,
+    positions: BTreeMap<T, usize>,
+}
+
+impl<T, const D: usize> Default for DaryHeapQueue<T, D>
+where
+    T: Ord + Clone,
+{
+    fn default() -> Self {
+        Self {
+            heap: Vec::new(),
+            positions: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T, const D: usize> DaryHeapQueue<T, D>
+where
+    T: Ord + Clone,
+{
+    /// Create a new DaryHeapQueue with no elements.
+    ///
+    /// # Panics
+    /// If `D` is below 2.
+    pub fn new() -> Self {
+        assert!(D >= 2, "a heap needs at least 2 children per entry");
+        Self::default()
+    }
+
+    /// Insert a new element with its key into the priority queue, or change
+    /// its key if it is already in it.
+    pub fn insert(&mut self, element: T, key: usize) {
+        if self.positions.contains_key(&element) {
+            MinPriorityQueue::change_key(self, &element, key);
+            return;
+        }
+        self.positions.insert(element.clone(), self.heap.len());
+        self.heap.push((key, element));
+        self.sift_up(self.heap.len() - 1);
+    }
+
+    /// Estimated number of bytes the queue holds on the heap.
+    pub fn memory_usage(&self) -> usize {
+        self.heap.capacity() * mem::size_of::<(usize, T)>()
+            + btree_memory::<T, usize>(self.positions.len())
+    }
+
+    /// Swap the entries at `i` and `j`, keeping their positions up to date.
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        for k in [i, j] {
+            *self.positions.get_mut(&self.heap[k].1).unwrap() = k;
+        }
+    }
+
+    /// Move the entry at `i` up while it is below its parent.
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 && self.heap[i] < self.heap[(i - 1) / D] {
+            self.swap(i, (i - 1) / D);
+            i = (i - 1) / D;
+        }
+    }
+
+    /// Move the entry at `i` down while a child is below it.
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let children = (D * i + 1)..(D * i + 1 + D).min(self.heap.len());
+            let Some(child) = children.min_by(|&a, &b| self.heap[a].cmp(&self.heap[b])) else {
+                return;
+            };
+            if self.heap[child] >= self.heap[i] {
+                return;
+            }
+            self.swap(i, child);
+            i = child;
+        }
+    }
+}
+
+impl<T, const D: usize> MinPriorityQueue<T> for DaryHeapQueue<T, D>
+where
+    T: Ord + Clone,
+{
+    fn from_keys<I>(input: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut pq = Self::new();
+        for element in input {
+            pq.insert(element, usize::MAX);
+        }
+        pq
+    }
+
+    fn change_key(&mut self, element: &T, key: usize) {
+        let Some(&i) = self.positions.get(element) else {
+            return;
+        };
+        let old = mem::replace(&mut self.heap[i].0, key);
+        if key < old {
+            self.sift_up(i);
+        } else {
+            self.sift_down(i);
+        }
+    }
+
+    fn extract_min(&mut self) -> Option<(T, usize)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let last = self.heap.len() - 1;
+        self.swap(0, last);
+        let (key, element) = self.heap.pop().unwrap();
+        self.positions.remove(&element);
+        self.sift_down(0);
+        Some((element, key))
+    }
+}
+
+/// Radix heap based monotone min priority queue.
+///
+/// Entries are kept in buckets by the highest bit where their key differs
+/// from the last extracted key, and a bucket is only sorted out again when
+/// the lower ones are empty, so every entry moves at most once per bit of
+/// its key. The entries with the last extracted key are kept in a binary
+/// heap, so that ties are extracted by increasing element as in the other
+/// queues. Like [`BinaryHeapQueue`], changing a key pushes a new entry and
+/// stale entries are skipped.
+///
+/// Keys can only be set to values at least the last extracted one, as is the
+/// case in Dijkstra's algorithm, which only ever lowers the distance of a
+/// vertex to one at least the distance of the vertex being settled.
+pub struct RadixHeapQueue<T>
+where
+    T: Ord,
+{
+    // the elements whose key is `last`
+    current: BinaryHeap<Reverse<T>>,
+    // the entries whose key differs from `last` first at bit `i`, in `buckets[i]`
+    buckets: Vec<Vec<(usize, T)>>,
+    keys: BTreeMap<T, usize>,
+    last: usize,
+}
+
+impl<T> Default for RadixHeapQueue<T>
+where
+    T: Ord + Clone,
+{
+    fn default() -> Self {
+        Self {
+            current: BinaryHeap::new(),
+            buckets: vec![Vec::new(); usize::BITS as usize],
+            keys: BTreeMap::new(),
+            last: 0,
+        }
+    }
+}
+
+impl<T> RadixHeapQueue<T>
+where
+    T: Ord + Clone,
+{
+    /// Create a new RadixHeapQueue with no elements.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a new element with its key into the priority queue.
+    ///
+    /// # Panics
+    /// If `key` is below the last extracted key.
+    pub fn insert(&mut self, element: T, key: usize) {
+        assert!(
+            key >= self.last,
+            "key {} is below the last extracted key {}",
+            key,
+            self.last
+        );
+        self.keys.insert(element.clone(), key);
+        self.push(key, element);
+    }
+
+    /// Estimated number of bytes the queue holds on the heap, the entries
+    /// left behind by changed keys included.
+    pub fn memory_usage(&self) -> usize {
+        let entries: usize = self.buckets.iter().map(Vec::capacity).sum();
+        self.current.capacity() * mem::size_of::<T>()
+            + self.buckets.capacity() * mem::size_of::<Vec<(usize, T)>>()
+            + entries * mem::size_of::<(usize, T)>()
+            + btree_memory::<T, usize>(self.keys.len())
+    }
+
+    /// Put an entry in the heap of the last extracted key or in its bucket.
+    fn push(&mut self, key: usize, element: T) {
+        match (key ^ self.last).checked_ilog2() {
+            None => self.current.push(Reverse(element)),
+            Some(bit) => self.buckets[bit as usize].push((key, element)),
+        }
+    }
+}
+
+impl<T> MinPriorityQueue<T> for RadixHeapQueue<T>
+where
+    T: Ord + Clone,
+{
+    fn from_keys<I>(input: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut pq = Self::new();
+        for element in input {
+            pq.insert(element, usize::MAX);
+        }
+        pq
+    }
+
+    /// Change the key for an element in the priority queue.
+    /// Elements that are not in the queue are ignored.
+    ///
+    /// # Panics
+    /// If `key` is below the last extracted key.
+    fn change_key(&mut self, element: &T, key: usize) {
+        if self.keys.contains_key(element) {
+            self.insert(element.clone(), key);
+        }
+    }
+
+    fn extract_min(&mut self) -> Option<(T, usize)> {
+        loop {
+            while let Some(Reverse(element)) = self.current.pop() {
+                if self.keys.get(&element) == Some(&self.last) {
+                    self.keys.remove(&element);
+                    return Some((element, self.last));
+                }
+            }
+
+            let i = self.buckets.iter().position(|bucket| !bucket.is_empty())?;
+            let mut entries = mem::take(&mut self.buckets[i]);
+            entries.retain(|(key, element)| self.keys.get(element) == Some(key));
+            if let Some(min) = entries.iter().map(|(key, _element)| *key).min() {
+                self.last = min;
+            }
+            // every entry of the bucket goes to a lower one
+            for (key, element) in entries {
+                self.push(key, element);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::pq::PriorityQueue;
+    use crate::pq::{
+        BinaryHeapQueue, DaryHeapQueue, MinPriorityQueue, PriorityQueue, RadixHeapQueue,
+    };
+    use alloc::vec;
+    use alloc::vec::Vec;
 
     #[test]
     fn removes_minimum() {
@@ -131,4 +536,108 @@ mod tests {
         let min = pq.extract_min();
         assert_eq!(min, None)
     }
+
+    #[test]
+    fn binary_heap_changes_key() {
+        let mut pq: BinaryHeapQueue<usize> = MinPriorityQueue::from_keys(0..4);
+        pq.change_key(&0, 0);
+        pq.change_key(&1, 4);
+        pq.change_key(&2, 1);
+        pq.change_key(&1, 3);
+        assert_eq!(pq.extract_min(), Some((0, 0)));
+        assert_eq!(pq.extract_min(), Some((2, 1)));
+        assert_eq!(pq.extract_min(), Some((1, 3)));
+        assert_eq!(pq.extract_min(), Some((3, usize::MAX)));
+        assert_eq!(pq.extract_min(), None);
+    }
+
+    #[test]
+    fn binary_heap_ignores_extracted() {
+        let mut pq: BinaryHeapQueue<usize> = MinPriorityQueue::from_keys(0..2);
+        pq.change_key(&0, 1);
+        assert_eq!(pq.extract_min(), Some((0, 1)));
+        pq.change_key(&0, 0);
+        assert_eq!(pq.extract_min(), Some((1, usize::MAX)));
+        assert_eq!(pq.extract_min(), None);
+    }
+
+    /// Extract every element of a queue of `0..8` after a fixed sequence of
+    /// monotone key changes, as Dijkstra's algorithm makes them.
+    fn extract_all<Q: MinPriorityQueue<usize>>() -> Vec<(usize, usize)> {
+        let mut pq = Q::from_keys(0..8);
+        pq.change_key(&3, 0);
+        pq.change_key(&5, 9);
+        pq.change_key(&1, 40);
+        let mut extracted = vec![pq.extract_min().unwrap()];
+        pq.change_key(&1, 7);
+        pq.change_key(&6, 7);
+        pq.change_key(&5, 8);
+        pq.change_key(&3, 5);
+        extracted.push(pq.extract_min().unwrap());
+        pq.change_key(&0, 1000);
+        pq.change_key(&2, 8);
+        while let Some(entry) = pq.extract_min() {
+            extracted.push(entry);
+        }
+        extracted
+    }
+
+    #[test]
+    fn backends_extract_in_the_same_order() {
+        let expected = vec![
+            (3, 0),
+            (1, 7),
+            (6, 7),
+            (2, 8),
+            (5, 8),
+            (0, 1000),
+            (4, usize::MAX),
+            (7, usize::MAX),
+        ];
+        assert_eq!(extract_all::<PriorityQueue<usize>>(), expected);
+        assert_eq!(extract_all::<BinaryHeapQueue<usize>>(), expected);
+        assert_eq!(extract_all::<DaryHeapQueue<usize>>(), expected);
+        assert_eq!(extract_all::<DaryHeapQueue<usize, 2>>(), expected);
+        assert_eq!(extract_all::<RadixHeapQueue<usize>>(), expected);
+    }
+
+    #[test]
+    fn dary_heap_stays_ordered() {
+        let mut pq: DaryHeapQueue<usize, 3> = DaryHeapQueue::new();
+        // a pseudo random permutation of the keys
+        for i in 0..100 {
+            pq.insert(i, (i * 37) % 101);
+        }
+        pq.change_key(&50, 0);
+        pq.change_key(&0, 500);
+        let keys: Vec<usize> =
+            core::iter::from_fn(|| pq.extract_min().map(|(_e, key)| key)).collect();
+        assert_eq!(keys.len(), 100);
+        assert!(keys.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!((keys[0], keys[99]), (0, 500));
+    }
+
+    #[test]
+    #[should_panic]
+    fn radix_heap_is_monotone() {
+        let mut pq: RadixHeapQueue<usize> = MinPriorityQueue::from_keys(0..2);
+        pq.change_key(&0, 5);
+        pq.extract_min();
+        pq.change_key(&1, 4);
+    }
+
+    #[test]
+    fn memory_shrinks_with_the_queue() {
+        let mut pq: PriorityQueue<usize> = MinPriorityQueue::from_keys(0..100);
+        let full = pq.memory_usage();
+        assert!(full >= 100 * 2 * core::mem::size_of::<usize>());
+        for _ in 0..90 {
+            pq.extract_min();
+        }
+        assert!(pq.memory_usage() < full);
+        assert_eq!(PriorityQueue::<usize>::new().memory_usage(), 0);
+
+        let heap: BinaryHeapQueue<usize> = MinPriorityQueue::from_keys(0..100);
+        assert!(heap.memory_usage() > full);
+    }
 }
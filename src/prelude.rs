@@ -0,0 +1,29 @@
+//! The graph, the algorithms and their results in one place, to import them
+//! all at once instead of through their modules.
+//!
+//! ```
+//! use djikstra::prelude::*;
+//!
+//! let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+//! let result = shortest_paths(&graph, 0)?;
+//! assert_eq!(result.dist_to(2), Some(4));
+//! assert_eq!(shortest_path(&graph, 0, 2)?, Some((vec![0, 1, 2], 4)));
+//!
+//! let (_paths, dists) = dijkstra_with::<BinaryHeapQueue<usize>>(&graph, 0)?;
+//! assert_eq!(dists, astar(&graph, 0, |_vertex| 0)?.1);
+//! # Ok::<(), Error>(())
+//! ```
+pub use crate::astar::{astar, astar_path};
+pub use crate::bellman_ford::bellman_ford;
+pub use crate::bfs::bfs;
+pub use crate::csr::CsrGraph;
+pub use crate::dijkstra::{
+    batch_shortest_paths, dijkstra, dijkstra_with, shortest_path, shortest_path_tree,
+    shortest_paths, DijkstraError, DijkstraResult, PathAndDist, PathsAndDists, ShortestPathTree,
+};
+pub use crate::error::Error;
+pub use crate::graph::{Adjacency, Graph, IndexType, ParseGraphError};
+pub use crate::path::Path;
+pub use crate::pq::{
+    BinaryHeapQueue, DaryHeapQueue, MinPriorityQueue, PriorityQueue, RadixHeapQueue,
+};
@@ -0,0 +1,278 @@
+//! Interactive queries on a graph kept in memory between commands.
+//!
+use crate::dijkstra::{dijkstra, DijkstraError};
+use crate::editor::{Edit, GraphEditor};
+use crate::graph::Graph;
+use crate::output::Results;
+use crate::stats::GraphStats;
+use std::io::{self, Write};
+
+/// Help text listing the commands the REPL understands.
+pub const HELP: &str = "\
+Commands:
+  path <from> <to>   shortest path and distance between two vertices
+  dist <from> [to]   distances from a vertex to every vertex, or to one vertex
+  stats              summary statistics of the graph
+  add-edge <from> <to> <weight>
+                     add an edge
+  remove-edge <from> <to>
+                     remove every edge from a vertex to another
+  set-weight <from> <to> <weight>
+                     set the weight of every edge from a vertex to another
+  undo               undo the last edit
+  redo               apply again the last edit undone
+  journal            the edits applied, to replay them later
+  help               show this help
+  quit               leave the REPL
+";
+
+/// Interpreter for REPL commands on a graph.
+///
+/// The shortest path tree of the last source queried is kept, so that
+/// several queries from the same source only run the algorithm once.
+pub struct Repl<'a> {
+    graph: Target<'a>,
+    // source, paths and distances of the last tree computed
+    source: Option<usize>,
+    paths: Vec<Option<Vec<usize>>>,
+    dists: Vec<Option<usize>>,
+}
+
+/// The graph of a REPL, which can only be edited if the REPL owns it.
+enum Target<'a> {
+    Borrowed(&'a Graph),
+    Editing(GraphEditor),
+}
+
+impl Repl<'static> {
+    /// Create a REPL answering queries on the graph of `editor` and editing
+    /// it, with its commands recorded in the journal of the editor.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::editor::GraphEditor;
+    /// use djikstra::graph::Graph;
+    /// use djikstra::repl::Repl;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3)], vec![]]);
+    /// let mut repl = Repl::editing(GraphEditor::new(graph));
+    /// let mut out = vec![];
+    /// for command in ["set-weight 0 1 9", "dist 0 1", "undo", "dist 0 1"] {
+    ///     repl.execute(command, &mut out).unwrap();
+    /// }
+    /// assert_eq!(String::from_utf8(out).unwrap(), "9\nundone set-weight 0 1 9\n3\n");
+    /// ```
+    pub fn editing(editor: GraphEditor) -> Self {
+        Self {
+            graph: Target::Editing(editor),
+            source: None,
+            paths: vec![],
+            dists: vec![],
+        }
+    }
+}
+
+impl<'a> Repl<'a> {
+    /// Create a REPL answering queries on `graph`, which it cannot edit.
+    pub fn new(graph: &'a Graph) -> Self {
+        Self {
+            graph: Target::Borrowed(graph),
+            source: None,
+            paths: vec![],
+            dists: vec![],
+        }
+    }
+
+    /// The graph the queries are answered on, with the edits applied.
+    pub fn graph(&self) -> &Graph {
+        match &self.graph {
+            Target::Borrowed(graph) => graph,
+            Target::Editing(editor) => editor.graph(),
+        }
+    }
+
+    /// The editor of the graph, `None` if the REPL cannot edit it.
+    pub fn editor(&self) -> Option<&GraphEditor> {
+        match &self.graph {
+            Target::Borrowed(_) => None,
+            Target::Editing(editor) => Some(editor),
+        }
+    }
+
+    /// Execute a single command line, writing its output to `w`.
+    /// Returns `false` if the REPL should stop.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    /// use djikstra::repl::Repl;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3)], vec![]]);
+    /// let mut repl = Repl::new(&graph);
+    /// let mut out = vec![];
+    /// repl.execute("path 0 1", &mut out).unwrap();
+    /// assert_eq!(String::from_utf8(out).unwrap(), "1 3 (0 -> 1)\n");
+    /// ```
+    pub fn execute<W: Write + ?Sized>(&mut self, line: &str, w: &mut W) -> io::Result<bool> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if let Some(&("add-edge" | "remove-edge" | "set-weight" | "undo" | "redo" | "journal")) =
+            words.first()
+        {
+            return self.edit(line, words[0], w).map(|_| true);
+        }
+        let n_vertices = self.graph().n_vertices();
+        let vertices: Option<Vec<usize>> = words
+            .iter()
+            .skip(1)
+            .map(|word| word.parse().ok().filter(|&v| v < n_vertices))
+            .collect();
+
+        let command = (words.first().copied(), vertices.as_deref());
+        // `path` and `dist` need the paths and distances from their first vertex
+        if let (Some("path" | "dist"), Some(&[from, ..])) = command {
+            if let Err(e) = self.tree_from(from) {
+                writeln!(w, "error: {}", e)?;
+                return Ok(true);
+            }
+        }
+
+        match command {
+            (None, _) => {}
+            (Some("quit" | "exit"), _) => return Ok(false),
+            (Some("help"), _) => write!(w, "{}", HELP)?,
+            (Some("stats"), _) => write!(w, "{}", GraphStats::of(self.graph()))?,
+            (Some("path"), Some(&[from, to])) => {
+                let mut results = Results::new(from, &self.paths, &self.dists, 0);
+                results.vertices.retain(|row| row.vertex == to);
+                results.write_text(w)?;
+            }
+            (Some("dist"), Some(&[from])) => {
+                Results::new(from, &self.paths, &self.dists, 0).write_text(w)?
+            }
+            (Some("dist"), Some(&[_from, to])) => match self.dists[to] {
+                None => writeln!(w, "inf")?,
+                Some(dist) => writeln!(w, "{}", dist)?,
+            },
+            (Some("path" | "dist"), _) => writeln!(
+                w,
+                "error: expected vertices below {}, see `help`",
+                n_vertices
+            )?,
+            (Some(command), _) => writeln!(w, "error: unknown command {:?}, see `help`", command)?,
+        }
+        Ok(true)
+    }
+
+    /// Execute the edit command `line`, whose first word is `command`.
+    fn edit<W: Write + ?Sized>(&mut self, line: &str, command: &str, w: &mut W) -> io::Result<()> {
+        let Target::Editing(editor) = &mut self.graph else {
+            return writeln!(w, "error: the graph of this REPL cannot be edited");
+        };
+        let done = match command {
+            "undo" => editor.undo().map(|edit| format!("undone {}", edit)),
+            "redo" => editor.redo().map(|edit| format!("redone {}", edit)),
+            "journal" => {
+                for edit in editor.journal() {
+                    writeln!(w, "{}", edit)?;
+                }
+                return Ok(());
+            }
+            _ => {
+                let applied = line.parse::<Edit>().and_then(|edit| editor.apply(edit));
+                if let Err(e) = applied {
+                    return writeln!(w, "error: {}", e);
+                }
+                None
+            }
+        };
+        match (command, done) {
+            ("undo" | "redo", None) => writeln!(w, "error: nothing to {}", command)?,
+            (_, Some(done)) => writeln!(w, "{}", done)?,
+            _ => {}
+        }
+        // the paths of the last source may have changed
+        self.source = None;
+        Ok(())
+    }
+
+    /// Compute the paths and distances from `src`, unless `src` is the last source.
+    fn tree_from(&mut self, src: usize) -> Result<(), DijkstraError> {
+        if self.source != Some(src) {
+            (self.paths, self.dists) = dijkstra(self.graph(), src)?;
+            self.source = Some(src);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(commands: &[&str]) -> String {
+        let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![], vec![]]);
+        let mut repl = Repl::new(&graph);
+        let mut out = vec![];
+        for command in commands {
+            if !repl.execute(command, &mut out).unwrap() {
+                break;
+            }
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn answers_queries() {
+        assert_eq!(
+            run(&["path 0 2", "dist 0 3", "dist 1", ""]),
+            "2 4 (0 -> 1 -> 2)\ninf\n0 inf\n1 0 (1)\n2 1 (1 -> 2)\n3 inf\n"
+        );
+    }
+
+    #[test]
+    fn reports_errors_and_quits() {
+        assert_eq!(
+            run(&["path 0 9", "walk", "quit", "path 0 1"]),
+            "error: expected vertices below 4, see `help`\nerror: unknown command \"walk\", see `help`\n"
+        );
+    }
+
+    #[test]
+    fn edits_undo_and_redo() {
+        let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![], vec![]]);
+        let mut repl = Repl::editing(GraphEditor::new(graph));
+        let mut out = vec![];
+        for command in [
+            "dist 0 3",
+            "add-edge 2 3 5",
+            "dist 0 3",
+            "remove-edge 0 2",
+            "undo",
+            "undo",
+            "undo",
+            "dist 0 3",
+            "redo",
+            "journal",
+            "set-weight 9 1 1",
+        ] {
+            repl.execute(command, &mut out).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "inf\n9\nerror: there is no edge from 0 to 2\nundone add-edge 2 3 5\n\
+             error: nothing to undo\nerror: nothing to undo\ninf\nredone add-edge 2 3 5\n\
+             add-edge 2 3 5\nerror: vertex 9 is not in the graph of 4 vertices\n"
+        );
+        assert_eq!(repl.editor().unwrap().journal().len(), 1);
+        assert_eq!(repl.graph().n_edges(), 3);
+
+        let mut out = vec![];
+        Repl::new(&Graph::new(vec![]))
+            .execute("undo", &mut out)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "error: the graph of this REPL cannot be edited\n"
+        );
+    }
+}
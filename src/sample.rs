@@ -0,0 +1,234 @@
+//! Sampling small graphs out of large ones, to prototype on before running
+//! on the full graph.
+//!
+//! The samplers pick a set of vertices, sorted by their original index, and
+//! `induced_subgraph` keeps the edges between them.
+use crate::graph::Graph;
+use rand::{Rng, RngExt};
+use std::collections::VecDeque;
+
+/// Graph of the edges between `vertices`, which are renumbered by their
+/// position in the slice.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::sample::induced_subgraph;
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 1)], vec![(2, 1)], vec![(0, 2)]]);
+/// let sample = induced_subgraph(&graph, &[0, 2]);
+/// assert_eq!(sample, Graph::new(vec![vec![(1, 1)], vec![(0, 2)]]));
+/// ```
+pub fn induced_subgraph(graph: &Graph, vertices: &[usize]) -> Graph {
+    let mut index = vec![usize::MAX; graph.n_vertices()];
+    for (new, &old) in vertices.iter().enumerate() {
+        index[old] = new;
+    }
+    let adj = vertices
+        .iter()
+        .map(|&old| {
+            graph
+                .neighbors_of(old)
+                .iter()
+                .filter(|&&(v, _)| index[v] != usize::MAX)
+                .map(|&(v, weight)| (index[v], weight))
+                .collect()
+        })
+        .collect();
+    Graph::new(adj)
+}
+
+/// `size` distinct vertices chosen uniformly at random, always including `start`.
+/// Returns all the vertices if the graph has at most `size`.
+///
+/// The induced subgraph of a uniform sample of a sparse graph has few edges
+/// and is rarely connected, see `forest_fire` for a connected sample.
+///
+/// # Panics
+/// If `start` is not a vertex of the graph.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::sample::random_vertices;
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let graph = Graph::new(vec![vec![]; 10]);
+/// let vertices = random_vertices(&graph, 4, 7, &mut StdRng::seed_from_u64(42));
+/// assert_eq!(vertices.len(), 4);
+/// assert!(vertices.contains(&7));
+/// ```
+pub fn random_vertices<R: Rng + ?Sized>(
+    graph: &Graph,
+    size: usize,
+    start: usize,
+    rng: &mut R,
+) -> Vec<usize> {
+    assert!(
+        start < graph.n_vertices(),
+        "start {} is out of {}",
+        start,
+        graph.n_vertices()
+    );
+    let mut candidates: Vec<usize> = (0..graph.n_vertices()).filter(|&v| v != start).collect();
+    let n_others = size.saturating_sub(1).min(candidates.len());
+    // partial Fisher-Yates shuffle of the first `n_others` candidates
+    for i in 0..n_others {
+        let j = rng.random_range(i..candidates.len());
+        candidates.swap(i, j);
+    }
+    let mut vertices = candidates[..n_others].to_vec();
+    vertices.push(start);
+    vertices.sort_unstable();
+    vertices
+}
+
+/// `size` vertices burned by a forest fire (Leskovec & Faloutsos) lit at
+/// `start`, ignoring edge directions. Returns all the vertices if the graph
+/// has at most `size`.
+///
+/// Every burning vertex sets fire to a geometrically distributed number of
+/// its unburned neighbors, with mean `p / (1 - p)`, so the sample keeps the
+/// local structure of the graph. When the fire dies out, it is lit again next
+/// to a random burned vertex, so the sample is connected as long as the
+/// component of `start` has `size` vertices.
+///
+/// # Panics
+/// If `start` is not a vertex of the graph.
+///
+/// # Example
+/// ```
+/// use djikstra::generate::grid;
+/// use djikstra::sample::forest_fire;
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let mut rng = StdRng::seed_from_u64(42);
+/// let graph = grid(100, 10, &mut rng);
+/// let vertices = forest_fire(&graph, 20, 0.7, 0, &mut rng);
+/// assert_eq!(vertices.len(), 20);
+/// assert!(vertices.contains(&0));
+/// ```
+pub fn forest_fire<R: Rng + ?Sized>(
+    graph: &Graph,
+    size: usize,
+    p: f64,
+    start: usize,
+    rng: &mut R,
+) -> Vec<usize> {
+    assert!(
+        start < graph.n_vertices(),
+        "start {} is out of {}",
+        start,
+        graph.n_vertices()
+    );
+    let undirected = graph.to_undirected();
+    let size = size.min(graph.n_vertices());
+    let mut burned = vec![false; graph.n_vertices()];
+    let mut vertices = vec![start];
+    let mut burning = VecDeque::from([start]);
+    burned[start] = true;
+
+    let unburned_neighbors = |burned: &[bool], v: usize| -> Vec<usize> {
+        undirected
+            .neighbors_of(v)
+            .iter()
+            .map(|&(u, _)| u)
+            .filter(|&u| !burned[u])
+            .collect()
+    };
+
+    while vertices.len() < size {
+        let Some(v) = burning.pop_front() else {
+            // the fire died out, light it again next to a random burned vertex
+            // that still has unburned neighbors, or anywhere if there are none
+            let offset = rng.random_range(0..vertices.len());
+            let next = (0..vertices.len())
+                .map(|i| vertices[(offset + i) % vertices.len()])
+                .find_map(|v| unburned_neighbors(&burned, v).first().copied())
+                .unwrap_or_else(|| {
+                    let unburned: Vec<usize> = (0..burned.len()).filter(|&u| !burned[u]).collect();
+                    unburned[rng.random_range(0..unburned.len())]
+                });
+            burned[next] = true;
+            vertices.push(next);
+            burning.push_back(next);
+            continue;
+        };
+
+        let mut neighbors = unburned_neighbors(&burned, v);
+        let mut n_burned = 0;
+        while n_burned < neighbors.len() && rng.random::<f64>() < p {
+            n_burned += 1;
+        }
+        for i in 0..n_burned.min(size - vertices.len()) {
+            let j = rng.random_range(i..neighbors.len());
+            neighbors.swap(i, j);
+            burned[neighbors[i]] = true;
+            vertices.push(neighbors[i]);
+            burning.push_back(neighbors[i]);
+        }
+    }
+
+    vertices.sort_unstable();
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generate::gnp;
+    use crate::graph::Graph;
+    use crate::sample::{forest_fire, induced_subgraph, random_vertices};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn induced_subgraph_keeps_inner_edges() {
+        let g1 = Graph::new(vec![
+            vec![(1, 1), (3, 2)],
+            vec![(2, 3)],
+            vec![(0, 4), (3, 5)],
+            vec![(1, 6)],
+        ]);
+        assert_eq!(
+            induced_subgraph(&g1, &[0, 2, 3]),
+            Graph::new(vec![vec![(2, 2)], vec![(0, 4), (2, 5)], vec![]])
+        );
+        assert_eq!(induced_subgraph(&g1, &[0, 1, 2, 3]), g1);
+    }
+
+    #[test]
+    fn forest_fire_sample_is_connected() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let g1 = gnp(500, 0.01, 10, &mut rng);
+        // vertex 0 is in the giant component
+        for p in [0.0, 0.3, 0.9] {
+            let vertices = forest_fire(&g1, 50, p, 0, &mut rng);
+            assert_eq!(vertices.len(), 50);
+            assert!(vertices.windows(2).all(|pair| pair[0] < pair[1]));
+
+            let sample = induced_subgraph(&g1, &vertices);
+            assert!(sample.components().iter().all(|&c| c == 0));
+        }
+    }
+
+    #[test]
+    fn samples_are_capped_by_the_graph() {
+        let mut rng = StdRng::seed_from_u64(7);
+        // two components, so the fire has to be lit again in the other one
+        let g1 = Graph::new(vec![vec![(1, 1)], vec![], vec![(3, 1)], vec![]]);
+        assert_eq!(forest_fire(&g1, 10, 0.5, 2, &mut rng), vec![0, 1, 2, 3]);
+        assert_eq!(random_vertices(&g1, 10, 2, &mut rng), vec![0, 1, 2, 3]);
+        assert_eq!(random_vertices(&g1, 1, 2, &mut rng), vec![2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "start 0 is out of 0")]
+    fn samples_start_in_the_graph() {
+        forest_fire(
+            &Graph::new(vec![]),
+            1,
+            0.5,
+            0,
+            &mut StdRng::seed_from_u64(7),
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! Minimal HTTP server answering shortest path queries on a graph kept in memory.
+//!
+//! Only `GET /route?from=<vertex>&to=<vertex>` is supported, answered with the
+//! same JSON as `query --format json`. Every connection is handled on its own thread.
+use crate::cache::{path_in, RouteCache};
+use crate::dijkstra::{shortest_path, shortest_path_tree, DijkstraError, PathAndDist};
+use crate::graph::Graph;
+use crate::output::{Results, VertexResult};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+/// An HTTP response with a JSON body.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+/// Answer a `GET` request for `target`, the path and query of the request.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::server::route;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![]]);
+/// assert_eq!(route(&graph, "/route?from=0&to=1").status, 200);
+/// assert_eq!(route(&graph, "/route?from=0").status, 400);
+/// ```
+pub fn route(graph: &Graph, target: &str) -> Response {
+    route_with(graph, target, |from, to| shortest_path(graph, from, to))
+}
+
+/// Answer a `GET` request for `target` as [`route`] does, from the shortest
+/// path trees kept in `cache` for the sources queried before.
+///
+/// The cache is only locked to look a tree up and to keep a new one, so
+/// other requests are answered while a tree is computed.
+///
+/// # Example
+/// ```
+/// use djikstra::cache::RouteCache;
+/// use djikstra::graph::Graph;
+/// use djikstra::server::route_cached;
+/// use std::sync::Mutex;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![]]);
+/// let cache = Mutex::new(RouteCache::new(16));
+/// assert_eq!(route_cached(&graph, &cache, "/route?from=0&to=1").status, 200);
+/// assert_eq!(route_cached(&graph, &cache, "/route?from=0&to=0").status, 200);
+/// assert_eq!(cache.lock().unwrap().hits(), 1);
+/// ```
+pub fn route_cached(graph: &Graph, cache: &Mutex<RouteCache>, target: &str) -> Response {
+    let lock = || {
+        cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    };
+    route_with(graph, target, |from, to| {
+        let kept = lock().get(graph, from);
+        if let Some(tree) = kept {
+            return Ok(path_in(&tree, to));
+        }
+        let tree = shortest_path_tree(graph, from)?;
+        let path = path_in(&tree, to);
+        lock().insert(graph, tree);
+        Ok(path)
+    })
+}
+
+/// Answer a `GET` request for `target`, finding the path with `find`.
+fn route_with<F>(graph: &Graph, target: &str, find: F) -> Response
+where
+    F: FnOnce(usize, usize) -> Result<Option<PathAndDist>, DijkstraError>,
+{
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != "/route" {
+        return Response::error(404, "not found");
+    }
+
+    let param = |name: &str| {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| value.parse::<usize>().ok())
+            .filter(|&v| v < graph.n_vertices())
+    };
+    let (Some(from), Some(to)) = (param("from"), param("to")) else {
+        return Response::error(
+            400,
+            &format!("from and to must be vertices below {}", graph.n_vertices()),
+        );
+    };
+
+    let start = Instant::now();
+    let result = match find(from, to) {
+        Ok(result) => result,
+        Err(e) => return Response::error(500, &e.to_string()),
+    };
+    let duration = start.elapsed();
+
+    let results = Results {
+        source: from,
+        time_ns: duration.as_nanos(),
+        vertices: vec![VertexResult {
+            vertex: to,
+            distance: result.as_ref().map(|(_path, dist)| *dist),
+            path: result.as_ref().map(|(path, _dist)| path.as_slice()),
+        }],
+    };
+    Response {
+        status: 200,
+        body: serde_json::to_string(&results).unwrap_or_default(),
+    }
+}
+
+/// Answer requests on `listener` until it fails.
+pub fn serve(graph: &Graph, listener: TcpListener) -> io::Result<()> {
+    serve_with(graph, None, listener)
+}
+
+/// Answer requests on `listener` until it fails, with [`route_cached`].
+pub fn serve_cached(
+    graph: &Graph,
+    cache: &Mutex<RouteCache>,
+    listener: TcpListener,
+) -> io::Result<()> {
+    serve_with(graph, Some(cache), listener)
+}
+
+fn serve_with(
+    graph: &Graph,
+    cache: Option<&Mutex<RouteCache>>,
+    listener: TcpListener,
+) -> io::Result<()> {
+    thread::scope(|scope| {
+        for stream in listener.incoming() {
+            let stream = stream?;
+            scope.spawn(move || {
+                // a client hanging up early is not an error of the server
+                let _ = handle_connection(graph, cache, stream);
+            });
+        }
+        Ok(())
+    })
+}
+
+/// Read a single request from the stream and write the response to it.
+fn handle_connection(
+    graph: &Graph,
+    cache: Option<&Mutex<RouteCache>>,
+    stream: TcpStream,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // skip the headers, the request has no body we care about
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        header.clear();
+    }
+
+    let response = match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["GET", target, _version] => match cache {
+            Some(cache) => route_cached(graph, cache, target),
+            None => route(graph, target),
+        },
+        _ => Response::error(405, "only GET requests are supported"),
+    };
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        500 => "Internal Server Error",
+        _ => "Method Not Allowed",
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Graph {
+        Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![], vec![]])
+    }
+
+    #[test]
+    fn routes_path() {
+        let response = route(&sample(), "/route?to=2&from=0");
+        assert_eq!(response.status, 200);
+        assert!(response
+            .body
+            .contains(r#""vertices":[{"vertex":2,"distance":4,"path":[0,1,2]}]"#));
+    }
+
+    #[test]
+    fn routes_unreachable() {
+        let response = route(&sample(), "/route?from=0&to=3");
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains(r#""distance":null,"path":null"#));
+    }
+
+    #[test]
+    fn rejects_bad_requests() {
+        assert_eq!(route(&sample(), "/route?from=0&to=4").status, 400);
+        assert_eq!(route(&sample(), "/route?from=x&to=1").status, 400);
+        assert_eq!(route(&sample(), "/other").status, 404);
+    }
+
+    #[test]
+    fn cached_routes_are_the_same() {
+        let graph = sample();
+        let cache = Mutex::new(RouteCache::new(1));
+        for target in [
+            "/route?from=0&to=2",
+            "/route?from=0&to=3",
+            "/route?from=1&to=2",
+        ] {
+            let cached = route_cached(&graph, &cache, target);
+            let expected = route(&graph, target);
+            assert_eq!(cached.status, expected.status);
+            let without_time = |body: &str| body.split_once(r#""vertices""#).unwrap().1.to_string();
+            assert_eq!(without_time(&cached.body), without_time(&expected.body));
+        }
+        let cache = cache.into_inner().unwrap();
+        assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 2, 1));
+        assert_eq!(
+            route_cached(&graph, &Mutex::new(cache), "/route?from=0&to=4").status,
+            400
+        );
+    }
+}
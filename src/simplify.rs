@@ -0,0 +1,187 @@
+//! Shrinking graphs by contracting chains of degree-2 vertices, as found
+//! along the roads of graphs derived from OpenStreetMap.
+//!
+use crate::graph::Graph;
+use std::collections::BTreeMap;
+
+/// Contract every vertex with exactly two distinct neighbors `a` and `b`
+/// (counting the edges in both directions and ignoring self-loops) that is
+/// not in `keep`, replacing the paths `a -> v -> b` and `b -> v -> a` through
+/// it with direct edges. Parallel edges are merged, keeping the minimum weight.
+///
+/// The shortest distances between the remaining vertices are the same as in
+/// the original graph. Returns the simplified graph and the original index
+/// of every vertex in it.
+///
+/// # Panics
+/// If a vertex of `keep` is not a vertex of the graph.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::simplify::contract_chains;
+///
+/// // a path 0 - 1 - 2 - 3 with edges in both directions
+/// let graph = Graph::new(vec![
+///     vec![(1, 1)],
+///     vec![(0, 1), (2, 2)],
+///     vec![(1, 2), (3, 3)],
+///     vec![(2, 3)],
+/// ]);
+/// let (simplified, original) = contract_chains(&graph, &[]);
+/// assert_eq!(simplified, Graph::new(vec![vec![(1, 6)], vec![(0, 6)]]));
+/// assert_eq!(original, vec![0, 3]);
+/// ```
+pub fn contract_chains(graph: &Graph, keep: &[usize]) -> (Graph, Vec<usize>) {
+    let n_elems = graph.n_vertices();
+    // sorted maps so the simplified graph does not depend on hashing
+    let mut outgoing: Vec<BTreeMap<usize, usize>> = vec![BTreeMap::new(); n_elems];
+    let mut incoming: Vec<BTreeMap<usize, usize>> = vec![BTreeMap::new(); n_elems];
+    for (u, neighbors) in graph.adj.iter().enumerate() {
+        for &(v, weight) in neighbors.iter().filter(|&&(v, _)| v != u) {
+            add_edge(&mut outgoing, &mut incoming, u, v, weight);
+        }
+    }
+
+    let mut kept = vec![true; n_elems];
+    let mut candidates: Vec<usize> = (0..n_elems).rev().collect();
+    let mut pinned = vec![false; n_elems];
+    for &v in keep.iter() {
+        assert!(v < n_elems, "vertex {} to keep is out of {}", v, n_elems);
+        pinned[v] = true;
+    }
+
+    while let Some(v) = candidates.pop() {
+        if !kept[v] || pinned[v] {
+            continue;
+        }
+        let mut neighbors: Vec<usize> = outgoing[v]
+            .keys()
+            .chain(incoming[v].keys())
+            .cloned()
+            .collect();
+        neighbors.sort_unstable();
+        neighbors.dedup();
+        if neighbors.len() != 2 {
+            continue;
+        }
+
+        kept[v] = false;
+        let into_v = std::mem::take(&mut incoming[v]);
+        let out_of_v = std::mem::take(&mut outgoing[v]);
+        for &u in neighbors.iter() {
+            outgoing[u].remove(&v);
+            incoming[u].remove(&v);
+        }
+        for (&from, &weight_in) in into_v.iter() {
+            for (&to, &weight_out) in out_of_v.iter().filter(|&(&to, _)| to != from) {
+                add_edge(
+                    &mut outgoing,
+                    &mut incoming,
+                    from,
+                    to,
+                    weight_in + weight_out,
+                );
+            }
+        }
+        // merged edges can leave the neighbors with two neighbors themselves
+        candidates.extend(neighbors);
+    }
+
+    let original: Vec<usize> = (0..n_elems).filter(|&v| kept[v]).collect();
+    let mut index = vec![usize::MAX; n_elems];
+    for (new, &old) in original.iter().enumerate() {
+        index[old] = new;
+    }
+    let adj = original
+        .iter()
+        .map(|&old| {
+            outgoing[old]
+                .iter()
+                .map(|(&v, &weight)| (index[v], weight))
+                .collect()
+        })
+        .collect();
+
+    (Graph::new(adj), original)
+}
+
+/// Add the edge `from -> to`, keeping the minimum weight if it is already there.
+fn add_edge(
+    outgoing: &mut [BTreeMap<usize, usize>],
+    incoming: &mut [BTreeMap<usize, usize>],
+    from: usize,
+    to: usize,
+    weight: usize,
+) {
+    let weight = outgoing[from].get(&to).map_or(weight, |&w| w.min(weight));
+    outgoing[from].insert(to, weight);
+    incoming[to].insert(from, weight);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dijkstra::dijkstra;
+    use crate::graph::Graph;
+    use crate::simplify::contract_chains;
+
+    #[test]
+    fn kept_vertices_stay() {
+        let g1 = Graph::new(vec![
+            vec![(1, 1)],
+            vec![(0, 1), (2, 2)],
+            vec![(1, 2), (3, 3)],
+            vec![(2, 3)],
+        ]);
+        let (simplified, original) = contract_chains(&g1, &[2]);
+        assert_eq!(original, vec![0, 2, 3]);
+        assert_eq!(
+            simplified,
+            Graph::new(vec![vec![(1, 3)], vec![(0, 3), (2, 3)], vec![(1, 3)]])
+        );
+    }
+
+    #[test]
+    fn distances_between_kept_vertices_are_preserved() {
+        // a cycle 0 - 1 - 2 - 3 - 4 - 5 - 0 with a chord 0 - 3 and a one-way spur 5 -> 6 -> 1
+        let g1 = Graph::new(vec![
+            vec![(1, 4), (5, 2), (3, 10)],
+            vec![(0, 4), (2, 1)],
+            vec![(1, 1), (3, 1)],
+            vec![(2, 1), (4, 3), (0, 10)],
+            vec![(3, 3), (5, 1)],
+            vec![(4, 1), (0, 2), (6, 1)],
+            vec![(1, 1)],
+        ]);
+        let (simplified, original) = contract_chains(&g1, &[]);
+        assert!(simplified.n_vertices() < g1.n_vertices());
+
+        for (new_src, &src) in original.iter().enumerate() {
+            let (_paths, dists) = dijkstra(&g1, src).unwrap();
+            let (_paths, simplified_dists) = dijkstra(&simplified, new_src).unwrap();
+            for (new_v, &v) in original.iter().enumerate() {
+                assert_eq!(simplified_dists[new_v], dists[v]);
+            }
+        }
+    }
+
+    #[test]
+    fn cycle_is_not_contracted_away() {
+        // every vertex of a triangle has degree 2, but contracting one
+        // leaves the other two with a single neighbor each
+        let g1 = Graph::new(vec![
+            vec![(1, 1), (2, 1)],
+            vec![(0, 1), (2, 1)],
+            vec![(0, 1), (1, 1)],
+        ]);
+        let (simplified, original) = contract_chains(&g1, &[]);
+        assert_eq!(original.len(), 2);
+        assert_eq!(simplified.n_edges(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "vertex 5 to keep is out of 3")]
+    fn keeping_a_missing_vertex_panics() {
+        contract_chains(&Graph::new(vec![vec![]; 3]), &[5]);
+    }
+}
@@ -0,0 +1,216 @@
+//! Graph whose short adjacency lists are stored inline, behind the
+//! `inline-neighbors` feature.
+//!
+//! Most vertices of sparse graphs, e.g. road networks with an average degree
+//! around 2.5, have a handful of edges. [`SmallGraph`] keeps up to
+//! [`INLINE_EDGES`] of them next to the vertex instead of in a heap allocation
+//! of their own, and only allocates for the vertices with more.
+use crate::graph::{
+    parse_header, parse_neighbors, Adjacency, Graph, IndexType, ParseGraphError, WeightType,
+};
+use crate::trace;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::{mem, str::FromStr};
+use smallvec::SmallVec;
+
+/// Number of edges of a vertex stored inline, without a heap allocation.
+pub const INLINE_EDGES: usize = 4;
+
+/// Edges out of a vertex as `(neighbor, weight)`, inline up to [`INLINE_EDGES`]
+/// of them and moved to the heap past that. Inline, they take as much room as
+/// a `Vec` of two edges.
+pub type Neighbors = SmallVec<[(u32, u32); INLINE_EDGES]>;
+
+/// Graph with `u32` vertices and weights whose adjacency lists are
+/// [`Neighbors`], so the vertices with at most [`INLINE_EDGES`] edges take no
+/// allocation of their own.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra;
+/// use djikstra::small::SmallGraph;
+///
+/// let graph: SmallGraph = "3\n1,3\n2,1\n\n".parse().unwrap();
+/// assert_eq!(graph.neighbors_of(1), &[(2, 1)]);
+/// assert_eq!(dijkstra(&graph, 0), dijkstra(&graph.to_graph(), 0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmallGraph {
+    adj: Vec<Neighbors>,
+}
+
+impl SmallGraph {
+    /// The graph with the same edges, or `None` if a vertex or a weight does
+    /// not fit in a `u32`.
+    pub fn from_graph<Ix: IndexType, W: WeightType>(graph: &Graph<Ix, W>) -> Option<Self> {
+        let adj = graph
+            .adj
+            .iter()
+            .map(|neighbors| {
+                neighbors
+                    .iter()
+                    .map(|&(v, weight)| {
+                        let v = u32::try_from(v.index()).ok()?;
+                        Some((v, u32::try_from(weight.weight()).ok()?))
+                    })
+                    .collect::<Option<Neighbors>>()
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { adj })
+    }
+
+    /// Number of vertices
+    pub fn n_vertices(&self) -> usize {
+        self.adj.len()
+    }
+
+    /// Number of edges
+    pub fn n_edges(&self) -> usize {
+        self.adj.iter().map(|neighbors| neighbors.len()).sum()
+    }
+
+    /// Get neighbors of a vertex
+    pub fn neighbors_of(&self, vertex: usize) -> &[(u32, u32)] {
+        &self.adj[vertex]
+    }
+
+    /// Number of bytes the graph holds on the heap, the lists stored inline
+    /// included as they are part of the vector of vertices.
+    pub fn memory_usage(&self) -> usize {
+        let spilled: usize = self
+            .adj
+            .iter()
+            .filter(|neighbors| neighbors.spilled())
+            .map(|neighbors| neighbors.capacity() * mem::size_of::<(u32, u32)>())
+            .sum();
+        self.adj.capacity() * mem::size_of::<Neighbors>() + spilled
+    }
+
+    /// The same graph as adjacency lists.
+    pub fn to_graph(&self) -> Graph<u32, u32> {
+        Graph {
+            adj: self
+                .adj
+                .iter()
+                .map(|neighbors| neighbors.to_vec())
+                .collect(),
+        }
+    }
+}
+
+impl Adjacency for SmallGraph {
+    type Index = u32;
+    type Weight = u32;
+
+    fn n_vertices(&self) -> usize {
+        SmallGraph::n_vertices(self)
+    }
+
+    fn neighbors_of(&self, vertex: usize) -> &[(u32, u32)] {
+        SmallGraph::neighbors_of(self, vertex)
+    }
+}
+
+impl FromStr for SmallGraph {
+    type Err = ParseGraphError;
+
+    /// Parse a string in the input format of [`Graph`] straight into the inline lists.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let _span = trace::span!("parse");
+        let (n_vertex, lines) = parse_header(s)?;
+
+        let mut adj = Vec::with_capacity(n_vertex);
+        let mut edges = vec![];
+        for neighbors in lines.lines().take(n_vertex) {
+            edges.clear();
+            parse_neighbors(neighbors, &mut edges)?;
+            let parsed = edges
+                .iter()
+                .map(|&(v, weight)| {
+                    let v = u32::try_from(v).map_err(|_| {
+                        ParseGraphError(format!("vertex {} does not fit in a u32", v))
+                    })?;
+                    let weight = u32::try_from(weight).map_err(|_| {
+                        ParseGraphError(format!("weight {} does not fit in a u32", weight))
+                    })?;
+                    Ok((v, weight))
+                })
+                .collect::<Result<Neighbors, ParseGraphError>>()?;
+            adj.push(parsed);
+        }
+        // the vertices without a line have no edges
+        adj.resize_with(n_vertex, Neighbors::new);
+
+        Ok(Self { adj })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dijkstra::dijkstra;
+    use crate::graph::Graph;
+    use crate::small::{Neighbors, SmallGraph, INLINE_EDGES};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn spills_past_the_inline_edges() {
+        let mut neighbors = Neighbors::new();
+        for v in 0..INLINE_EDGES as u32 {
+            neighbors.push((v, 1));
+        }
+        assert!(!neighbors.spilled());
+        neighbors.push((9, 2));
+        assert!(neighbors.spilled());
+        assert_eq!(neighbors.len(), INLINE_EDGES + 1);
+        assert_eq!(neighbors.last(), Some(&(9, 2)));
+        assert_eq!(neighbors[..2], [(0, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn parses_and_runs_like_the_adjacency_lists() {
+        let input = "4\n1,3 2,1 3,5 1,1 2,2\n2,1\n\n";
+        let graph: Graph = input.parse().unwrap();
+        let small: SmallGraph = input.parse().unwrap();
+        assert_eq!(Some(&small), SmallGraph::from_graph(&graph).as_ref());
+        let narrow = graph.to_index_type::<u32>().unwrap();
+        assert_eq!(small.to_graph(), narrow.to_weight_type::<u32>().unwrap());
+        assert_eq!((small.n_vertices(), small.n_edges()), (4, 6));
+        for src in 0..graph.n_vertices() {
+            assert_eq!(dijkstra(&small, src), dijkstra(&graph, src));
+        }
+
+        // only the vertex with five edges allocates
+        let lists = small.adj.capacity() * core::mem::size_of::<Neighbors>();
+        assert!(small.memory_usage() > lists);
+        let spilled: Vec<_> = small.adj.iter().filter(|n| n.spilled()).collect();
+        assert_eq!(spilled.len(), 1);
+
+        assert!("2\n4294967296,1\n".parse::<SmallGraph>().is_err());
+        assert!("2\n1,4294967296\n".parse::<SmallGraph>().is_err());
+        let too_large = u32::MAX as usize + 1;
+        assert_eq!(
+            SmallGraph::from_graph(&Graph::new(vec![vec![(too_large, 1)]])),
+            None
+        );
+        assert_eq!(
+            SmallGraph::from_graph(&Graph::new(vec![vec![(0, too_large)]])),
+            None
+        );
+    }
+
+    #[test]
+    fn inline_edges_take_as_much_room_as_two_in_a_vec() {
+        // the capacity, then the edges or the pointer and length of the heap
+        assert_eq!(
+            size_of::<Neighbors>(),
+            size_of::<usize>() + INLINE_EDGES * size_of::<(u32, u32)>()
+        );
+        assert_eq!(
+            size_of::<Neighbors>(),
+            size_of::<Vec<(u32, u32)>>() + 2 * size_of::<(u32, u32)>()
+        );
+    }
+}
@@ -0,0 +1,276 @@
+//! Summary statistics of a graph for sanity-checking inputs.
+//!
+use crate::graph::Graph;
+use std::fmt;
+
+/// Minimum, maximum and mean of a set of values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f64,
+}
+
+impl Summary {
+    /// Summarize the values, `None` if there are none.
+    pub fn of<I>(values: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut values = values.into_iter();
+        let first = values.next()?;
+        let (min, max, sum, count) = values.fold(
+            (first, first, first as f64, 1),
+            |(min, max, sum, count), x| (min.min(x), max.max(x), sum + x as f64, count + 1),
+        );
+        Some(Self {
+            min,
+            max,
+            mean: sum / count as f64,
+        })
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {}, max {}, mean {:.2}",
+            self.min, self.max, self.mean
+        )
+    }
+}
+
+/// Statistics describing the shape of a graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    pub n_vertices: usize,
+    pub n_edges: usize,
+    /// Number of neighbors of each vertex, `None` for an empty graph.
+    pub out_degree: Option<Summary>,
+    /// Number of edges into each vertex, `None` for an empty graph.
+    pub in_degree: Option<Summary>,
+    /// Edge weights, `None` if there are no edges.
+    pub weight: Option<Summary>,
+    /// Number of weakly connected components.
+    pub n_components: usize,
+    /// Number of bytes the graph holds on the heap.
+    pub memory: usize,
+}
+
+impl GraphStats {
+    /// Compute the statistics of a graph.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::graph::Graph;
+    /// use djikstra::stats::GraphStats;
+    ///
+    /// let graph = Graph::new(vec![vec![(1, 3), (2, 5)], vec![], vec![]]);
+    /// let stats = GraphStats::of(&graph);
+    /// assert_eq!(stats.weight.unwrap().max, 5);
+    /// ```
+    pub fn of(graph: &Graph) -> Self {
+        let mut in_degrees = vec![0; graph.n_vertices()];
+        for &(v, _) in graph.adj.iter().flatten() {
+            in_degrees[v] += 1;
+        }
+
+        Self {
+            n_vertices: graph.n_vertices(),
+            n_edges: graph.n_edges(),
+            out_degree: Summary::of(graph.adj.iter().map(|neighbors| neighbors.len())),
+            in_degree: Summary::of(in_degrees),
+            weight: Summary::of(graph.adj.iter().flatten().map(|&(_, weight)| weight)),
+            n_components: graph.components().into_iter().max().map_or(0, |c| c + 1),
+            memory: graph.memory_usage(),
+        }
+    }
+}
+
+impl fmt::Display for GraphStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_none =
+            |summary: Option<Summary>| summary.map_or("none".to_string(), |s| s.to_string());
+
+        writeln!(f, "Vertices: {}", self.n_vertices)?;
+        writeln!(f, "Edges: {}", self.n_edges)?;
+        writeln!(f, "Out-degree: {}", or_none(self.out_degree))?;
+        writeln!(f, "In-degree: {}", or_none(self.in_degree))?;
+        writeln!(f, "Weight: {}", or_none(self.weight))?;
+        writeln!(f, "Components: {}", self.n_components)?;
+        writeln!(f, "Memory: {} bytes", self.memory)
+    }
+}
+
+/// Counts of integer values in equally wide bins, the first starting at `low`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    /// Smallest value of the first bin.
+    pub low: usize,
+    /// Number of distinct values in every bin.
+    pub width: usize,
+    /// Number of values in every bin.
+    pub counts: Vec<usize>,
+}
+
+impl Histogram {
+    /// Empty histogram of the values from `low` to `high` in at most `bins` bins.
+    /// There are fewer bins if the range has fewer than `bins` values.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::stats::Histogram;
+    ///
+    /// let mut histogram = Histogram::new(1, 10, 3);
+    /// for value in [1, 4, 5, 10] {
+    ///     histogram.add(value);
+    /// }
+    /// assert_eq!(histogram.bounds(0), (1, 4));
+    /// assert_eq!(histogram.counts, vec![2, 1, 1]);
+    /// ```
+    pub fn new(low: usize, high: usize, bins: usize) -> Self {
+        let n_values = high - low + 1;
+        let width = n_values.div_ceil(bins.max(1));
+        Self {
+            low,
+            width,
+            counts: vec![0; n_values.div_ceil(width)],
+        }
+    }
+
+    /// Count `value`, which must be in the range of the histogram.
+    pub fn add(&mut self, value: usize) {
+        self.counts[(value - self.low) / self.width] += 1;
+    }
+
+    /// Smallest and largest value of a bin.
+    pub fn bounds(&self, bin: usize) -> (usize, usize) {
+        let low = self.low + bin * self.width;
+        (low, low + self.width - 1)
+    }
+}
+
+/// Histograms of the out-degrees and the in-degrees of the vertices in the
+/// same `bins` bins, `None` for an empty graph.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::stats::degree_histograms;
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 5)], vec![(2, 1)], vec![]]);
+/// let (out_degrees, in_degrees) = degree_histograms(&graph, 10).unwrap();
+/// assert_eq!(out_degrees.counts, vec![1, 1, 1]);
+/// assert_eq!(in_degrees.counts, vec![1, 1, 1]);
+/// ```
+pub fn degree_histograms(graph: &Graph, bins: usize) -> Option<(Histogram, Histogram)> {
+    let out_degrees: Vec<usize> = graph.adj.iter().map(|neighbors| neighbors.len()).collect();
+    let mut in_degrees = vec![0; graph.n_vertices()];
+    for &(v, _) in graph.adj.iter().flatten() {
+        in_degrees[v] += 1;
+    }
+
+    let all = Summary::of(out_degrees.iter().chain(in_degrees.iter()).cloned())?;
+    let mut out_histogram = Histogram::new(all.min, all.max, bins);
+    let mut in_histogram = out_histogram.clone();
+    for (&out_degree, &in_degree) in out_degrees.iter().zip(in_degrees.iter()) {
+        out_histogram.add(out_degree);
+        in_histogram.add(in_degree);
+    }
+    Some((out_histogram, in_histogram))
+}
+
+/// Histogram of the edge weights in `bins` bins, `None` if there are no edges.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::stats::weight_histogram;
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 5)], vec![(2, 1)], vec![]]);
+/// assert_eq!(weight_histogram(&graph, 2).unwrap().counts, vec![2, 1]);
+/// ```
+pub fn weight_histogram(graph: &Graph, bins: usize) -> Option<Histogram> {
+    let weights = || graph.adj.iter().flatten().map(|&(_, weight)| weight);
+    let summary = Summary::of(weights())?;
+    let mut histogram = Histogram::new(summary.min, summary.max, bins);
+    for weight in weights() {
+        histogram.add(weight);
+    }
+    Some(histogram)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_stats() {
+        let graph = Graph::new(vec![vec![(1, 3), (2, 5)], vec![(0, 1)], vec![], vec![]]);
+        let stats = GraphStats::of(&graph);
+
+        assert_eq!(stats.n_vertices, 4);
+        assert_eq!(stats.n_edges, 3);
+        assert_eq!(
+            stats.out_degree,
+            Some(Summary {
+                min: 0,
+                max: 2,
+                mean: 0.75
+            })
+        );
+        assert_eq!(stats.in_degree.unwrap().max, 1);
+        assert_eq!(
+            stats.weight,
+            Some(Summary {
+                min: 1,
+                max: 5,
+                mean: 3.0
+            })
+        );
+        assert_eq!(stats.n_components, 2);
+        assert_eq!(stats.memory, Graph::<usize>::estimate_memory(4, 3));
+    }
+
+    #[test]
+    fn empty_graph_has_no_summaries() {
+        let stats = GraphStats::of(&Graph::new(vec![]));
+        assert_eq!(stats.out_degree, None);
+        assert_eq!(stats.weight, None);
+        assert_eq!(stats.n_components, 0);
+        assert_eq!(degree_histograms(&Graph::new(vec![]), 10), None);
+        assert_eq!(weight_histogram(&Graph::new(vec![vec![]]), 10), None);
+    }
+
+    #[test]
+    fn histogram_bins_cover_the_range() {
+        let mut histogram = Histogram::new(5, 104, 20);
+        assert_eq!((histogram.width, histogram.counts.len()), (5, 20));
+        assert_eq!(histogram.bounds(19), (100, 104));
+        histogram.add(5);
+        histogram.add(104);
+        assert_eq!((histogram.counts[0], histogram.counts[19]), (1, 1));
+
+        // a single value gets a single bin
+        let histogram = Histogram::new(7, 7, 20);
+        assert_eq!(histogram.counts, vec![0]);
+        assert_eq!(histogram.bounds(0), (7, 7));
+    }
+
+    #[test]
+    fn degree_histograms_share_bins() {
+        let graph = Graph::new(vec![
+            vec![(1, 1), (2, 1), (3, 1), (4, 1)],
+            vec![(0, 1)],
+            vec![(0, 1)],
+            vec![],
+            vec![],
+        ]);
+        let (out_degrees, in_degrees) = degree_histograms(&graph, 2).unwrap();
+        // degrees from 0 to 4 in bins 0..=2 and 3..=5
+        assert_eq!(out_degrees.bounds(1), (3, 5));
+        assert_eq!(out_degrees.counts, vec![4, 1]);
+        assert_eq!(in_degrees.counts, vec![5, 0]);
+    }
+}
@@ -0,0 +1,414 @@
+//! Shortest paths on graphs whose edge weights are uncertain, e.g. travel times.
+//!
+//! Every edge has a mean and a variance, and the weights of the edges are
+//! taken as independent, so both add up along a path. [`expected_shortest_path`]
+//! minimizes the mean, and [`most_reliable_path`] maximizes the probability of
+//! arriving within a budget, taking the cost of a path as normally distributed.
+//!
+use crate::dijkstra::{check_source, shortest_path, DijkstraError};
+use crate::graph::{Adjacency, Graph};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// Weight of an edge, or cost of a path, known by its mean and variance.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Uncertain {
+    /// Expected value.
+    pub mean: usize,
+    /// Variance, the square of the standard deviation.
+    pub variance: usize,
+}
+
+impl Uncertain {
+    /// Weight of `mean` and `variance`.
+    pub fn new(mean: usize, variance: usize) -> Self {
+        Self { mean, variance }
+    }
+
+    /// Probability that the actual value is at most `budget`, taking it as
+    /// normally distributed.
+    ///
+    /// # Example
+    /// ```
+    /// use djikstra::stochastic::Uncertain;
+    ///
+    /// assert!((Uncertain::new(10, 4).probability_within(10) - 0.5).abs() < 1e-6);
+    /// assert!(Uncertain::new(10, 4).probability_within(14) > 0.97);
+    /// assert_eq!(Uncertain::new(10, 0).probability_within(9), 0.0);
+    /// ```
+    pub fn probability_within(&self, budget: usize) -> f64 {
+        if self.variance == 0 {
+            return if self.mean <= budget { 1.0 } else { 0.0 };
+        }
+        let z = (budget as f64 - self.mean as f64) / (self.variance as f64).sqrt();
+        normal_cdf(z)
+    }
+
+    /// Cost of a path followed by an edge of weight `other`, `None` if the
+    /// mean does not fit in a `usize`. The variance saturates.
+    fn checked_add(self, other: Uncertain) -> Option<Uncertain> {
+        Some(Uncertain {
+            mean: self.mean.checked_add(other.mean)?,
+            variance: self.variance.saturating_add(other.variance),
+        })
+    }
+
+    /// Whether a path of this cost is at least as good as one of `other`
+    /// whatever the budget, both being within it on average.
+    fn dominates(&self, other: &Uncertain) -> bool {
+        self.mean <= other.mean && self.variance <= other.variance
+    }
+}
+
+/// Graph whose edges have an [`Uncertain`] weight.
+///
+/// The algorithms of the `dijkstra` module run on it through [`Adjacency`]
+/// with the means as the weights, i.e. they find the paths of least expected cost.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra;
+/// use djikstra::stochastic::{StochasticGraph, Uncertain};
+///
+/// let graph = StochasticGraph::new(vec![vec![(1, Uncertain::new(3, 1))], vec![]]);
+/// assert_eq!(graph.neighbors_of(0).collect::<Vec<_>>(), vec![(1, Uncertain::new(3, 1))]);
+/// assert_eq!(dijkstra(&graph, 0).unwrap().1, vec![Some(0), Some(3)]);
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct StochasticGraph {
+    // the means as the weights of the edges, and the variances of the same
+    // edges in the same order
+    means: Graph,
+    variances: Vec<Vec<usize>>,
+}
+
+impl StochasticGraph {
+    /// Create a graph from adjacency lists of `(neighbor, weight)`.
+    pub fn new(adj: Vec<Vec<(usize, Uncertain)>>) -> Self {
+        let (means, variances) = adj
+            .into_iter()
+            .map(|neighbors| {
+                neighbors
+                    .into_iter()
+                    .map(|(v, weight)| ((v, weight.mean), weight.variance))
+                    .unzip()
+            })
+            .unzip();
+        Self {
+            means: Graph::new(means),
+            variances,
+        }
+    }
+
+    /// Number of vertices
+    pub fn n_vertices(&self) -> usize {
+        self.means.n_vertices()
+    }
+
+    /// Number of edges
+    pub fn n_edges(&self) -> usize {
+        self.means.n_edges()
+    }
+
+    /// Get neighbors of a vertex with the weights of the edges to them.
+    pub fn neighbors_of(&self, vertex: usize) -> impl Iterator<Item = (usize, Uncertain)> + '_ {
+        self.means
+            .neighbors_of(vertex)
+            .iter()
+            .zip(self.variances[vertex].iter())
+            .map(|(&(v, mean), &variance)| (v, Uncertain { mean, variance }))
+    }
+
+    /// The graph with the means as the weights.
+    pub fn means(&self) -> &Graph {
+        &self.means
+    }
+
+    /// Cost of the path through `vertices`, taking the edge of least mean
+    /// (then of least variance) between consecutive vertices.
+    fn cost_of(&self, vertices: &[usize]) -> Uncertain {
+        vertices
+            .windows(2)
+            .map(|edge| {
+                self.neighbors_of(edge[0])
+                    .filter(|&(v, _weight)| v == edge[1])
+                    .map(|(_v, weight)| weight)
+                    .min_by_key(|weight| (weight.mean, weight.variance))
+                    .expect("the path follows the edges")
+            })
+            .fold(Uncertain::default(), |cost, weight| Uncertain {
+                mean: cost.mean + weight.mean,
+                variance: cost.variance.saturating_add(weight.variance),
+            })
+    }
+}
+
+impl Adjacency for StochasticGraph {
+    type Index = usize;
+    type Weight = usize;
+
+    fn n_vertices(&self) -> usize {
+        StochasticGraph::n_vertices(self)
+    }
+
+    fn neighbors_of(&self, vertex: usize) -> &[(usize, usize)] {
+        self.means.neighbors_of(vertex)
+    }
+}
+
+/// Path of the least expected cost from `src` to `dst`, with its cost, or
+/// `None` if `dst` cannot be reached.
+///
+/// By linearity of expectation, this is the shortest path on the means.
+///
+/// # Errors
+/// Fails if `src` or `dst` is not a vertex of the graph, or if the mean cost
+/// of a path to a reachable vertex does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::stochastic::{expected_shortest_path, StochasticGraph, Uncertain};
+///
+/// let graph = StochasticGraph::new(vec![
+///     vec![(1, Uncertain::new(4, 9)), (2, Uncertain::new(5, 0))],
+///     vec![(2, Uncertain::new(0, 0))],
+///     vec![],
+/// ]);
+/// let (path, cost) = expected_shortest_path(&graph, 0, 2).unwrap().unwrap();
+/// assert_eq!((path, cost), (vec![0, 1, 2], Uncertain::new(4, 9)));
+/// ```
+pub fn expected_shortest_path(
+    graph: &StochasticGraph,
+    src: usize,
+    dst: usize,
+) -> Result<Option<(Vec<usize>, Uncertain)>, DijkstraError> {
+    check_source(graph, dst)?;
+    Ok(shortest_path(graph, src, dst)?.map(|(path, _mean)| {
+        let cost = graph.cost_of(&path);
+        (path, cost)
+    }))
+}
+
+/// Path from `src` to `dst` with the highest probability of costing at most
+/// `budget`, with its cost and that probability, e.g. the route most likely
+/// to arrive on time. `None` if no path costs at most `budget` on average.
+///
+/// The cost of a path is taken as normally distributed. Only the paths that
+/// cost at most `budget` on average are considered, i.e. that are within it
+/// with a probability of at least one half, as a path that is both cheaper
+/// and less variable than another is then always at least as reliable.
+///
+/// Every vertex keeps the costs of the paths to it that no other path beats in
+/// both mean and variance, so the search is slower than Dijkstra's, and can
+/// explore many paths on large graphs with a large budget.
+///
+/// # Errors
+/// Fails if `src` or `dst` is not a vertex of the graph.
+///
+/// # Example
+/// ```
+/// use djikstra::stochastic::{most_reliable_path, StochasticGraph, Uncertain};
+///
+/// // a fast but unreliable road, and a slower steady one
+/// let graph = StochasticGraph::new(vec![
+///     vec![(1, Uncertain::new(4, 9)), (2, Uncertain::new(5, 0))],
+///     vec![(2, Uncertain::new(0, 0))],
+///     vec![],
+/// ]);
+/// let (path, cost, probability) = most_reliable_path(&graph, 0, 2, 6).unwrap().unwrap();
+/// assert_eq!((path, cost, probability), (vec![0, 2], Uncertain::new(5, 0), 1.0));
+/// ```
+pub fn most_reliable_path(
+    graph: &StochasticGraph,
+    src: usize,
+    dst: usize,
+    budget: usize,
+) -> Result<Option<(Vec<usize>, Uncertain, f64)>, DijkstraError> {
+    struct Label {
+        vertex: usize,
+        cost: Uncertain,
+        parent: Option<usize>,
+        dominated: bool,
+    }
+
+    check_source(graph, src)?;
+    check_source(graph, dst)?;
+    let mut labels = vec![Label {
+        vertex: src,
+        cost: Uncertain::default(),
+        parent: None,
+        dominated: false,
+    }];
+    // labels not dominated by another one, of every vertex
+    let mut fronts: Vec<Vec<usize>> = vec![vec![]; graph.n_vertices()];
+    fronts[src].push(0);
+    let mut queue = BinaryHeap::from([Reverse((0, 0, 0))]);
+
+    while let Some(Reverse((_mean, _variance, label))) = queue.pop() {
+        if labels[label].dominated {
+            continue;
+        }
+        let (u, cost) = (labels[label].vertex, labels[label].cost);
+        for (v, weight) in graph.neighbors_of(u) {
+            let Some(new_cost) = cost.checked_add(weight).filter(|c| c.mean <= budget) else {
+                continue;
+            };
+            if fronts[v]
+                .iter()
+                .any(|&other| labels[other].cost.dominates(&new_cost))
+            {
+                continue;
+            }
+            fronts[v].retain(|&other| {
+                let dominated = new_cost.dominates(&labels[other].cost);
+                labels[other].dominated |= dominated;
+                !dominated
+            });
+            fronts[v].push(labels.len());
+            queue.push(Reverse((new_cost.mean, new_cost.variance, labels.len())));
+            labels.push(Label {
+                vertex: v,
+                cost: new_cost,
+                parent: Some(label),
+                dominated: false,
+            });
+        }
+    }
+
+    let best = fronts[dst].iter().copied().max_by(|&a, &b| {
+        let (a, b) = (labels[a].cost, labels[b].cost);
+        let by_probability = a
+            .probability_within(budget)
+            .total_cmp(&b.probability_within(budget));
+        by_probability.then(b.mean.cmp(&a.mean))
+    });
+    Ok(best.map(|label| {
+        let mut path = vec![];
+        let mut next = Some(label);
+        while let Some(label) = next {
+            path.push(labels[label].vertex);
+            next = labels[label].parent;
+        }
+        path.reverse();
+        let cost = labels[label].cost;
+        (path, cost, cost.probability_within(budget))
+    }))
+}
+
+/// Cumulative distribution function of the standard normal distribution,
+/// with the approximation 7.1.26 of the error function by Abramowitz and
+/// Stegun, within 1.5e-7.
+fn normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dijkstra::{shortest_path, DijkstraError};
+    use crate::stochastic::{
+        expected_shortest_path, most_reliable_path, normal_cdf, StochasticGraph, Uncertain,
+    };
+
+    fn roads() -> StochasticGraph {
+        let w = Uncertain::new;
+        StochasticGraph::new(vec![
+            vec![(1, w(3, 1)), (6, w(2, 8))],
+            vec![
+                (0, w(3, 1)),
+                (2, w(4, 0)),
+                (3, w(1, 5)),
+                (6, w(1, 1)),
+                (4, w(4, 2)),
+                (7, w(6, 0)),
+            ],
+            vec![(6, w(6, 3)), (1, w(4, 0)), (3, w(2, 2)), (4, w(2, 9))],
+            vec![(1, w(1, 5)), (2, w(2, 2)), (4, w(1, 7)), (7, w(2, 4))],
+            vec![
+                (2, w(2, 9)),
+                (3, w(1, 7)),
+                (1, w(4, 2)),
+                (7, w(1, 1)),
+                (5, w(3, 0)),
+            ],
+            vec![(4, w(3, 0)), (7, w(4, 4))],
+            vec![(0, w(2, 8)), (1, w(1, 1)), (2, w(6, 3)), (4, w(5, 0))],
+            vec![(4, w(1, 1)), (5, w(4, 4)), (3, w(2, 4)), (1, w(6, 0))],
+        ])
+    }
+
+    #[test]
+    fn expected_paths_are_the_paths_on_the_means() {
+        let graph = roads();
+        for dst in 0..graph.n_vertices() {
+            let (path, cost) = expected_shortest_path(&graph, 0, dst).unwrap().unwrap();
+            let (_path, mean) = shortest_path(graph.means(), 0, dst).unwrap().unwrap();
+            assert_eq!(cost.mean, mean);
+            assert_eq!((path[0], path.last()), (0, Some(&dst)));
+        }
+        assert!(expected_shortest_path(&graph, 0, 8).is_err());
+    }
+
+    #[test]
+    fn reliable_paths_beat_every_other_path() {
+        let graph = roads();
+        // every simple path from 0 to 5 with its cost
+        let mut paths = vec![];
+        let mut stack = vec![(vec![0], Uncertain::default())];
+        while let Some((path, cost)) = stack.pop() {
+            let u = *path.last().unwrap();
+            if u == 5 {
+                paths.push(cost);
+                continue;
+            }
+            for (v, weight) in graph.neighbors_of(u) {
+                if !path.contains(&v) {
+                    let mut next = path.clone();
+                    next.push(v);
+                    let cost =
+                        Uncertain::new(cost.mean + weight.mean, cost.variance + weight.variance);
+                    stack.push((next, cost));
+                }
+            }
+        }
+
+        for budget in 0..20 {
+            let best = paths
+                .iter()
+                .filter(|cost| cost.mean <= budget)
+                .map(|cost| cost.probability_within(budget))
+                .max_by(f64::total_cmp);
+            let found = most_reliable_path(&graph, 0, 5, budget).unwrap();
+            assert_eq!(found.as_ref().map(|(_path, _cost, p)| *p), best);
+            if let Some((path, cost, _probability)) = found {
+                assert_eq!((path[0], path.last()), (0, Some(&5)));
+                assert!(cost.mean <= budget);
+            }
+        }
+        assert_eq!(
+            most_reliable_path(&graph, 9, 5, 10),
+            Err(DijkstraError::InvalidSource {
+                source: 9,
+                n_vertices: 8
+            })
+        );
+    }
+
+    #[test]
+    fn normal_distribution() {
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.0) - 0.841_344_7).abs() < 1e-6);
+        assert!((normal_cdf(-1.96) - 0.024_997_9).abs() < 1e-6);
+        assert!(normal_cdf(-10.0) < 1e-9);
+    }
+}
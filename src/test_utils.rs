@@ -0,0 +1,292 @@
+//! Random graphs for property tests of shortest path algorithms.
+//!
+//! A [`GraphStrategy`] is a [`proptest`] strategy of graphs, so it composes
+//! with the other strategies and runs in `proptest!` tests, which shrink the
+//! failing graphs by removing edges, lowering weights and dropping vertices.
+//! It is also the parameters of `any::<Graph>()`. [`check`] runs a property
+//! on many graphs of a strategy outside of `proptest!`. [`sample_graph`] is
+//! the fixed graph the unit tests of the crate share.
+//!
+//! # Example
+//! ```
+//! use djikstra::bellman_ford::bellman_ford;
+//! use djikstra::dijkstra::dijkstra;
+//! use djikstra::test_utils::GraphStrategy;
+//! use proptest::prelude::*;
+//! use proptest::test_runner::TestRunner;
+//!
+//! let strategy = GraphStrategy { connected: true, ..GraphStrategy::default() };
+//! // a graph with a vertex to start from
+//! let graphs = (strategy, any::<prop::sample::Index>());
+//! TestRunner::default()
+//!     .run(&graphs, |(graph, src)| {
+//!         let src = src.index(graph.n_vertices());
+//!         let (_paths, dists) = dijkstra(&graph, src).unwrap();
+//!         let (_paths, expected) = bellman_ford(&graph, src).unwrap();
+//!         prop_assert_eq!(dists, expected);
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+use crate::bfs::bfs;
+use crate::graph::Graph;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use proptest::arbitrary::Arbitrary;
+use proptest::strategy::{BoxedStrategy, NewTree, Strategy, ValueTree};
+use proptest::test_runner::{Config, RngAlgorithm, TestCaseError, TestError, TestRng, TestRunner};
+use proptest::{collection, option};
+/// Adjacency lists of [`sample_graph`], to build variants of it.
+pub fn sample_adjacency() -> Vec<Vec<(usize, usize)>> {
+    vec![
+        vec![(1, 3), (6, 2)],
+        vec![(0, 3), (2, 4), (3, 1), (6, 1), (4, 4), (7, 6)],
+        vec![(6, 6), (1, 4), (3, 2), (4, 2)],
+        vec![(1, 1), (2, 2), (4, 1), (7, 2)],
+        vec![(2, 2), (3, 1), (1, 4), (7, 1), (5, 3)],
+        vec![(4, 3), (7, 4)],
+        vec![(0, 2), (1, 1), (2, 6), (4, 5)],
+        vec![(4, 1), (5, 4), (3, 2), (1, 6)],
+    ]
+}
+
+/// A strongly connected graph of 8 vertices and 31 edges, with weights from
+/// 1 to 6.
+pub fn sample_graph() -> Graph {
+    Graph::new(sample_adjacency())
+}
+
+/// The random graphs to generate, as a [`Strategy`] and as the parameters of
+/// `any_with::<Graph>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStrategy {
+    /// Graphs have between 1 and `max_vertices` vertices.
+    pub max_vertices: usize,
+    /// Probability of an edge between any two vertices.
+    pub edge_probability: f64,
+    /// Weights are drawn uniformly from `1..=max_weight`.
+    pub max_weight: usize,
+    /// Whether edges go one way only, or are stored for both of their vertices.
+    pub directed: bool,
+    /// Whether every vertex can be reached from vertex 0.
+    pub connected: bool,
+}
+
+impl Default for GraphStrategy {
+    /// Directed graphs of up to 20 vertices with weights up to 100, not
+    /// necessarily connected.
+    fn default() -> Self {
+        Self {
+            max_vertices: 20,
+            edge_probability: 0.2,
+            max_weight: 100,
+            directed: true,
+            connected: false,
+        }
+    }
+}
+
+impl GraphStrategy {
+    /// Whether the graph could have been generated by the strategy, apart
+    /// from the number of vertices and edges.
+    pub fn contains(&self, graph: &Graph) -> bool {
+        let weights_in_range = graph
+            .adj
+            .iter()
+            .flatten()
+            .all(|&(_v, weight)| (1..=self.max_weight.max(1)).contains(&weight));
+        let undirected = graph.adj.iter().enumerate().all(|(u, neighbors)| {
+            neighbors
+                .iter()
+                .all(|&(v, weight)| graph.neighbors_of(v).contains(&(u, weight)))
+        });
+        let connected = graph.n_vertices() > 0 && bfs(graph, 0).1.iter().all(Option::is_some);
+
+        weights_in_range && (self.directed || undirected) && (!self.connected || connected)
+    }
+
+    /// The graphs of the strategy, built from the number of vertices, a
+    /// random spanning tree rooted at vertex 0 for connected graphs, and
+    /// the weight of the edge between every other pair of vertices, if any.
+    fn graphs(&self) -> BoxedStrategy<Graph> {
+        let weights = 1..=self.max_weight.max(1);
+        let edge_probability = self.edge_probability;
+        let (directed, connected) = (self.directed, self.connected);
+        (1..=self.max_vertices.max(1))
+            .prop_flat_map(move |n| {
+                let n_pairs = if directed {
+                    n * (n - 1)
+                } else {
+                    n * (n - 1) / 2
+                };
+                let n_parents = if connected { n - 1 } else { 0 };
+                let parents: Vec<_> = (1..=n_parents).map(|v| 0..v).collect();
+                (
+                    parents,
+                    collection::vec(weights.clone(), n_parents),
+                    collection::vec(option::weighted(edge_probability, weights.clone()), n_pairs),
+                )
+                    .prop_map(move |(parents, tree_weights, edges)| {
+                        let mut adj = vec![vec![]; n];
+                        let add_edge =
+                            |adj: &mut Vec<Vec<(usize, usize)>>, u: usize, v: usize, weight| {
+                                adj[u].push((v, weight));
+                                if !directed {
+                                    adj[v].push((u, weight));
+                                }
+                            };
+
+                        for (v, (&parent, &weight)) in
+                            parents.iter().zip(tree_weights.iter()).enumerate()
+                        {
+                            add_edge(&mut adj, parent, v + 1, weight);
+                        }
+                        let pairs = (0..n)
+                            .flat_map(|u| (0..n).map(move |v| (u, v)))
+                            .filter(|&(u, v)| if directed { u != v } else { u < v });
+                        for ((u, v), edge) in pairs.zip(edges) {
+                            let exists = adj[u].iter().any(|&(w, _weight)| w == v);
+                            if let (Some(weight), false) = (edge, exists) {
+                                add_edge(&mut adj, u, v, weight);
+                            }
+                        }
+                        Graph::new(adj)
+                    })
+            })
+            .boxed()
+    }
+}
+
+impl Strategy for GraphStrategy {
+    type Tree = Box<dyn ValueTree<Value = Graph>>;
+    type Value = Graph;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.graphs().new_tree(runner)
+    }
+}
+
+impl Arbitrary for Graph {
+    type Parameters = GraphStrategy;
+    type Strategy = GraphStrategy;
+
+    /// The graphs of `strategy`, of [`GraphStrategy::default`] for `any::<Graph>()`.
+    fn arbitrary_with(strategy: GraphStrategy) -> GraphStrategy {
+        strategy
+    }
+}
+
+/// A graph on which a property fails, with the message of the failure.
+#[derive(Debug, PartialEq)]
+pub struct Counterexample {
+    /// The failing graph, shrunk as far as the property kept failing.
+    pub graph: Graph,
+    /// What the property returned on `graph`.
+    pub message: String,
+}
+
+impl fmt::Display for Counterexample {
+    /// Write the message followed by the graph in the input format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}\ngraph:\n{}", self.message, self.graph)
+    }
+}
+
+/// Run `property` on `cases` random graphs of `strategy`, from the seed `seed`,
+/// with a proptest runner that saves no regressions.
+///
+/// # Errors
+/// The first graph the property fails on, shrunk as far as it keeps failing.
+pub fn check<F>(
+    strategy: &GraphStrategy,
+    cases: usize,
+    seed: u64,
+    property: F,
+) -> Result<(), Counterexample>
+where
+    F: Fn(&Graph) -> Result<(), String>,
+{
+    let config = Config {
+        cases: cases.try_into().unwrap_or(u32::MAX),
+        failure_persistence: None,
+        max_shrink_iters: u32::MAX,
+        ..Config::default()
+    };
+    let mut bytes = [0; 32];
+    bytes[..8].copy_from_slice(&seed.to_le_bytes());
+    let rng = TestRng::from_seed(RngAlgorithm::ChaCha, &bytes);
+    let result = TestRunner::new_with_rng(config, rng).run(strategy, |graph| {
+        property(&graph).map_err(TestCaseError::fail)
+    });
+    match result {
+        Ok(()) => Ok(()),
+        Err(TestError::Fail(reason, graph)) => Err(Counterexample {
+            graph,
+            message: reason.message().into(),
+        }),
+        // no graph is ever rejected
+        Err(TestError::Abort(reason)) => panic!("{}", reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+    use crate::test_utils::{check, GraphStrategy};
+    use proptest::prelude::*;
+    use proptest::test_runner::TestRunner;
+
+    #[test]
+    fn graphs_are_in_the_strategy() {
+        let mut runner = TestRunner::deterministic();
+        for directed in [true, false] {
+            for connected in [true, false] {
+                let strategy = GraphStrategy {
+                    directed,
+                    connected,
+                    ..GraphStrategy::default()
+                };
+                for _ in 0..50 {
+                    let graph = strategy.new_tree(&mut runner).unwrap().current();
+                    assert!(strategy.contains(&graph));
+                }
+            }
+        }
+        let graph = any::<Graph>().new_tree(&mut runner).unwrap().current();
+        assert!(GraphStrategy::default().contains(&graph));
+    }
+
+    #[test]
+    fn counterexamples_are_shrunk() {
+        let strategy = GraphStrategy {
+            max_vertices: 10,
+            edge_probability: 0.5,
+            ..GraphStrategy::default()
+        };
+        let failure = check(&strategy, 100, 1, |graph| {
+            if graph.n_edges() < 2 {
+                Ok(())
+            } else {
+                Err(format!("{} edges", graph.n_edges()))
+            }
+        })
+        .unwrap_err();
+        assert_eq!(failure.message, "2 edges");
+        assert!(failure.graph.adj.iter().flatten().all(|&(_v, w)| w == 1));
+
+        let passing = check(&strategy, 100, 1, |_graph: &Graph| Ok(()));
+        assert_eq!(passing, Ok(()));
+    }
+
+    proptest! {
+        #[test]
+        fn connected_graphs_reach_every_vertex(
+            graph in GraphStrategy { connected: true, directed: false, ..GraphStrategy::default() }
+        ) {
+            prop_assert!(graph.components().iter().all(|&c| c == 0));
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Instrumentation of the phases of the algorithm, behind the `tracing` feature.
+//!
+//! Every phase (parsing a graph, initialising a search, its main loop and the
+//! reconstruction of the paths) is a `tracing` span at the `TRACE` level with
+//! the target `djikstra`, entered for as long as the phase runs, so that
+//! subscribers such as `tracing-subscriber` or `tracing-flame` see them nested
+//! in the spans of the caller. The main loop also emits an event every
+//! [`PROGRESS_EVERY`] settled vertices. When no subscriber is set, spans and
+//! events are emitted as `log` records instead.
+//!
+//! Without the feature, spans and events compile to nothing.
+
+/// Number of settled vertices between two progress events of the main loop.
+pub const PROGRESS_EVERY: usize = 10_000;
+
+/// Enter the phase `$name` until the end of the scope of the returned guard.
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($name:literal) => {
+        tracing::span!(target: "djikstra", tracing::Level::TRACE, $name).entered()
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($name:literal) => {
+        $crate::trace::Span
+    };
+}
+
+pub(crate) use span;
+
+/// The guard of a phase when there is no `tracing`.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Span;
+
+/// Emit a progress event of the main loop after `settled` vertices, the last
+/// at distance `dist`, if it is a multiple of [`PROGRESS_EVERY`].
+#[inline(always)]
+pub(crate) fn progress(settled: usize, dist: usize) {
+    #[cfg(feature = "tracing")]
+    if settled.is_multiple_of(PROGRESS_EVERY) {
+        tracing::event!(target: "djikstra", tracing::Level::TRACE, settled, dist, "progress");
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (settled, dist);
+}
@@ -0,0 +1,255 @@
+//! Structural checks of input files with precise diagnostics.
+//!
+//! Unlike `Graph::from_str`, which stops at the first problem and makes no
+//! guarantees about the graph being valid, the checks here go through the whole
+//! input and report every problem with its line and column.
+use std::{collections::HashSet, fmt};
+
+/// An edge as `(from, to, weight)`.
+type Edge = (usize, usize, usize);
+
+/// How bad a problem found in the input is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The input cannot be used as is.
+    Error,
+    /// The input is usable but probably not what was intended.
+    Warning,
+}
+
+/// A problem found in the input, lines and columns are counted from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.line, self.column, severity, self.message
+        )
+    }
+}
+
+/// Check an input file: the start vertex line, the number of vertices line, and
+/// one line of `vertex,weight` pairs per vertex.
+///
+/// Checks that every number parses, that the start vertex and all neighbors are
+/// in range, and that there are no more adjacency lines than declared vertices.
+/// If `undirected` is set, every edge must also have a reverse edge with the same weight.
+///
+/// # Example
+/// ```
+/// use djikstra::validate::validate_input;
+///
+/// let diagnostics = validate_input("0\n2\n1,3\n5,3", false);
+/// assert_eq!(diagnostics[0].to_string(), "4:1: error: vertex 5 out of range 0..2");
+/// ```
+pub fn validate_input(s: &str, undirected: bool) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut lines = s.lines();
+    let mut error = |line: usize, column: usize, message: String| {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Error,
+            line,
+            column,
+            message,
+        })
+    };
+
+    let start_vertex = lines
+        .next()
+        .and_then(|line| match line.trim().parse::<usize>() {
+            Ok(v) => Some(v),
+            Err(e) => {
+                error(1, 1, format!("cannot parse start vertex: {}", e));
+                None
+            }
+        });
+    let n_vertex = match lines.next().map(|line| line.trim().parse::<usize>()) {
+        Some(Ok(n)) => n,
+        Some(Err(e)) => {
+            error(2, 1, format!("cannot parse n_vertices: {}", e));
+            return diagnostics;
+        }
+        None => {
+            error(2, 1, "missing number of vertices".to_string());
+            return diagnostics;
+        }
+    };
+    if let Some(start_vertex) = start_vertex {
+        if start_vertex >= n_vertex {
+            error(
+                1,
+                1,
+                format!("start vertex {} out of range 0..{}", start_vertex, n_vertex),
+            );
+        }
+    }
+
+    // every edge with its (line, column) in the input
+    let mut edges: Vec<(Edge, (usize, usize))> = vec![];
+    let mut n_lines = 0;
+
+    for (vertex, line) in lines.enumerate() {
+        let line_no = vertex + 3;
+        if vertex >= n_vertex {
+            if !line.trim().is_empty() {
+                error(
+                    line_no,
+                    1,
+                    format!(
+                        "more adjacency lines than the {} declared vertices",
+                        n_vertex
+                    ),
+                );
+            }
+            continue;
+        }
+        n_lines += 1;
+
+        for (column, token) in tokens(line) {
+            let Some((v, weight)) = token.split_once(',') else {
+                error(
+                    line_no,
+                    column,
+                    format!("{:?} doesnt have weight with it", token),
+                );
+                continue;
+            };
+            let v = match v.parse::<usize>() {
+                Ok(v) if v < n_vertex => Some(v),
+                Ok(v) => {
+                    error(
+                        line_no,
+                        column,
+                        format!("vertex {} out of range 0..{}", v, n_vertex),
+                    );
+                    None
+                }
+                Err(e) => {
+                    error(line_no, column, format!("cannot parse vertex: {}", e));
+                    None
+                }
+            };
+            let weight = match weight.parse::<usize>() {
+                Ok(weight) => Some(weight),
+                Err(e) => {
+                    let weight_column = column + token.find(',').unwrap() + 1;
+                    error(
+                        line_no,
+                        weight_column,
+                        format!("cannot parse weight: {}", e),
+                    );
+                    None
+                }
+            };
+            if let (Some(v), Some(weight)) = (v, weight) {
+                edges.push(((vertex, v, weight), (line_no, column)));
+            }
+        }
+    }
+
+    if n_lines < n_vertex {
+        diagnostics.push(Diagnostic {
+            severity: Severity::Warning,
+            line: n_lines + 3,
+            column: 1,
+            message: format!(
+                "{} adjacency lines for {} declared vertices, the rest have no neighbors",
+                n_lines, n_vertex
+            ),
+        });
+    }
+
+    if undirected {
+        let edge_set: HashSet<Edge> = edges.iter().map(|(e, _)| *e).collect();
+        for &((u, v, weight), (line, column)) in edges.iter() {
+            if !edge_set.contains(&(v, u, weight)) {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    line,
+                    column,
+                    message: format!(
+                        "edge {} -> {} has no reverse edge {} -> {} with weight {}",
+                        u, v, v, u, weight
+                    ),
+                });
+            }
+        }
+    }
+
+    diagnostics.sort_by_key(|d| (d.line, d.column));
+    diagnostics
+}
+
+/// Whitespace separated tokens of a line with the 1-based column they start at.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split(|c: char| c.is_whitespace())
+        .scan(1, |column, token| {
+            let start = *column;
+            *column += token.chars().count() + 1;
+            Some((start, token))
+        })
+        .filter(|(_, token)| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input_has_no_diagnostics() {
+        let input = "0\n3\n1,3 2,3\n2,2 0,3\n1,2 0,3";
+        assert_eq!(validate_input(input, true), vec![]);
+    }
+
+    #[test]
+    fn reports_positions() {
+        let input = "4\n3\n1,3  2,x\n9,2 0\n\n1,1";
+        let messages: Vec<String> = validate_input(input, false)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "1:1: error: start vertex 4 out of range 0..3",
+                "3:8: error: cannot parse weight: invalid digit found in string",
+                "4:1: error: vertex 9 out of range 0..3",
+                "4:5: error: \"0\" doesnt have weight with it",
+                "6:1: error: more adjacency lines than the 3 declared vertices",
+            ]
+        );
+    }
+
+    #[test]
+    fn warns_about_missing_lines() {
+        let diagnostics = validate_input("0\n3\n1,3\n0,3", false);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn reports_asymmetric_edges() {
+        let input = "0\n3\n1,3 2,3\n0,3\n0,4";
+        let diagnostics = validate_input(input, true);
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|d| (d.line, d.column))
+                .collect::<Vec<_>>(),
+            vec![(3, 5), (5, 1)]
+        );
+        assert!(validate_input(input, false).is_empty());
+    }
+}
@@ -0,0 +1,322 @@
+//! Checks that shortest path results are correct, without trusting the
+//! algorithm that found them.
+//!
+//! Distances are the shortest ones exactly when the source is at distance
+//! 0, no edge can shorten the distance of the vertex it goes to, and every
+//! reachable vertex has a path in the graph costing its distance. Checking
+//! this takes one pass over the edges and the paths, far less than a second
+//! algorithm to compare with.
+//!
+use crate::dijkstra::{add_distance, PathsAndDists};
+use crate::graph::{Adjacency, IndexType, WeightType};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A reason shortest path results are wrong, found by [`check_sssp`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// The results do not have one path and one distance per vertex.
+    Length {
+        n_vertices: usize,
+        n_paths: usize,
+        n_dists: usize,
+    },
+    /// The source is not a vertex of the graph.
+    InvalidSource { source: usize, n_vertices: usize },
+    /// The source is not at distance 0.
+    Source { dist: Option<usize> },
+    /// The edge from `from` to `to` is shorter than the distance of `to`,
+    /// `None` if `to` is said to be unreachable.
+    Relaxable {
+        from: usize,
+        to: usize,
+        weight: usize,
+        dist: Option<usize>,
+    },
+    /// A vertex has a distance but no path, or a path but no distance.
+    Inconsistent { vertex: usize },
+    /// The path of `vertex` does not go from the source to `vertex`.
+    Endpoints { vertex: usize },
+    /// The path of `vertex` goes from `from` to `to` without an edge between them.
+    MissingEdge {
+        vertex: usize,
+        from: usize,
+        to: usize,
+    },
+    /// The path of `vertex` costs `cost`, `None` if the cost overflows, and
+    /// not the distance of `vertex`.
+    PathCost {
+        vertex: usize,
+        cost: Option<usize>,
+        dist: usize,
+    },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |f: &mut fmt::Formatter<'_>, dist: &Option<usize>| match dist {
+            Some(dist) => write!(f, "{}", dist),
+            None => write!(f, "unreachable"),
+        };
+        match self {
+            Violation::Length {
+                n_vertices,
+                n_paths,
+                n_dists,
+            } => write!(
+                f,
+                "{} paths and {} distances for {} vertices",
+                n_paths, n_dists, n_vertices
+            ),
+            Violation::InvalidSource { source, n_vertices } => write!(
+                f,
+                "source {} is not in the graph of {} vertices",
+                source, n_vertices
+            ),
+            Violation::Source { dist } => {
+                write!(f, "source: distance ")?;
+                show(f, dist)?;
+                write!(f, ", not 0")
+            }
+            Violation::Relaxable {
+                from,
+                to,
+                weight,
+                dist,
+            } => {
+                write!(f, "vertex {}: distance ", to)?;
+                show(f, dist)?;
+                write!(
+                    f,
+                    ", but the edge from {} of weight {} is shorter",
+                    from, weight
+                )
+            }
+            Violation::Inconsistent { vertex } => write!(
+                f,
+                "vertex {}: a distance without a path or a path without a distance",
+                vertex
+            ),
+            Violation::Endpoints { vertex } => write!(
+                f,
+                "vertex {}: the path does not go from the source to the vertex",
+                vertex
+            ),
+            Violation::MissingEdge { vertex, from, to } => write!(
+                f,
+                "vertex {}: the path goes from {} to {} without an edge",
+                vertex, from, to
+            ),
+            Violation::PathCost { vertex, cost, dist } => {
+                write!(f, "vertex {}: the path costs ", vertex)?;
+                show(f, cost)?;
+                write!(f, ", but the distance is {}", dist)
+            }
+        }
+    }
+}
+
+/// Check that `result`, the paths and distances from `src` as returned by
+/// [`crate::dijkstra::dijkstra`] and the other algorithms, are the shortest
+/// ones in `graph`: every edge satisfies the relaxation inequality, every path
+/// is in the graph and costs its distance, and the vertices said to be
+/// unreachable cannot be reached from a reachable one. Returns every
+/// violation found, none if the results are correct.
+///
+/// # Example
+/// ```
+/// use djikstra::dijkstra::dijkstra;
+/// use djikstra::graph::Graph;
+/// use djikstra::verify::{check_sssp, Violation};
+///
+/// let graph = Graph::new(vec![vec![(1, 3), (2, 5)], vec![(2, 1)], vec![]]);
+/// let result = dijkstra(&graph, 0).unwrap();
+/// assert!(check_sssp(&graph, 0, &result).is_empty());
+///
+/// // the path 0 -> 2 of weight 5 is not the shortest
+/// let wrong = (vec![Some(vec![0]), Some(vec![0, 1]), Some(vec![0, 2])], vec![Some(0), Some(3), Some(5)]);
+/// assert_eq!(
+///     check_sssp(&graph, 0, &wrong),
+///     vec![Violation::Relaxable { from: 1, to: 2, weight: 1, dist: Some(5) }]
+/// );
+/// ```
+pub fn check_sssp(graph: &impl Adjacency, src: usize, result: &PathsAndDists) -> Vec<Violation> {
+    let (paths, dists) = result;
+    let n_vertices = graph.n_vertices();
+    if src >= n_vertices {
+        return vec![Violation::InvalidSource {
+            source: src,
+            n_vertices,
+        }];
+    }
+    if paths.len() != n_vertices || dists.len() != n_vertices {
+        return vec![Violation::Length {
+            n_vertices,
+            n_paths: paths.len(),
+            n_dists: dists.len(),
+        }];
+    }
+
+    let mut violations = vec![];
+    if dists[src] != Some(0) {
+        violations.push(Violation::Source { dist: dists[src] });
+    }
+
+    for (from, &from_dist) in dists.iter().enumerate() {
+        let Some(from_dist) = from_dist else {
+            continue;
+        };
+        for &(to, weight) in graph.neighbors_of(from).iter() {
+            let (to, weight) = (to.index(), weight.weight());
+            // a distance that overflows cannot be shorter
+            let Some(through) = add_distance(from_dist, weight) else {
+                continue;
+            };
+            if dists[to].is_none_or(|dist| dist > through) {
+                violations.push(Violation::Relaxable {
+                    from,
+                    to,
+                    weight,
+                    dist: dists[to],
+                });
+            }
+        }
+    }
+
+    for (vertex, (path, &dist)) in paths.iter().zip(dists.iter()).enumerate() {
+        let (path, dist) = match (path, dist) {
+            (None, None) => continue,
+            (Some(path), Some(dist)) => (path, dist),
+            _ => {
+                violations.push(Violation::Inconsistent { vertex });
+                continue;
+            }
+        };
+        if path.first() != Some(&src) || path.last() != Some(&vertex) {
+            violations.push(Violation::Endpoints { vertex });
+            continue;
+        }
+        match path_cost(graph, path) {
+            Err((from, to)) => violations.push(Violation::MissingEdge { vertex, from, to }),
+            Ok(cost) if cost != Some(dist) => {
+                violations.push(Violation::PathCost { vertex, cost, dist })
+            }
+            Ok(_cost) => {}
+        }
+    }
+    violations
+}
+
+/// Cost of `path` following the cheapest edge between every two vertices,
+/// `None` if it overflows, or the first two vertices without an edge.
+fn path_cost(graph: &impl Adjacency, path: &[usize]) -> Result<Option<usize>, (usize, usize)> {
+    let mut cost = Some(0);
+    for edge in path.windows(2) {
+        let weight = graph
+            .neighbors_of(edge[0])
+            .iter()
+            .filter(|&&(v, _weight)| v.index() == edge[1])
+            .map(|&(_v, weight)| weight.weight())
+            .min()
+            .ok_or((edge[0], edge[1]))?;
+        cost = cost.and_then(|cost| add_distance(cost, weight));
+    }
+    Ok(cost)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bellman_ford::bellman_ford;
+    use crate::dijkstra::dijkstra;
+    use crate::graph::Graph;
+    use crate::test_utils::sample_graph;
+    use crate::verify::{check_sssp, Violation};
+    use alloc::vec;
+
+    #[test]
+    fn correct_results_pass() {
+        let g1 = sample_graph();
+        for src in 0..g1.n_vertices() {
+            assert_eq!(check_sssp(&g1, src, &dijkstra(&g1, src).unwrap()), vec![]);
+            assert_eq!(
+                check_sssp(&g1, src, &bellman_ford(&g1, src).unwrap()),
+                vec![]
+            );
+        }
+        // unreachable vertices
+        let g2 = Graph::new(vec![vec![(1, 1)], vec![], vec![(0, 1)]]);
+        assert_eq!(check_sssp(&g2, 0, &dijkstra(&g2, 0).unwrap()), vec![]);
+    }
+
+    #[test]
+    fn wrong_results_fail() {
+        let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+
+        // 2 is reachable through 1
+        let (paths, mut dists) = dijkstra(&graph, 0).unwrap();
+        dists[2] = None;
+        let mut unreachable_paths = paths.clone();
+        unreachable_paths[2] = None;
+        assert_eq!(
+            check_sssp(&graph, 0, &(unreachable_paths, dists.clone())),
+            vec![Violation::Relaxable {
+                from: 1,
+                to: 2,
+                weight: 1,
+                dist: None
+            }]
+        );
+
+        // a path without a distance, a path skipping an edge, a wrong cost
+        let wrong = (
+            vec![Some(vec![0]), Some(vec![0, 1]), Some(vec![0, 2])],
+            vec![Some(0), None, Some(4)],
+        );
+        assert_eq!(
+            check_sssp(&graph, 0, &wrong),
+            vec![
+                Violation::Relaxable {
+                    from: 0,
+                    to: 1,
+                    weight: 3,
+                    dist: None
+                },
+                Violation::Inconsistent { vertex: 1 },
+                Violation::MissingEdge {
+                    vertex: 2,
+                    from: 0,
+                    to: 2
+                },
+            ]
+        );
+        let wrong = (
+            vec![Some(vec![1]), Some(vec![0, 1]), Some(vec![0, 1, 2])],
+            vec![Some(1), Some(3), Some(3)],
+        );
+        assert_eq!(
+            check_sssp(&graph, 0, &wrong),
+            vec![
+                Violation::Source { dist: Some(1) },
+                Violation::Endpoints { vertex: 0 },
+                Violation::PathCost {
+                    vertex: 2,
+                    cost: Some(4),
+                    dist: 3
+                },
+            ]
+        );
+        assert!(matches!(
+            check_sssp(&graph, 0, &(vec![], vec![]))[..],
+            [Violation::Length { .. }]
+        ));
+        let result = dijkstra(&graph, 0).unwrap();
+        assert_eq!(
+            check_sssp(&graph, 7, &result),
+            vec![Violation::InvalidSource {
+                source: 7,
+                n_vertices: graph.n_vertices()
+            }]
+        );
+    }
+}
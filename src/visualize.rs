@@ -0,0 +1,246 @@
+//! Drawing small graphs as SVG images, with a shortest path highlighted.
+//!
+use crate::graph::Graph;
+use std::collections::HashSet;
+use std::f64::consts::PI;
+use std::io::{self, Write};
+
+/// Width and height of the drawing in pixels, without the margin.
+const SIZE: f64 = 800.0;
+/// Empty space around the drawing in pixels.
+const MARGIN: f64 = 40.0;
+/// Radius of the circle drawn for a vertex in pixels.
+const RADIUS: f64 = 14.0;
+
+/// Place the vertices in the unit square with the Fruchterman-Reingold
+/// force-directed algorithm: vertices repel each other and edges pull their
+/// ends together, with the moves shrinking over `iterations` rounds.
+///
+/// The vertices start on a circle, so the layout is the same on every call.
+/// Every round takes quadratic time in the number of vertices, which is
+/// fine for the small graphs worth looking at.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::visualize::force_layout;
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// let positions = force_layout(&graph, 100);
+/// assert!(positions.iter().all(|&(x, y)| (0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y)));
+/// ```
+pub fn force_layout(graph: &Graph, iterations: usize) -> Vec<(f64, f64)> {
+    let n = graph.n_vertices();
+    let mut positions: Vec<(f64, f64)> = (0..n)
+        .map(|v| {
+            let angle = 2.0 * PI * v as f64 / n as f64;
+            (0.5 + 0.4 * angle.cos(), 0.5 + 0.4 * angle.sin())
+        })
+        .collect();
+    // ideal distance between vertices
+    let k = (1.0 / n.max(1) as f64).sqrt();
+
+    for iteration in 0..iterations {
+        let temperature = 0.1 * (1.0 - iteration as f64 / iterations as f64);
+        let mut moves = vec![(0.0, 0.0); n];
+
+        for u in 0..n {
+            for v in u + 1..n {
+                let (dx, dy, dist) = offset(positions[u], positions[v]);
+                let force = k * k / dist;
+                moves[u].0 += dx / dist * force;
+                moves[u].1 += dy / dist * force;
+                moves[v].0 -= dx / dist * force;
+                moves[v].1 -= dy / dist * force;
+            }
+        }
+        for (u, neighbors) in graph.adj.iter().enumerate() {
+            for &(v, _weight) in neighbors.iter().filter(|&&(v, _)| v != u) {
+                let (dx, dy, dist) = offset(positions[u], positions[v]);
+                let force = dist * dist / k;
+                moves[u].0 -= dx / dist * force;
+                moves[u].1 -= dy / dist * force;
+                moves[v].0 += dx / dist * force;
+                moves[v].1 += dy / dist * force;
+            }
+        }
+
+        for (position, (dx, dy)) in positions.iter_mut().zip(moves) {
+            let length = (dx * dx + dy * dy).sqrt();
+            if length > 0.0 {
+                let step = length.min(temperature);
+                position.0 = (position.0 + dx / length * step).clamp(0.0, 1.0);
+                position.1 = (position.1 + dy / length * step).clamp(0.0, 1.0);
+            }
+        }
+    }
+
+    fit_to_unit_square(positions)
+}
+
+/// Offset from `b` to `a` and its length, kept away from 0 so it can be divided by.
+fn offset(a: (f64, f64), b: (f64, f64)) -> (f64, f64, f64) {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    (dx, dy, (dx * dx + dy * dy).sqrt().max(1e-6))
+}
+
+/// Scale and move the positions so they span the unit square,
+/// centering them along an axis they do not spread over.
+fn fit_to_unit_square(positions: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    let fit = |coords: Vec<f64>| -> Vec<f64> {
+        let min = coords.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = coords.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        coords
+            .iter()
+            .map(|&c| {
+                if max - min > 1e-9 {
+                    (c - min) / (max - min)
+                } else {
+                    0.5
+                }
+            })
+            .collect()
+    };
+    let xs = fit(positions.iter().map(|p| p.0).collect());
+    let ys = fit(positions.iter().map(|p| p.1).collect());
+    xs.into_iter().zip(ys).collect()
+}
+
+/// Write the graph as an SVG image, with every vertex at its position in the
+/// unit square (e.g. from `force_layout`) and the edges labeled with their weights.
+/// The vertices and edges of `path` are drawn in red.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::visualize::{force_layout, write_svg};
+///
+/// let graph = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![]]);
+/// let mut svg = Vec::new();
+/// write_svg(&graph, &force_layout(&graph, 100), &[0, 1, 2], &mut svg).unwrap();
+/// assert!(String::from_utf8(svg).unwrap().starts_with("<svg"));
+/// ```
+pub fn write_svg<W: Write + ?Sized>(
+    graph: &Graph,
+    positions: &[(f64, f64)],
+    path: &[usize],
+    w: &mut W,
+) -> io::Result<()> {
+    let path_vertices: HashSet<usize> = path.iter().cloned().collect();
+    let path_edges: HashSet<(usize, usize)> =
+        path.windows(2).map(|edge| (edge[0], edge[1])).collect();
+    let pixel = |(x, y): (f64, f64)| (MARGIN + x * SIZE, MARGIN + y * SIZE);
+    let total = SIZE + 2.0 * MARGIN;
+
+    writeln!(
+        w,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+        total
+    )?;
+    writeln!(w, r#"  <defs>"#)?;
+    for (id, color) in [("arrow", "gray"), ("arrow-path", "red")] {
+        writeln!(
+            w,
+            r#"    <marker id="{0}" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="6" markerHeight="6" orient="auto"><path d="M 0 0 L 10 5 L 0 10 z" fill="{1}"/></marker>"#,
+            id, color
+        )?;
+    }
+    writeln!(w, r#"  </defs>"#)?;
+    writeln!(w, r#"  <rect width="100%" height="100%" fill="white"/>"#)?;
+
+    // edges first so the vertices are drawn over them
+    for (u, neighbors) in graph.adj.iter().enumerate() {
+        for &(v, weight) in neighbors.iter().filter(|&&(v, _)| v != u) {
+            let (x1, y1) = pixel(positions[u]);
+            let (x2, y2) = pixel(positions[v]);
+            let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt().max(1e-6);
+            // stop the line at the border of the circles
+            let (ux, uy) = ((x2 - x1) / length, (y2 - y1) / length);
+            let (x1, y1) = (x1 + ux * RADIUS, y1 + uy * RADIUS);
+            let (x2, y2) = (x2 - ux * RADIUS, y2 - uy * RADIUS);
+            let (color, width, marker) = if path_edges.contains(&(u, v)) {
+                ("red", 3, "arrow-path")
+            } else {
+                ("gray", 1, "arrow")
+            };
+            writeln!(
+                w,
+                r#"  <line x1="{0:.1}" y1="{1:.1}" x2="{2:.1}" y2="{3:.1}" stroke="{4}" stroke-width="{5}" marker-end="url(#{6})"/>"#,
+                x1, y1, x2, y2, color, width, marker
+            )?;
+            writeln!(
+                w,
+                r#"  <text x="{0:.1}" y="{1:.1}" font-size="10" fill="{2}" text-anchor="middle">{3}</text>"#,
+                (x1 + x2) / 2.0,
+                (y1 + y2) / 2.0 - 3.0,
+                color,
+                weight
+            )?;
+        }
+    }
+
+    for (v, &position) in positions.iter().enumerate() {
+        let (x, y) = pixel(position);
+        let color = if path_vertices.contains(&v) {
+            "red"
+        } else {
+            "black"
+        };
+        writeln!(
+            w,
+            r#"  <circle cx="{0:.1}" cy="{1:.1}" r="{2}" fill="white" stroke="{3}" stroke-width="2"/>"#,
+            x, y, RADIUS, color
+        )?;
+        writeln!(
+            w,
+            r#"  <text x="{0:.1}" y="{1:.1}" font-size="12" fill="{2}" text-anchor="middle" dominant-baseline="central">{3}</text>"#,
+            x, y, color, v
+        )?;
+    }
+    writeln!(w, "</svg>")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+    use crate::visualize::{force_layout, write_svg};
+
+    #[test]
+    fn layout_is_deterministic_and_spans_the_square() {
+        let g1 = Graph::new(vec![
+            vec![(1, 1), (2, 1)],
+            vec![(2, 1)],
+            vec![(3, 1)],
+            vec![],
+        ]);
+        let positions = force_layout(&g1, 50);
+        assert_eq!(positions, force_layout(&g1, 50));
+        assert_eq!(positions.len(), 4);
+
+        let min_x = positions.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+        let max_x = positions
+            .iter()
+            .map(|p| p.0)
+            .fold(f64::NEG_INFINITY, f64::max);
+        assert_eq!((min_x, max_x), (0.0, 1.0));
+    }
+
+    #[test]
+    fn single_vertex_is_centered() {
+        let g1 = Graph::new(vec![vec![]]);
+        assert_eq!(force_layout(&g1, 10), vec![(0.5, 0.5)]);
+    }
+
+    #[test]
+    fn path_is_highlighted() {
+        let g1 = Graph::new(vec![vec![(1, 3)], vec![(2, 1)], vec![], vec![(0, 1)]]);
+        let mut svg = Vec::new();
+        write_svg(&g1, &force_layout(&g1, 10), &[0, 1], &mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+
+        assert_eq!(svg.matches("<circle").count(), 4);
+        assert_eq!(svg.matches("<line").count(), 3);
+        assert_eq!(svg.matches(r#"stroke="red" stroke-width="3""#).count(), 1);
+        assert_eq!(svg.matches(r#"stroke="red" stroke-width="2""#).count(), 2);
+    }
+}
@@ -0,0 +1,152 @@
+//! Shortest walks with a fixed number of edges, by dynamic programming over
+//! the number of edges.
+//!
+use crate::dijkstra::{add_distance, check_source, DijkstraError, PathAndDist};
+use crate::graph::{Adjacency, IndexType, WeightType};
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Cheapest walk from `src` to `dst` made of exactly `k` edges, e.g. a
+/// schedule of exactly `k` steps. A walk may go through a vertex or an edge
+/// more than once. Returns the walk and its cost, or `None` if there is no
+/// walk of `k` edges from `src` to `dst`.
+///
+/// The cheapest walks of `i` edges to every vertex are computed from the
+/// ones of `i - 1` edges, in `O(k * n_edges)` time, keeping `k * n_vertices`
+/// parents to build the walk.
+///
+/// # Errors
+/// Fails if `src` or `dst` is not a vertex of the graph, or if the cost of
+/// every walk of `k` edges to `dst` does not fit in a `usize`.
+///
+/// # Example
+/// ```
+/// use djikstra::graph::Graph;
+/// use djikstra::walk::shortest_walk;
+///
+/// let graph = Graph::new(vec![vec![(1, 1), (2, 5)], vec![(2, 1), (0, 1)], vec![]]);
+/// assert_eq!(shortest_walk(&graph, 0, 2, 1), Ok(Some((vec![0, 2], 5))));
+/// assert_eq!(shortest_walk(&graph, 0, 2, 2), Ok(Some((vec![0, 1, 2], 2))));
+/// assert_eq!(shortest_walk(&graph, 0, 2, 3), Ok(Some((vec![0, 1, 0, 2], 7))));
+/// ```
+pub fn shortest_walk(
+    graph: &impl Adjacency,
+    src: usize,
+    dst: usize,
+    k: usize,
+) -> Result<Option<PathAndDist>, DijkstraError> {
+    check_source(graph, src)?;
+    check_source(graph, dst)?;
+    let n_elems = graph.n_vertices();
+
+    // costs of the cheapest walks of `i` edges, and the vertex before the
+    // last one of the walk of `i` edges to `v` at `parents[i * n_elems + v]`
+    let mut costs: Vec<Option<usize>> = vec![None; n_elems];
+    let mut overflowed = vec![false; n_elems];
+    let mut parents = vec![None; k * n_elems];
+    costs[src] = Some(0);
+
+    for i in 0..k {
+        let mut next_costs: Vec<Option<usize>> = vec![None; n_elems];
+        let mut next_overflowed = vec![false; n_elems];
+        for u in 0..n_elems {
+            for &(v, weight) in graph.neighbors_of(u).iter() {
+                let v = v.index();
+                // a walk that overflowed still overflows one edge later
+                next_overflowed[v] |= overflowed[u];
+                let Some(cost) = costs[u] else {
+                    continue;
+                };
+                let Some(new_cost) = add_distance(cost, weight.weight()) else {
+                    next_overflowed[v] = true;
+                    continue;
+                };
+                if next_costs[v].is_none_or(|old| old > new_cost) {
+                    next_costs[v] = Some(new_cost);
+                    parents[i * n_elems + v] = Some(u);
+                }
+            }
+        }
+        costs = next_costs;
+        overflowed = next_overflowed;
+    }
+
+    let Some(cost) = costs[dst] else {
+        // an overflowing walk is only a problem if there is no other one
+        return match overflowed[dst] {
+            true => Err(DijkstraError::Overflow),
+            false => Ok(None),
+        };
+    };
+    let mut walk = vec![dst];
+    for i in (0..k).rev() {
+        let v = *walk.last().unwrap();
+        walk.push(parents[i * n_elems + v].expect("every step of the walk has a parent"));
+    }
+    walk.reverse();
+    Ok(Some((walk, cost)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dijkstra::{shortest_path, DijkstraError};
+    use crate::graph::Graph;
+    use crate::test_utils::sample_graph;
+    use crate::walk::shortest_walk;
+    use alloc::vec;
+
+    #[test]
+    fn walks_have_exactly_k_edges() {
+        let g1 = sample_graph();
+        for dst in 0..g1.n_vertices() {
+            let (_path, dist) = shortest_path(&g1, 0, dst).unwrap().unwrap();
+            let mut cheapest = usize::MAX;
+            for k in 0..2 * g1.n_vertices() {
+                let Some((walk, cost)) = shortest_walk(&g1, 0, dst, k).unwrap() else {
+                    continue;
+                };
+                assert_eq!(walk.len(), k + 1);
+                assert_eq!((walk[0], walk[k]), (0, dst));
+                let weights = walk.windows(2).map(|edge| {
+                    g1.neighbors_of(edge[0])
+                        .iter()
+                        .find(|&&(v, _w)| v == edge[1])
+                        .expect("the walk follows the edges")
+                        .1
+                });
+                assert_eq!(weights.sum::<usize>(), cost);
+                assert!(cost >= dist);
+                cheapest = cheapest.min(cost);
+            }
+            // the shortest path is the cheapest walk of any length
+            assert_eq!(cheapest, dist);
+        }
+    }
+
+    #[test]
+    fn no_walk_of_k_edges() {
+        // only walks of an even number of edges between 0 and 2
+        let g1 = Graph::new(vec![vec![(1, 1)], vec![(0, 1), (2, 1)], vec![(1, 1)]]);
+        assert_eq!(shortest_walk(&g1, 0, 0, 0), Ok(Some((vec![0], 0))));
+        assert_eq!(shortest_walk(&g1, 0, 2, 3), Ok(None));
+        assert_eq!(
+            shortest_walk(&g1, 0, 2, 4),
+            Ok(Some((vec![0, 1, 0, 1, 2], 4)))
+        );
+        assert!(shortest_walk(&g1, 0, 3, 1).is_err());
+    }
+
+    #[test]
+    fn overflowing_walks_are_errors() {
+        let big = usize::MAX / 2;
+        let g1 = Graph::new(vec![vec![(1, big)], vec![(2, big + 2)], vec![]]);
+        assert_eq!(shortest_walk(&g1, 0, 2, 2), Err(DijkstraError::Overflow));
+        let g2 = Graph::new(vec![
+            vec![(1, big), (3, 1)],
+            vec![(2, big + 2)],
+            vec![],
+            vec![(2, 1)],
+        ]);
+        assert_eq!(shortest_walk(&g2, 0, 2, 2), Ok(Some((vec![0, 3, 2], 2))));
+    }
+}
@@ -0,0 +1,98 @@
+//! Bindings for JavaScript, to run the algorithm in the browser.
+//!
+//! Build with `wasm-pack build --target web -- --no-default-features --features wasm`.
+//! Results are returned as the same JSON as `query --format json`, except that
+//! `time_ns` is always 0: there is no clock on `wasm32-unknown-unknown`.
+use crate::dijkstra::{shortest_path, shortest_paths};
+use crate::graph::{Graph, ParseGraphError};
+use crate::output::{Results, VertexResult};
+use wasm_bindgen::prelude::*;
+
+/// A graph parsed once and kept on the Rust side between queries.
+#[wasm_bindgen]
+pub struct WasmGraph(Graph);
+
+/// Parse a graph in the input format of the command line.
+#[wasm_bindgen]
+pub fn parse_graph(input: &str) -> Result<WasmGraph, JsError> {
+    Ok(WasmGraph(parse(input)?))
+}
+
+/// [`parse_graph`] before the error is converted for JavaScript, which
+/// cannot be done off `wasm32`. Edges to vertices past the vertex count are
+/// rejected here, so that the queries cannot index out of the graph.
+fn parse(input: &str) -> Result<Graph, ParseGraphError> {
+    let graph: Graph = input.parse()?;
+    graph.check_targets()?;
+    Ok(graph)
+}
+
+#[wasm_bindgen]
+impl WasmGraph {
+    /// Number of vertices in the graph.
+    #[wasm_bindgen(getter)]
+    pub fn n_vertices(&self) -> usize {
+        self.0.n_vertices()
+    }
+
+    /// Shortest path from `src` to `dst` as JSON, with a `null` path and
+    /// distance if `dst` cannot be reached.
+    pub fn shortest_path(&self, src: usize, dst: usize) -> Result<String, JsError> {
+        self.check_vertex(dst)?;
+        let result = shortest_path(&self.0, src, dst)?;
+        let results = Results {
+            source: src,
+            time_ns: 0,
+            vertices: vec![VertexResult {
+                vertex: dst,
+                distance: result.as_ref().map(|(_path, dist)| *dist),
+                path: result.as_ref().map(|(path, _dist)| path.as_slice()),
+            }],
+        };
+        Ok(serde_json::to_string(&results)?)
+    }
+
+    /// Shortest paths from `src` to every vertex as JSON.
+    pub fn shortest_paths(&self, src: usize) -> Result<String, JsError> {
+        let result = shortest_paths(&self.0, src)?;
+        let (paths, dists) = result.into_parts();
+        let results = Results::new(src, &paths, &dists, 0);
+        Ok(serde_json::to_string(&results)?)
+    }
+}
+
+impl WasmGraph {
+    fn check_vertex(&self, v: usize) -> Result<(), JsError> {
+        if v < self.0.n_vertices() {
+            Ok(())
+        } else {
+            Err(JsError::new(&format!(
+                "vertex {v} is not in the graph of {} vertices",
+                self.0.n_vertices()
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wasm::{parse, parse_graph};
+
+    #[test]
+    fn answers_in_query_json() {
+        let graph = parse_graph("3\n1,3\n2,1\n\n").ok().unwrap();
+        assert_eq!(graph.n_vertices(), 3);
+        let json = graph.shortest_path(0, 2).ok().unwrap();
+        assert_eq!(
+            json,
+            r#"{"source":0,"time_ns":0,"vertices":[{"vertex":2,"distance":4,"path":[0,1,2]}]}"#
+        );
+    }
+
+    #[test]
+    fn rejects_edges_to_missing_vertices() {
+        let error = parse("2\n9,1\n\n").unwrap_err();
+        assert_eq!(error.to_string(), "vertex 9 out of range 0..2");
+        assert!(parse("2\n1,1\n\n").is_ok());
+    }
+}
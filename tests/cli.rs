@@ -0,0 +1,740 @@
+//! Runs of the command line on files, checking what it writes and its exit codes.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// A directory of its own for a test, emptied first.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("djikstra-cli-{0}-{1}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Run the command line with `args` in `dir`.
+fn djikstra(dir: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_djikstra"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+/// Run the command line with `args` in `dir`, writing `input` to its stdin.
+fn djikstra_reading(dir: &PathBuf, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_djikstra"))
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+/// Graph of the tests of every subcommand: 0 -> 1 -> 2 costs 2, less than
+/// the edge 0 -> 2.
+const GRAPH: &str = "0\n3\n1,1 2,5\n2,1\n\n";
+
+#[test]
+fn edits_a_graph_in_place() {
+    let dir = test_dir("edit-in-place");
+    fs::write(dir.join("g.txt"), "0\n3\n1,1 2,5\n2,1\n\n").unwrap();
+
+    let output = djikstra(
+        &dir,
+        &["edit", "-i", "g.txt", "--add-edge", "2,0,7", "-o", "g.txt"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let edited = fs::read_to_string(dir.join("g.txt")).unwrap();
+    assert_eq!(edited, "0\n3\n1,1 2,5\n2,1\n0,7\n");
+
+    // a failing edit leaves the file as it was, and no temporary file behind
+    let output = djikstra(
+        &dir,
+        &["edit", "-i", "g.txt", "--remove-edge", "2,1", "-o", "g.txt"],
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(fs::read_to_string(dir.join("g.txt")).unwrap(), edited);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+    // watching writes as it goes, so it cannot write over its input
+    let output = djikstra(&dir, &["run", "-i", "g.txt", "--watch", "-o", "./g.txt"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(fs::read_to_string(dir.join("g.txt")).unwrap(), edited);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn watch_replaces_the_output_on_every_run() {
+    let dir = test_dir("watch");
+    fs::write(dir.join("g.txt"), "0\n2\n1,1\n\n").unwrap();
+    let mut watch = Command::new(env!("CARGO_BIN_EXE_djikstra"))
+        .args(["run", "-i", "g.txt", "--watch", "-o", "out.txt"])
+        .current_dir(&dir)
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    // the results without the running time, once the watch wrote them
+    let results_after = |previous: &str| {
+        for _ in 0..500 {
+            let written = fs::read_to_string(dir.join("out.txt")).unwrap_or_default();
+            let results: String = written
+                .lines()
+                .filter(|line| !line.starts_with("Algorithm ran"))
+                .map(|line| format!("{0}\n", line))
+                .collect();
+            if !results.is_empty() && results != previous {
+                return results;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        panic!("the watch did not write new results");
+    };
+
+    let first = results_after("");
+    assert_eq!(first, "0 0 (0)\n1 1 (0 -> 1)\n");
+    thread::sleep(Duration::from_millis(50));
+    fs::write(dir.join("g.txt"), "0\n2\n1,4\n\n").unwrap();
+    // the second run replaces the results of the first instead of following them
+    assert_eq!(results_after(&first), "0 0 (0)\n1 4 (0 -> 1)\n");
+
+    watch.kill().unwrap();
+    watch.wait().unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn generate_rejects_what_it_could_not_read_back() {
+    let dir = test_dir("generate");
+    let output = djikstra(
+        &dir,
+        &["generate", "--model", "gnp", "--n", "3", "--start", "7"],
+    );
+    assert_eq!(output.status.code(), Some(2));
+    let output = djikstra(
+        &dir,
+        &[
+            "generate",
+            "--model",
+            "grid",
+            "--n",
+            "3",
+            "--max-weight",
+            "0",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = djikstra(
+        &dir,
+        &["generate", "--model", "grid", "--n", "3", "--start", "2"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let generated = String::from_utf8(output.stdout).unwrap();
+    assert!(generated.starts_with("2\n3\n"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rejects_edges_to_missing_vertices() {
+    let dir = test_dir("missing-target");
+    fs::write(dir.join("g.txt"), "0\n2\n9,1\n\n").unwrap();
+    for command in ["run", "stats", "components"] {
+        let output = djikstra(&dir, &[command, "-i", "g.txt"]);
+        assert_eq!(output.status.code(), Some(3), "{:?}", output);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.contains("vertex 9 out of range 0..2"), "{}", stderr);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn hashmap_is_an_alias_of_the_btree_queue() {
+    let dir = test_dir("pq-alias");
+    fs::write(dir.join("g.txt"), "0\n3\n1,1 2,5\n2,1\n\n").unwrap();
+    for pq in ["btree", "hashmap"] {
+        let output = djikstra(&dir, &["profile", "-i", "g.txt", "--pq", pq]);
+        assert!(output.status.success(), "{:?}", output);
+        let profile = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            profile.starts_with("Profile of dijkstra (btree)"),
+            "{}",
+            profile
+        );
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn compares_with_other_crates_when_built_to() {
+    let dir = test_dir("compare-crates");
+    fs::write(dir.join("g.txt"), "0\n3\n1,1 2,5\n2,1\n\n").unwrap();
+    let output = djikstra(
+        &dir,
+        &[
+            "benchmark",
+            "-i",
+            "g.txt",
+            "-n",
+            "3",
+            "--pq",
+            "binary",
+            "--compare-crates",
+        ],
+    );
+    if cfg!(feature = "compare") {
+        assert!(output.status.success(), "{:?}", output);
+        let comparison = String::from_utf8(output.stdout).unwrap();
+        for name in [
+            "dijkstra (binary)",
+            "petgraph::algo::dijkstra",
+            "pathfinding::dijkstra_all",
+        ] {
+            assert!(comparison.contains(name), "{}", comparison);
+        }
+    } else {
+        assert_eq!(output.status.code(), Some(2));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn verify_checks_the_expected_paths() {
+    let dir = test_dir("verify-paths");
+    fs::write(dir.join("g.txt"), "0\n3\n1,1 2,5\n2,1\n").unwrap();
+    let golden = |path: &str| {
+        format!(
+            r#"{{"source":0,"vertices":[{{"vertex":0,"distance":0,"path":[0]}},{{"vertex":1,"distance":1,"path":[0,1]}},{{"vertex":2,"distance":2,"path":{0}}}]}}"#,
+            path
+        )
+    };
+
+    fs::write(dir.join("good.json"), golden("[0,1,2]")).unwrap();
+    let output = djikstra(&dir, &["verify", "-i", "g.txt", "--expected", "good.json"]);
+    assert!(output.status.success(), "{:?}", output);
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.starts_with("0 mismatches"), "{}", report);
+
+    // the right distance, with a path that costs 5, starts elsewhere or leaves the graph
+    for (path, mismatch) in [
+        ("[0,2]", "vertex 2: expected path [0, 2] does not cost 2"),
+        (
+            "[1,2]",
+            "vertex 2: expected path [1, 2] does not go from 0 to 2",
+        ),
+        (
+            "[0,7,2]",
+            "vertex 2: expected path [0, 7, 2] goes through 7",
+        ),
+    ] {
+        fs::write(dir.join("bad.json"), golden(path)).unwrap();
+        let output = djikstra(&dir, &["verify", "-i", "g.txt", "--expected", "bad.json"]);
+        assert_eq!(output.status.code(), Some(1), "{:?}", output);
+        let report = String::from_utf8(output.stdout).unwrap();
+        assert!(report.starts_with(mismatch), "{}", report);
+        assert!(report.contains("1 mismatches"), "{}", report);
+    }
+
+    fs::write(dir.join("bad.json"), "{").unwrap();
+    let output = djikstra(&dir, &["verify", "-i", "g.txt", "--expected", "bad.json"]);
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn simplify_starts_in_the_graph() {
+    let dir = test_dir("missing-start");
+    fs::write(dir.join("g.txt"), "5\n3\n1,1\n\n\n").unwrap();
+    fs::write(dir.join("empty.txt"), "0\n0\n").unwrap();
+    for input in ["g.txt", "empty.txt"] {
+        let output = djikstra(&dir, &["simplify", "-i", input]);
+        assert_eq!(output.status.code(), Some(4), "{:?}", output);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sample_starts_in_the_graph() {
+    let dir = test_dir("sample-start");
+    fs::write(dir.join("g.txt"), "5\n3\n1,1\n\n\n").unwrap();
+    fs::write(dir.join("empty.txt"), "0\n0\n").unwrap();
+    for input in ["g.txt", "empty.txt"] {
+        for method in ["forest-fire", "random"] {
+            let output = djikstra(
+                &dir,
+                &["sample", "-i", input, "--size", "2", "--method", method],
+            );
+            assert_eq!(output.status.code(), Some(4), "{:?}", output);
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_writes_the_distances_and_paths() {
+    let dir = test_dir("run");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(&dir, &["run", "-i", "g.txt", "--format", "csv"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "vertex,distance,path\n0,0,0\n1,1,0 1\n2,2,0 1 2\n"
+    );
+    let output = djikstra(&dir, &["run", "-i", "g.txt", "--start", "9"]);
+    assert_eq!(output.status.code(), Some(4), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn run_streams_the_settled_vertices() {
+    let dir = test_dir("stream");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(&dir, &["run", "-i", "g.txt", "--stream", "-q"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "0 0 -\n1 1 0\n2 2 1\n"
+    );
+    let output = djikstra(&dir, &["run", "-i", "g.txt", "--stream", "--format", "dot"]);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn query_finds_a_path_between_two_vertices() {
+    let dir = test_dir("query");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(
+        &dir,
+        &["query", "-i", "g.txt", "--from", "0", "--to", "2", "-q"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "2 2 (0 -> 1 -> 2)\n"
+    );
+    let output = djikstra(&dir, &["query", "-i", "g.txt", "--from", "0", "--to", "7"]);
+    assert_eq!(output.status.code(), Some(4), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn query_answers_a_batch_of_pairs() {
+    let dir = test_dir("query-batch");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    fs::write(dir.join("pairs.txt"), "0 2\n1 2\n").unwrap();
+    let output = djikstra(
+        &dir,
+        &["query", "-i", "g.txt", "--batch", "pairs.txt", "-q"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "0 2 2 (0 -> 1 -> 2)\n1 2 1 (1 -> 2)\n"
+    );
+    fs::write(dir.join("pairs.txt"), "0 x\n").unwrap();
+    let output = djikstra(&dir, &["query", "-i", "g.txt", "--batch", "pairs.txt"]);
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn benchmark_reports_the_statistics_of_the_runs() {
+    let dir = test_dir("benchmark");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(
+        &dir,
+        &["benchmark", "-i", "g.txt", "-n", "2", "--pq", "dary"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        report.starts_with("Benchmark (dijkstra (dary)):"),
+        "{}",
+        report
+    );
+    assert!(report.contains("settled: 3"), "{}", report);
+
+    let output = djikstra(&dir, &["benchmark", "-i", "g.txt", "-n", "0"]);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    let output = djikstra(&dir, &["benchmark", "-i", "missing.txt"]);
+    assert_eq!(output.status.code(), Some(5), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn convert_writes_the_graph_in_another_format() {
+    let dir = test_dir("convert");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(&dir, &["convert", "-i", "g.txt", "--to", "dimacs"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "p sp 3 3\na 1 2 1\na 1 3 5\na 2 3 1\n"
+    );
+    let output = djikstra(&dir, &["convert", "-i", "g.txt", "--from", "dimacs"]);
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn repl_answers_the_commands_read() {
+    let dir = test_dir("repl");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra_reading(
+        &dir,
+        &["repl", "-i", "g.txt"],
+        "path 0 2\npath 0 7\nset-weight 1 2 9\npath 0 2\nquit\n",
+    );
+    // a wrong command is reported without leaving the REPL
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "2 2 (0 -> 1 -> 2)\nerror: expected vertices below 3, see `help`\n2 5 (0 -> 2)\n"
+    );
+
+    let output = djikstra_reading(&dir, &["repl", "-i", "missing.txt"], "quit\n");
+    assert_eq!(output.status.code(), Some(5), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn serve_answers_route_requests() {
+    let dir = test_dir("serve");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    // a port nothing listens on, for as long as the test needs it
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+        .to_string();
+    let mut server = Command::new(env!("CARGO_BIN_EXE_djikstra"))
+        .args(["serve", "-i", "g.txt", "--port", &port, "--cache", "4"])
+        .current_dir(&dir)
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut stream = (0..500)
+        .find_map(|_| {
+            let connected = TcpStream::connect(format!("127.0.0.1:{0}", port)).ok();
+            if connected.is_none() {
+                thread::sleep(Duration::from_millis(20));
+            }
+            connected
+        })
+        .expect("the server did not listen");
+    stream
+        .write_all(b"GET /route?from=0&to=2 HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"), "{}", response);
+    assert!(response.contains(r#""path":[0,1,2]"#), "{}", response);
+
+    // the port is taken now
+    let output = djikstra(&dir, &["serve", "-i", "g.txt", "--port", &port]);
+    assert_eq!(output.status.code(), Some(5), "{:?}", output);
+    server.kill().unwrap();
+    server.wait().unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn stats_summarizes_the_graph() {
+    let dir = test_dir("stats");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(&dir, &["stats", "-i", "g.txt"]);
+    assert!(output.status.success(), "{:?}", output);
+    let stats = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stats.starts_with("Start vertex: 0\nVertices: 3\nEdges: 3\n"),
+        "{}",
+        stats
+    );
+    let output = djikstra(
+        &dir,
+        &[
+            "stats",
+            "-i",
+            "g.txt",
+            "--histogram",
+            "weights",
+            "--bins",
+            "2",
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "low,high,count\n1,3,2\n4,6,1\n"
+    );
+    let output = djikstra(
+        &dir,
+        &[
+            "stats",
+            "-i",
+            "g.txt",
+            "--histogram",
+            "weights",
+            "--bins",
+            "0",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn validate_reports_the_errors_of_the_input() {
+    let dir = test_dir("validate");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(&dir, &["validate", "-i", "g.txt"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "0 errors, 0 warnings.\n"
+    );
+    let output = djikstra(&dir, &["validate", "-i", "g.txt", "--undirected"]);
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        report.starts_with("g.txt:3:1: error: edge 0 -> 1 has no reverse edge"),
+        "{}",
+        report
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn components_labels_every_vertex() {
+    let dir = test_dir("components");
+    fs::write(dir.join("g.txt"), "0\n3\n1,1\n\n\n").unwrap();
+    let output = djikstra(
+        &dir,
+        &["components", "-i", "g.txt", "--labels", "labels.csv"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let summary = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        summary.starts_with("2 components in graph with 3 vertices:\n"),
+        "{}",
+        summary
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("labels.csv")).unwrap(),
+        "vertex,component\n0,0\n1,0\n2,1\n"
+    );
+    let output = djikstra(
+        &dir,
+        &[
+            "components",
+            "-i",
+            "g.txt",
+            "--labels",
+            "missing/labels.csv",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(5), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn matrix_writes_the_distances_between_all_pairs() {
+    let dir = test_dir("matrix");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    for method in ["dijkstra", "floyd-warshall"] {
+        let output = djikstra(&dir, &["matrix", "-i", "g.txt", "--method", method]);
+        assert!(output.status.success(), "{:?}", output);
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "vertex,0,1,2\n0,0,1,2\n1,,0,1\n2,,,0\n"
+        );
+    }
+    let output = djikstra(&dir, &["matrix", "-i", "g.txt", "--format", "binary"]);
+    assert!(output.status.success(), "{:?}", output);
+    // the number of vertices and 3 rows of 3 distances
+    assert_eq!(output.stdout.len(), 8 * 10);
+    assert_eq!(output.stdout[..8], 3u64.to_le_bytes());
+
+    let output = djikstra(&dir, &["matrix", "-i", "missing.txt"]);
+    assert_eq!(output.status.code(), Some(5), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn mst_writes_the_edges_of_the_tree() {
+    let dir = test_dir("mst");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    for algorithm in ["prim", "kruskal"] {
+        let output = djikstra(&dir, &["mst", "-i", "g.txt", "--algorithm", algorithm]);
+        assert!(output.status.success(), "{:?}", output);
+        let tree = String::from_utf8(output.stdout).unwrap();
+        assert!(
+            tree.starts_with("0 1 1\n1 2 1\nTotal weight: 2\n"),
+            "{}",
+            tree
+        );
+    }
+    let output = djikstra(&dir, &["mst", "-i", "g.txt", "--format", "geojson"]);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn simplify_contracts_the_chains() {
+    let dir = test_dir("simplify");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(&dir, &["simplify", "-i", "g.txt", "--mapping", "map.csv"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "0\n2\n1,2\n\n");
+    assert_eq!(
+        fs::read_to_string(dir.join("map.csv")).unwrap(),
+        "vertex,original\n0,0\n1,2\n"
+    );
+    let output = djikstra(&dir, &["simplify", "-i", "g.txt", "--keep", "9"]);
+    assert_eq!(output.status.code(), Some(4), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sample_writes_a_smaller_graph() {
+    let dir = test_dir("sample");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(
+        &dir,
+        &["sample", "-i", "g.txt", "--size", "2", "--seed", "1"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let sample = String::from_utf8(output.stdout).unwrap();
+    assert!(sample.starts_with("0\n2\n"), "{}", sample);
+    let output = djikstra(&dir, &["sample", "-i", "g.txt", "--size", "0"]);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn visualize_draws_the_graph() {
+    let dir = test_dir("visualize");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(
+        &dir,
+        &["visualize", "-i", "g.txt", "--path", "0:2", "--seed", "1"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    let svg = String::from_utf8(output.stdout).unwrap();
+    assert!(svg.starts_with("<svg"), "{}", svg);
+    assert!(svg.trim_end().ends_with("</svg>"), "{}", svg);
+    let output = djikstra(&dir, &["visualize", "-i", "g.txt", "--path", "0:9"]);
+    assert_eq!(output.status.code(), Some(4), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn head_shows_the_first_vertices() {
+    let dir = test_dir("head");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(&dir, &["head", "-i", "g.txt", "--vertices", "2"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Start vertex: 0\nVertices: 3\n0: 1,1 2,5\n1: 2,1\n... 1 more vertices\n"
+    );
+    let output = djikstra(&dir, &["head", "-i", "g.txt", "--from", "dimacs"]);
+    assert_eq!(output.status.code(), Some(3), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn diff_compares_the_algorithms() {
+    let dir = test_dir("diff");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(&dir, &["diff", "-i", "g.txt"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "0 disagreements between dijkstra, bellman-ford on 3 vertices.\n"
+    );
+    // breadth-first search counts the edges
+    let output = djikstra(
+        &dir,
+        &["diff", "-i", "g.txt", "--algorithms", "dijkstra,bfs"],
+    );
+    assert_eq!(output.status.code(), Some(1), "{:?}", output);
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        report.starts_with("vertex 2: dijkstra distance 2, bfs distance 1\n"),
+        "{}",
+        report
+    );
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn selftest_checks_random_graphs() {
+    let dir = test_dir("selftest");
+    let output = djikstra(&dir, &["selftest", "--graphs", "5", "--seed", "1"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "5 of 5 random graphs passed with seed 1.\n"
+    );
+    // weights so large that two of them overflow a distance
+    let max_weight = usize::MAX.to_string();
+    let output = djikstra(
+        &dir,
+        &[
+            "selftest",
+            "--graphs",
+            "20",
+            "--max-weight",
+            &max_weight,
+            "--seed",
+            "1",
+        ],
+    );
+    assert_eq!(output.status.code(), Some(6), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn profile_starts_in_the_graph() {
+    let dir = test_dir("profile");
+    fs::write(dir.join("g.txt"), GRAPH).unwrap();
+    let output = djikstra(&dir, &["profile", "-i", "g.txt", "--pq", "radix"]);
+    assert!(output.status.success(), "{:?}", output);
+    let profile = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        profile.starts_with("Profile of dijkstra (radix) on graph with 3 vertices and 3 edges:"),
+        "{}",
+        profile
+    );
+    let output = djikstra(&dir, &["profile", "-i", "g.txt", "--start", "5"]);
+    assert_eq!(output.status.code(), Some(4), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn completions_are_written_for_known_shells() {
+    let dir = test_dir("completions");
+    let output = djikstra(&dir, &["completions", "bash"]);
+    assert!(output.status.success(), "{:?}", output);
+    let script = String::from_utf8(output.stdout).unwrap();
+    assert!(script.starts_with("_djikstra() {"), "{}", script);
+    let output = djikstra(&dir, &["completions", "cmd"]);
+    assert_eq!(output.status.code(), Some(2), "{:?}", output);
+    fs::remove_dir_all(&dir).unwrap();
+}